@@ -0,0 +1 @@
+export { default as WorkspaceTrustBanner } from "./WorkspaceTrustBanner.svelte";
@@ -1,4 +1,6 @@
+import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 
 import type { AgentEvent } from "$lib/types/agent";
 import type { SessionRuntime } from "$lib/types/session";
@@ -13,6 +15,20 @@ export interface AgentEventBridgeDependencies {
   scheduleScrollToBottom: () => void;
   handleAgentError: (errorPayload: string) => void;
   handleAgentTerminated: (exitCode: number | null) => void;
+  /**
+   * Only receive `agent-event`s of these sessions (plus session-less ones).
+   * Unset receives every session's events.
+   */
+  subscribedSessionIds?: string[];
+  /**
+   * Called when a session's events skip sequence numbers (a dropped event),
+   * with the inclusive range that never arrived. Defaults to a warning.
+   */
+  handleSequenceGap?: (
+    sessionId: string,
+    firstMissing: number,
+    lastMissing: number,
+  ) => void;
 }
 
 interface ChunkedAgentEventPayload {
@@ -23,6 +39,15 @@ interface ChunkedAgentEventPayload {
   payloadChunk: string;
 }
 
+/** Reference to a payload too large to emit, kept in an overflow file. */
+interface OverflowAgentEventPayload {
+  type: "agent_event_overflow";
+  overflowId: string;
+  payloadKind: string;
+  bytes: number;
+  sessionId?: string;
+}
+
 interface PendingChunkedAgentEvent {
   createdAt: number;
   chunkCount: number;
@@ -69,6 +94,34 @@ function isChunkedAgentEventPayload(
   );
 }
 
+function isOverflowAgentEventPayload(
+  payload: unknown,
+): payload is OverflowAgentEventPayload {
+  if (!payload || typeof payload !== "object") {
+    return false;
+  }
+
+  const typed = payload as { type?: unknown; overflowId?: unknown };
+  return (
+    typed.type === "agent_event_overflow" &&
+    typeof typed.overflowId === "string"
+  );
+}
+
+async function loadOverflowAgentPayload(
+  payload: OverflowAgentEventPayload,
+): Promise<unknown | null> {
+  try {
+    const payloadString = await invoke<string>("read_event_overflow", {
+      overflowId: payload.overflowId,
+    });
+    return JSON.parse(payloadString) as unknown;
+  } catch (error) {
+    console.error("Failed to load overflowed agent event:", error, payload);
+    return null;
+  }
+}
+
 function isExtensionUiRequestPayload(
   payload: unknown,
 ): payload is ExtensionUiRequestPayload {
@@ -151,6 +204,39 @@ export async function setupAgentEventBridge(
   let unlistenError: UnlistenFn | null = null;
   let unlistenTerminated: UnlistenFn | null = null;
 
+  // Last `seq` seen per session. A window's first event for a session only
+  // sets the baseline, since it may have subscribed mid-stream. Numbering
+  // starts over at 1 when a closed session is resumed.
+  const lastSequenceBySession = new Map<string, number>();
+
+  const trackSequence = (sessionId: string, seq: unknown): void => {
+    if (typeof seq !== "number") {
+      return;
+    }
+
+    const last = lastSequenceBySession.get(sessionId);
+    if (seq === 1) {
+      lastSequenceBySession.set(sessionId, seq);
+      return;
+    }
+    if (last !== undefined && seq > last + 1) {
+      if (dependencies.handleSequenceGap) {
+        dependencies.handleSequenceGap(sessionId, last + 1, seq - 1);
+      } else {
+        console.warn(
+          `Missed session events ${last + 1}-${seq - 1} for ${sessionId}`,
+        );
+      }
+    } else if (last !== undefined && seq <= last) {
+      console.warn(
+        `Out-of-order session event ${seq} (after ${last}) for ${sessionId}`,
+      );
+      return;
+    }
+
+    lastSequenceBySession.set(sessionId, seq);
+  };
+
   const routePayload = (payload: unknown): void => {
     if (!payload || typeof payload !== "object") {
       return;
@@ -181,6 +267,7 @@ export async function setupAgentEventBridge(
 
     const wrapped = payload as {
       sessionId?: unknown;
+      seq?: unknown;
       event?: unknown;
       type?: unknown;
     };
@@ -190,6 +277,8 @@ export async function setupAgentEventBridge(
       wrapped.event &&
       typeof wrapped.event === "object"
     ) {
+      trackSequence(wrapped.sessionId, wrapped.seq);
+
       const runtime = dependencies.getRuntime(wrapped.sessionId);
       if (!runtime) {
         return;
@@ -212,7 +301,9 @@ export async function setupAgentEventBridge(
         agentEvent.message.role === "user" &&
         userMessageCountBeforeEvent <= 1;
 
-      if (isFirstUserMessageStart || isFirstUserMessageEnd) {
+      if (agentEvent.type === "session_auto_closed") {
+        dependencies.scheduleSessionSidebarRefresh(0);
+      } else if (isFirstUserMessageStart || isFirstUserMessageEnd) {
         dependencies.markSessionSidebarSyncPending(wrapped.sessionId);
         dependencies.scheduleSessionSidebarRefresh(240);
       } else if (
@@ -233,7 +324,45 @@ export async function setupAgentEventBridge(
     }
   };
 
-  unlistenEvent = await listen<
+  const subscribedSessionIds = dependencies.subscribedSessionIds;
+  if (subscribedSessionIds) {
+    await invoke("subscribe_window_events", {
+      sessionIds: subscribedSessionIds,
+    }).catch((error) => {
+      console.warn("Failed to subscribe window to session events:", error);
+    });
+  }
+
+  // While an overflowed payload loads, later payloads wait behind it so
+  // events keep their order.
+  let overflowQueue: Promise<void> | null = null;
+
+  const enqueuePayload = (payload: unknown): void => {
+    if (!overflowQueue && !isOverflowAgentEventPayload(payload)) {
+      routePayload(payload);
+      return;
+    }
+
+    const queued: Promise<void> = (overflowQueue ?? Promise.resolve())
+      .then(async () => {
+        const resolved = isOverflowAgentEventPayload(payload)
+          ? await loadOverflowAgentPayload(payload)
+          : payload;
+        if (resolved !== null) {
+          routePayload(resolved);
+        }
+      })
+      .finally(() => {
+        if (overflowQueue === queued) {
+          overflowQueue = null;
+        }
+      });
+    overflowQueue = queued;
+  };
+
+  // Window-scoped so the backend can skip sessions this window is not
+  // subscribed to.
+  unlistenEvent = await getCurrentWebviewWindow().listen<
     string | { sessionId?: string; event?: AgentEvent }
   >("agent-event", (event) => {
     try {
@@ -242,7 +371,7 @@ export async function setupAgentEventBridge(
           ? (JSON.parse(event.payload) as unknown)
           : event.payload;
 
-      routePayload(payload);
+      enqueuePayload(payload);
     } catch (error) {
       console.error("Failed to parse agent event:", error, event.payload);
     }
@@ -261,8 +390,13 @@ export async function setupAgentEventBridge(
 
   return () => {
     pendingChunkedAgentEvents.clear();
+    lastSequenceBySession.clear();
     unlistenEvent?.();
     unlistenError?.();
     unlistenTerminated?.();
+
+    if (subscribedSessionIds) {
+      void invoke("unsubscribe_window_events").catch(() => undefined);
+    }
   };
 }
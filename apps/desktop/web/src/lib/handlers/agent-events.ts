@@ -509,6 +509,21 @@ export function handleToolExecutionEnd(
 ): void {
   runtime.messages.setToolCallPending(event.toolCallId, false);
 
+  // The backend sends repeated identical results as a reference to the first one.
+  const original = event.sameAsToolCallId
+    ? runtime.messages.getToolResult(event.sameAsToolCallId)
+    : undefined;
+  if (original) {
+    runtime.messages.upsertToolResult({
+      ...original,
+      toolCallId: event.toolCallId,
+      toolName: event.toolName,
+      isError: event.isError,
+      timestamp: Date.now(),
+    });
+    return;
+  }
+
   const toolResult = buildToolResultMessage({
     toolCallId: event.toolCallId,
     toolName: event.toolName,
@@ -594,6 +609,14 @@ export function handleAgentEvent(
       handleTurnEnd(runtime);
       break;
 
+    case "session_auto_closed":
+      // Sidebar refresh is scheduled by the event bridge.
+      break;
+
+    case "turn_summary":
+      // Aggregated turn data for headers; the transcript needs no update.
+      break;
+
     case "tool_execution_start":
       handleToolExecutionStart(runtime, event);
       break;
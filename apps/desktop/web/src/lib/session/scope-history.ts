@@ -1,4 +1,5 @@
 import {
+  compareHistoryItems,
   normalizeScopePath,
   type PersistedSessionHistoryItem,
 } from "$lib/stores/projectScopes.svelte";
@@ -46,17 +47,12 @@ export function mergeScopeHistory(
         optimistic.text.length > 0 ? optimistic.text : undefined,
       source: "unknown",
       filePath,
+      readOnly: false,
     });
   }
 
   for (const history of Object.values(merged)) {
-    history.sort((a, b) => {
-      const timestampCmp = (b.timestamp ?? "").localeCompare(a.timestamp ?? "");
-      if (timestampCmp !== 0) {
-        return timestampCmp;
-      }
-      return a.sessionId.localeCompare(b.sessionId);
-    });
+    history.sort(compareHistoryItems);
   }
 
   return merged;
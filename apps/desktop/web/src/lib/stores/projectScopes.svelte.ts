@@ -3,9 +3,28 @@ import { invoke } from "@tauri-apps/api/core";
 export interface PersistedSessionHistoryItem {
   sessionId: string;
   timestamp?: string;
+  /** Instant the backend ordered the session by (Unix millis). */
+  timestampMillis?: number;
+  /** `timestampMillis` formatted in `timeZone`. */
+  formattedTimestamp?: string;
+  /** IANA zone history timestamps are shown in (preference or system zone). */
+  timeZone?: string;
   firstUserMessage?: string;
   source: "global" | "local" | "unknown";
   filePath: string;
+  /** Stored on read-only storage; deleting it fails with `read-only-storage`. */
+  readOnly: boolean;
+}
+
+/** Server-side sorting/filtering for `list_session_project_scopes`. */
+export interface SessionListOptions {
+  sort?: "created" | "lastModified" | "size" | "title";
+  ascending?: boolean;
+  source?: "global" | "local";
+  tag?: string;
+  hasImages?: boolean;
+  createdAfter?: number;
+  createdBefore?: number;
 }
 
 interface SessionProjectScopesResponse {
@@ -13,6 +32,13 @@ interface SessionProjectScopesResponse {
   histories?: unknown;
 }
 
+export interface DeleteProjectScopeResponse {
+  dryRun: boolean;
+  files: string[];
+  directories: string[];
+  deletedCount: number;
+}
+
 interface DeleteProjectSessionResponse {
   deleted?: unknown;
 }
@@ -23,6 +49,27 @@ function normalizeScopePath(path: string): string {
 
 export { normalizeScopePath };
 
+function historyInstant(item: PersistedSessionHistoryItem): number {
+  if (item.timestampMillis !== undefined) {
+    return item.timestampMillis;
+  }
+
+  const parsed = item.timestamp ? Date.parse(item.timestamp) : Number.NaN;
+  return Number.isNaN(parsed) ? 0 : parsed;
+}
+
+/** Newest first; ties broken by session id. */
+export function compareHistoryItems(
+  a: PersistedSessionHistoryItem,
+  b: PersistedSessionHistoryItem,
+): number {
+  const instantCmp = historyInstant(b) - historyInstant(a);
+  if (instantCmp !== 0) {
+    return instantCmp;
+  }
+  return a.sessionId.localeCompare(b.sessionId);
+}
+
 function normalizeScopes(value: unknown): string[] {
   if (!Array.isArray(value)) {
     return [];
@@ -48,6 +95,7 @@ function normalizeScopes(value: unknown): string[] {
 
 function normalizeHistories(
   value: unknown,
+  keepServerOrder = false,
 ): Record<string, PersistedSessionHistoryItem[]> {
   if (!Array.isArray(value)) {
     return {};
@@ -103,6 +151,29 @@ function normalizeHistories(
           ? rawTimestamp.trim()
           : undefined;
 
+      const rawTimestampMillis = (session as { timestampMillis?: unknown })
+        .timestampMillis;
+      const timestampMillis =
+        typeof rawTimestampMillis === "number" &&
+        Number.isFinite(rawTimestampMillis)
+          ? rawTimestampMillis
+          : undefined;
+
+      const rawFormattedTimestamp = (
+        session as { formattedTimestamp?: unknown }
+      ).formattedTimestamp;
+      const formattedTimestamp =
+        typeof rawFormattedTimestamp === "string" &&
+        rawFormattedTimestamp.length > 0
+          ? rawFormattedTimestamp
+          : undefined;
+
+      const rawTimeZone = (session as { timeZone?: unknown }).timeZone;
+      const timeZone =
+        typeof rawTimeZone === "string" && rawTimeZone.length > 0
+          ? rawTimeZone
+          : undefined;
+
       const rawFirstUserMessage =
         (
           session as {
@@ -121,6 +192,8 @@ function normalizeHistories(
       const source =
         rawSource === "global" || rawSource === "local" ? rawSource : "unknown";
 
+      const readOnly = (session as { readOnly?: unknown }).readOnly === true;
+
       const dedupKey = `${sessionId}::${filePath}`;
       if (dedup.has(dedupKey)) {
         continue;
@@ -130,19 +203,19 @@ function normalizeHistories(
       normalized.push({
         sessionId,
         timestamp,
+        timestampMillis,
+        formattedTimestamp,
+        timeZone,
         firstUserMessage,
         source,
         filePath,
+        readOnly,
       });
     }
 
-    normalized.sort((a, b) => {
-      const timestampCmp = (b.timestamp ?? "").localeCompare(a.timestamp ?? "");
-      if (timestampCmp !== 0) {
-        return timestampCmp;
-      }
-      return a.sessionId.localeCompare(b.sessionId);
-    });
+    if (!keepServerOrder) {
+      normalized.sort(compareHistoryItems);
+    }
 
     grouped.set(scope, normalized);
   }
@@ -160,7 +233,10 @@ export class ProjectScopesStore {
     return this.historyByScope[scope] ?? [];
   }
 
-  async refresh(seedScopes: string[] = []): Promise<void> {
+  async refresh(
+    seedScopes: string[] = [],
+    options?: SessionListOptions,
+  ): Promise<void> {
     this.loading = true;
     this.error = null;
 
@@ -169,10 +245,14 @@ export class ProjectScopesStore {
         "list_session_project_scopes",
         {
           seedScopes,
+          options,
         },
       );
       this.scopes = normalizeScopes(response?.scopes);
-      this.historyByScope = normalizeHistories(response?.histories);
+      this.historyByScope = normalizeHistories(
+        response?.histories,
+        options !== undefined,
+      );
     } catch (error) {
       this.error = error instanceof Error ? error.message : String(error);
       this.scopes = [];
@@ -186,11 +266,22 @@ export class ProjectScopesStore {
     projectDir: string,
     seedScopes: string[] = [],
   ): Promise<number> {
-    const deletedCount = await invoke<number>("delete_project_scope", {
+    const response = await invoke<DeleteProjectScopeResponse>(
+      "delete_project_scope",
+      { projectDir },
+    );
+    await this.refresh(seedScopes);
+    return response.deletedCount;
+  }
+
+  /** Files and directories `deleteScope` would remove, for confirmation. */
+  async previewDeleteScope(
+    projectDir: string,
+  ): Promise<DeleteProjectScopeResponse> {
+    return invoke<DeleteProjectScopeResponse>("delete_project_scope", {
       projectDir,
+      dryRun: true,
     });
-    await this.refresh(seedScopes);
-    return deletedCount;
   }
 
   async deleteSession(
@@ -1,5 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
 
+import { parseBackendError } from "$lib/utils/backend-error";
+
 export interface SessionModelRef {
   provider: string;
   id: string;
@@ -30,6 +32,8 @@ interface CreateSessionData {
 
 interface CreateSessionOptions {
   activate?: boolean;
+  /** Scope the session to the enclosing git root; unset follows the preference. */
+  useGitRoot?: boolean;
 }
 
 interface ListSessionsData {
@@ -56,14 +60,7 @@ function formatCreateSessionError(message: string): string {
     return "Failed to create session";
   }
 
-  if (
-    trimmed.includes("Failed to run npm root -g") ||
-    trimmed.includes("npm install -g")
-  ) {
-    return "Graphone could not initialize sessions because npm was not available to load your configured pi packages/extensions. Launch Graphone from the desktop launcher wrapper or a shell where Node/npm is available.";
-  }
-
-  return trimmed;
+  return parseBackendError(trimmed).message;
 }
 
 export class SessionsStore {
@@ -100,12 +97,15 @@ export class SessionsStore {
 
     try {
       const response = await invoke<RpcResponse<CreateSessionData>>(
-        "create_agent",
+        "create_session",
         {
-          projectDir,
-          provider,
-          model,
-          sessionFile,
+          options: {
+            projectDir,
+            provider,
+            modelId: model,
+            sessionFile,
+            useGitRoot: options?.useGitRoot,
+          },
         },
       );
 
@@ -130,7 +130,7 @@ export class SessionsStore {
       const cwd = response.data.cwd;
 
       if (!sessionId || !cwd) {
-        throw new Error("Malformed create_agent response");
+        throw new Error("Malformed create_session response");
       }
 
       const resolvedSessionFile =
@@ -0,0 +1,57 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Mirrors the backend `ProjectTrust` response. */
+export interface ProjectTrust {
+  projectDir: string;
+  trusted: boolean;
+  firstOpenedAt?: number;
+  trustedAt?: number;
+  /** Tools switched off in the project's sessions while it is untrusted. */
+  restrictedTools: string[];
+}
+
+class WorkspaceTrustStore {
+  /** Trust state per project dir, as last read from the backend. */
+  trustByProject = $state<Record<string, ProjectTrust>>({});
+
+  /** Project currently being trusted, while `trust_project` runs. */
+  trusting = $state<string | null>(null);
+
+  error = $state<string | null>(null);
+
+  get(projectDir: string | null | undefined): ProjectTrust | null {
+    return projectDir ? (this.trustByProject[projectDir] ?? null) : null;
+  }
+
+  private apply(projectDir: string, trust: ProjectTrust): void {
+    this.trustByProject = { ...this.trustByProject, [projectDir]: trust };
+  }
+
+  async refresh(projectDir: string): Promise<void> {
+    try {
+      const trust = await invoke<ProjectTrust>("get_project_trust", {
+        projectDir,
+      });
+      this.apply(projectDir, trust);
+    } catch (error) {
+      console.warn(`Failed to read trust of ${projectDir}:`, error);
+    }
+  }
+
+  async trust(projectDir: string): Promise<void> {
+    this.trusting = projectDir;
+    try {
+      const trust = await invoke<ProjectTrust>("trust_project", {
+        projectDir,
+      });
+      this.apply(projectDir, trust);
+      this.error = null;
+    } catch (error) {
+      this.error = String(error);
+    } finally {
+      this.trusting = null;
+    }
+  }
+}
+
+export const workspaceTrustStore = new WorkspaceTrustStore();
@@ -183,11 +183,38 @@ export interface TurnEndEvent {
   toolResults: ToolResultMessage[];
 }
 
+/** Synthesized by the backend right after `turn_end`. */
+export interface TurnSummaryEvent {
+  type: "turn_summary";
+  durationMs: number;
+  /** Tool name -> number of calls in the turn. */
+  tools: Record<string, number>;
+  /** Files modified by edit/write tools. */
+  filesTouched: string[];
+  tokens: {
+    input: number;
+    output: number;
+    cacheRead: number;
+    cacheWrite: number;
+    total: number;
+  };
+  errors: string[];
+}
+
+/** The backend closed the session after the idle-close preference elapsed. */
+export interface SessionAutoClosedEvent {
+  type: "session_auto_closed";
+  reason: "idle";
+  idleHours: number;
+}
+
 export interface ToolExecutionStartEvent {
   type: "tool_execution_start";
   toolCallId: string;
   toolName: string;
   args: Record<string, unknown>;
+  /** Absolute paths of path args rewritten relative to the session cwd. */
+  absolutePaths?: Record<string, string>;
 }
 
 export interface ToolExecutionUpdateEvent {
@@ -196,6 +223,8 @@ export interface ToolExecutionUpdateEvent {
   toolName: string;
   args: Record<string, unknown>;
   partialResult: unknown;
+  /** Absolute paths of path args rewritten relative to the session cwd. */
+  absolutePaths?: Record<string, string>;
 }
 
 export interface ToolExecutionEndEvent {
@@ -204,6 +233,39 @@ export interface ToolExecutionEndEvent {
   toolName: string;
   result: unknown;
   isError: boolean;
+  /** Syntax-highlighting language hint detected by the backend. */
+  language?: string;
+  /** Set when the result is identical to an earlier tool call's (result is then null). */
+  sameAsToolCallId?: string;
+  /** Present when the output was truncated; read it with `read_tool_output_lines` (session id + tool call id). */
+  spilledOutput?: { bytes: number; lines: number };
+}
+
+/** Payload of the dedicated `file-diff` event emitted after edit/write tools. */
+export interface FileDiffEvent {
+  sessionId: string;
+  toolCallId: string;
+  /** Absolute path of the edited file. */
+  path: string;
+  /** True when the tool created the file. */
+  created: boolean;
+  /** Unified diff (3 lines of context). */
+  diff: string;
+  additions: number;
+  deletions: number;
+  truncated: boolean;
+}
+
+/**
+ * Payload of the `tool-still-running` event, emitted every few seconds while
+ * a tool runs without producing output.
+ */
+export interface ToolStillRunningEvent {
+  sessionId: string;
+  toolCallId: string;
+  toolName: string;
+  /** Time since `tool_execution_start`. */
+  elapsedMs: number;
 }
 
 export interface BashExecutionStartEvent {
@@ -269,6 +331,8 @@ export type AgentEvent =
   | AgentEndEvent
   | TurnStartEvent
   | TurnEndEvent
+  | TurnSummaryEvent
+  | SessionAutoClosedEvent
   | ToolExecutionStartEvent
   | ToolExecutionUpdateEvent
   | ToolExecutionEndEvent
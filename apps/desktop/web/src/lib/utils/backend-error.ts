@@ -0,0 +1,21 @@
+export interface BackendError {
+  /** Stable error code such as `unknown-session`, when the backend set one. */
+  code: string | null;
+  /** Message in the locale passed to `set_locale`. */
+  message: string;
+}
+
+const ERROR_CODE_PATTERN = /^([a-z0-9]+(?:-[a-z0-9]+)+):\s+/;
+
+/** Split a `<code>: <message>` backend error into its parts. */
+export function parseBackendError(error: string): BackendError {
+  const match = ERROR_CODE_PATTERN.exec(error);
+  if (!match) {
+    return { code: null, message: error };
+  }
+
+  return {
+    code: match[1] ?? null,
+    message: error.slice(match[0].length),
+  };
+}
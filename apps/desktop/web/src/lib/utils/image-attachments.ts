@@ -272,6 +272,40 @@ export async function tryReadNativeClipboardImage(
   }
 }
 
+/**
+ * Read image files copied in the OS file manager via Tauri invoke.
+ * Uses the pasted `text/uri-list` payload when available, otherwise the
+ * native clipboard file list (uri-list / CF_HDROP).
+ */
+export async function tryReadNativeClipboardFiles(
+  invokeFn: <T>(cmd: string, args?: Record<string, unknown>) => Promise<T>,
+  uriList?: string,
+): Promise<Blob[]> {
+  try {
+    const images = uriList?.includes("file://")
+      ? await invokeFn<PromptImageAttachment[]>(
+          "read_image_files_from_uri_list",
+          { uriList },
+        )
+      : await invokeFn<PromptImageAttachment[]>("read_clipboard_files");
+
+    return (images ?? [])
+      .filter(
+        (image) =>
+          image.type === "image" &&
+          typeof image.data === "string" &&
+          typeof image.mimeType === "string",
+      )
+      .map(
+        (image) =>
+          new Blob([decodeBase64ToBytes(image.data)], { type: image.mimeType }),
+      )
+      .filter((blob) => blob.size > 0);
+  } catch {
+    return [];
+  }
+}
+
 /**
  * Extract image files from a ClipboardEvent's DataTransferItemList.
  */
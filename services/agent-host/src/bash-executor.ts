@@ -1,8 +1,10 @@
 import { spawn } from "node:child_process";
 
 import {
+  createBashTool,
   getShellConfig,
   type BashOperations,
+  type ToolDefinition,
 } from "@earendil-works/pi-coding-agent";
 
 // ── Types ───────────────────────────────────────────────────────────────────
@@ -22,7 +24,10 @@ export interface BashCommandResult {
  * Handles process spawning, signal-based abort (SIGTERM → SIGKILL escalation),
  * and platform-specific process group management.
  */
-export function createSessionScopedBashOperations(cwd: string): BashOperations {
+export function createSessionScopedBashOperations(
+  cwd: string,
+  env: Record<string, string> = {},
+): BashOperations {
   return {
     exec: async (command, _cwd, options) => {
       const { shell, args } = getShellConfig();
@@ -33,6 +38,7 @@ export function createSessionScopedBashOperations(cwd: string): BashOperations {
             cwd,
             env: {
               ...process.env,
+              ...env,
               ...(options.env ?? {}),
             },
             stdio: ["ignore", "pipe", "pipe"],
@@ -124,13 +130,37 @@ export function createSessionScopedBashOperations(cwd: string): BashOperations {
 /**
  * Wrap bash operations with a fixed working directory.
  * Useful when extension event results provide their own operations
- * but need to be scoped to the session's cwd.
+ * but need to be scoped to the session's cwd (and its env overrides).
  */
+/**
+ * The agent's `bash` tool, running commands with the session's environment
+ * overrides. `getEnv` is read on every call, so `set_session_env` applies to
+ * the next command.
+ */
+export function createSessionBashTool(
+  cwd: string,
+  getEnv: () => Record<string, string>,
+): ToolDefinition {
+  const bashTool = createBashTool(cwd);
+  return {
+    ...bashTool,
+    execute: (toolCallId, params, signal, onUpdate) =>
+      createBashTool(cwd, {
+        operations: createSessionScopedBashOperations(cwd, getEnv()),
+      }).execute(toolCallId, params, signal, onUpdate),
+  };
+}
+
 export function wrapBashOperationsWithCwd(
   operations: BashOperations,
   cwd: string,
+  env: Record<string, string> = {},
 ): BashOperations {
   return {
-    exec: (command, _cwd, options) => operations.exec(command, cwd, options),
+    exec: (command, _cwd, options) =>
+      operations.exec(command, cwd, {
+        ...options,
+        env: { ...env, ...(options.env ?? {}) },
+      }),
   };
 }
@@ -3,6 +3,7 @@
 import { basename, delimiter, dirname, join } from "node:path";
 
 import { handleHostCommand } from "./commands.js";
+import type { HostRuntimeOptions } from "./host-runtime.js";
 import { attachJsonlLineReader, serializeJsonLine } from "./jsonl.js";
 import {
   failure,
@@ -115,14 +116,23 @@ function applyBunPackageDirExecPathOverride(): void {
   }
 }
 
-async function runHostMode(): Promise<void> {
+function parseHostRuntimeOptions(args: string[]): HostRuntimeOptions {
+  return {
+    noSession: args.includes("--no-session"),
+    noSkills: args.includes("--no-skills"),
+    noExtensions: args.includes("--no-extensions"),
+    noPromptTemplates: args.includes("--no-prompt-templates"),
+  };
+}
+
+async function runHostMode(options: HostRuntimeOptions): Promise<void> {
   const { HostRuntime } = await import("./host-runtime.js");
 
   const writer = new LineWriter();
 
   const runtime = new HostRuntime((event) => {
     writer.writeObject(event);
-  });
+  }, options);
   const startupPromise = runtime.initialize();
 
   let detachInput = () => {};
@@ -278,7 +288,7 @@ async function main(): Promise<void> {
       process.argv[1] ?? "cli.js",
       ...passthroughArgs,
     ];
-    await runHostMode();
+    await runHostMode(parseHostRuntimeOptions(passthroughArgs));
     return;
   }
 
@@ -1,6 +1,10 @@
 import type { HostRuntime } from "./host-runtime.js";
 import {
   failure,
+  HOST_COMMAND_TYPES,
+  HOST_EVENT_TYPES,
+  HOST_FEATURES,
+  HOST_PROTOCOL_VERSION,
   success,
   type HostCommand,
   type HostImageAttachment,
@@ -56,6 +60,29 @@ function parseImageAttachments(
   return parsed.length > 0 ? parsed : undefined;
 }
 
+function parseEnv(value: unknown): Record<string, string> | undefined {
+  if (!value || typeof value !== "object" || Array.isArray(value)) {
+    return undefined;
+  }
+
+  const entries = Object.entries(value).filter(
+    (entry): entry is [string, string] => typeof entry[1] === "string",
+  );
+
+  return Object.fromEntries(entries);
+}
+
+function parseToolNames(value: unknown): string[] | undefined {
+  if (!Array.isArray(value)) {
+    return undefined;
+  }
+
+  return value
+    .filter((tool): tool is string => typeof tool === "string")
+    .map((tool) => tool.trim())
+    .filter((tool) => tool.length > 0);
+}
+
 export async function handleHostCommand(
   runtime: HostRuntime,
   command: HostCommand,
@@ -80,11 +107,32 @@ export async function handleHostCommand(
           provider,
           modelId,
           sessionFile,
+          env: parseEnv(command.env),
+          disabledTools: parseToolNames(command.tools),
         });
 
         return success(requestId, "create_session", data);
       }
 
+      case "set_session_env": {
+        const sessionId = requireSessionId(command);
+        runtime.setSessionEnv(sessionId, parseEnv(command.env) ?? {});
+        return success(requestId, "set_session_env", { sessionId });
+      }
+
+      case "set_session_profile": {
+        const sessionId = requireSessionId(command);
+        return success(
+          requestId,
+          "set_session_profile",
+          runtime.setSessionProfile(sessionId, {
+            disabledTools: parseToolNames(command.tools),
+            systemPrompt:
+              typeof command.message === "string" ? command.message : undefined,
+          }),
+        );
+      }
+
       case "close_session": {
         const sessionId = requireSessionId(command);
         await runtime.closeSession(sessionId);
@@ -331,6 +379,15 @@ export async function handleHostCommand(
         return success(requestId, "shutdown");
       }
 
+      case "get_capabilities": {
+        return success(requestId, "get_capabilities", {
+          protocolVersion: HOST_PROTOCOL_VERSION,
+          commands: [...HOST_COMMAND_TYPES],
+          eventTypes: [...HOST_EVENT_TYPES],
+          features: [...HOST_FEATURES],
+        });
+      }
+
       case "ping": {
         return success(requestId, "ping", { ready: true });
       }
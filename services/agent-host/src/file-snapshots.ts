@@ -0,0 +1,100 @@
+import { readFileSync, statSync } from "node:fs";
+import { isAbsolute, resolve } from "node:path";
+
+/** Tools whose calls rewrite a file given by a `path` argument. */
+const FILE_EDIT_TOOLS = new Set(["edit", "write"]);
+const MAX_SNAPSHOT_BYTES = 2 * 1024 * 1024;
+
+export interface FileSnapshot {
+  /** Absolute path of the edited file. */
+  path: string;
+  /** File content before the tool ran; null when the file did not exist. */
+  before: string | null;
+}
+
+/**
+ * Captures file content right before edit/write tools run so the backend can
+ * diff against it once the tool finishes.
+ *
+ * Session subscribers run synchronously when `tool_execution_start` is
+ * emitted, before the tool executes, so reading here sees the old content.
+ */
+export class FileSnapshotTracker {
+  private readonly pending = new Map<string, FileSnapshot>();
+
+  constructor(private readonly cwd: string) {}
+
+  /**
+   * Record a snapshot on `tool_execution_start`; on `tool_execution_end`
+   * return (and forget) the snapshot for that call.
+   */
+  observe(event: unknown): FileSnapshot | undefined {
+    if (!event || typeof event !== "object") {
+      return undefined;
+    }
+
+    const source = event as {
+      type?: unknown;
+      toolCallId?: unknown;
+      toolName?: unknown;
+      args?: { path?: unknown; file_path?: unknown } | unknown;
+    };
+
+    if (typeof source.toolCallId !== "string") {
+      return undefined;
+    }
+
+    if (source.type === "tool_execution_end") {
+      const snapshot = this.pending.get(source.toolCallId);
+      this.pending.delete(source.toolCallId);
+      return snapshot;
+    }
+
+    if (
+      source.type !== "tool_execution_start" ||
+      typeof source.toolName !== "string" ||
+      !FILE_EDIT_TOOLS.has(source.toolName)
+    ) {
+      return undefined;
+    }
+
+    const args =
+      source.args && typeof source.args === "object"
+        ? (source.args as { path?: unknown; file_path?: unknown })
+        : {};
+    const rawPath =
+      typeof args.path === "string"
+        ? args.path
+        : typeof args.file_path === "string"
+          ? args.file_path
+          : undefined;
+    if (!rawPath) {
+      return undefined;
+    }
+
+    const path = isAbsolute(rawPath) ? rawPath : resolve(this.cwd, rawPath);
+    const snapshot = readSnapshot(path);
+    if (snapshot) {
+      this.pending.set(source.toolCallId, snapshot);
+    }
+
+    return undefined;
+  }
+}
+
+function readSnapshot(path: string): FileSnapshot | undefined {
+  try {
+    const stats = statSync(path);
+    if (!stats.isFile() || stats.size > MAX_SNAPSHOT_BYTES) {
+      return undefined;
+    }
+
+    return { path, before: readFileSync(path, "utf8") };
+  } catch (error) {
+    if ((error as NodeJS.ErrnoException).code === "ENOENT") {
+      return { path, before: null };
+    }
+
+    return undefined;
+  }
+}
@@ -10,6 +10,8 @@ import type { ImageContent } from "@earendil-works/pi-ai";
 import {
   AuthStorage,
   createAgentSession,
+  DefaultResourceLoader,
+  getAgentDir,
   ModelRegistry,
   SessionManager,
   stripFrontmatter,
@@ -19,6 +21,7 @@ import {
 } from "@earendil-works/pi-coding-agent";
 
 import {
+  createSessionBashTool,
   createSessionScopedBashOperations,
   wrapBashOperationsWithCwd,
   type BashCommandResult,
@@ -27,6 +30,7 @@ import {
   ExtensionNameResolver,
   type RegisteredExtensionSummary,
 } from "./extension-name-resolver.js";
+import { FileSnapshotTracker } from "./file-snapshots.js";
 import {
   ModelCatalogSync,
   type ModelCatalogSyncResult,
@@ -36,6 +40,7 @@ import {
   type OAuthLoginStatus,
   type OAuthLoginUpdate,
 } from "./oauth-login-manager.js";
+import { applyProjectToolSettings } from "./project-tools.js";
 import type { HostedSession } from "./session-runtime.js";
 import { validateCwd, validateSessionFile } from "./session-validation.js";
 import {
@@ -663,6 +668,18 @@ function resolveVisibleLeafId(session: AgentSession): string | null {
 
 // ── HostRuntime ─────────────────────────────────────────────────────────────
 
+/** Startup flags passed through by the desktop app (its `sidecarArgs`). */
+export interface HostRuntimeOptions {
+  /** `--no-session`: new sessions are not written to a session file. */
+  noSession?: boolean;
+  /** `--no-skills` */
+  noSkills?: boolean;
+  /** `--no-extensions` */
+  noExtensions?: boolean;
+  /** `--no-prompt-templates` */
+  noPromptTemplates?: boolean;
+}
+
 export class HostRuntime {
   private readonly sessions = new Map<string, HostedSession>();
   private readonly extensionUiStates = new Map<
@@ -683,6 +700,7 @@ export class HostRuntime {
 
   constructor(
     private readonly emitOutboundEvent: (event: HostOutboundEnvelope) => void,
+    private readonly options: HostRuntimeOptions = {},
   ) {}
 
   async initialize(): Promise<void> {
@@ -692,12 +710,37 @@ export class HostRuntime {
 
   // ── Session lifecycle ─────────────────────────────────────────────────────
 
+  /**
+   * A resource loader honouring the `--no-*` startup flags, or `undefined`
+   * to let `createAgentSession` load everything as usual.
+   */
+  private async createResourceLoader(
+    cwd: string,
+  ): Promise<DefaultResourceLoader | undefined> {
+    const { noSkills, noExtensions, noPromptTemplates } = this.options;
+    if (!noSkills && !noExtensions && !noPromptTemplates) {
+      return undefined;
+    }
+
+    const loader = new DefaultResourceLoader({
+      cwd,
+      agentDir: getAgentDir(),
+      noSkills,
+      noExtensions,
+      noPromptTemplates,
+    });
+    await loader.reload();
+    return loader;
+  }
+
   async createSession(args: {
     sessionId?: string;
     cwd: string;
     provider?: string;
     modelId?: string;
     sessionFile?: string;
+    env?: Record<string, string>;
+    disabledTools?: string[];
   }): Promise<{
     sessionId: string;
     cwd: string;
@@ -717,16 +760,28 @@ export class HostRuntime {
     const fallbackCwd = validateCwd(args.cwd);
     const sessionManager = args.sessionFile
       ? SessionManager.open(validateSessionFile(args.sessionFile))
-      : SessionManager.create(fallbackCwd);
+      : this.options.noSession
+        ? SessionManager.inMemory(fallbackCwd)
+        : SessionManager.create(fallbackCwd);
 
     const resolvedCwd = validateCwd(sessionManager.getCwd());
     this.modelRegistry.refresh();
+    const resourceLoader = await this.createResourceLoader(resolvedCwd);
 
     const { session, modelFallbackMessage } = await createAgentSession({
       cwd: resolvedCwd,
       sessionManager,
       authStorage: this.authStorage,
       modelRegistry: this.modelRegistry,
+      // Replaces the built-in bash tool so the agent's commands get the
+      // session's environment overrides too.
+      customTools: [
+        createSessionBashTool(
+          resolvedCwd,
+          () => this.sessions.get(sessionId)?.env ?? args.env ?? {},
+        ),
+      ],
+      ...(resourceLoader ? { resourceLoader } : {}),
     });
 
     this.extensionUiStates.set(sessionId, { statuses: new Map() });
@@ -758,11 +813,20 @@ export class HostRuntime {
       },
     });
 
+    // After extensions bind, so tools they register can be disabled too.
+    applyProjectToolSettings(session, resolvedCwd, args.disabledTools);
+
+    const fileSnapshots = new FileSnapshotTracker(resolvedCwd);
     const unsubscribe = session.subscribe((event) => {
+      const fileSnapshot = fileSnapshots.observe(event);
+      const wireEvent = compactSessionEventForWire(event);
       this.emitOutboundEvent({
         type: "session_event",
         sessionId,
-        event: compactSessionEventForWire(event),
+        event:
+          fileSnapshot && wireEvent && typeof wireEvent === "object"
+            ? { ...wireEvent, fileSnapshot }
+            : wireEvent,
       });
     });
 
@@ -771,6 +835,7 @@ export class HostRuntime {
       cwd: resolvedCwd,
       createdAt: Date.now(),
       session,
+      env: args.env ?? {},
       unsubscribe,
     });
 
@@ -808,6 +873,49 @@ export class HostRuntime {
     });
   }
 
+  setSessionEnv(sessionId: string, env: Record<string, string>): void {
+    this.requireHostedSession(sessionId, "set_session_env").env = { ...env };
+  }
+
+  /**
+   * Apply a graphone profile's tool set and system prompt addition.
+   *
+   * pi rebuilds the system prompt when the active tools change, so the suffix
+   * is re-applied after every change rather than stacked.
+   */
+  setSessionProfile(
+    sessionId: string,
+    options: { disabledTools?: string[]; systemPrompt?: string },
+  ): { activeTools: string[] } {
+    const hosted = this.requireHostedSession(sessionId, "set_session_profile");
+    const { session } = hosted;
+
+    if (options.disabledTools) {
+      const disabled = new Set(options.disabledTools);
+      session.setActiveToolsByName(
+        disabled.has("*")
+          ? []
+          : session
+              .getAllTools()
+              .map((tool) => tool.name)
+              .filter((name) => !disabled.has(name)),
+      );
+    }
+
+    const previousSuffix = hosted.systemPromptSuffix;
+    const suffix = (options.systemPrompt ?? previousSuffix ?? "").trim();
+    const current = session.systemPrompt;
+    const base =
+      previousSuffix && current.endsWith(`\n\n${previousSuffix}`)
+        ? current.slice(0, current.length - previousSuffix.length - 2)
+        : current;
+
+    hosted.systemPromptSuffix = suffix || undefined;
+    session.agent.setSystemPrompt(suffix ? `${base}\n\n${suffix}` : base);
+
+    return { activeTools: session.getActiveToolNames() };
+  }
+
   async closeSession(sessionId: string): Promise<void> {
     const hosted = this.sessions.get(sessionId);
     if (!hosted) {
@@ -945,8 +1053,12 @@ export class HostRuntime {
     }
 
     const operations = eventResult?.operations
-      ? wrapBashOperationsWithCwd(eventResult.operations, hosted.cwd)
-      : createSessionScopedBashOperations(hosted.cwd);
+      ? wrapBashOperationsWithCwd(
+          eventResult.operations,
+          hosted.cwd,
+          hosted.env,
+        )
+      : createSessionScopedBashOperations(hosted.cwd, hosted.env);
 
     try {
       const result = await session.executeBash(
@@ -0,0 +1,80 @@
+/**
+ * Parse JSONC text (comments and trailing commas allowed), as graphone
+ * writes pi settings files.
+ *
+ * Mirrors the Rust side's `jsonc::parse`: comments are blanked and trailing
+ * commas dropped, then the result is parsed as plain JSON.
+ */
+export function parseJsonc(text: string): unknown {
+  return JSON.parse(stripCommentsAndTrailingCommas(text));
+}
+
+function stringEnd(text: string, start: number): number {
+  let index = start + 1;
+  while (index < text.length) {
+    const char = text[index];
+    if (char === "\\") {
+      index += 2;
+    } else if (char === '"') {
+      return index + 1;
+    } else {
+      index += 1;
+    }
+  }
+  return text.length;
+}
+
+/** Index just past the comment starting at `start`, or -1 if there is none. */
+function commentEnd(text: string, start: number): number {
+  const next = text[start + 1];
+  if (next === "/") {
+    const newline = text.indexOf("\n", start);
+    return newline === -1 ? text.length : newline;
+  }
+  if (next === "*") {
+    const close = text.indexOf("*/", start + 2);
+    return close === -1 ? text.length : close + 2;
+  }
+  return -1;
+}
+
+function stripCommentsAndTrailingCommas(text: string): string {
+  const out = text.split("");
+  let index = 0;
+  let pendingComma: number | undefined;
+
+  while (index < text.length) {
+    const char = text[index];
+    if (char === '"') {
+      pendingComma = undefined;
+      index = stringEnd(text, index);
+      continue;
+    }
+    if (char === "/") {
+      const end = commentEnd(text, index);
+      if (end !== -1) {
+        for (let blank = index; blank < end; blank += 1) {
+          if (out[blank] !== "\n") {
+            out[blank] = " ";
+          }
+        }
+        index = end;
+        continue;
+      }
+      pendingComma = undefined;
+    } else if (char === ",") {
+      pendingComma = index;
+    } else if (char === "}" || char === "]") {
+      if (pendingComma !== undefined) {
+        out[pendingComma] = " ";
+        pendingComma = undefined;
+      }
+    } else if (!/\s/.test(char)) {
+      pendingComma = undefined;
+    }
+
+    index += 1;
+  }
+
+  return out.join("");
+}
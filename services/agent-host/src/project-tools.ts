@@ -0,0 +1,59 @@
+import { readFileSync } from "node:fs";
+import { join } from "node:path";
+
+import type { AgentSession } from "@earendil-works/pi-coding-agent";
+
+import { parseJsonc } from "./jsonc.js";
+
+/**
+ * Read `disabledTools` from the project's `.pi/settings.json`, which may
+ * contain comments and trailing commas.
+ *
+ * graphone writes this key per project; pi itself ignores it, so the host
+ * applies it when it creates a session.
+ */
+export function readDisabledTools(cwd: string): string[] {
+  let raw: string;
+  try {
+    raw = readFileSync(join(cwd, ".pi", "settings.json"), "utf8");
+  } catch {
+    return [];
+  }
+
+  try {
+    const settings = parseJsonc(raw) as { disabledTools?: unknown };
+    if (!Array.isArray(settings.disabledTools)) {
+      return [];
+    }
+
+    return settings.disabledTools
+      .filter((tool): tool is string => typeof tool === "string")
+      .map((tool) => tool.trim())
+      .filter((tool) => tool.length > 0);
+  } catch (error) {
+    console.error(
+      `[pi-host-sidecar] failed to parse project settings in ${cwd}: ${String(error)}`,
+    );
+    return [];
+  }
+}
+
+/**
+ * Deactivate the project's disabled tools, plus `extraDisabled` (e.g. the
+ * write tools of an untrusted project), on a freshly created session.
+ */
+export function applyProjectToolSettings(
+  session: AgentSession,
+  cwd: string,
+  extraDisabled: string[] = [],
+): void {
+  const disabled = new Set([...readDisabledTools(cwd), ...extraDisabled]);
+  if (disabled.size === 0) {
+    return;
+  }
+
+  const active = session
+    .getActiveToolNames()
+    .filter((tool) => !disabled.has(tool));
+  session.setActiveToolsByName(active);
+}
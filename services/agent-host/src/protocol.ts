@@ -1,32 +1,65 @@
-export type HostCommandType =
-  | "create_session"
-  | "close_session"
-  | "list_sessions"
-  | "prompt"
-  | "steer"
-  | "follow_up"
-  | "abort"
-  | "bash"
-  | "abort_bash"
-  | "abort_branch_summary"
-  | "get_messages"
-  | "get_session_tree"
-  | "navigate_session_tree"
-  | "get_state"
-  | "set_model"
-  | "cycle_model"
-  | "get_available_models"
-  | "get_registered_extensions"
-  | "get_commands"
-  | "set_thinking_level"
-  | "oauth_list_providers"
-  | "oauth_start_login"
-  | "oauth_poll_login"
-  | "oauth_submit_login_input"
-  | "oauth_cancel_login"
-  | "oauth_logout"
-  | "shutdown"
-  | "ping";
+export const HOST_PROTOCOL_VERSION = 1;
+
+export const HOST_COMMAND_TYPES = [
+  "create_session",
+  "close_session",
+  "list_sessions",
+  "prompt",
+  "steer",
+  "follow_up",
+  "abort",
+  "bash",
+  "abort_bash",
+  "abort_branch_summary",
+  "get_messages",
+  "get_session_tree",
+  "navigate_session_tree",
+  "get_state",
+  "set_model",
+  "cycle_model",
+  "get_available_models",
+  "get_registered_extensions",
+  "get_commands",
+  "set_thinking_level",
+  "oauth_list_providers",
+  "oauth_start_login",
+  "oauth_poll_login",
+  "oauth_submit_login_input",
+  "oauth_cancel_login",
+  "oauth_logout",
+  "get_capabilities",
+  "set_session_env",
+  "set_session_profile",
+  "shutdown",
+  "ping",
+] as const;
+
+export type HostCommandType = (typeof HOST_COMMAND_TYPES)[number];
+
+/** Outbound envelope and session event types this host build can emit. */
+export const HOST_EVENT_TYPES = [
+  "response",
+  "session_event",
+  "extension_ui_request",
+  "agent_start",
+  "agent_end",
+  "turn_start",
+  "turn_end",
+  "message_start",
+  "message_update",
+  "message_end",
+  "tool_execution_start",
+  "tool_execution_update",
+  "tool_execution_end",
+  "auto_compaction_start",
+  "auto_compaction_end",
+  "bash_execution_start",
+  "bash_execution_update",
+  "bash_execution_end",
+] as const;
+
+/** Optional features clients may need to probe for beyond the command list. */
+export const HOST_FEATURES = ["image_attachments", "thinking_levels"] as const;
 
 export interface HostCommandBase {
   id?: string;
@@ -40,6 +73,28 @@ export interface CreateSessionCommand extends HostCommandBase {
   provider?: string;
   modelId?: string;
   sessionFile?: string;
+  /** Environment overrides for the session's bash executions. */
+  env?: Record<string, string>;
+  /** Tools to deactivate on top of the project's `disabledTools`. */
+  tools?: string[];
+}
+
+export interface SetSessionEnvCommand extends HostCommandBase {
+  type: "set_session_env";
+  sessionId: string;
+  env: Record<string, string>;
+}
+
+export interface SetSessionProfileCommand extends HostCommandBase {
+  type: "set_session_profile";
+  sessionId: string;
+  /**
+   * Tools to deactivate; an empty list re-enables every tool and `"*"`
+   * deactivates all of them.
+   */
+  tools?: string[];
+  /** Text appended to the system prompt; empty removes a previous one. */
+  message?: string;
 }
 
 export interface HostImageAttachment {
@@ -97,6 +152,8 @@ export interface OAuthSubmitInputCommand extends HostCommandBase {
 
 export type HostCommand =
   | CreateSessionCommand
+  | SetSessionEnvCommand
+  | SetSessionProfileCommand
   | PromptCommand
   | SessionMessageCommand
   | NavigateSessionTreeCommand
@@ -122,6 +179,7 @@ export type HostCommand =
         | "oauth_list_providers"
         | "oauth_poll_login"
         | "oauth_cancel_login"
+        | "get_capabilities"
         | "shutdown"
         | "ping";
     });
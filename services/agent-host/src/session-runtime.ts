@@ -5,5 +5,12 @@ export interface HostedSession {
   cwd: string;
   createdAt: number;
   session: AgentSession;
+  /**
+   * Environment overrides applied to bash executions in this session, both
+   * the agent's bash tool and the `bash` RPC.
+   */
+  env: Record<string, string>;
+  /** Text a profile appended to the system prompt, if any. */
+  systemPromptSuffix?: string;
   unsubscribe: () => void;
 }
@@ -739,6 +739,65 @@ fn copy_runtime_assets(
     if external_koffi {
         copy_external_koffi_runtime(project_root, destination, target_os, target_triple);
     }
+
+    write_runtime_manifest(destination);
+}
+
+/// Runtime assets recorded in the manifest, as (asset name, path relative to the runtime dir).
+const RUNTIME_MANIFEST_ASSETS: [(&str, &str); 4] = [
+    ("photon-wasm", "photon_rs_bg.wasm"),
+    ("theme", "theme"),
+    ("export-html", "export-html"),
+    ("koffi", "node_modules/koffi"),
+];
+const RUNTIME_MANIFEST_FILE: &str = "runtime-manifest.tsv";
+
+fn collect_manifest_files(root: &Path, path: &Path, files: &mut Vec<(String, u64)>) {
+    let Ok(metadata) = fs::metadata(path) else {
+        return;
+    };
+
+    if metadata.is_dir() {
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            collect_manifest_files(root, &entry.path(), files);
+        }
+        return;
+    }
+
+    let relative = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    files.push((relative, metadata.len()));
+}
+
+/// Write `<asset>\t<relative path>\t<size>` lines for every file of the runtime
+/// assets, so the app can verify its installation later.
+fn write_runtime_manifest(destination: &Path) {
+    let mut lines = String::new();
+
+    for (asset, relative_path) in RUNTIME_MANIFEST_ASSETS {
+        let mut files = Vec::new();
+        collect_manifest_files(destination, &destination.join(relative_path), &mut files);
+        files.sort();
+
+        for (file, size) in files {
+            lines.push_str(&format!("{}\t{}\t{}\n", asset, file, size));
+        }
+    }
+
+    let manifest_path = destination.join(RUNTIME_MANIFEST_FILE);
+    fs::write(&manifest_path, lines).unwrap_or_else(|error| {
+        panic!(
+            "Failed to write runtime manifest {}: {}",
+            manifest_path.display(),
+            error
+        )
+    });
 }
 
 fn sidecar_destination_binary_path(
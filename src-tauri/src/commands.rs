@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use tauri::{AppHandle, State};
@@ -5,29 +6,155 @@ use tauri_plugin_opener::OpenerExt;
 use tokio::sync::Mutex;
 
 #[cfg(target_os = "linux")]
-use crate::logger;
+use crate::instance_lock::{self, InstanceStatus};
+use crate::logger::{self, LogDestination};
+use crate::messages::{self, Locale};
+use crate::preferences::{self, Preferences};
+use crate::sidecar::{
+    CodeBlock, EventPipelineStats, ParseFailuresResponse, SessionStateSnapshot, ToolOutputLines,
+};
 use crate::state::SidecarState;
 use crate::types::{RpcCommand, RpcImageAttachment, RpcResponse};
 use crate::utils::crypto_random_uuid;
-
+use crate::window_registry::{self, WindowSubscription};
+
+mod abort_all;
+mod action_sequence;
+mod activity_stats;
+mod batch_run;
+mod capabilities;
+mod change_summary;
+mod code_blocks;
+mod disk_space;
+mod feature_flags;
+mod git;
+mod git_hooks;
+mod idle_sessions;
+mod installation;
 mod oauth_and_models;
+mod operations;
+mod orphan_sidecars;
+mod paste_spill;
+mod pause;
+mod pinned_context;
+mod profiles;
+mod project_brief;
+mod project_metadata;
+mod project_scaffold;
+mod prompt_attachments;
+mod prompt_footer;
+mod provider_queue;
+mod quiet_hours;
+mod reauthenticate;
+mod runtime_cleanup;
+mod session_create;
+mod session_env;
+mod session_metadata;
 mod session_scopes;
+mod session_tail;
 mod settings;
+mod setup;
+mod sidecar_cli;
 mod sidecar_lifecycle;
-
-pub use session_scopes::{DeleteProjectSessionResponse, SessionProjectScopesResponse};
-pub use settings::EnabledModelsResponse;
-
+#[cfg(target_os = "windows")]
+mod toast_actions;
+mod turn_guard;
+mod turn_timeline;
+mod watch_rules;
+mod workspace_trust;
+
+pub use abort_all::AbortAllResponse;
+pub use action_sequence::{ActionSequenceResult, SequenceAction};
+pub use activity_stats::ActivityStats;
+pub use batch_run::{BatchOptions, BatchRunResult};
+pub use capabilities::SidecarCapabilities;
+pub use change_summary::ChangeSummary;
+pub use feature_flags::FeatureFlags;
+pub(crate) use git_hooks::spawn_git_hook_server;
+pub use git_hooks::{GitHook, GitHookResponse};
+pub(crate) use idle_sessions::spawn_idle_session_reaper;
+pub use installation::InstallationReport;
+pub(crate) use orphan_sidecars::{clear_sidecar_pid, sweep_orphaned_sidecars};
+pub use paste_spill::SpilledPaste;
+pub(crate) use pause::{admit_command, forget_suspension, send_or_hold_prompt};
+pub use pause::{HeldPrompt, PauseState, PauseStatus};
+pub use pinned_context::{PendingPinnedContext, PinnedContextFilesResponse};
+pub use profiles::{Profile, ProfilesResponse};
+pub use project_brief::ProjectBriefDraft;
+pub use project_scaffold::CreateProjectResponse;
+pub use prompt_footer::PromptFooterResponse;
+pub(crate) use provider_queue::send_queued_prompts;
+pub use provider_queue::QueuedPrompt;
+pub use quiet_hours::QuietHoursStatus;
+pub(crate) use runtime_cleanup::sweep_orphaned_runtimes;
+pub use session_create::CreateSessionOptions;
+pub use session_env::ProjectEnvResponse;
+pub use session_metadata::{SessionNotesResponse, SessionTagsResponse};
+pub use session_scopes::{
+    DeleteProjectScopeResponse, DeleteProjectSessionResponse, SessionListOptions,
+    SessionProjectScopesResponse,
+};
+pub use session_tail::SessionFileTail;
+pub use settings::{EnabledModelsResponse, ProjectToolsResponse, RestoreSettingsBackupResponse};
+pub use setup::{OnboardingState, OnboardingStep};
+pub use sidecar_cli::SidecarCliOutput;
+#[cfg(target_os = "windows")]
+pub(crate) use toast_actions::{notify_run_error, notify_run_finished};
+pub use turn_timeline::TurnTimeline;
+pub(crate) use watch_rules::spawn_watch_rule_runner;
+pub use watch_rules::{WatchRule, WatchRulesResponse};
+pub(crate) use workspace_trust::migrate_workspace_trust;
+pub use workspace_trust::ProjectTrust;
+
+/// Scan the session roots for project scopes and their session histories.
+/// With `op_id` the scan reports `operation-progress` and can be cancelled.
 #[tauri::command]
-pub fn list_session_project_scopes(
+pub async fn list_session_project_scopes(
+    app: AppHandle,
     seed_scopes: Option<Vec<String>>,
-) -> SessionProjectScopesResponse {
-    session_scopes::list_session_project_scopes(seed_scopes)
+    options: Option<SessionListOptions>,
+    op_id: Option<String>,
+) -> Result<SessionProjectScopesResponse, String> {
+    let options = options.unwrap_or_default();
+    let tagged_session_ids = match options.tag.as_deref().map(str::trim) {
+        Some(tag) if !tag.is_empty() => Some(session_metadata::session_ids_with_tag(&app, tag)?),
+        _ => None,
+    };
+    let operation = operations::begin_operation(&app, "session-scan", op_id)?;
+    let token = operation.token();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        session_scopes::list_session_project_scopes(
+            seed_scopes,
+            project_metadata::registered_project_dirs(&app),
+            options,
+            tagged_session_ids,
+            Some(&token),
+        )
+    })
+    .await
+    .map_err(|error| format!("Failed to scan session scopes: {}", error))?
 }
 
+/// Per-day session/turn/token counts for an activity heatmap (`range` like `90d`, `1y`).
 #[tauri::command]
-pub fn delete_project_scope(project_dir: String) -> Result<usize, String> {
-    session_scopes::delete_project_scope(project_dir)
+pub async fn get_activity_stats(
+    app: AppHandle,
+    range: Option<String>,
+) -> Result<ActivityStats, String> {
+    tauri::async_runtime::spawn_blocking(move || activity_stats::get_activity_stats(&app, range))
+        .await
+        .map_err(|error| format!("Failed to collect activity stats: {}", error))?
+}
+
+/// Delete a project scope's session files and directories. With `dry_run`,
+/// only list what would be deleted.
+#[tauri::command]
+pub fn delete_project_scope(
+    project_dir: String,
+    dry_run: Option<bool>,
+) -> Result<DeleteProjectScopeResponse, String> {
+    session_scopes::delete_project_scope(project_dir, dry_run.unwrap_or(false))
 }
 
 #[tauri::command]
@@ -39,6 +166,251 @@ pub fn delete_project_session(
     session_scopes::delete_project_session(project_dir, session_id, file_path)
 }
 
+/// New entries of a session JSONL file since `from_offset`, plus the offset
+/// to continue from; for following a transcript the sidecar is writing.
+#[tauri::command]
+pub fn tail_session_file(
+    file_path: String,
+    from_offset: Option<u64>,
+) -> Result<SessionFileTail, String> {
+    session_tail::tail_session_file(file_path, from_offset)
+}
+
+/// Get the user's markdown notes for a session (empty when none were saved).
+#[tauri::command]
+pub fn get_session_notes(
+    app: AppHandle,
+    session_id: String,
+) -> Result<SessionNotesResponse, String> {
+    session_metadata::get_session_notes(&app, session_id)
+}
+
+/// Save markdown notes for a session. Blank notes clear the entry.
+#[tauri::command]
+pub fn set_session_notes(
+    app: AppHandle,
+    session_id: String,
+    markdown: String,
+) -> Result<SessionNotesResponse, String> {
+    session_metadata::set_session_notes(&app, session_id, markdown)
+}
+
+/// Replace the tags of a persisted session (used by the session list tag filter).
+#[tauri::command]
+pub fn set_session_tags(
+    app: AppHandle,
+    session_id: String,
+    tags: Vec<String>,
+) -> Result<SessionTagsResponse, String> {
+    session_metadata::set_session_tags(&app, session_id, tags)
+}
+
+/// List files pinned as context for a project, with change/existence status.
+#[tauri::command]
+pub fn list_pinned_context_files(
+    app: AppHandle,
+    project_dir: String,
+) -> Result<PinnedContextFilesResponse, String> {
+    pinned_context::list_pinned_context_files(&app, project_dir)
+}
+
+/// Pin a project file so it is attached as context when sessions are created.
+#[tauri::command]
+pub fn pin_context_file(
+    app: AppHandle,
+    project_dir: String,
+    file_path: String,
+) -> Result<PinnedContextFilesResponse, String> {
+    pinned_context::pin_context_file(&app, project_dir, file_path)
+}
+
+#[tauri::command]
+pub fn unpin_context_file(
+    app: AppHandle,
+    project_dir: String,
+    file_path: String,
+) -> Result<PinnedContextFilesResponse, String> {
+    pinned_context::unpin_context_file(&app, project_dir, file_path)
+}
+
+/// Environment overrides applied to tool executions in a project's sessions.
+#[tauri::command]
+pub fn get_project_env(
+    app: AppHandle,
+    project_dir: String,
+) -> Result<std::collections::BTreeMap<String, String>, String> {
+    session_env::get_project_env(&app, project_dir)
+}
+
+/// Replace a project's env overrides and apply them to its open sessions.
+#[tauri::command]
+pub async fn set_project_env(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    project_dir: String,
+    env: std::collections::BTreeMap<String, String>,
+) -> Result<ProjectEnvResponse, String> {
+    session_env::set_project_env(&app, state.inner(), project_dir, env).await
+}
+
+/// Whether prompts in a project get the environment footer (git branch,
+/// dirty files, OS, model).
+#[tauri::command]
+pub fn get_prompt_footer(
+    app: AppHandle,
+    project_dir: String,
+) -> Result<PromptFooterResponse, String> {
+    prompt_footer::get_prompt_footer(&app, project_dir)
+}
+
+/// Turn the environment footer on or off for a project's prompts.
+#[tauri::command]
+pub fn set_prompt_footer(
+    app: AppHandle,
+    project_dir: String,
+    enabled: bool,
+) -> Result<PromptFooterResponse, String> {
+    prompt_footer::set_prompt_footer(&app, project_dir, enabled)
+}
+
+/// Inspect a project and draft an AGENTS.md for it. Nothing is written until
+/// the user confirms via `write_project_brief`.
+#[tauri::command]
+pub async fn generate_project_brief(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    project_dir: String,
+) -> Result<ProjectBriefDraft, String> {
+    project_brief::generate_project_brief(app, state.inner(), project_dir).await
+}
+
+/// Write a reviewed project brief to `<project_dir>/AGENTS.md`.
+#[tauri::command]
+pub fn write_project_brief(
+    project_dir: String,
+    content: String,
+    overwrite: Option<bool>,
+) -> Result<String, String> {
+    project_brief::write_project_brief(project_dir, content, overwrite)
+}
+
+/// Scaffold a new project (directory, optional git repo, starter pi settings
+/// and AGENTS.md) and open a session in it.
+#[tauri::command]
+pub async fn create_project(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    path: String,
+    template: Option<String>,
+    git_init: Option<bool>,
+) -> Result<CreateProjectResponse, String> {
+    project_scaffold::create_project(app, state.inner(), path, template, git_init).await
+}
+
+/// Report which RPC commands, events and features the running sidecar supports.
+#[tauri::command]
+pub async fn get_sidecar_capabilities(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+) -> Result<SidecarCapabilities, String> {
+    capabilities::get_sidecar_capabilities(state.inner()).await
+}
+
+/// Optional backend subsystems this build has and which are active.
+#[tauri::command]
+pub async fn get_feature_flags() -> FeatureFlags {
+    feature_flags::get_feature_flags()
+}
+
+/// First-run onboarding progress, merged with what the backend can observe.
+#[tauri::command]
+pub async fn get_onboarding_state(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+) -> Result<OnboardingState, String> {
+    setup::get_onboarding_state(&app, state.inner()).await
+}
+
+/// Mark an onboarding step as done (completed or skipped by the user).
+#[tauri::command]
+pub fn complete_onboarding_step(
+    app: AppHandle,
+    step: OnboardingStep,
+) -> Result<OnboardingState, String> {
+    setup::complete_onboarding_step(&app, step)
+}
+
+/// Whether this window's instance owns the shared app data. Instances that
+/// do not reject settings and store writes with an `instance-conflict:` error.
+#[tauri::command]
+pub fn get_instance_status() -> InstanceStatus {
+    instance_lock::status()
+}
+
+/// Pick the language of backend error messages from a BCP 47 tag such as
+/// `de-DE`. Returns the locale in effect; unsupported tags fall back to `en`.
+#[tauri::command]
+pub fn set_locale(locale: String) -> Locale {
+    messages::set_locale(&locale)
+}
+
+/// Limit the calling window's `agent-event` stream to `session_ids`. The
+/// subscription is dropped when the window closes.
+#[tauri::command]
+pub fn subscribe_window_events(
+    window: tauri::WebviewWindow,
+    session_ids: Vec<String>,
+) -> WindowSubscription {
+    window_registry::subscribe(window.label(), session_ids)
+}
+
+/// Let the calling window receive every session's events again.
+#[tauri::command]
+pub fn unsubscribe_window_events(window: tauri::WebviewWindow) -> bool {
+    window_registry::unsubscribe(window.label())
+}
+
+#[tauri::command]
+pub fn list_window_subscriptions() -> Vec<WindowSubscription> {
+    window_registry::subscriptions()
+}
+
+/// Check the sidecar binary and runtime assets against the build manifest.
+#[tauri::command]
+pub fn verify_installation(app: AppHandle) -> Result<InstallationReport, String> {
+    installation::verify_installation(&app)
+}
+
+/// Re-extract the sidecar runtime (Linux) and return the new report.
+#[tauri::command]
+pub async fn repair_installation(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+) -> Result<InstallationReport, String> {
+    installation::repair_installation(&app, state.inner()).await
+}
+
+/// Install a git hook in the project's repository that sends the staged diff
+/// to graphone for review and prints the result.
+#[tauri::command]
+pub fn install_git_hook(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    project_dir: String,
+    hook: GitHook,
+) -> Result<GitHookResponse, String> {
+    git_hooks::install_git_hook(&app, state.inner(), project_dir, hook)
+}
+
+/// Remove a git hook installed by `install_git_hook`.
+#[tauri::command]
+pub fn uninstall_git_hook(
+    app: AppHandle,
+    project_dir: String,
+    hook: GitHook,
+) -> Result<GitHookResponse, String> {
+    git_hooks::uninstall_git_hook(&app, project_dir, hook)
+}
+
 /// Check whether a project directory currently exists on disk.
 #[tauri::command]
 pub fn path_exists(path: String) -> bool {
@@ -120,10 +492,142 @@ pub fn set_enabled_models(
     settings::set_enabled_models(patterns, scope, project_dir)
 }
 
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Result<ProfilesResponse, String> {
+    profiles::list_profiles(&app)
+}
+
+/// Create or replace a named profile (model, thinking level, tools, system prompt).
+#[tauri::command]
+pub fn save_profile(app: AppHandle, profile: Profile) -> Result<ProfilesResponse, String> {
+    profiles::save_profile(&app, profile)
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, name: String) -> Result<ProfilesResponse, String> {
+    profiles::delete_profile(&app, name)
+}
+
+/// Switch an open session to a saved profile in one action.
+#[tauri::command]
+pub async fn apply_profile(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: String,
+    name: String,
+) -> Result<Profile, String> {
+    profiles::apply_profile(&app, state.inner(), session_id, name).await
+}
+
+/// Tools disabled for a project via its pi settings.
+#[tauri::command]
+pub fn get_project_tools(project_dir: String) -> Result<ProjectToolsResponse, String> {
+    settings::get_project_tools(project_dir)
+}
+
+/// Whether a project is trusted, and the tools its sessions lack until it is.
+#[tauri::command]
+pub fn get_project_trust(app: AppHandle, project_dir: String) -> Result<ProjectTrust, String> {
+    workspace_trust::get_project_trust(&app, project_dir)
+}
+
+/// Trust a project: its sessions get write and shell tools, and watch rules
+/// and git-hook reviews may prompt in it.
+#[tauri::command]
+pub async fn trust_project(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    project_dir: String,
+) -> Result<ProjectTrust, String> {
+    workspace_trust::trust_project(&app, state.inner(), project_dir).await
+}
+
+/// Disable agent tools (e.g. `bash`, `write`) for new sessions in a project.
+#[tauri::command]
+pub fn set_project_tools(
+    project_dir: String,
+    disabled_tools: Vec<String>,
+) -> Result<ProjectToolsResponse, String> {
+    settings::set_project_tools(project_dir, disabled_tools)
+}
+
+/// Restore the most recent backup of a pi settings file.
+///
+/// - scope: "auto" (default), "project", or "global".
+#[tauri::command]
+pub fn restore_settings_backup(
+    scope: Option<String>,
+    project_dir: Option<String>,
+) -> Result<RestoreSettingsBackupResponse, String> {
+    settings::restore_settings_backup(scope, project_dir)
+}
+
+/// Get graphone backend preferences.
+/// Redirect the backend log at runtime: a file path, `stdout`, `disabled`,
+/// or `default` to re-resolve `GRAPHONE_LOG_PATH` and the platform default.
+#[tauri::command]
+pub fn set_log_destination(destination: String) -> Result<LogDestination, String> {
+    logger::set_destination(LogDestination::parse(&destination)?)
+}
+
+#[tauri::command]
+pub fn get_preferences() -> Preferences {
+    Preferences::clone(&preferences::current())
+}
+
+/// Check and normalize preferences, whether they come from `set_preferences`
+/// or from an edit of the store file.
+pub(crate) fn validate_preferences(mut preferences: Preferences) -> Result<Preferences, String> {
+    preferences.sidecar_args = crate::sidecar::validate_sidecar_args(&preferences.sidecar_args)?;
+    preferences.time_zone = preferences
+        .time_zone
+        .as_deref()
+        .filter(|time_zone| !time_zone.trim().is_empty())
+        .map(session_scopes::validate_time_zone)
+        .transpose()?;
+    preferences.max_event_chars =
+        crate::sidecar::validate_max_event_chars(preferences.max_event_chars)?;
+    preferences.quiet_hours = preferences
+        .quiet_hours
+        .map(quiet_hours::validate_quiet_hours)
+        .transpose()?;
+    Ok(preferences)
+}
+
+/// Persist graphone backend preferences. Changes apply immediately.
+#[tauri::command]
+pub fn set_preferences(app: AppHandle, preferences: Preferences) -> Result<Preferences, String> {
+    preferences::save(&app, validate_preferences(preferences)?)
+}
+
+/// Whether quiet hours or a snooze currently silence run notifications.
+#[tauri::command]
+pub fn get_quiet_hours_status() -> QuietHoursStatus {
+    quiet_hours::quiet_hours_status()
+}
+
+/// Silence run notifications for `duration_minutes`; 0 ends the snooze.
+/// Unlike quiet hours, a snooze does not defer watch-rule prompts.
+#[tauri::command]
+pub fn snooze_notifications(duration_minutes: u64) -> QuietHoursStatus {
+    quiet_hours::snooze_notifications(duration_minutes)
+}
+
 pub async fn shutdown_sidecar_gracefully(state: &Arc<Mutex<SidecarState>>) -> Result<(), String> {
     sidecar_lifecycle::shutdown_sidecar_gracefully(state).await
 }
 
+/// Create or resume a session. See `CreateSessionOptions` for the choices.
+#[tauri::command]
+pub async fn create_session(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    options: CreateSessionOptions,
+) -> Result<RpcResponse, String> {
+    session_create::create_session(app, state.inner(), options).await
+}
+
+/// Compatibility wrapper for `create_session` with positional arguments.
 #[tauri::command]
 pub async fn create_agent(
     app: AppHandle,
@@ -132,14 +636,19 @@ pub async fn create_agent(
     provider: Option<String>,
     model: Option<String>,
     session_file: Option<String>,
+    use_git_root: Option<bool>,
 ) -> Result<RpcResponse, String> {
-    sidecar_lifecycle::create_session_internal(
+    session_create::open_session(
         app,
         state.inner(),
-        project_dir,
-        provider,
-        model,
-        session_file,
+        CreateSessionOptions {
+            project_dir,
+            provider,
+            model_id: model,
+            inherit_model_from: None,
+            session_file,
+            use_git_root,
+        },
     )
     .await
 }
@@ -175,6 +684,115 @@ pub async fn read_clipboard_image() -> Result<Option<RpcImageAttachment>, String
     }
 }
 
+/// Read image files copied in the OS file manager (file URI lists / `CF_HDROP`)
+/// as prompt attachments. Non-image files are ignored.
+#[tauri::command]
+pub async fn read_clipboard_files() -> Result<Vec<RpcImageAttachment>, String> {
+    #[cfg(target_os = "linux")]
+    let paths = crate::platform::linux_clipboard::read_clipboard_file_paths_linux();
+
+    #[cfg(target_os = "windows")]
+    let paths = crate::platform::windows_clipboard::read_clipboard_file_paths_windows();
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    let paths = Vec::new();
+
+    Ok(crate::platform::clipboard_files::read_image_attachments(
+        &paths,
+    ))
+}
+
+/// Read image files referenced by a pasted `text/uri-list` payload.
+#[tauri::command]
+pub fn read_image_files_from_uri_list(uri_list: String) -> Vec<RpcImageAttachment> {
+    let paths = crate::platform::clipboard_files::parse_file_uri_list(&uri_list);
+    crate::platform::clipboard_files::read_image_attachments(&paths)
+}
+
+/// Paste length (chars) above which the UI should offer to spill it to a file.
+#[tauri::command]
+pub fn get_paste_spill_threshold() -> usize {
+    paste_spill::paste_spill_threshold()
+}
+
+/// Write a large pasted prompt to a file in the session's project and return
+/// a short reference to use in the prompt instead.
+#[tauri::command]
+pub async fn spill_paste_to_file(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: String,
+    text: String,
+) -> Result<SpilledPaste, String> {
+    paste_spill::spill_paste_to_file(state.inner(), session_id, text).await
+}
+
+/// Read a window of lines from a tool output that was too large to send in full.
+#[tauri::command]
+pub async fn read_tool_output_lines(
+    app: AppHandle,
+    session_id: String,
+    tool_call_id: String,
+    start: u64,
+    count: usize,
+) -> Result<ToolOutputLines, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::sidecar::read_tool_output_lines(&app, &session_id, tool_call_id, start, count)
+    })
+    .await
+    .map_err(|error| format!("Failed to read tool output: {}", error))?
+}
+
+/// Load an `agent-event` payload that was too large to emit and was
+/// referenced by an `agent_event_overflow` envelope instead.
+#[tauri::command]
+pub async fn read_event_overflow(app: AppHandle, overflow_id: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::sidecar::read_event_overflow(&app, &overflow_id)
+    })
+    .await
+    .map_err(|error| format!("Failed to read overflow payload: {}", error))?
+}
+
+/// Run the bundled pi CLI once with allow-listed arguments (`--version`,
+/// `--help`, `--list-models`, `--export <session file> [name.html]`) and capture
+/// its output. Exports land in the downloads dir.
+/// With `op_id` the run can be cancelled, which kills the process.
+#[tauri::command]
+pub async fn run_sidecar_cli(
+    app: AppHandle,
+    args: Vec<String>,
+    op_id: Option<String>,
+) -> Result<SidecarCliOutput, String> {
+    let operation = operations::begin_operation(&app, "sidecar-cli", op_id)?;
+    sidecar_cli::run_sidecar_cli(&app, args, &operation.token()).await
+}
+
+/// Cancel a long-running command started with this `op_id` (session scan,
+/// sidecar CLI run such as an export, batch run). Returns whether it was
+/// still running. A cancelled batch returns its outcomes with `cancelled`
+/// set; the other commands fail with `operation-cancelled`.
+#[tauri::command]
+pub fn cancel_operation(op_id: String) -> bool {
+    operations::cancel_operation(&op_id)
+}
+
+/// Event pipeline counters (received/emitted, coalescing savings, drops, queue
+/// depths) for a performance HUD. `reset` starts a new counting window.
+#[tauri::command]
+pub async fn get_event_pipeline_stats(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    reset: Option<bool>,
+) -> Result<EventPipelineStats, String> {
+    Ok(crate::sidecar::event_pipeline_stats(state.inner(), reset.unwrap_or(false)).await)
+}
+
+/// Sidecar stdout lines that failed to parse, newest first, along with the
+/// quarantine file they were also written to.
+#[tauri::command]
+pub async fn get_parse_failures(limit: Option<usize>) -> Result<ParseFailuresResponse, String> {
+    Ok(crate::sidecar::parse_failures(limit))
+}
+
 fn require_session_id(session_id: String, command: &str) -> Result<String, String> {
     let trimmed = session_id.trim().to_string();
     if trimmed.is_empty() {
@@ -194,14 +812,146 @@ fn require_non_empty_string(value: String, field: &str) -> Result<String, String
 }
 
 /// Send a prompt to the agent
+///
+/// Pinned project context prepared at session creation is prepended to the
+/// first prompt sent to that session.
 #[tauri::command]
 pub async fn send_prompt(
+    app: AppHandle,
     state: State<'_, Arc<Mutex<SidecarState>>>,
     prompt: String,
     session_id: String,
     images: Option<Vec<RpcImageAttachment>>,
 ) -> Result<(), String> {
     let session_id = require_session_id(session_id, "prompt")?;
+    turn_guard::begin_turn(state.inner(), &session_id).await?;
+
+    let result = dispatch_prompt(&app, state.inner(), prompt, &session_id, images).await;
+    if result.is_err() {
+        turn_guard::cancel_turn(state.inner(), &session_id).await;
+    }
+
+    result
+}
+
+/// Run an ordered list of built-in actions (create session, set model, set
+/// thinking level, send templated prompt), rolling back on failure.
+#[tauri::command]
+pub async fn run_action_sequence(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: Option<String>,
+    actions: Vec<SequenceAction>,
+    variables: Option<HashMap<String, String>>,
+) -> Result<ActionSequenceResult, String> {
+    action_sequence::run_action_sequence(
+        &app,
+        state.inner(),
+        session_id,
+        actions,
+        variables.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Run a list of prompts against a project, sequentially in one session or
+/// across parallel sessions, emitting `batch-progress` events. With `op_id`
+/// the batch can be cancelled: the running prompts are aborted and the rest
+/// skipped.
+#[tauri::command]
+pub async fn run_batch(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    project_dir: String,
+    prompts: Vec<String>,
+    options: Option<BatchOptions>,
+    op_id: Option<String>,
+) -> Result<BatchRunResult, String> {
+    let operation = operations::begin_operation(&app, "batch-run", op_id)?;
+    batch_run::run_batch(
+        &app,
+        state.inner(),
+        project_dir,
+        prompts,
+        options.unwrap_or_default(),
+        operation.token(),
+    )
+    .await
+}
+
+/// List a project's watch rules.
+#[tauri::command]
+pub fn list_watch_rules(app: AppHandle, project_dir: String) -> Result<WatchRulesResponse, String> {
+    watch_rules::list_watch_rules(&app, project_dir)
+}
+
+/// Create or update a watch rule: a glob whose changes, after a debounce,
+/// send a templated prompt to a session.
+#[tauri::command]
+pub fn save_watch_rule(
+    app: AppHandle,
+    project_dir: String,
+    rule: WatchRule,
+) -> Result<WatchRulesResponse, String> {
+    watch_rules::save_watch_rule(&app, project_dir, rule)
+}
+
+#[tauri::command]
+pub fn delete_watch_rule(
+    app: AppHandle,
+    project_dir: String,
+    rule_id: String,
+) -> Result<WatchRulesResponse, String> {
+    watch_rules::delete_watch_rule(&app, project_dir, rule_id)
+}
+
+#[tauri::command]
+pub fn set_watch_rule_enabled(
+    app: AppHandle,
+    project_dir: String,
+    rule_id: String,
+    enabled: bool,
+) -> Result<WatchRulesResponse, String> {
+    watch_rules::set_watch_rule_enabled(&app, project_dir, rule_id, enabled)
+}
+
+/// Summarize the session's last run as a PR title and description, and write
+/// its file changes to a combined patch.
+#[tauri::command]
+pub async fn generate_change_summary(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: String,
+) -> Result<ChangeSummary, String> {
+    change_summary::generate_change_summary(&app, state.inner(), session_id).await
+}
+
+/// Code blocks from the session's assistant messages, newest first.
+#[tauri::command]
+pub async fn list_code_blocks(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: String,
+) -> Result<Vec<CodeBlock>, String> {
+    code_blocks::list_code_blocks(state.inner(), session_id).await
+}
+
+/// Copy a code block returned by `list_code_blocks` to the clipboard.
+#[tauri::command]
+pub async fn copy_code_block(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    id: String,
+) -> Result<CodeBlock, String> {
+    code_blocks::copy_code_block(state.inner(), id).await
+}
+
+async fn dispatch_prompt(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    prompt: String,
+    session_id: &str,
+    images: Option<Vec<RpcImageAttachment>>,
+) -> Result<(), String> {
+    let session_id = session_id.to_string();
 
     let images = images
         .map(|attachments| {
@@ -215,11 +965,29 @@ pub async fn send_prompt(
                 .collect::<Vec<_>>()
         })
         .filter(|attachments| !attachments.is_empty());
+    let images =
+        prompt_attachments::filter_images_for_model(app, state, &session_id, &prompt, images)
+            .await?;
+    let images = prompt_attachments::enforce_attachment_budget(images).await?;
+
+    let pinned_context = {
+        let mut state_guard = state.lock().await;
+        state_guard.pending_pinned_context.remove(&session_id)
+    };
+
+    let prompt = match prompt_footer::prompt_footer(app, state, &session_id, &prompt).await {
+        Some(footer) => format!("{}\n\n{}", prompt, footer),
+        None => prompt,
+    };
+    let prompt = match pinned_context.as_ref() {
+        Some(context) => format!("{}\n\n{}", context.block, prompt),
+        None => prompt,
+    };
 
     let cmd = RpcCommand {
         id: Some(crypto_random_uuid()),
         r#type: "prompt".to_string(),
-        session_id: Some(session_id),
+        session_id: Some(session_id.clone()),
         cwd: None,
         message: Some(prompt),
         provider: None,
@@ -228,9 +996,29 @@ pub async fn send_prompt(
         session_file: None,
         level: None,
         images,
+        env: None,
+        tools: None,
     };
 
-    crate::sidecar::RpcClient::send_command(state.inner(), cmd).await
+    {
+        let mut state_guard = state.lock().await;
+        state_guard
+            .last_prompts
+            .insert(session_id.clone(), cmd.clone());
+        state_guard.model_fallback_retried.remove(&session_id);
+    }
+
+    let result = pause::send_or_hold_prompt(app, state, cmd, pinned_context.clone()).await;
+
+    if let (Err(_), Some(context)) = (&result, pinned_context) {
+        // Keep the context for the next attempt.
+        let mut state_guard = state.lock().await;
+        state_guard
+            .pending_pinned_context
+            .insert(session_id, context);
+    }
+
+    result.map(|_| ())
 }
 
 #[tauri::command]
@@ -261,6 +1049,8 @@ pub async fn send_bash_command(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     sidecar_lifecycle::send_command_with_response(state.inner(), cmd, 3600).await
@@ -269,10 +1059,12 @@ pub async fn send_bash_command(
 /// Abort the current agent operation
 #[tauri::command]
 pub async fn abort_agent(
+    app: AppHandle,
     state: State<'_, Arc<Mutex<SidecarState>>>,
     session_id: String,
 ) -> Result<(), String> {
     let session_id = require_session_id(session_id, "abort")?;
+    provider_queue::drop_queued_prompt(&app, state.inner(), &session_id).await;
 
     let cmd = RpcCommand {
         id: Some(crypto_random_uuid()),
@@ -286,11 +1078,46 @@ pub async fn abort_agent(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     crate::sidecar::RpcClient::send_command(state.inner(), cmd).await
 }
 
+/// Abort every active session at once (panic button).
+#[tauri::command]
+pub async fn abort_all_agents(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+) -> Result<AbortAllResponse, String> {
+    abort_all::abort_all_agents(state.inner()).await
+}
+
+/// Hold new prompts until `resume_agents`, optionally suspending the sidecar process.
+#[tauri::command]
+pub async fn pause_agents(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    suspend_process: Option<bool>,
+) -> Result<PauseStatus, String> {
+    pause::pause_agents(state.inner(), suspend_process.unwrap_or(false)).await
+}
+
+/// Resume agent activity and send any prompts held while paused.
+#[tauri::command]
+pub async fn resume_agents(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+) -> Result<PauseStatus, String> {
+    pause::resume_agents(&app, state.inner()).await
+}
+
+#[tauri::command]
+pub async fn get_pause_status(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+) -> Result<PauseStatus, String> {
+    Ok(pause::get_pause_status(state.inner()).await)
+}
+
 /// Abort in-progress branch summarization for tree navigation.
 #[tauri::command]
 pub async fn abort_branch_summary(
@@ -311,6 +1138,8 @@ pub async fn abort_branch_summary(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     crate::sidecar::RpcClient::send_command(state.inner(), cmd).await
@@ -335,6 +1164,8 @@ pub async fn abort_bash(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     sidecar_lifecycle::send_command_with_response(state.inner(), cmd, 5).await
@@ -360,6 +1191,8 @@ pub async fn get_messages(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     sidecar_lifecycle::send_command_with_response(state.inner(), cmd, 5).await
@@ -385,6 +1218,8 @@ pub async fn get_session_tree(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     sidecar_lifecycle::send_command_with_response(state.inner(), cmd, 5).await
@@ -418,6 +1253,8 @@ pub async fn navigate_session_tree(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     let timeout_secs = if summarize { 3600 } else { 10 };
@@ -444,11 +1281,39 @@ pub async fn get_state(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     sidecar_lifecycle::send_command_with_response(state.inner(), cmd, 5).await
 }
 
+/// Typed session state, served from the cache kept current by responses and
+/// events; `refresh` forces a `get_state` round trip.
+#[tauri::command]
+pub async fn get_session_state(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: String,
+    refresh: Option<bool>,
+) -> Result<SessionStateSnapshot, String> {
+    let session_id = require_session_id(session_id, "get_session_state")?;
+    crate::sidecar::session_state_snapshot(state.inner(), &session_id, refresh.unwrap_or(false))
+        .await
+}
+
+/// Ordered timeline of one turn (the latest when `turn` is unset), rebuilt
+/// from the session file: text, thinking, tool calls with durations, files
+/// touched and errors.
+#[tauri::command]
+pub async fn get_turn_timeline(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: String,
+    turn: Option<usize>,
+) -> Result<TurnTimeline, String> {
+    let session_id = require_session_id(session_id, "get_turn_timeline")?;
+    turn_timeline::get_turn_timeline(state.inner(), session_id, turn).await
+}
+
 #[tauri::command]
 pub async fn get_available_models(
     state: State<'_, Arc<Mutex<SidecarState>>>,
@@ -476,6 +1341,8 @@ pub async fn get_registered_extensions(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     sidecar_lifecycle::send_command_with_response(state.inner(), cmd, 5).await
@@ -500,6 +1367,8 @@ pub async fn get_commands(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     sidecar_lifecycle::send_command_with_response(state.inner(), cmd, 5).await
@@ -547,6 +1416,17 @@ pub async fn cancel_oauth_login(
     oauth_and_models::cancel_oauth_login(state.inner(), session_id).await
 }
 
+/// Log in again to the provider whose credentials expired mid-run, then
+/// resubmit the session's failed prompt.
+#[tauri::command]
+pub async fn reauthenticate_and_retry(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: String,
+) -> Result<(), String> {
+    reauthenticate::reauthenticate_and_retry(&app, state.inner(), session_id).await
+}
+
 #[tauri::command]
 pub async fn logout_oauth_provider(
     state: State<'_, Arc<Mutex<SidecarState>>>,
@@ -575,6 +1455,14 @@ pub async fn set_thinking_level(
     oauth_and_models::set_thinking_level(state.inner(), level, session_id).await
 }
 
+#[tauri::command]
+pub async fn get_supported_thinking_levels(
+    state: State<'_, Arc<Mutex<SidecarState>>>,
+    session_id: String,
+) -> Result<RpcResponse, String> {
+    oauth_and_models::get_supported_thinking_levels(state.inner(), session_id).await
+}
+
 #[tauri::command]
 pub async fn cycle_model(
     state: State<'_, Arc<Mutex<SidecarState>>>,
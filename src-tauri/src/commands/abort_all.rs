@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use super::sidecar_lifecycle::{list_agents, send_command_with_response};
+use crate::logger;
+use crate::sidecar::RpcClient;
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::crypto_random_uuid;
+
+const ABORT_TIMEOUT_SECS: u64 = 3;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAbortResult {
+    pub session_id: String,
+    /// The sidecar acknowledged the abort.
+    pub aborted: bool,
+    /// The abort went unacknowledged, so bash and branch-summary aborts were sent too.
+    pub forced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbortAllResponse {
+    pub sessions: Vec<SessionAbortResult>,
+}
+
+fn session_command(command_type: &str, session_id: &str) -> RpcCommand {
+    RpcCommand {
+        id: Some(crypto_random_uuid()),
+        r#type: command_type.to_string(),
+        session_id: Some(session_id.to_string()),
+        cwd: None,
+        message: None,
+        provider: None,
+        model_id: None,
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    }
+}
+
+async fn active_session_ids(state: &Arc<Mutex<SidecarState>>) -> Vec<String> {
+    let listed = list_agents(state).await.ok().and_then(|response| {
+        let sessions = response.data?.get("sessions")?.as_array()?.clone();
+        Some(
+            sessions
+                .iter()
+                .filter_map(|session| session.get("sessionId")?.as_str().map(str::to_string))
+                .collect::<Vec<_>>(),
+        )
+    });
+
+    let mut session_ids = match listed {
+        Some(session_ids) => session_ids,
+        None => {
+            let state_guard = state.lock().await;
+            state_guard.session_cwds.keys().cloned().collect()
+        }
+    };
+    session_ids.sort();
+    session_ids.dedup();
+    session_ids
+}
+
+pub(super) async fn abort_session(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: String,
+) -> SessionAbortResult {
+    let error = match send_command_with_response(
+        state,
+        session_command("abort", &session_id),
+        ABORT_TIMEOUT_SECS,
+    )
+    .await
+    {
+        Ok(response) if response.success => {
+            return SessionAbortResult {
+                session_id,
+                aborted: true,
+                forced: false,
+                error: None,
+            };
+        }
+        Ok(response) => response.error.unwrap_or_else(|| "abort failed".to_string()),
+        Err(error) => error,
+    };
+
+    logger::log(format!(
+        "abort for {} not acknowledged ({}); forcing",
+        session_id, error
+    ));
+
+    // Kill whatever may still be running: user bash and branch summarization
+    // are aborted separately from the agent run.
+    for command_type in ["abort_bash", "abort_branch_summary", "abort"] {
+        let _ = RpcClient::send_command(state, session_command(command_type, &session_id)).await;
+    }
+
+    state
+        .lock()
+        .await
+        .prompts_awaiting_start
+        .remove(&session_id);
+
+    SessionAbortResult {
+        session_id,
+        aborted: false,
+        forced: true,
+        error: Some(error),
+    }
+}
+
+/// Abort every active session in the sidecar (panic button).
+///
+/// Aborts run concurrently; sessions that don't acknowledge in time get the
+/// bash and branch-summary aborts as well.
+pub async fn abort_all_agents(
+    state: &Arc<Mutex<SidecarState>>,
+) -> Result<AbortAllResponse, String> {
+    let session_ids = active_session_ids(state).await;
+    logger::log(format!("abort_all_agents: {} sessions", session_ids.len()));
+
+    let dropped = std::mem::take(&mut state.lock().await.queued_prompts);
+    if !dropped.is_empty() {
+        logger::log(format!(
+            "abort_all_agents: dropped {} queued prompts",
+            dropped.len()
+        ));
+    }
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for session_id in session_ids {
+        let state = state.clone();
+        tasks.spawn(async move { abort_session(&state, session_id).await });
+    }
+
+    let mut sessions = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(session) => sessions.push(session),
+            Err(error) => logger::log(format!("abort_all_agents task failed: {}", error)),
+        }
+    }
+    sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+
+    Ok(AbortAllResponse { sessions })
+}
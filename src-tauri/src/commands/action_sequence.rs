@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::oauth_and_models::{set_model, set_thinking_level};
+use super::sidecar_lifecycle::{close_agent, create_session_internal, send_command_with_response};
+use super::{dispatch_prompt, turn_guard};
+use crate::logger;
+use crate::preferences::ModelRef;
+use crate::state::SidecarState;
+use crate::types::{RpcCommand, RpcResponse};
+use crate::utils::crypto_random_uuid;
+
+/// One built-in step of an action sequence.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SequenceAction {
+    /// Open a new session; only allowed as the first action.
+    CreateSession {
+        project_dir: String,
+        provider: Option<String>,
+        model_id: Option<String>,
+    },
+    SetModel {
+        provider: String,
+        model_id: String,
+    },
+    SetThinkingLevel {
+        level: String,
+    },
+    /// Send a prompt with `{{name}}` placeholders filled from the sequence
+    /// variables (plus `projectDir`); only allowed as the last action.
+    SendPrompt {
+        template: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionSequenceResult {
+    pub session_id: String,
+    pub created_session: bool,
+    pub completed_actions: usize,
+}
+
+/// Session settings captured before the sequence changes them.
+pub(super) struct SessionSnapshot {
+    pub(super) model: Option<ModelRef>,
+    pub(super) thinking_level: Option<String>,
+}
+
+/// Substitute `{{name}}` placeholders, failing on any name without a value.
+pub(super) fn render_template(
+    template: &str,
+    variables: &HashMap<String, String>,
+) -> Result<String, String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            return Err("Unclosed `{{` in prompt template".to_string());
+        };
+
+        let name = after_open[..end].trim();
+        let value = variables
+            .get(name)
+            .ok_or_else(|| format!("Unknown template variable: {}", name))?;
+        rendered.push_str(value);
+        rest = &after_open[end + 2..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Reject malformed sequences before anything is executed, and render the prompt.
+fn validate_sequence(
+    actions: &[SequenceAction],
+    session_id: Option<&str>,
+    project_dir: Option<&str>,
+    variables: &HashMap<String, String>,
+) -> Result<Option<String>, String> {
+    if actions.is_empty() {
+        return Err("actions cannot be empty".to_string());
+    }
+
+    let mut prompt = None;
+    let last_index = actions.len() - 1;
+
+    for (index, action) in actions.iter().enumerate() {
+        match action {
+            SequenceAction::CreateSession { project_dir, .. } => {
+                if index != 0 {
+                    return Err("createSession must be the first action".to_string());
+                }
+                if session_id.is_some() {
+                    return Err("createSession cannot be combined with a sessionId".to_string());
+                }
+                if project_dir.trim().is_empty() {
+                    return Err("createSession requires a projectDir".to_string());
+                }
+            }
+            SequenceAction::SetModel { provider, model_id } => {
+                if provider.trim().is_empty() || model_id.trim().is_empty() {
+                    return Err("setModel requires a provider and modelId".to_string());
+                }
+            }
+            SequenceAction::SetThinkingLevel { level } => {
+                if level.trim().is_empty() {
+                    return Err("setThinkingLevel requires a level".to_string());
+                }
+            }
+            SequenceAction::SendPrompt { template } => {
+                // A sent prompt cannot be taken back, so nothing may follow it.
+                if index != last_index {
+                    return Err("sendPrompt must be the last action".to_string());
+                }
+
+                let mut variables = variables.clone();
+                if let Some(project_dir) = project_dir {
+                    variables
+                        .entry("projectDir".to_string())
+                        .or_insert_with(|| project_dir.to_string());
+                }
+
+                let rendered = render_template(template, &variables)?;
+                if rendered.trim().is_empty() {
+                    return Err("sendPrompt template renders to an empty prompt".to_string());
+                }
+                prompt = Some(rendered);
+            }
+        }
+    }
+
+    if session_id.is_none() && !matches!(actions[0], SequenceAction::CreateSession { .. }) {
+        return Err("sessionId is required unless the first action is createSession".to_string());
+    }
+
+    Ok(prompt)
+}
+
+fn ensure_success(response: RpcResponse, action: &str) -> Result<RpcResponse, String> {
+    if response.success {
+        Ok(response)
+    } else {
+        Err(format!(
+            "{} failed: {}",
+            action,
+            response
+                .error
+                .unwrap_or_else(|| "unknown error".to_string())
+        ))
+    }
+}
+
+pub(super) async fn snapshot_session(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+) -> Result<SessionSnapshot, String> {
+    let command = RpcCommand {
+        id: Some(crypto_random_uuid()),
+        r#type: "get_state".to_string(),
+        session_id: Some(session_id.to_string()),
+        cwd: None,
+        message: None,
+        provider: None,
+        model_id: None,
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    };
+
+    let response = ensure_success(
+        send_command_with_response(state, command, 5).await?,
+        "get_state",
+    )?;
+    let data = response.data.unwrap_or_default();
+
+    Ok(SessionSnapshot {
+        model: data.get("model").and_then(|model| {
+            Some(ModelRef {
+                provider: model.get("provider")?.as_str()?.to_string(),
+                model_id: model.get("id")?.as_str()?.to_string(),
+            })
+        }),
+        thinking_level: data
+            .get("thinkingLevel")
+            .and_then(|level| level.as_str())
+            .map(|level| level.to_string()),
+    })
+}
+
+/// Undo the sequence: close a session it created, or restore the settings of
+/// an existing one.
+async fn roll_back(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    created_session: bool,
+    snapshot: Option<SessionSnapshot>,
+) {
+    if created_session {
+        if let Err(error) = close_agent(state, session_id.to_string()).await {
+            logger::log(format!(
+                "Action sequence rollback: failed to close session {}: {}",
+                session_id, error
+            ));
+        }
+        return;
+    }
+
+    let Some(snapshot) = snapshot else {
+        return;
+    };
+
+    if let Some(model) = snapshot.model {
+        let result = set_model(
+            state,
+            model.provider,
+            model.model_id,
+            session_id.to_string(),
+        )
+        .await
+        .and_then(|response| ensure_success(response, "set_model"));
+        if let Err(error) = result {
+            logger::log(format!(
+                "Action sequence rollback: failed to restore model for {}: {}",
+                session_id, error
+            ));
+        }
+    }
+
+    // Restored after the model, which may reset the level.
+    if let Some(level) = snapshot.thinking_level {
+        let result = set_thinking_level(state, level, session_id.to_string())
+            .await
+            .and_then(|response| ensure_success(response, "set_thinking_level"));
+        if let Err(error) = result {
+            logger::log(format!(
+                "Action sequence rollback: failed to restore thinking level for {}: {}",
+                session_id, error
+            ));
+        }
+    }
+}
+
+async fn run_action(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    action: &SequenceAction,
+    prompt: Option<&String>,
+) -> Result<(), String> {
+    match action {
+        SequenceAction::CreateSession { .. } => Ok(()),
+        SequenceAction::SetModel { provider, model_id } => ensure_success(
+            set_model(
+                state,
+                provider.clone(),
+                model_id.clone(),
+                session_id.to_string(),
+            )
+            .await?,
+            "set_model",
+        )
+        .map(|_| ()),
+        SequenceAction::SetThinkingLevel { level } => ensure_success(
+            set_thinking_level(state, level.clone(), session_id.to_string()).await?,
+            "set_thinking_level",
+        )
+        .map(|_| ()),
+        SequenceAction::SendPrompt { .. } => {
+            let prompt = prompt
+                .cloned()
+                .ok_or_else(|| "sendPrompt has no rendered prompt".to_string())?;
+            turn_guard::begin_turn(state, session_id).await?;
+            let result = dispatch_prompt(app, state, prompt, session_id, None).await;
+            if result.is_err() {
+                turn_guard::cancel_turn(state, session_id).await;
+            }
+            result
+        }
+    }
+}
+
+/// Run built-in actions in order against one session, all or nothing.
+///
+/// The sequence is validated and its prompt rendered before any action runs.
+/// If an action fails, a session created by the sequence is closed again and
+/// an existing session gets its previous model and thinking level back.
+pub async fn run_action_sequence(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: Option<String>,
+    actions: Vec<SequenceAction>,
+    variables: HashMap<String, String>,
+) -> Result<ActionSequenceResult, String> {
+    let session_id = session_id
+        .map(|session_id| session_id.trim().to_string())
+        .filter(|session_id| !session_id.is_empty());
+
+    let project_dir = match (session_id.as_deref(), actions.first()) {
+        (None, Some(SequenceAction::CreateSession { project_dir, .. })) => {
+            Some(project_dir.trim().to_string())
+        }
+        (Some(session_id), _) => state.lock().await.session_cwds.get(session_id).cloned(),
+        _ => None,
+    };
+
+    let prompt = validate_sequence(
+        &actions,
+        session_id.as_deref(),
+        project_dir.as_deref(),
+        &variables,
+    )?;
+
+    let (session_id, created_session) = match (session_id, actions.first()) {
+        (Some(session_id), _) => (session_id, false),
+        (
+            None,
+            Some(SequenceAction::CreateSession {
+                project_dir,
+                provider,
+                model_id,
+            }),
+        ) => {
+            let response = ensure_success(
+                create_session_internal(
+                    app.clone(),
+                    state,
+                    project_dir.trim().to_string(),
+                    provider.clone(),
+                    model_id.clone(),
+                    None,
+                )
+                .await?,
+                "create_session",
+            )?;
+            let session_id = response
+                .data
+                .as_ref()
+                .and_then(|data| data.get("sessionId"))
+                .and_then(|value| value.as_str())
+                .ok_or_else(|| "create_session returned no sessionId".to_string())?
+                .to_string();
+            (session_id, true)
+        }
+        (None, _) => {
+            return Err(
+                "sessionId is required unless the first action is createSession".to_string(),
+            )
+        }
+    };
+
+    let changes_settings = actions.iter().any(|action| {
+        matches!(
+            action,
+            SequenceAction::SetModel { .. } | SequenceAction::SetThinkingLevel { .. }
+        )
+    });
+    let snapshot = if !created_session && changes_settings {
+        Some(snapshot_session(state, &session_id).await?)
+    } else {
+        None
+    };
+
+    for (index, action) in actions.iter().enumerate() {
+        if let Err(error) = run_action(app, state, &session_id, action, prompt.as_ref()).await {
+            logger::log(format!(
+                "Action sequence failed at action {} for session {}: {}",
+                index + 1,
+                session_id,
+                error
+            ));
+            roll_back(state, &session_id, created_session, snapshot).await;
+            return Err(format!("Action {} failed: {}", index + 1, error));
+        }
+    }
+
+    Ok(ActionSequenceResult {
+        session_id,
+        created_session,
+        completed_actions: actions.len(),
+    })
+}
@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::project_metadata::registered_project_dirs;
+use super::session_scopes::persisted_session_files;
+use crate::utils::now_millis;
+
+const DEFAULT_RANGE_DAYS: u64 = 365;
+const MAX_RANGE_DAYS: u64 = 3 * 365;
+const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityDay {
+    /// UTC calendar day, `YYYY-MM-DD`.
+    pub date: String,
+    pub sessions: u64,
+    pub turns: u64,
+    pub tokens: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityStats {
+    pub start_date: String,
+    pub end_date: String,
+    /// One entry per day in the range, oldest first, including empty days.
+    pub days: Vec<ActivityDay>,
+    pub total_sessions: u64,
+    pub total_turns: u64,
+    pub total_tokens: u64,
+    /// Busiest day by turns, for scaling heatmap intensity.
+    pub max_turns_per_day: u64,
+}
+
+/// Parse ranges like `30d`, `12w`, `6m`, `1y` (or a bare day count).
+fn parse_range_days(range: Option<&str>) -> Result<u64, String> {
+    let Some(range) = range.map(str::trim).filter(|range| !range.is_empty()) else {
+        return Ok(DEFAULT_RANGE_DAYS);
+    };
+
+    let (count, unit_days) = match range.char_indices().last() {
+        Some((index, 'd')) => (&range[..index], 1),
+        Some((index, 'w')) => (&range[..index], 7),
+        Some((index, 'm')) => (&range[..index], 30),
+        Some((index, 'y')) => (&range[..index], 365),
+        _ => (range, 1),
+    };
+
+    let days = count
+        .parse::<u64>()
+        .ok()
+        .filter(|count| *count > 0)
+        .map(|count| count * unit_days)
+        .ok_or_else(|| format!("Invalid activity range '{}' (expected e.g. 90d, 1y)", range))?;
+
+    Ok(days.min(MAX_RANGE_DAYS))
+}
+
+/// `YYYY-MM-DD` for a count of days since 1970-01-01 (proleptic Gregorian).
+fn date_from_epoch_days(days: u64) -> String {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// UTC day of an ISO-8601 timestamp as written in session files.
+fn timestamp_day(timestamp: &str) -> Option<&str> {
+    let day = timestamp.get(..10)?;
+    let bytes = day.as_bytes();
+    (bytes[4] == b'-' && bytes[7] == b'-').then_some(day)
+}
+
+fn entry_timestamp_day(entry: &serde_json::Value) -> Option<String> {
+    entry
+        .get("timestamp")
+        .and_then(|value| value.as_str())
+        .and_then(timestamp_day)
+        .map(str::to_string)
+}
+
+fn accumulate_session_file(path: &Path, days: &mut BTreeMap<String, ActivityDay>) {
+    let Ok(file) = std::fs::File::open(path) else {
+        return;
+    };
+
+    let mut session_counted = false;
+    for line in BufReader::new(file).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        let Some(date) = entry_timestamp_day(&entry) else {
+            continue;
+        };
+
+        match entry.get("type").and_then(|value| value.as_str()) {
+            Some("session") if !session_counted => {
+                session_counted = true;
+                if let Some(day) = days.get_mut(&date) {
+                    day.sessions += 1;
+                }
+            }
+            Some("message") => {
+                let Some(message) = entry.get("message").filter(|message| {
+                    message.get("role").and_then(|v| v.as_str()) == Some("assistant")
+                }) else {
+                    continue;
+                };
+                let Some(day) = days.get_mut(&date) else {
+                    continue;
+                };
+
+                day.turns += 1;
+                day.tokens += message
+                    .get("usage")
+                    .and_then(|usage| usage.get("totalTokens"))
+                    .and_then(|value| value.as_u64())
+                    .unwrap_or(0);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Sessions, turns (assistant messages) and tokens per UTC day over `range`,
+/// shaped for a contributions-style heatmap.
+///
+/// Session files are discovered like the project list (global roots plus
+/// registered projects); files untouched since the range start are skipped.
+pub fn get_activity_stats(app: &AppHandle, range: Option<String>) -> Result<ActivityStats, String> {
+    let range_days = parse_range_days(range.as_deref())?;
+    let today = now_millis() / MILLIS_PER_DAY;
+    let first_day = today + 1 - range_days;
+
+    let mut days = (first_day..=today)
+        .map(|day| {
+            let date = date_from_epoch_days(day);
+            (
+                date.clone(),
+                ActivityDay {
+                    date,
+                    ..ActivityDay::default()
+                },
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let range_start = SystemTime::UNIX_EPOCH + Duration::from_millis(first_day * MILLIS_PER_DAY);
+    for path in persisted_session_files(&registered_project_dirs(app)) {
+        let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified());
+        if matches!(modified, Ok(modified) if modified < range_start) {
+            continue;
+        }
+
+        accumulate_session_file(&path, &mut days);
+    }
+
+    let days = days.into_values().collect::<Vec<_>>();
+    Ok(ActivityStats {
+        start_date: date_from_epoch_days(first_day),
+        end_date: date_from_epoch_days(today),
+        total_sessions: days.iter().map(|day| day.sessions).sum(),
+        total_turns: days.iter().map(|day| day.turns).sum(),
+        total_tokens: days.iter().map(|day| day.tokens).sum(),
+        max_turns_per_day: days.iter().map(|day| day.turns).max().unwrap_or(0),
+        days,
+    })
+}
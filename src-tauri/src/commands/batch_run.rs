@@ -0,0 +1,469 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::{sleep, Duration, Instant};
+
+use super::abort_all::abort_session;
+use super::operations::OperationToken;
+use super::provider_queue::has_queued_prompt;
+use super::sidecar_lifecycle::create_session_internal;
+use super::{dispatch_prompt, turn_guard};
+use crate::logger;
+use crate::state::SidecarState;
+use crate::utils::crypto_random_uuid;
+
+const RUN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long a sent prompt may go without `agent_start` before it counts as failed.
+const RUN_START_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BatchOptions {
+    pub provider: Option<String>,
+    pub model_id: Option<String>,
+    /// Run every prompt in its own new session, this many at a time. Unset
+    /// (or 0) runs all prompts one after another in a single session.
+    pub parallel_sessions: Option<usize>,
+    /// Skip prompts that have not started once one fails.
+    pub stop_on_error: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchPromptStatus {
+    Completed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchPromptOutcome {
+    pub index: usize,
+    pub session_id: Option<String>,
+    pub status: BatchPromptStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRunResult {
+    pub batch_id: String,
+    pub project_dir: String,
+    pub outcomes: Vec<BatchPromptOutcome>,
+    pub completed: usize,
+    pub failed: usize,
+    /// Stopped by `cancel_operation`; prompts not yet started are skipped.
+    pub cancelled: bool,
+}
+
+/// Emit a `batch-progress` event. `status` is `started`, `completed`,
+/// `failed` or `skipped` for a prompt, and `finished` once for the batch.
+fn emit_batch_progress(
+    app: &AppHandle,
+    batch_id: &str,
+    total: usize,
+    index: Option<usize>,
+    session_id: Option<&str>,
+    status: &str,
+    error: Option<&str>,
+) {
+    let payload = serde_json::json!({
+        "batchId": batch_id,
+        "total": total,
+        "index": index,
+        "sessionId": session_id,
+        "status": status,
+        "error": error,
+    });
+
+    if let Err(error) = app.emit("batch-progress", &payload) {
+        logger::log(format!("Failed to emit batch-progress event: {}", error));
+    }
+}
+
+async fn create_batch_session(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: &str,
+    options: &BatchOptions,
+) -> Result<String, String> {
+    let response = create_session_internal(
+        app.clone(),
+        state,
+        project_dir.to_string(),
+        options.provider.clone(),
+        options.model_id.clone(),
+        None,
+    )
+    .await?;
+
+    if !response.success {
+        return Err(format!(
+            "create_session failed: {}",
+            response
+                .error
+                .unwrap_or_else(|| "unknown error".to_string())
+        ));
+    }
+
+    response
+        .data
+        .as_ref()
+        .and_then(|data| data.get("sessionId"))
+        .and_then(|value| value.as_str())
+        .map(|session_id| session_id.to_string())
+        .ok_or_else(|| "create_session returned no sessionId".to_string())
+}
+
+/// Wait until the session's run has ended, then report its error, if any.
+/// Cancelling the operation aborts the run.
+async fn wait_for_run_end(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    operation: &OperationToken,
+) -> Result<(), String> {
+    loop {
+        sleep(RUN_POLL_INTERVAL).await;
+
+        if operation.is_cancelled() {
+            abort_session(state, session_id.to_string()).await;
+            return Err(operation.cancelled_error());
+        }
+
+        let state_guard = state.lock().await;
+        if !state_guard.session_cwds.contains_key(session_id) {
+            return Err("Session was closed before the run finished".to_string());
+        }
+        if state_guard.running_sessions.contains(session_id)
+            || has_queued_prompt(&state_guard, session_id)
+        {
+            continue;
+        }
+
+        match state_guard.prompts_awaiting_start.get(session_id) {
+            // Prompts held by `pause_agents` wait for the resume.
+            Some(_) if state_guard.pause.is_some() => continue,
+            Some(sent_at) if sent_at.elapsed() < RUN_START_TIMEOUT => continue,
+            Some(_) => return Err("The run did not start".to_string()),
+            None => {}
+        }
+
+        return match state_guard.run_errors.get(session_id) {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        };
+    }
+}
+
+async fn run_prompt(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    prompt: String,
+    operation: &OperationToken,
+) -> Result<(), String> {
+    turn_guard::begin_turn(state, session_id).await?;
+    if let Err(error) = dispatch_prompt(app, state, prompt, session_id, None).await {
+        turn_guard::cancel_turn(state, session_id).await;
+        return Err(error);
+    }
+
+    wait_for_run_end(state, session_id, operation).await
+}
+
+struct BatchContext {
+    app: AppHandle,
+    state: Arc<Mutex<SidecarState>>,
+    batch_id: String,
+    project_dir: String,
+    total: usize,
+    options: BatchOptions,
+    stopped: AtomicBool,
+    operation: OperationToken,
+}
+
+impl BatchContext {
+    /// Prompts that have not started yet are skipped after a failure with
+    /// `stopOnError`, or once the batch is cancelled.
+    fn should_skip(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed) || self.operation.is_cancelled()
+    }
+
+    fn report_progress(&self, finished: usize) {
+        self.operation
+            .report_progress(finished, Some(self.total), Some(&self.batch_id));
+    }
+
+    fn skipped(&self, index: usize) -> BatchPromptOutcome {
+        emit_batch_progress(
+            &self.app,
+            &self.batch_id,
+            self.total,
+            Some(index),
+            None,
+            "skipped",
+            None,
+        );
+        BatchPromptOutcome {
+            index,
+            session_id: None,
+            status: BatchPromptStatus::Skipped,
+            error: None,
+            duration_ms: 0,
+        }
+    }
+
+    /// Run one prompt, creating a session for it when `session_id` is `None`.
+    async fn run(
+        &self,
+        index: usize,
+        session_id: Option<String>,
+        prompt: String,
+    ) -> BatchPromptOutcome {
+        let started = Instant::now();
+
+        let session_id = match session_id {
+            Some(session_id) => Ok(session_id),
+            None => {
+                create_batch_session(&self.app, &self.state, &self.project_dir, &self.options).await
+            }
+        };
+
+        let result = match &session_id {
+            Ok(session_id) => {
+                emit_batch_progress(
+                    &self.app,
+                    &self.batch_id,
+                    self.total,
+                    Some(index),
+                    Some(session_id),
+                    "started",
+                    None,
+                );
+                run_prompt(&self.app, &self.state, session_id, prompt, &self.operation).await
+            }
+            Err(error) => Err(error.clone()),
+        };
+        let session_id = session_id.ok();
+
+        let (status, error) = match result {
+            Ok(()) => (BatchPromptStatus::Completed, None),
+            Err(error) => {
+                logger::log(format!(
+                    "Batch {} prompt {} failed: {}",
+                    self.batch_id,
+                    index + 1,
+                    error
+                ));
+                if self.options.stop_on_error {
+                    self.stopped.store(true, Ordering::Relaxed);
+                }
+                (BatchPromptStatus::Failed, Some(error))
+            }
+        };
+
+        emit_batch_progress(
+            &self.app,
+            &self.batch_id,
+            self.total,
+            Some(index),
+            session_id.as_deref(),
+            if status == BatchPromptStatus::Completed {
+                "completed"
+            } else {
+                "failed"
+            },
+            error.as_deref(),
+        );
+
+        BatchPromptOutcome {
+            index,
+            session_id,
+            status,
+            error,
+            duration_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+async fn run_sequential(context: &BatchContext, prompts: Vec<String>) -> Vec<BatchPromptOutcome> {
+    let mut outcomes = Vec::with_capacity(prompts.len());
+
+    let session_id = match create_batch_session(
+        &context.app,
+        &context.state,
+        &context.project_dir,
+        &context.options,
+    )
+    .await
+    {
+        Ok(session_id) => session_id,
+        Err(error) => {
+            // Without a session nothing can run; report it on the first prompt.
+            emit_batch_progress(
+                &context.app,
+                &context.batch_id,
+                context.total,
+                Some(0),
+                None,
+                "failed",
+                Some(&error),
+            );
+            outcomes.push(BatchPromptOutcome {
+                index: 0,
+                session_id: None,
+                status: BatchPromptStatus::Failed,
+                error: Some(error),
+                duration_ms: 0,
+            });
+            outcomes.extend((1..prompts.len()).map(|index| context.skipped(index)));
+            context.report_progress(outcomes.len());
+            return outcomes;
+        }
+    };
+
+    for (index, prompt) in prompts.into_iter().enumerate() {
+        let outcome = if context.should_skip() {
+            context.skipped(index)
+        } else {
+            context.run(index, Some(session_id.clone()), prompt).await
+        };
+        outcomes.push(outcome);
+        context.report_progress(outcomes.len());
+    }
+
+    outcomes
+}
+
+async fn run_parallel(
+    context: Arc<BatchContext>,
+    prompts: Vec<String>,
+    max_sessions: usize,
+) -> Result<Vec<BatchPromptOutcome>, String> {
+    let permits = Arc::new(Semaphore::new(max_sessions));
+    let mut tasks = JoinSet::new();
+
+    for (index, prompt) in prompts.into_iter().enumerate() {
+        let context = context.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            if context.should_skip() {
+                return context.skipped(index);
+            }
+            context.run(index, None, prompt).await
+        });
+    }
+
+    let mut outcomes = Vec::new();
+    while let Some(outcome) = tasks.join_next().await {
+        outcomes.push(outcome.map_err(|error| format!("Batch task failed: {}", error))?);
+        context.report_progress(outcomes.len());
+    }
+    outcomes.sort_by_key(|outcome| outcome.index);
+
+    Ok(outcomes)
+}
+
+/// Run `prompts` against a project and report how each one went.
+///
+/// By default the prompts run in order in one new session, each waiting for
+/// the previous run to end. With `parallelSessions` every prompt gets its own
+/// new session instead. Sessions stay open afterwards for review. Progress is
+/// emitted as `batch-progress` events, and as `operation-progress` events
+/// when the batch runs under an op id.
+pub async fn run_batch(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: String,
+    prompts: Vec<String>,
+    options: BatchOptions,
+    operation: OperationToken,
+) -> Result<BatchRunResult, String> {
+    let project_dir = project_dir.trim().to_string();
+    if project_dir.is_empty() {
+        return Err("project_dir is required for run_batch".to_string());
+    }
+
+    let prompts = prompts
+        .into_iter()
+        .map(|prompt| prompt.trim().to_string())
+        .collect::<Vec<_>>();
+    if prompts.is_empty() {
+        return Err("run_batch needs at least one prompt".to_string());
+    }
+    if let Some(index) = prompts.iter().position(|prompt| prompt.is_empty()) {
+        return Err(format!("Prompt {} is empty", index + 1));
+    }
+
+    let parallel_sessions = options.parallel_sessions.filter(|count| *count > 0);
+    let context = Arc::new(BatchContext {
+        app: app.clone(),
+        state: state.clone(),
+        batch_id: crypto_random_uuid(),
+        project_dir: project_dir.clone(),
+        total: prompts.len(),
+        options,
+        stopped: AtomicBool::new(false),
+        operation,
+    });
+
+    logger::log(format!(
+        "Batch {} started: {} prompts in {} ({})",
+        context.batch_id,
+        context.total,
+        project_dir,
+        match parallel_sessions {
+            Some(count) => format!("up to {} parallel sessions", count),
+            None => "sequential".to_string(),
+        }
+    ));
+
+    let outcomes = match parallel_sessions {
+        Some(count) => run_parallel(context.clone(), prompts, count).await?,
+        None => run_sequential(&context, prompts).await,
+    };
+
+    let count = |status| {
+        outcomes
+            .iter()
+            .filter(|outcome| outcome.status == status)
+            .count()
+    };
+    let completed = count(BatchPromptStatus::Completed);
+    let failed = count(BatchPromptStatus::Failed);
+    let cancelled = context.operation.is_cancelled();
+
+    emit_batch_progress(
+        app,
+        &context.batch_id,
+        context.total,
+        None,
+        None,
+        "finished",
+        None,
+    );
+    logger::log(format!(
+        "Batch {} finished: {} completed, {} failed{}",
+        context.batch_id,
+        completed,
+        failed,
+        if cancelled { " (cancelled)" } else { "" }
+    ));
+
+    Ok(BatchRunResult {
+        batch_id: context.batch_id.clone(),
+        project_dir,
+        outcomes,
+        completed,
+        failed,
+        cancelled,
+    })
+}
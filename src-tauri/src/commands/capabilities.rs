@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use super::sidecar_lifecycle::send_command_with_response;
+use crate::messages::{self, ErrorCode};
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::crypto_random_uuid;
+
+/// Commands every host build understood before `get_capabilities` existed.
+const BASELINE_COMMANDS: [&str; 28] = [
+    "create_session",
+    "close_session",
+    "list_sessions",
+    "prompt",
+    "steer",
+    "follow_up",
+    "abort",
+    "bash",
+    "abort_bash",
+    "abort_branch_summary",
+    "get_messages",
+    "get_session_tree",
+    "navigate_session_tree",
+    "get_state",
+    "set_model",
+    "cycle_model",
+    "get_available_models",
+    "get_registered_extensions",
+    "get_commands",
+    "set_thinking_level",
+    "oauth_list_providers",
+    "oauth_start_login",
+    "oauth_poll_login",
+    "oauth_submit_login_input",
+    "oauth_cancel_login",
+    "oauth_logout",
+    "shutdown",
+    "ping",
+];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarFeatures {
+    pub oauth: bool,
+    pub image_attachments: bool,
+    pub thinking_levels: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarCapabilities {
+    /// Host protocol version; `None` for builds that predate capability discovery.
+    pub protocol_version: Option<u64>,
+    pub commands: Vec<String>,
+    pub event_types: Vec<String>,
+    pub features: SidecarFeatures,
+    /// True when the host could not report capabilities and a baseline was assumed.
+    pub inferred: bool,
+}
+
+impl SidecarCapabilities {
+    fn from_host(data: &serde_json::Value) -> Self {
+        let strings = |key: &str| {
+            data.get(key)
+                .and_then(|value| value.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|value| value.as_str().map(str::to_string))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        };
+
+        let commands = strings("commands");
+        let host_features = strings("features");
+        let has_command = |name: &str| commands.iter().any(|command| command == name);
+
+        Self {
+            protocol_version: data.get("protocolVersion").and_then(|value| value.as_u64()),
+            features: SidecarFeatures {
+                oauth: has_command("oauth_start_login"),
+                image_attachments: host_features
+                    .iter()
+                    .any(|feature| feature == "image_attachments"),
+                thinking_levels: has_command("set_thinking_level"),
+            },
+            event_types: strings("eventTypes"),
+            commands,
+            inferred: false,
+        }
+    }
+
+    fn baseline() -> Self {
+        Self {
+            protocol_version: None,
+            commands: BASELINE_COMMANDS.iter().map(|c| c.to_string()).collect(),
+            event_types: Vec::new(),
+            features: SidecarFeatures {
+                oauth: true,
+                image_attachments: true,
+                thinking_levels: true,
+            },
+            inferred: true,
+        }
+    }
+}
+
+/// Ask the running sidecar which commands, events and features it supports.
+///
+/// The answer is cached until the sidecar is restarted. Hosts that predate
+/// `get_capabilities` get the baseline command set, flagged as `inferred`.
+pub async fn get_sidecar_capabilities(
+    state: &Arc<Mutex<SidecarState>>,
+) -> Result<SidecarCapabilities, String> {
+    {
+        let state_guard = state.lock().await;
+        if let Some(capabilities) = state_guard.capabilities.clone() {
+            return Ok(capabilities);
+        }
+        if state_guard.child.is_none() {
+            return Err(messages::error(ErrorCode::SidecarNotRunning, &[]));
+        }
+    }
+
+    let command = RpcCommand {
+        id: Some(crypto_random_uuid()),
+        r#type: "get_capabilities".to_string(),
+        session_id: None,
+        cwd: None,
+        message: None,
+        provider: None,
+        model_id: None,
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    };
+
+    let response = send_command_with_response(state, command, 5).await?;
+    let capabilities = match response.data.as_ref() {
+        Some(data) if response.success => SidecarCapabilities::from_host(data),
+        _ if response
+            .error
+            .as_deref()
+            .is_some_and(|error| error.starts_with("Unknown command")) =>
+        {
+            SidecarCapabilities::baseline()
+        }
+        _ => {
+            return Err(response
+                .error
+                .unwrap_or_else(|| "Failed to read sidecar capabilities".to_string()))
+        }
+    };
+
+    state.lock().await.capabilities = Some(capabilities.clone());
+    Ok(capabilities)
+}
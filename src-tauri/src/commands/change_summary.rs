@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use super::git::find_git_root;
+use super::project_brief::run_tool_free_prompt;
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::sidecar::{file_patch, FilePatch};
+use crate::state::SidecarState;
+use crate::utils::now_millis;
+
+const PATCH_DIR: &str = "change-summaries";
+/// Larger patches are cut before they go into the summary prompt.
+const MAX_PROMPT_PATCH_CHARS: usize = 120_000;
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(300);
+/// Patches older than this are removed when the next one is written; the
+/// summary's `patchPath` is only meant to be applied soon after.
+const PATCH_FILE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedFile {
+    pub path: String,
+    pub created: bool,
+    pub deleted: bool,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSummary {
+    pub session_id: String,
+    pub title: String,
+    /// Markdown PR description.
+    pub description: String,
+    /// The run's changes as one patch, applicable from `patchRoot`.
+    pub patch_path: String,
+    pub patch_root: String,
+    pub files: Vec<ChangedFile>,
+}
+
+fn patch_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join(PATCH_DIR))
+        .map_err(|error| format!("Failed to resolve app cache dir: {}", error))
+}
+
+fn prune_expired_patches(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > PATCH_FILE_TTL);
+        if expired {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Diff every touched file from its pre-run content to what is on disk now.
+fn build_patch(root: &Path, snapshots: &[(String, Option<String>)]) -> (String, Vec<ChangedFile>) {
+    let mut patch = String::new();
+    let mut files = Vec::new();
+
+    for (path, before) in snapshots {
+        let after = match fs::read_to_string(path) {
+            Ok(content) => Some(content),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+            Err(error) => {
+                logger::log(format!(
+                    "change-summary: failed to read {}: {}",
+                    path, error
+                ));
+                continue;
+            }
+        };
+
+        let relative = Path::new(path)
+            .strip_prefix(root)
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| path.clone());
+
+        let Some(FilePatch {
+            diff,
+            additions,
+            deletions,
+        }) = file_patch(&relative, before.as_deref(), after.as_deref())
+        else {
+            continue;
+        };
+
+        patch.push_str(&format!("diff --git a/{0} b/{0}\n", relative));
+        patch.push_str(&diff);
+        if !diff.ends_with('\n') {
+            patch.push('\n');
+        }
+        files.push(ChangedFile {
+            path: relative,
+            created: before.is_none(),
+            deleted: after.is_none(),
+            additions,
+            deletions,
+        });
+    }
+
+    (patch, files)
+}
+
+fn build_summary_prompt(patch: &str) -> String {
+    let (patch, note) = match patch.char_indices().nth(MAX_PROMPT_PATCH_CHARS) {
+        Some((index, _)) => (&patch[..index], "\n(patch truncated)"),
+        None => (patch, ""),
+    };
+
+    format!(
+        "Write a pull request title and description for this patch.\n\
+         Reply with the title alone on the first line, then a blank line, then a \
+         markdown description: what changed and why, and how to verify it. \
+         Do not wrap the reply in code fences.\n\n```diff\n{}\n```{}",
+        patch.trim_end(),
+        note
+    )
+}
+
+/// Split the reply into title and description, tolerating a `#` heading or a
+/// `Title:` prefix on the first line.
+fn split_summary(reply: &str) -> (String, String) {
+    let reply = reply.trim();
+    let (first_line, rest) = reply.split_once('\n').unwrap_or((reply, ""));
+
+    let title = first_line.trim().trim_start_matches('#').trim();
+    let title = title
+        .strip_prefix("Title:")
+        .or_else(|| title.strip_prefix("title:"))
+        .unwrap_or(title)
+        .trim()
+        .trim_matches('*')
+        .trim();
+
+    (title.to_string(), rest.trim().to_string())
+}
+
+/// Turn the session's last run into a ready-to-paste PR title and
+/// description plus a combined patch file.
+///
+/// The patch covers every file the run's edit/write tools touched, diffed
+/// from before the run to their current content. The summary is written by
+/// the agent in a throwaway session so the conversation is left alone.
+pub async fn generate_change_summary(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: String,
+) -> Result<ChangeSummary, String> {
+    let (cwd, snapshots) = {
+        let state_guard = state.lock().await;
+        let cwd = state_guard
+            .session_cwds
+            .get(&session_id)
+            .cloned()
+            .ok_or_else(|| messages::error(ErrorCode::UnknownSession, &[&session_id]))?;
+        let snapshots = state_guard
+            .turn_file_snapshots
+            .get(&session_id)
+            .map(|snapshots| {
+                snapshots
+                    .iter()
+                    .map(|(path, before)| (path.clone(), before.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        (cwd, snapshots)
+    };
+
+    if snapshots.is_empty() {
+        return Err("The last run did not change any files".to_string());
+    }
+
+    let cwd = PathBuf::from(cwd);
+    let root = find_git_root(&cwd).unwrap_or_else(|| cwd.clone());
+    let (patch, files) = {
+        let root = root.clone();
+        tauri::async_runtime::spawn_blocking(move || build_patch(&root, &snapshots))
+            .await
+            .map_err(|error| format!("Failed to build patch: {}", error))?
+    };
+    if files.is_empty() {
+        return Err(
+            "The files changed in the last run are back to their original content".to_string(),
+        );
+    }
+
+    let dir = patch_dir(app)?;
+    fs::create_dir_all(&dir)
+        .map_err(|error| format!("Failed to create {}: {}", dir.display(), error))?;
+    prune_expired_patches(&dir);
+    let patch_path = dir.join(format!("{}-{}.patch", session_id, now_millis()));
+    fs::write(&patch_path, &patch)
+        .map_err(|error| format!("Failed to write {}: {}", patch_path.display(), error))?;
+
+    // The agent only describes the changes; with tools it could alter the
+    // very tree it is summarizing.
+    let reply = run_tool_free_prompt(
+        app.clone(),
+        state,
+        &cwd,
+        build_summary_prompt(&patch),
+        SUMMARY_TIMEOUT,
+    )
+    .await?;
+    let (title, description) = split_summary(&reply);
+
+    logger::log(format!(
+        "Generated change summary for session {} ({} files, patch at {})",
+        session_id,
+        files.len(),
+        patch_path.display()
+    ));
+
+    Ok(ChangeSummary {
+        session_id,
+        title,
+        description,
+        patch_path: patch_path.to_string_lossy().to_string(),
+        patch_root: root.to_string_lossy().to_string(),
+        files,
+    })
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::platform::clipboard_text::write_clipboard_text;
+use crate::sidecar::CodeBlock;
+use crate::state::SidecarState;
+
+/// Code blocks from the session's assistant messages, newest first.
+pub async fn list_code_blocks(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: String,
+) -> Result<Vec<CodeBlock>, String> {
+    let state_guard = state.lock().await;
+    if !state_guard.session_cwds.contains_key(&session_id) {
+        return Err(messages::error(ErrorCode::UnknownSession, &[&session_id]));
+    }
+
+    Ok(state_guard
+        .code_blocks
+        .get(&session_id)
+        .map(|history| history.blocks().rev().cloned().collect())
+        .unwrap_or_default())
+}
+
+/// Copy a code block from any open session to the system clipboard.
+pub async fn copy_code_block(
+    state: &Arc<Mutex<SidecarState>>,
+    id: String,
+) -> Result<CodeBlock, String> {
+    let block = state
+        .lock()
+        .await
+        .code_blocks
+        .values()
+        .find_map(|history| history.find(&id))
+        .cloned()
+        .ok_or_else(|| format!("Unknown code block: {}", id))?;
+
+    let code = block.code.clone();
+    tauri::async_runtime::spawn_blocking(move || write_clipboard_text(&code))
+        .await
+        .map_err(|error| format!("Failed to copy code block: {}", error))??;
+
+    logger::log(format!(
+        "Copied code block {} ({} lines) from session {}",
+        block.id, block.line_count, block.session_id
+    ));
+    Ok(block)
+}
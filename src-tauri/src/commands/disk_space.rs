@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager};
+
+use super::session_scopes::global_session_root;
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+
+/// Free space required before starting work that appends to session JSONL.
+///
+/// Generous compared to a typical session file so a long turn does not run the
+/// disk dry halfway through and leave a truncated line behind.
+const MIN_SESSION_FREE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Fail with an `insufficient-disk-space` error unless the app data dir and the
+/// session root both have room for a new session.
+pub fn ensure_session_disk_space(app: &AppHandle, project_dir: &str) -> Result<(), String> {
+    let mut paths = vec![PathBuf::from(project_dir)];
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        paths.push(app_data_dir);
+    }
+    if let Some(session_root) = global_session_root() {
+        paths.push(session_root);
+    }
+
+    ensure_disk_space(&paths, MIN_SESSION_FREE_BYTES)
+}
+
+/// Fail with an `insufficient-disk-space` error if any of `paths` has less than
+/// `required_bytes` free. Paths whose free space cannot be determined are skipped.
+pub fn ensure_disk_space(paths: &[PathBuf], required_bytes: u64) -> Result<(), String> {
+    for path in paths {
+        let available = match available_bytes(path) {
+            Ok(available) => available,
+            Err(error) => {
+                logger::log(format!(
+                    "Skipping disk space check for {}: {}",
+                    path.display(),
+                    error
+                ));
+                continue;
+            }
+        };
+
+        if available < required_bytes {
+            return Err(messages::error(
+                ErrorCode::InsufficientDiskSpace,
+                &[
+                    &(available / (1024 * 1024)),
+                    &path.display(),
+                    &required_bytes.div_ceil(1024 * 1024),
+                ],
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Nearest ancestor of `path` that exists, so not-yet-created directories can
+/// be checked against the filesystem they will live on.
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|candidate| candidate.exists())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn available_bytes(path: &Path) -> Result<u64, String> {
+    let target = existing_ancestor(path)
+        .ok_or_else(|| format!("No existing ancestor for {}", path.display()))?;
+
+    // POSIX output format: one header line, then
+    // `<filesystem> <1024-blocks> <used> <available> <capacity> <mounted on>`.
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(target)
+        .output()
+        .map_err(|error| format!("Failed to invoke df: {}", error))?;
+
+    if !output.status.success() {
+        return Err(format!("df failed with status {}", output.status));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|available_kib| available_kib.parse::<u64>().ok())
+        .map(|available_kib| available_kib * 1024)
+        .ok_or_else(|| format!("Unexpected df output: {}", stdout.trim()))
+}
+
+#[cfg(target_os = "windows")]
+fn available_bytes(path: &Path) -> Result<u64, String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let target = existing_ancestor(path)
+        .ok_or_else(|| format!("No existing ancestor for {}", path.display()))?;
+    let wide = target
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect::<Vec<u16>>();
+
+    let mut available: u64 = 0;
+    // SAFETY: `wide` is NUL-terminated and outlives the call; the unused
+    // totals are passed as null, which the API allows.
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+
+    if ok == 0 {
+        return Err(format!(
+            "GetDiskFreeSpaceExW failed for {}",
+            target.display()
+        ));
+    }
+
+    Ok(available)
+}
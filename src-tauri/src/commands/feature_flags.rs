@@ -0,0 +1,65 @@
+//! Which optional subsystems this build has and which are switched on, so the
+//! frontend can hide controls instead of calling commands that would fail.
+
+use serde::Serialize;
+
+use crate::preferences;
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlag {
+    /// Built into this binary (cargo feature or target platform).
+    pub compiled: bool,
+    /// Compiled and currently active per preferences and the host system.
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlags {
+    /// `latency-injection` cargo feature; enabled while `latencyInjection`
+    /// has any delay or drop set.
+    pub latency_injection: FeatureFlag,
+    /// Windows toast notifications when a run ends.
+    pub run_notifications: FeatureFlag,
+    /// Linux CPU quota for the sidecar; enabled when
+    /// `sidecarCpuQuotaPercent` is set and systemd user scopes work.
+    pub sidecar_cpu_quota: FeatureFlag,
+}
+
+fn cpu_quota_available() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        crate::platform::process_priority::systemd_user_scope_available()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        false
+    }
+}
+
+pub fn get_feature_flags() -> FeatureFlags {
+    let preferences = preferences::current();
+
+    let latency_injection = cfg!(feature = "latency-injection");
+    let run_notifications = cfg!(target_os = "windows");
+    let sidecar_cpu_quota = cfg!(target_os = "linux");
+
+    FeatureFlags {
+        latency_injection: FeatureFlag {
+            compiled: latency_injection,
+            enabled: latency_injection && !preferences.latency_injection.is_empty(),
+        },
+        run_notifications: FeatureFlag {
+            compiled: run_notifications,
+            enabled: run_notifications,
+        },
+        sidecar_cpu_quota: FeatureFlag {
+            compiled: sidecar_cpu_quota,
+            enabled: sidecar_cpu_quota
+                && preferences.sidecar_cpu_quota_percent.is_some()
+                && cpu_quota_available(),
+        },
+    }
+}
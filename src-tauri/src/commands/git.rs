@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Find the enclosing git work tree root (the directory holding `.git`).
+///
+/// `.git` may be a directory or, for worktrees and submodules, a file.
+pub(crate) fn find_git_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Whether the work tree at `path` has uncommitted changes (including untracked
+/// files). Returns `None` when git is unavailable or `path` is not a repository.
+pub(crate) fn has_uncommitted_changes(path: &Path) -> Option<bool> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(path)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(!output.stdout.iter().all(u8::is_ascii_whitespace))
+}
+
+/// Checked-out branch of the work tree at `path`; `None` outside a
+/// repository or on a detached HEAD.
+pub(crate) fn current_branch(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(path)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!branch.is_empty() && branch != "HEAD").then_some(branch)
+}
+
+/// Paths with uncommitted changes (including untracked files), relative to
+/// the repository root. `None` when git is unavailable or `path` is not a
+/// repository.
+pub(crate) fn dirty_files(path: &Path) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain", "--no-renames"])
+        .current_dir(path)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.get(3..))
+            .map(|file| file.trim_matches('"').to_string())
+            .collect(),
+    )
+}
+
+/// Run `git init` in `path`.
+pub(crate) fn init_repository(path: &Path) -> Result<(), String> {
+    let output = Command::new("git")
+        .arg("init")
+        .current_dir(path)
+        .output()
+        .map_err(|error| format!("Failed to invoke git init: {}", error))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    Err(format!(
+        "git init failed with status {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/// Directory git runs hooks from for the repository at `path`, honoring
+/// `core.hooksPath` and linked worktrees.
+pub(crate) fn hooks_dir(path: &Path) -> Result<PathBuf, String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--path-format=absolute", "--git-path", "hooks"])
+        .current_dir(path)
+        .output()
+        .map_err(|error| format!("Failed to invoke git rev-parse: {}", error))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} is not a git repository: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    ))
+}
@@ -0,0 +1,547 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+
+use super::git::hooks_dir;
+use super::project_brief::run_tool_free_prompt;
+use super::project_metadata::project_key;
+use super::workspace_trust::ensure_project_trusted;
+use crate::instance_lock;
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::state::SidecarState;
+use crate::utils::crypto_random_uuid;
+
+/// Marks hook scripts written by graphone, so foreign hooks are never replaced.
+const HOOK_MARKER: &str = "graphone-managed-hook";
+/// Port of the running hook endpoint, read by the hook scripts.
+const ENDPOINT_FILE_NAME: &str = "hook-endpoint";
+/// Token of each project with an installed hook, by project key. The token
+/// is written into the hook script only, so reading the endpoint file is not
+/// enough to call the endpoint.
+const GIT_HOOK_STORE_FILE: &str = "git-hooks.json";
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+/// Larger diffs are cut before they go into the review prompt.
+const MAX_PROMPT_DIFF_CHARS: usize = 200_000;
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// The commit waits for the review, so it gets little time.
+const REVIEW_TIMEOUT: Duration = Duration::from_secs(120);
+/// curl's `--max-time` in the hook script; leaves room for session setup.
+const HOOK_MAX_TIME_SECS: u64 = 150;
+
+static SERVER_STARTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitHook {
+    PreCommit,
+}
+
+impl GitHook {
+    fn file_name(self) -> &'static str {
+        match self {
+            GitHook::PreCommit => "pre-commit",
+        }
+    }
+
+    fn route(self) -> &'static str {
+        match self {
+            GitHook::PreCommit => "/hooks/pre-commit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHookResponse {
+    pub project_dir: String,
+    pub hook: GitHook,
+    pub hook_path: String,
+    pub installed: bool,
+}
+
+fn endpoint_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_local_data_dir()
+        .map(|dir| dir.join(ENDPOINT_FILE_NAME))
+        .map_err(|error| format!("Failed to resolve app local data dir: {}", error))
+}
+
+fn load_hook_token(app: &AppHandle, key: &str) -> Option<String> {
+    app.store(GIT_HOOK_STORE_FILE)
+        .ok()?
+        .get(key)
+        .and_then(|value| value.as_str().map(str::to_string))
+}
+
+fn save_hook_token(app: &AppHandle, key: &str, token: Option<&str>) -> Result<(), String> {
+    instance_lock::ensure_primary()?;
+
+    let store = app
+        .store(GIT_HOOK_STORE_FILE)
+        .map_err(|error| format!("Failed to open git hook store: {}", error))?;
+    match token {
+        Some(token) => store.set(key, serde_json::json!(token)),
+        None => {
+            store.delete(key);
+        }
+    }
+    store
+        .save()
+        .map_err(|error| format!("Failed to save git hook store: {}", error))
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// POSIX sh script that posts the staged diff to the hook endpoint and prints
+/// the review. It never blocks the commit: without a running graphone, or
+/// without curl, it exits quietly.
+fn hook_script(hook: GitHook, endpoint_file: &Path, project_dir: &Path, token: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+# {marker}: installed by Graphone, remove with uninstall_git_hook.
+ENDPOINT_FILE={endpoint_file}
+PROJECT_DIR={project_dir}
+TOKEN={token}
+
+[ -f "$ENDPOINT_FILE" ] || exit 0
+command -v curl >/dev/null 2>&1 || exit 0
+read -r PORT _ < "$ENDPOINT_FILE" || exit 0
+
+DIFF_FILE=$(mktemp) || exit 0
+git diff --cached --no-color > "$DIFF_FILE"
+if [ -s "$DIFF_FILE" ]; then
+  echo "graphone: reviewing staged changes..." >&2
+  curl -sS --max-time {max_time} \
+    -H "Authorization: Bearer $TOKEN" \
+    -H "X-Graphone-Project: $PROJECT_DIR" \
+    -H "Expect:" \
+    --data-binary "@$DIFF_FILE" \
+    "http://127.0.0.1:$PORT{route}" >&2 \
+    || echo "graphone: review unavailable, is Graphone running?" >&2
+fi
+rm -f "$DIFF_FILE"
+exit 0
+"#,
+        marker = HOOK_MARKER,
+        endpoint_file = shell_quote(&endpoint_file.to_string_lossy()),
+        project_dir = shell_quote(&project_dir.to_string_lossy()),
+        token = shell_quote(token),
+        max_time = HOOK_MAX_TIME_SECS,
+        route = hook.route(),
+    )
+}
+
+fn resolve_hook_path(project_dir: &str, hook: GitHook) -> Result<(PathBuf, PathBuf), String> {
+    let project_path = PathBuf::from(project_dir.trim());
+    if !project_path.is_dir() {
+        return Err(messages::error(
+            ErrorCode::ProjectDirNotFound,
+            &[&project_dir],
+        ));
+    }
+
+    let hook_path = hooks_dir(&project_path)?.join(hook.file_name());
+    Ok((project_path, hook_path))
+}
+
+fn is_graphone_hook(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content.contains(HOOK_MARKER))
+}
+
+fn hook_response(
+    project_path: &Path,
+    hook: GitHook,
+    hook_path: &Path,
+    installed: bool,
+) -> GitHookResponse {
+    GitHookResponse {
+        project_dir: project_path.to_string_lossy().to_string(),
+        hook,
+        hook_path: hook_path.to_string_lossy().to_string(),
+        installed,
+    }
+}
+
+/// Install a git hook that has graphone review the staged diff, and start
+/// the endpoint it calls.
+///
+/// Replaces an earlier graphone hook but refuses to touch one written by
+/// anything else.
+pub fn install_git_hook(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: String,
+    hook: GitHook,
+) -> Result<GitHookResponse, String> {
+    let (project_path, hook_path) = resolve_hook_path(&project_dir, hook)?;
+    let key = project_key(&project_dir)?;
+
+    if hook_path.exists() && !is_graphone_hook(&hook_path) {
+        return Err(format!(
+            "{} already exists and was not installed by Graphone",
+            hook_path.display()
+        ));
+    }
+
+    if let Some(parent) = hook_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to create {}: {}", parent.display(), error))?;
+    }
+
+    let token = crypto_random_uuid();
+    save_hook_token(app, &key, Some(&token))?;
+
+    let script = hook_script(hook, &endpoint_file_path(app)?, &project_path, &token);
+    fs::write(&hook_path, script)
+        .map_err(|error| format!("Failed to write {}: {}", hook_path.display(), error))?;
+
+    // The script holds the project's token; only its owner may read it.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o700)).map_err(|error| {
+            format!(
+                "Failed to set executable permissions on {}: {}",
+                hook_path.display(),
+                error
+            )
+        })?;
+    }
+
+    logger::log(format!("Installed git hook {}", hook_path.display()));
+    start_git_hook_server(app.clone(), state.clone());
+    Ok(hook_response(&project_path, hook, &hook_path, true))
+}
+
+/// Remove a hook installed by `install_git_hook`.
+pub fn uninstall_git_hook(
+    app: &AppHandle,
+    project_dir: String,
+    hook: GitHook,
+) -> Result<GitHookResponse, String> {
+    let (project_path, hook_path) = resolve_hook_path(&project_dir, hook)?;
+    let key = project_key(&project_dir)?;
+
+    if hook_path.exists() {
+        if !is_graphone_hook(&hook_path) {
+            return Err(format!(
+                "{} was not installed by Graphone; leaving it in place",
+                hook_path.display()
+            ));
+        }
+
+        fs::remove_file(&hook_path)
+            .map_err(|error| format!("Failed to remove {}: {}", hook_path.display(), error))?;
+        logger::log(format!("Removed git hook {}", hook_path.display()));
+    }
+    save_hook_token(app, &key, None)?;
+
+    Ok(hook_response(&project_path, hook, &hook_path, false))
+}
+
+struct HookRequest {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+type HttpError = (u16, String);
+
+async fn read_request(stream: &mut TcpStream) -> Result<HookRequest, HttpError> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+
+    let header_end = loop {
+        if let Some(index) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break index;
+        }
+        if buffer.len() > MAX_HEADER_BYTES {
+            return Err((431, "Request headers too large".to_string()));
+        }
+
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|error| (400, format!("Failed to read request: {}", error)))?;
+        if read == 0 {
+            return Err((400, "Incomplete request".to_string()));
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let content_length = headers
+        .get("content-length")
+        .map(|value| value.parse::<usize>())
+        .transpose()
+        .map_err(|_| (400, "Invalid Content-Length".to_string()))?
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Err((413, "Staged diff is too large to review".to_string()));
+    }
+
+    let mut body = buffer.split_off(header_end + 4);
+    if body.len() < content_length {
+        let already_read = body.len();
+        body.resize(content_length, 0);
+        stream
+            .read_exact(&mut body[already_read..])
+            .await
+            .map_err(|error| (400, format!("Failed to read request body: {}", error)))?;
+    }
+    body.truncate(content_length);
+
+    Ok(HookRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+fn build_review_prompt(diff: &str) -> String {
+    let (diff, note) = match diff.char_indices().nth(MAX_PROMPT_DIFF_CHARS) {
+        Some((index, _)) => (&diff[..index], "\n(diff truncated)"),
+        None => (diff, ""),
+    };
+
+    format!(
+        "Review this staged diff before it is committed. Point out bugs, risky \
+         changes and missing tests, citing file and line. Be concise. If it looks \
+         fine, say so in one line.\n\n```diff\n{}\n```{}",
+        diff.trim_end(),
+        note
+    )
+}
+
+fn emit_hook_review(
+    app: &AppHandle,
+    project_dir: &str,
+    hook: GitHook,
+    result: &Result<String, String>,
+) {
+    let (review, error) = match result {
+        Ok(review) => (Some(review.as_str()), None),
+        Err(error) => (None, Some(error.as_str())),
+    };
+    let payload = serde_json::json!({
+        "projectDir": project_dir,
+        "hook": hook,
+        "review": review,
+        "error": error,
+    });
+
+    if let Err(error) = app.emit("git-hook-review", &payload) {
+        logger::log(format!("Failed to emit git-hook-review event: {}", error));
+    }
+}
+
+async fn handle_request(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    request: HookRequest,
+) -> Result<String, HttpError> {
+    let hook = match (request.method.as_str(), request.path.as_str()) {
+        ("POST", path) if path == GitHook::PreCommit.route() => GitHook::PreCommit,
+        _ => return Err((404, "Unknown hook endpoint".to_string())),
+    };
+
+    let project_dir = request
+        .headers
+        .get("x-graphone-project")
+        .map(|value| value.trim().to_string())
+        .filter(|value| Path::new(value).is_dir())
+        .ok_or_else(|| (400, "Missing or unknown X-Graphone-Project".to_string()))?;
+
+    // Only projects with a hook installed from graphone are served, each
+    // with the token of its own hook.
+    let token = project_key(&project_dir)
+        .ok()
+        .and_then(|key| load_hook_token(app, &key))
+        .ok_or_else(|| {
+            (
+                403,
+                "graphone: no hook installed for this project; reinstall it".to_string(),
+            )
+        })?;
+    if request.headers.get("authorization").map(String::as_str)
+        != Some(format!("Bearer {}", token).as_str())
+    {
+        return Err((401, "Invalid hook token; reinstall the hook".to_string()));
+    }
+
+    ensure_project_trusted(app, &project_dir)
+        .map_err(|error| (403, format!("graphone: {}", error)))?;
+
+    let diff = String::from_utf8_lossy(&request.body);
+    if diff.trim().is_empty() {
+        return Ok("graphone: nothing staged to review".to_string());
+    }
+
+    logger::log(format!(
+        "Reviewing staged diff ({} bytes) for {} hook in {}",
+        request.body.len(),
+        hook.file_name(),
+        project_dir
+    ));
+
+    // The diff is untrusted input, so the reviewer gets no tools to act on it.
+    let result = run_tool_free_prompt(
+        app.clone(),
+        state,
+        Path::new(&project_dir),
+        build_review_prompt(&diff),
+        REVIEW_TIMEOUT,
+    )
+    .await;
+    emit_hook_review(app, &project_dir, hook, &result);
+
+    result.map_err(|error| (502, format!("graphone: review failed: {}", error)))
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: &str) {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Bad Gateway",
+    };
+    let body = format!("{}\n", body.trim_end());
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+
+    if let Err(error) = stream.write_all(response.as_bytes()).await {
+        logger::log(format!("Failed to answer git hook request: {}", error));
+    }
+    let _ = stream.shutdown().await;
+}
+
+async fn handle_connection(app: AppHandle, state: Arc<Mutex<SidecarState>>, mut stream: TcpStream) {
+    let request = match tokio::time::timeout(REQUEST_READ_TIMEOUT, read_request(&mut stream)).await
+    {
+        Ok(request) => request,
+        Err(_) => Err((400, "Timed out reading request".to_string())),
+    };
+
+    let result = match request {
+        Ok(request) => handle_request(&app, &state, request).await,
+        Err(error) => Err(error),
+    };
+
+    match result {
+        Ok(body) => write_response(&mut stream, 200, &body).await,
+        Err((status, body)) => write_response(&mut stream, status, &body).await,
+    }
+}
+
+fn write_endpoint_file(path: &Path, port: u16) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to create {}: {}", parent.display(), error))?;
+    }
+
+    fs::write(path, format!("{}\n", port))
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
+}
+
+/// Start the git hook endpoint at launch if a hook is installed.
+pub(crate) fn spawn_git_hook_server(app: AppHandle, state: Arc<Mutex<SidecarState>>) {
+    let has_hooks = app
+        .store(GIT_HOOK_STORE_FILE)
+        .is_ok_and(|store| !store.is_empty());
+    if has_hooks {
+        start_git_hook_server(app, state);
+    }
+}
+
+/// Serve the local endpoint the installed git hooks call into, unless it is
+/// already running. Only the primary instance serves it.
+///
+/// Listens on a random loopback port, written to the endpoint file the hook
+/// scripts read.
+fn start_git_hook_server(app: AppHandle, state: Arc<Mutex<SidecarState>>) {
+    if !instance_lock::is_primary() || SERVER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", 0)).await {
+            Ok(listener) => listener,
+            Err(error) => {
+                logger::log(format!("Failed to start git hook endpoint: {}", error));
+                SERVER_STARTED.store(false, Ordering::SeqCst);
+                return;
+            }
+        };
+        let port = match listener.local_addr() {
+            Ok(address) => address.port(),
+            Err(error) => {
+                logger::log(format!(
+                    "Failed to read git hook endpoint address: {}",
+                    error
+                ));
+                SERVER_STARTED.store(false, Ordering::SeqCst);
+                return;
+            }
+        };
+
+        if let Err(error) =
+            endpoint_file_path(&app).and_then(|path| write_endpoint_file(&path, port))
+        {
+            logger::log(format!("Git hook endpoint disabled: {}", error));
+            SERVER_STARTED.store(false, Ordering::SeqCst);
+            return;
+        }
+        logger::log(format!("Git hook endpoint listening on 127.0.0.1:{}", port));
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(handle_connection(
+                        app.clone(),
+                        state.clone(),
+                        stream,
+                    ));
+                }
+                Err(error) => {
+                    logger::log(format!("Git hook endpoint accept failed: {}", error));
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::sidecar_lifecycle::close_agent;
+use crate::logger;
+use crate::preferences;
+use crate::sidecar::EventHandler;
+use crate::state::SidecarState;
+
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Sessions open in the sidecar that have been idle longer than `max_idle`.
+///
+/// Sessions mid-run are never idle; sessions without recorded activity get a
+/// baseline now so they are measured from when the reaper first saw them.
+async fn idle_session_ids(state: &Arc<Mutex<SidecarState>>, max_idle: Duration) -> Vec<String> {
+    let mut state_guard = state.lock().await;
+    if state_guard.child.is_none() || state_guard.pause.is_some() {
+        return Vec::new();
+    }
+
+    let now = Instant::now();
+    let session_ids = state_guard.session_cwds.keys().cloned().collect::<Vec<_>>();
+    let mut idle = Vec::new();
+
+    for session_id in session_ids {
+        if state_guard.running_sessions.contains(&session_id) {
+            continue;
+        }
+
+        let last_activity = *state_guard
+            .session_last_activity
+            .entry(session_id.clone())
+            .or_insert(now);
+        if now.duration_since(last_activity) >= max_idle {
+            idle.push(session_id);
+        }
+    }
+
+    idle
+}
+
+async fn close_idle_sessions(app: &AppHandle, state: &Arc<Mutex<SidecarState>>, hours: u64) {
+    let max_idle = Duration::from_secs(hours.saturating_mul(60 * 60));
+
+    for session_id in idle_session_ids(state, max_idle).await {
+        match close_agent(state, session_id.clone()).await {
+            Ok(response) if response.success => {
+                logger::log(format!(
+                    "Auto-closed session {} after {}h without activity",
+                    session_id, hours
+                ));
+                EventHandler::emit_session_event(
+                    app,
+                    &session_id,
+                    serde_json::json!({
+                        "type": "session_auto_closed",
+                        "reason": "idle",
+                        "idleHours": hours,
+                    }),
+                );
+            }
+            Ok(response) => logger::log(format!(
+                "Failed to auto-close idle session {}: {}",
+                session_id,
+                response.error.unwrap_or_default()
+            )),
+            Err(error) => logger::log(format!(
+                "Failed to auto-close idle session {}: {}",
+                session_id, error
+            )),
+        }
+    }
+}
+
+/// Periodically close sessions idle for longer than the
+/// `idleSessionCloseHours` preference, keeping week-long app uptimes from
+/// accumulating sessions in the sidecar. Disabled while the preference is unset.
+pub(crate) fn spawn_idle_session_reaper(app: AppHandle, state: Arc<Mutex<SidecarState>>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            if let Some(hours) = preferences::current()
+                .idle_session_close_hours
+                .filter(|hours| *hours > 0)
+            {
+                close_idle_sessions(&app, &state, hours).await;
+            }
+        }
+    });
+}
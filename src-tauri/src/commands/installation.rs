@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::orphan_sidecars::{last_orphan_sweep, OrphanSidecarReport};
+use super::runtime_cleanup::{last_runtime_cleanup, RuntimeCleanupReport};
+use crate::logger;
+use crate::sidecar::{last_spawn_argv, resolve_sidecar_install_paths};
+use crate::state::SidecarState;
+
+/// Written by build.rs next to the runtime assets: `<asset>\t<path>\t<size>` per line.
+const RUNTIME_MANIFEST_FILE: &str = "runtime-manifest.tsv";
+const SIDECAR_BINARY_ASSET: &str = "sidecar-binary";
+
+/// Assets checked for presence when the manifest itself is missing.
+/// koffi is left out because only some builds ship it externally.
+const FALLBACK_ASSETS: [(&str, &str); 3] = [
+    ("photon-wasm", "photon_rs_bg.wasm"),
+    ("theme", "theme"),
+    ("export-html", "export-html"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssetState {
+    Ok,
+    Missing,
+    SizeMismatch,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetStatus {
+    pub name: String,
+    pub status: AssetState,
+    pub checked_files: usize,
+    /// First problem found, e.g. the missing file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallationReport {
+    pub runtime_dir: String,
+    pub manifest_found: bool,
+    pub healthy: bool,
+    pub assets: Vec<AssetStatus>,
+    /// What this launch's sweep of orphaned sidecar runtimes removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_cleanup: Option<RuntimeCleanupReport>,
+    /// What this launch's sweep of sidecars left by crashed runs did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orphan_sidecars: Option<OrphanSidecarReport>,
+    /// Program and arguments of the last sidecar launch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sidecar_argv: Option<Vec<String>>,
+}
+
+fn asset_status(
+    name: &str,
+    status: AssetState,
+    checked_files: usize,
+    detail: Option<String>,
+) -> AssetStatus {
+    AssetStatus {
+        name: name.to_string(),
+        status,
+        checked_files,
+        detail,
+    }
+}
+
+fn validate_sidecar_binary(path: &Path) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::sidecar::validate_linux_sidecar_binary(path)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() && metadata.len() > 0 => Ok(()),
+            Ok(_) => Err(format!("{} is empty or not a file", path.display())),
+            Err(error) => Err(format!("Failed to stat {}: {}", path.display(), error)),
+        }
+    }
+}
+
+fn check_sidecar_binary(binary: &Path) -> AssetStatus {
+    if !binary.exists() {
+        return asset_status(
+            SIDECAR_BINARY_ASSET,
+            AssetState::Missing,
+            1,
+            Some(binary.display().to_string()),
+        );
+    }
+
+    match validate_sidecar_binary(binary) {
+        Ok(()) => asset_status(SIDECAR_BINARY_ASSET, AssetState::Ok, 1, None),
+        Err(error) => asset_status(SIDECAR_BINARY_ASSET, AssetState::Invalid, 1, Some(error)),
+    }
+}
+
+/// Parse the manifest into asset name -> (relative path, expected size).
+fn read_manifest(path: &Path) -> Result<BTreeMap<String, Vec<(String, u64)>>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+
+    let mut assets: BTreeMap<String, Vec<(String, u64)>> = BTreeMap::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split('\t');
+        let (Some(asset), Some(file), Some(size)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!(
+                "Malformed line {} in {}",
+                index + 1,
+                path.display()
+            ));
+        };
+        let size = size.trim().parse::<u64>().map_err(|error| {
+            format!(
+                "Invalid size on line {} in {}: {}",
+                index + 1,
+                path.display(),
+                error
+            )
+        })?;
+
+        assets
+            .entry(asset.to_string())
+            .or_default()
+            .push((file.to_string(), size));
+    }
+
+    Ok(assets)
+}
+
+fn check_manifest_asset(runtime_dir: &Path, name: &str, files: &[(String, u64)]) -> AssetStatus {
+    for (file, expected_size) in files {
+        let path = runtime_dir.join(file);
+        match fs::metadata(&path) {
+            Ok(metadata) if metadata.len() == *expected_size => {}
+            Ok(metadata) => {
+                return asset_status(
+                    name,
+                    AssetState::SizeMismatch,
+                    files.len(),
+                    Some(format!(
+                        "{}: expected {} bytes, found {}",
+                        file,
+                        expected_size,
+                        metadata.len()
+                    )),
+                );
+            }
+            Err(_) => {
+                return asset_status(name, AssetState::Missing, files.len(), Some(file.clone()));
+            }
+        }
+    }
+
+    asset_status(name, AssetState::Ok, files.len(), None)
+}
+
+/// Check the sidecar binary and its runtime assets (photon wasm, theme,
+/// export-html and the external koffi module) against the build manifest.
+///
+/// On Linux this inspects the extracted runtime; a runtime that was never
+/// extracted reports everything missing until the sidecar first starts.
+pub fn verify_installation(app: &AppHandle) -> Result<InstallationReport, String> {
+    let (runtime_dir, binary) = resolve_sidecar_install_paths(app)?;
+
+    let mut assets = vec![check_sidecar_binary(&binary)];
+
+    let manifest_path = runtime_dir.join(RUNTIME_MANIFEST_FILE);
+    let manifest_found = manifest_path.exists();
+    if manifest_found {
+        for (name, files) in read_manifest(&manifest_path)? {
+            assets.push(check_manifest_asset(&runtime_dir, &name, &files));
+        }
+    } else {
+        for (name, relative_path) in FALLBACK_ASSETS {
+            let status = if runtime_dir.join(relative_path).exists() {
+                AssetState::Ok
+            } else {
+                AssetState::Missing
+            };
+            assets.push(asset_status(
+                name,
+                status,
+                1,
+                (status == AssetState::Missing).then(|| relative_path.to_string()),
+            ));
+        }
+    }
+
+    let healthy = assets.iter().all(|asset| asset.status == AssetState::Ok);
+
+    Ok(InstallationReport {
+        runtime_dir: runtime_dir.display().to_string(),
+        manifest_found,
+        healthy,
+        assets,
+        runtime_cleanup: last_runtime_cleanup(),
+        orphan_sidecars: last_orphan_sweep(),
+        sidecar_argv: last_spawn_argv(),
+    })
+}
+
+/// Re-extract the sidecar runtime and verify it again.
+///
+/// Only the Linux runtime is extracted by the app; on other platforms the
+/// assets live in the signed app bundle and a broken install must be reinstalled.
+pub async fn repair_installation(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+) -> Result<InstallationReport, String> {
+    if state.lock().await.child.is_some() {
+        return Err("Stop the sidecar before repairing the installation".to_string());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let runtime_dir = crate::sidecar::repair_linux_sidecar_runtime(app)?;
+        logger::log(format!(
+            "Re-extracted linux sidecar runtime at {}",
+            runtime_dir.display()
+        ));
+
+        verify_installation(app)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let report = verify_installation(app)?;
+        if report.healthy {
+            return Ok(report);
+        }
+
+        logger::log(format!(
+            "Installation at {} is damaged and cannot be repaired in place",
+            report.runtime_dir
+        ));
+        Err(format!(
+            "The runtime assets in {} are part of the app bundle; reinstall Graphone to repair them",
+            report.runtime_dir
+        ))
+    }
+}
@@ -35,6 +35,8 @@ pub async fn get_available_models(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     let mut response = send_command_with_response(state, cmd, 5).await?;
@@ -69,6 +71,21 @@ pub async fn get_available_models(
                 })
                 .unwrap_or_default();
 
+            {
+                let mut state_guard = state.lock().await;
+                for model in &compact_models {
+                    if let (Some(provider), Some(id), Some(supports_image_input)) = (
+                        model.get("provider").and_then(|v| v.as_str()),
+                        model.get("id").and_then(|v| v.as_str()),
+                        model.get("supportsImageInput").and_then(|v| v.as_bool()),
+                    ) {
+                        state_guard
+                            .model_image_support
+                            .insert(format!("{}/{}", provider, id), supports_image_input);
+                    }
+                }
+            }
+
             response.data = Some(serde_json::json!({ "models": compact_models }));
         }
     }
@@ -95,6 +112,8 @@ pub async fn get_oauth_providers(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
@@ -120,6 +139,8 @@ pub async fn start_oauth_login(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
@@ -144,6 +165,8 @@ pub async fn poll_oauth_login(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
@@ -169,6 +192,8 @@ pub async fn submit_oauth_login_input(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
@@ -193,6 +218,8 @@ pub async fn cancel_oauth_login(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
@@ -218,6 +245,8 @@ pub async fn logout_oauth_provider(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
@@ -244,6 +273,8 @@ pub async fn set_model(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
@@ -269,11 +300,86 @@ pub async fn set_thinking_level(
         session_file: None,
         level: Some(level),
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
 }
 
+/// Thinking levels accepted by the host when the running build does not report
+/// per-model levels itself.
+const ALL_THINKING_LEVELS: [&str; 6] = ["off", "minimal", "low", "medium", "high", "xhigh"];
+
+/// Get the thinking levels supported by the session's active model.
+///
+/// Derived from the host `get_state` payload (`availableThinkingLevels`), falling
+/// back to a static table keyed on `supportsThinking` for older host builds.
+pub async fn get_supported_thinking_levels(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: String,
+) -> Result<RpcResponse, String> {
+    let session_id = require_session_id(session_id, "get_supported_thinking_levels")?;
+
+    let cmd = RpcCommand {
+        id: Some(crypto_random_uuid()),
+        r#type: "get_state".to_string(),
+        session_id: Some(session_id),
+        cwd: None,
+        message: None,
+        provider: None,
+        model_id: None,
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    };
+
+    let mut response = send_command_with_response(state, cmd, 5).await?;
+
+    if response.success {
+        if let Some(data) = response.data.take() {
+            let supports_thinking = data
+                .get("supportsThinking")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+
+            let levels = data
+                .get("availableThinkingLevels")
+                .and_then(|v| v.as_array())
+                .map(|levels| {
+                    levels
+                        .iter()
+                        .filter_map(|level| level.as_str().map(|s| s.to_string()))
+                        .collect::<Vec<String>>()
+                })
+                .filter(|levels| !levels.is_empty())
+                .unwrap_or_else(|| {
+                    if supports_thinking {
+                        ALL_THINKING_LEVELS.iter().map(|s| s.to_string()).collect()
+                    } else {
+                        vec!["off".to_string()]
+                    }
+                });
+
+            let current_level = data
+                .get("thinkingLevel")
+                .and_then(|v| v.as_str())
+                .unwrap_or("off");
+
+            response.data = Some(serde_json::json!({
+                "supportsThinking": supports_thinking,
+                "levels": levels,
+                "currentLevel": current_level,
+            }));
+        }
+    }
+
+    Ok(response)
+}
+
 /// Cycle to next model
 pub async fn cycle_model(
     state: &Arc<Mutex<SidecarState>>,
@@ -293,6 +399,8 @@ pub async fn cycle_model(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     send_command_with_response(state, cmd, 5).await
@@ -0,0 +1,171 @@
+//! Cancellable long-running commands.
+//!
+//! A caller that wants a progress bar and a cancel button passes an `opId`.
+//! While the command runs, `cancel_operation(opId)` trips its token and its
+//! progress is emitted as `operation-progress` events carrying that id.
+//! Without an `opId` the command runs as before and cannot be cancelled.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+
+const OPERATION_PROGRESS_EVENT: &str = "operation-progress";
+
+#[derive(Default)]
+struct CancelFlag {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+/// Operations that can currently be cancelled, by op id.
+static OPERATIONS: Mutex<Option<HashMap<String, Arc<CancelFlag>>>> = Mutex::new(None);
+
+/// Handed to the code doing the work; cheap to clone into tasks.
+#[derive(Clone)]
+pub(crate) struct OperationToken {
+    app: AppHandle,
+    kind: &'static str,
+    op_id: Option<String>,
+    flag: Arc<CancelFlag>,
+}
+
+/// Keeps the operation registered; dropping it makes the op id unknown to
+/// `cancel_operation` again.
+pub(crate) struct OperationGuard {
+    token: OperationToken,
+}
+
+/// Register an operation of `kind` (e.g. `batch-run`) under `op_id`.
+pub(crate) fn begin_operation(
+    app: &AppHandle,
+    kind: &'static str,
+    op_id: Option<String>,
+) -> Result<OperationGuard, String> {
+    let op_id = op_id
+        .map(|op_id| op_id.trim().to_string())
+        .filter(|op_id| !op_id.is_empty());
+    let flag = Arc::new(CancelFlag::default());
+
+    if let Some(op_id) = &op_id {
+        let mut operations = OPERATIONS
+            .lock()
+            .map_err(|_| "Operation registry is poisoned".to_string())?;
+        let operations = operations.get_or_insert_with(HashMap::new);
+        if operations.contains_key(op_id) {
+            return Err(messages::error(
+                ErrorCode::OperationAlreadyRunning,
+                &[op_id],
+            ));
+        }
+        operations.insert(op_id.clone(), flag.clone());
+    }
+
+    Ok(OperationGuard {
+        token: OperationToken {
+            app: app.clone(),
+            kind,
+            op_id,
+            flag,
+        },
+    })
+}
+
+impl OperationGuard {
+    pub(crate) fn token(&self) -> OperationToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        let Some(op_id) = &self.token.op_id else {
+            return;
+        };
+        if let Ok(mut operations) = OPERATIONS.lock() {
+            if let Some(operations) = operations.as_mut() {
+                operations.remove(op_id);
+            }
+        }
+    }
+}
+
+impl OperationToken {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.flag.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// The `operation-cancelled` error to return once the work has stopped.
+    pub(crate) fn cancelled_error(&self) -> String {
+        messages::error(
+            ErrorCode::OperationCancelled,
+            &[&self.op_id.as_deref().unwrap_or(self.kind)],
+        )
+    }
+
+    /// `Err(operation-cancelled)` once the operation has been cancelled, for
+    /// checkpoints in loops.
+    pub(crate) fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(self.cancelled_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolves when the operation is cancelled; never for one without op id.
+    pub(crate) async fn cancelled(&self) {
+        loop {
+            let notified = self.flag.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Emit an `operation-progress` event. `total` is `None` while unknown
+    /// (e.g. during a directory walk). A no-op without op id.
+    pub(crate) fn report_progress(&self, done: usize, total: Option<usize>, detail: Option<&str>) {
+        let Some(op_id) = &self.op_id else {
+            return;
+        };
+
+        let payload = serde_json::json!({
+            "opId": op_id,
+            "kind": self.kind,
+            "done": done,
+            "total": total,
+            "detail": detail,
+        });
+        if let Err(error) = self.app.emit(OPERATION_PROGRESS_EVENT, &payload) {
+            logger::log(format!(
+                "Failed to emit {} event: {}",
+                OPERATION_PROGRESS_EVENT, error
+            ));
+        }
+    }
+}
+
+/// Cancel a running operation. Returns whether one with `op_id` was running;
+/// it stops at its next checkpoint and fails with `operation-cancelled`.
+pub fn cancel_operation(op_id: &str) -> bool {
+    let flag = OPERATIONS.lock().ok().and_then(|operations| {
+        operations
+            .as_ref()
+            .and_then(|operations| operations.get(op_id.trim()).cloned())
+    });
+
+    let Some(flag) = flag else {
+        return false;
+    };
+    flag.cancelled.store(true, Ordering::Relaxed);
+    flag.notify.notify_waiters();
+    logger::log(format!("Cancelling operation {}", op_id.trim()));
+    true
+}
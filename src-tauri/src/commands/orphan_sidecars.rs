@@ -0,0 +1,289 @@
+//! Sidecars left running by a graphone process that crashed.
+//!
+//! Each instance records its sidecar's pid in `sidecar-pids/<instance pid>.json`
+//! under app data and removes the record on a clean shutdown. At startup,
+//! records whose instance is gone are swept: a recorded sidecar that is still
+//! alive and still a graphone host (pids get reused) is killed, then the
+//! record is deleted. On Windows the process creation time recorded with the
+//! pid must match too, since any `pi.exe` has the sidecar's image name.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::sidecar_lifecycle::force_kill_process_by_pid;
+use crate::logger;
+use crate::utils::now_millis;
+
+const PID_RECORD_DIR: &str = "sidecar-pids";
+/// Present in the command line of every sidecar started by graphone.
+#[cfg(not(target_os = "windows"))]
+const SIDECAR_HOST_FLAG: &str = "--graphone-host";
+#[cfg(target_os = "windows")]
+const SIDECAR_IMAGE_NAME: &str = "pi.exe";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SidecarPidRecord {
+    pid: u32,
+    owner_pid: u32,
+    started_at: u64,
+    /// Creation time of the sidecar process (Windows FILETIME ticks).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    process_created_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanSidecarReport {
+    /// Orphaned sidecar pids that were killed.
+    pub killed: Vec<u32>,
+    /// Records of crashed instances that were removed.
+    pub cleared_records: usize,
+    pub swept_at: u64,
+}
+
+static LAST_SWEEP: OnceLock<OrphanSidecarReport> = OnceLock::new();
+/// This instance's record and the sidecar pid in it, removed again once that
+/// sidecar exits.
+static OWN_RECORD: Mutex<Option<(PathBuf, u32)>> = Mutex::new(None);
+
+/// Result of this launch's sweep, when one ran.
+pub(super) fn last_orphan_sweep() -> Option<OrphanSidecarReport> {
+    LAST_SWEEP.get().cloned()
+}
+
+fn record_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(PID_RECORD_DIR))
+        .map_err(|error| format!("Failed to resolve app data dir: {}", error))
+}
+
+fn run_quiet(program: &str, args: &[&str]) -> Option<std::process::Output> {
+    std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()
+}
+
+fn process_alive(pid: u32) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        process_image_name(pid).is_some()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        run_quiet("kill", &["-0", &pid.to_string()]).is_some_and(|output| output.status.success())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use windows_sys::Win32::Foundation::{CloseHandle, FILETIME};
+    use windows_sys::Win32::System::Threading::{
+        GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    /// Creation time of `pid` in FILETIME ticks; with the pid it identifies
+    /// one process.
+    pub fn process_created_at(pid: u32) -> Option<u64> {
+        let empty = || FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        let (mut created, mut exited, mut kernel, mut user) = (empty(), empty(), empty(), empty());
+
+        // SAFETY: the handle is checked for null and closed before returning.
+        unsafe {
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if process.is_null() {
+                return None;
+            }
+
+            let ok = GetProcessTimes(process, &mut created, &mut exited, &mut kernel, &mut user);
+            CloseHandle(process);
+
+            if ok == 0 {
+                return None;
+            }
+        }
+
+        Some(((created.dwHighDateTime as u64) << 32) | created.dwLowDateTime as u64)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn process_image_name(pid: u32) -> Option<String> {
+    let filter = format!("PID eq {}", pid);
+    let output = run_quiet("tasklist", &["/FI", &filter, "/FO", "CSV", "/NH"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // `"pi.exe","1234",...`; without a match tasklist prints an INFO line.
+    let image = stdout.lines().next()?.split(',').next()?.trim_matches('"');
+    image
+        .to_ascii_lowercase()
+        .ends_with(".exe")
+        .then(|| image.to_string())
+}
+
+/// Whether the recorded pid is (still) the sidecar started by graphone rather
+/// than an unrelated process that reused the pid.
+fn is_graphone_sidecar(record: &SidecarPidRecord) -> bool {
+    let pid = record.pid;
+
+    #[cfg(target_os = "windows")]
+    {
+        // Without a recorded creation time the pid cannot be told apart from
+        // any other `pi.exe`.
+        record.process_created_at.is_some()
+            && windows::process_created_at(pid) == record.process_created_at
+            && process_image_name(pid)
+                .is_some_and(|image| image.eq_ignore_ascii_case(SIDECAR_IMAGE_NAME))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        run_quiet("ps", &["-p", &pid.to_string(), "-o", "command="]).is_some_and(|output| {
+            String::from_utf8_lossy(&output.stdout).contains(SIDECAR_HOST_FLAG)
+        })
+    }
+}
+
+fn read_record(path: &Path) -> Option<SidecarPidRecord> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Remember the pid of the sidecar this instance just spawned.
+pub(super) fn record_sidecar_pid(app: &AppHandle, pid: u32) {
+    let write = || -> Result<PathBuf, String> {
+        let dir = record_dir(app)?;
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+        let owner_pid = std::process::id();
+        let record = SidecarPidRecord {
+            pid,
+            owner_pid,
+            started_at: now_millis(),
+            #[cfg(target_os = "windows")]
+            process_created_at: windows::process_created_at(pid),
+            #[cfg(not(target_os = "windows"))]
+            process_created_at: None,
+        };
+        let content = serde_json::to_string(&record)
+            .map_err(|error| format!("Failed to serialize sidecar pid record: {}", error))?;
+        let path = dir.join(format!("{}.json", owner_pid));
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    };
+
+    match write() {
+        Ok(path) => {
+            if let Ok(mut own_record) = OWN_RECORD.lock() {
+                *own_record = Some((path, pid));
+            }
+        }
+        Err(error) => logger::log(format!("Failed to record sidecar pid: {}", error)),
+    }
+}
+
+/// Drop this instance's record once sidecar `pid` has exited, however it
+/// exited. A record already replaced by a newer sidecar's is kept.
+pub(crate) fn clear_sidecar_pid(pid: u32) {
+    let path = OWN_RECORD
+        .lock()
+        .ok()
+        .and_then(|mut own_record| match own_record.as_ref() {
+            Some((_, recorded_pid)) if *recorded_pid == pid => own_record.take(),
+            _ => None,
+        });
+    if let Some((path, _)) = path {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn sweep_records(app: &AppHandle) -> Result<OrphanSidecarReport, String> {
+    let mut report = OrphanSidecarReport {
+        swept_at: now_millis(),
+        ..OrphanSidecarReport::default()
+    };
+
+    let dir = record_dir(app)?;
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(report);
+    };
+    let own_pid = std::process::id();
+
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let Some(record) = read_record(&path) else {
+            // Unreadable or half-written; nothing to act on.
+            let _ = std::fs::remove_file(&path);
+            report.cleared_records += 1;
+            continue;
+        };
+
+        // A record carrying our own pid is from an earlier run that reused it.
+        if record.owner_pid != own_pid && process_alive(record.owner_pid) {
+            continue;
+        }
+
+        if process_alive(record.pid) && is_graphone_sidecar(&record) {
+            match force_kill_process_by_pid(record.pid) {
+                Ok(()) => {
+                    logger::log(format!(
+                        "Killed orphaned sidecar {} left by graphone pid {}",
+                        record.pid, record.owner_pid
+                    ));
+                    report.killed.push(record.pid);
+                }
+                Err(error) => {
+                    logger::log(format!(
+                        "Failed to kill orphaned sidecar {}: {}",
+                        record.pid, error
+                    ));
+                    continue;
+                }
+            }
+        }
+
+        match std::fs::remove_file(&path) {
+            Ok(()) => report.cleared_records += 1,
+            Err(error) => logger::log(format!(
+                "Failed to remove sidecar pid record {}: {}",
+                path.display(),
+                error
+            )),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Kill sidecars orphaned by crashed instances and clear their records. Runs
+/// once at startup, before this instance spawns its own sidecar.
+pub fn sweep_orphaned_sidecars(app: &AppHandle) {
+    let report = match sweep_records(app) {
+        Ok(report) => report,
+        Err(error) => {
+            logger::log(format!("Skipped orphaned sidecar sweep: {}", error));
+            return;
+        }
+    };
+
+    if report.cleared_records > 0 {
+        logger::log(format!(
+            "Orphaned sidecar sweep: killed {:?}, cleared {} records",
+            report.killed, report.cleared_records
+        ));
+    }
+
+    let _ = LAST_SWEEP.set(report);
+}
@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::state::SidecarState;
+use crate::utils::now_millis;
+
+const DEFAULT_PASTE_SPILL_THRESHOLD_CHARS: usize = 20_000;
+const MAX_SPILL_BYTES: usize = 8 * 1024 * 1024;
+const PASTES_DIR: &str = ".pi/pastes";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpilledPaste {
+    /// Absolute path of the written file.
+    pub path: String,
+    /// Path relative to the session's project directory.
+    pub relative_path: String,
+    /// Text to put in the prompt in place of the paste.
+    pub reference: String,
+    pub chars: usize,
+    pub lines: usize,
+}
+
+/// Paste length (chars) above which the UI should offer `spill_paste_to_file`.
+pub fn paste_spill_threshold() -> usize {
+    crate::preferences::current()
+        .paste_spill_threshold_chars
+        .unwrap_or(DEFAULT_PASTE_SPILL_THRESHOLD_CHARS)
+}
+
+/// Write a large pasted text to `<project>/.pi/pastes/` and return a short
+/// prompt reference to it.
+///
+/// The target directory comes from the session's cwd (never from the UI) and
+/// the file name is generated here, so the file always lands inside the
+/// project. The pastes directory carries its own `.gitignore`.
+pub async fn spill_paste_to_file(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: String,
+    text: String,
+) -> Result<SpilledPaste, String> {
+    if text.trim().is_empty() {
+        return Err("text cannot be empty".to_string());
+    }
+
+    if text.len() > MAX_SPILL_BYTES {
+        return Err(format!(
+            "Paste is too large to spill ({} bytes, limit {})",
+            text.len(),
+            MAX_SPILL_BYTES
+        ));
+    }
+
+    let cwd = {
+        let state_guard = state.lock().await;
+        state_guard.session_cwds.get(session_id.trim()).cloned()
+    }
+    .ok_or_else(|| messages::error(ErrorCode::UnknownSession, &[&session_id]))?;
+
+    let project_dir = std::fs::canonicalize(&cwd)
+        .map_err(|e| format!("Project directory not available ({}): {}", cwd, e))?;
+    let pastes_dir = project_dir.join(PASTES_DIR);
+    std::fs::create_dir_all(&pastes_dir)
+        .map_err(|e| format!("Failed to create {}: {}", pastes_dir.display(), e))?;
+
+    let gitignore = pastes_dir.join(".gitignore");
+    if !gitignore.exists() {
+        let _ = std::fs::write(&gitignore, "*\n");
+    }
+
+    let file_name = format!("paste-{}.txt", now_millis());
+    let path: PathBuf = pastes_dir.join(&file_name);
+    if !path.starts_with(&project_dir) {
+        return Err("Refusing to write paste outside the project directory".to_string());
+    }
+
+    std::fs::write(&path, &text)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    let relative_path = format!("{}/{}", PASTES_DIR, file_name);
+    let chars = text.chars().count();
+    let lines = text.lines().count();
+
+    logger::log(format!(
+        "Spilled {} char paste for session {} to {}",
+        chars,
+        session_id,
+        path.display()
+    ));
+
+    Ok(SpilledPaste {
+        path: path.to_string_lossy().to_string(),
+        reference: format!(
+            "[Pasted text ({} lines) saved to `{}` — read it with the read tool]",
+            lines, relative_path
+        ),
+        relative_path,
+        chars,
+        lines,
+    })
+}
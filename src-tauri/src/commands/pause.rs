@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::pinned_context::{mark_pinned_context_attached, PendingPinnedContext};
+use super::provider_queue::send_or_queue_prompt;
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::platform::process_suspend::{resume_processes, suspend_process_tree};
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::now_millis;
+
+/// Commands that thaw a suspended sidecar instead of failing: stopping a run
+/// must work while it is frozen.
+const ABORT_COMMANDS: [&str; 3] = ["abort", "abort_bash", "abort_branch_summary"];
+
+/// Present in `SidecarState` while agent activity is paused.
+#[derive(Debug, Clone)]
+pub struct PauseState {
+    pub paused_at: u64,
+    /// The sidecar and the processes it started, if they were suspended at
+    /// the OS level; the sidecar comes first.
+    pub suspended_pids: Vec<u32>,
+}
+
+impl PauseState {
+    pub fn suspended(&self) -> bool {
+        !self.suspended_pids.is_empty()
+    }
+}
+
+/// A prompt held while paused, with the pinned context it carries, which
+/// counts as attached only once the prompt is sent.
+#[derive(Debug, Clone)]
+pub struct HeldPrompt {
+    pub command: RpcCommand,
+    pub pinned_context: Option<PendingPinnedContext>,
+}
+
+/// What `send_or_hold_prompt` did with a prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PromptDispatch {
+    Sent,
+    /// Waiting for its provider to drop below the turn limit.
+    Queued,
+    /// Waiting for `resume_agents`.
+    Held,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseStatus {
+    pub paused: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paused_at: Option<u64>,
+    /// The sidecar and its child processes are suspended (SIGSTOP /
+    /// NtSuspendProcess).
+    pub suspended: bool,
+    /// Prompts waiting to be sent on resume.
+    pub held_prompts: usize,
+}
+
+/// Let `command_type` through to the sidecar. While it is suspended, aborts
+/// resume its process tree (agents stay paused and prompts held) and any
+/// other command fails with `sidecar-suspended`, as it would only time out.
+pub(crate) fn admit_command(state: &mut SidecarState, command_type: &str) -> Result<(), String> {
+    let Some(pause) = state.pause.as_mut().filter(|pause| pause.suspended()) else {
+        return Ok(());
+    };
+    if !ABORT_COMMANDS.contains(&command_type) {
+        return Err(messages::error(ErrorCode::SidecarSuspended, &[]));
+    }
+
+    resume_processes(&pause.suspended_pids)?;
+    logger::log(format!(
+        "Resumed sidecar process tree of {} for {}",
+        pause.suspended_pids[0], command_type
+    ));
+    pause.suspended_pids.clear();
+    Ok(())
+}
+
+/// Forget the suspension of a sidecar that has exited; agents stay paused
+/// and their prompts held. Its pids may already belong to other processes.
+pub(crate) async fn forget_suspension(state: &Arc<Mutex<SidecarState>>, sidecar_pid: u32) {
+    let mut state_guard = state.lock().await;
+    if let Some(pause) = state_guard
+        .pause
+        .as_mut()
+        .filter(|pause| pause.suspended_pids.first() == Some(&sidecar_pid))
+    {
+        pause.suspended_pids.clear();
+        logger::log(format!(
+            "Sidecar {} exited while suspended; agents stay paused",
+            sidecar_pid
+        ));
+    }
+}
+
+fn pause_status(state: &SidecarState) -> PauseStatus {
+    PauseStatus {
+        paused: state.pause.is_some(),
+        paused_at: state.pause.as_ref().map(|pause| pause.paused_at),
+        suspended: state.pause.as_ref().is_some_and(PauseState::suspended),
+        held_prompts: state.held_prompts.len(),
+    }
+}
+
+/// Send a prompt, or hold it until `resume_agents` while paused. Prompts over
+/// their provider's turn limit are queued instead of sent.
+///
+/// `pinned_context` is the context block already in the prompt; it is marked
+/// attached when the prompt is sent, now or on resume.
+pub(crate) async fn send_or_hold_prompt(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    command: RpcCommand,
+    pinned_context: Option<PendingPinnedContext>,
+) -> Result<PromptDispatch, String> {
+    {
+        let mut state_guard = state.lock().await;
+        if state_guard.pause.is_some() {
+            state_guard.held_prompts.push(HeldPrompt {
+                command,
+                pinned_context,
+            });
+            return Ok(PromptDispatch::Held);
+        }
+    }
+
+    let dispatch = if send_or_queue_prompt(app, state, command).await? {
+        PromptDispatch::Sent
+    } else {
+        PromptDispatch::Queued
+    };
+    // A queued prompt may still be dropped; its files are offered again in
+    // a later session rather than counted as shared.
+    if let (PromptDispatch::Sent, Some(context)) = (dispatch, pinned_context.as_ref()) {
+        mark_pinned_context_attached(app, context);
+    }
+    Ok(dispatch)
+}
+
+/// Pause agent activity: new and retried prompts are held until resume.
+///
+/// With `suspend_process`, the sidecar process and every process it started
+/// (and with them any running turn and tool) are frozen so they release the
+/// CPU entirely. Until resume, aborts thaw them and other commands fail with
+/// `sidecar-suspended`.
+pub async fn pause_agents(
+    state: &Arc<Mutex<SidecarState>>,
+    suspend_process_requested: bool,
+) -> Result<PauseStatus, String> {
+    let mut state_guard = state.lock().await;
+
+    let already_suspended = state_guard
+        .pause
+        .as_ref()
+        .map(|pause| pause.suspended_pids.clone())
+        .unwrap_or_default();
+
+    let suspended_pids = if !already_suspended.is_empty() || !suspend_process_requested {
+        already_suspended
+    } else {
+        let pid = match state_guard.child.as_ref() {
+            Some(child) => Some(child.lock().await.pid()),
+            None => None,
+        };
+        match pid {
+            Some(pid) => {
+                let suspended = suspend_process_tree(pid)?;
+                logger::log(format!(
+                    "Suspended sidecar process {} and {} child processes",
+                    pid,
+                    suspended.len() - 1
+                ));
+                suspended
+            }
+            None => Vec::new(),
+        }
+    };
+
+    let paused_at = state_guard
+        .pause
+        .as_ref()
+        .map(|pause| pause.paused_at)
+        .unwrap_or_else(now_millis);
+    state_guard.pause = Some(PauseState {
+        paused_at,
+        suspended_pids,
+    });
+
+    Ok(pause_status(&state_guard))
+}
+
+/// Resume the sidecar (if suspended) and send held prompts in order.
+pub async fn resume_agents(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+) -> Result<PauseStatus, String> {
+    let held_prompts = {
+        let mut state_guard = state.lock().await;
+        let Some(pause) = state_guard.pause.as_ref() else {
+            return Ok(pause_status(&state_guard));
+        };
+
+        if pause.suspended() {
+            resume_processes(&pause.suspended_pids)?;
+            logger::log(format!(
+                "Resumed sidecar process tree of {}",
+                pause.suspended_pids[0]
+            ));
+        }
+
+        state_guard.pause = None;
+        std::mem::take(&mut state_guard.held_prompts)
+    };
+
+    for prompt in held_prompts {
+        match send_or_queue_prompt(app, state, prompt.command).await {
+            Ok(true) => {
+                if let Some(context) = prompt.pinned_context.as_ref() {
+                    mark_pinned_context_attached(app, context);
+                }
+            }
+            Ok(false) => {}
+            Err(error) => logger::log(format!("Failed to send held prompt: {}", error)),
+        }
+    }
+
+    Ok(pause_status(&*state.lock().await))
+}
+
+pub async fn get_pause_status(state: &Arc<Mutex<SidecarState>>) -> PauseStatus {
+    pause_status(&*state.lock().await)
+}
@@ -0,0 +1,266 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use super::project_metadata::{
+    load_project_metadata, project_key, update_project_metadata, PinnedContextFile,
+};
+use crate::logger;
+
+const MAX_PINNED_FILE_BYTES: u64 = 64 * 1024;
+const MAX_PINNED_CONTEXT_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedContextFileStatus {
+    /// Path relative to the project directory.
+    pub path: String,
+    /// Whether the file still exists on disk.
+    pub exists: bool,
+    pub size_bytes: Option<u64>,
+    /// Whether the content differs from what was last attached to a session.
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedContextFilesResponse {
+    pub project_dir: String,
+    pub files: Vec<PinnedContextFileStatus>,
+}
+
+/// Pinned file content prepared for injection into a session's next prompt.
+#[derive(Debug, Clone)]
+pub struct PendingPinnedContext {
+    pub project_key: String,
+    /// Context block prepended to the prompt.
+    pub block: String,
+    /// `(relative path, sha256)` of every file included in `block`.
+    pub hashes: Vec<(String, String)>,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Resolve `file_path` (absolute or project-relative) to a path relative to the project root.
+fn resolve_relative_path(project_dir: &str, file_path: &str) -> Result<String, String> {
+    let trimmed = file_path.trim();
+    if trimmed.is_empty() {
+        return Err("file_path cannot be empty".to_string());
+    }
+
+    let project_root = std::fs::canonicalize(project_dir)
+        .map_err(|e| format!("Failed to resolve project directory {}: {}", project_dir, e))?;
+
+    let candidate = Path::new(trimmed);
+    let absolute = if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        project_root.join(candidate)
+    };
+
+    let canonical = std::fs::canonicalize(&absolute)
+        .map_err(|e| format!("Failed to resolve {}: {}", absolute.display(), e))?;
+
+    if !canonical.is_file() {
+        return Err(format!("{} is not a file", canonical.display()));
+    }
+
+    let relative = canonical
+        .strip_prefix(&project_root)
+        .map_err(|_| "file_path is outside the project directory".to_string())?;
+
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn file_status(project_dir: &Path, file: &PinnedContextFile) -> PinnedContextFileStatus {
+    let absolute = project_dir.join(&file.path);
+    let content = std::fs::read(&absolute).ok();
+
+    let changed = match (&content, &file.attached_sha256) {
+        (Some(bytes), Some(attached)) => sha256_hex(bytes) != *attached,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    PinnedContextFileStatus {
+        path: file.path.clone(),
+        exists: content.is_some(),
+        size_bytes: content.as_ref().map(|bytes| bytes.len() as u64),
+        changed,
+    }
+}
+
+fn build_response(project_key: String, files: &[PinnedContextFile]) -> PinnedContextFilesResponse {
+    let project_dir = PathBuf::from(&project_key);
+    PinnedContextFilesResponse {
+        files: files
+            .iter()
+            .map(|file| file_status(&project_dir, file))
+            .collect(),
+        project_dir: project_key,
+    }
+}
+
+pub fn list_pinned_context_files(
+    app: &AppHandle,
+    project_dir: String,
+) -> Result<PinnedContextFilesResponse, String> {
+    let key = project_key(&project_dir)?;
+    let metadata = load_project_metadata(app, &key)?;
+    Ok(build_response(key, &metadata.pinned_context_files))
+}
+
+pub fn pin_context_file(
+    app: &AppHandle,
+    project_dir: String,
+    file_path: String,
+) -> Result<PinnedContextFilesResponse, String> {
+    let key = project_key(&project_dir)?;
+    let relative = resolve_relative_path(&key, &file_path)?;
+
+    let size = std::fs::metadata(PathBuf::from(&key).join(&relative))
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    if size > MAX_PINNED_FILE_BYTES {
+        return Err(format!(
+            "{} is too large to pin ({} KB limit)",
+            relative,
+            MAX_PINNED_FILE_BYTES / 1024
+        ));
+    }
+
+    let (metadata, _) = update_project_metadata(app, &key, |metadata| {
+        if !metadata
+            .pinned_context_files
+            .iter()
+            .any(|file| file.path == relative)
+        {
+            metadata.pinned_context_files.push(PinnedContextFile {
+                path: relative.clone(),
+                attached_sha256: None,
+            });
+        }
+        Ok(())
+    })?;
+
+    Ok(build_response(key, &metadata.pinned_context_files))
+}
+
+pub fn unpin_context_file(
+    app: &AppHandle,
+    project_dir: String,
+    file_path: String,
+) -> Result<PinnedContextFilesResponse, String> {
+    let key = project_key(&project_dir)?;
+    let target = file_path.trim().replace('\\', "/");
+
+    // Allow unpinning files that no longer exist by matching the stored relative path too.
+    let relative = resolve_relative_path(&key, &file_path).unwrap_or(target);
+
+    let (metadata, _) = update_project_metadata(app, &key, |metadata| {
+        metadata
+            .pinned_context_files
+            .retain(|file| file.path != relative);
+        Ok(())
+    })?;
+
+    Ok(build_response(key, &metadata.pinned_context_files))
+}
+
+/// Collect pinned files to attach to a newly created session.
+///
+/// New sessions get every pinned file; resumed sessions only get files whose
+/// content changed since it was last attached. Returns `None` when there is
+/// nothing to attach.
+pub fn prepare_pinned_context(
+    app: &AppHandle,
+    project_dir: &str,
+    resumed: bool,
+) -> Option<PendingPinnedContext> {
+    let key = project_key(project_dir).ok()?;
+    let metadata = load_project_metadata(app, &key).ok()?;
+    if metadata.pinned_context_files.is_empty() {
+        return None;
+    }
+
+    let project_root = PathBuf::from(&key);
+    let mut sections = Vec::new();
+    let mut hashes = Vec::new();
+    let mut total_bytes = 0usize;
+
+    for file in &metadata.pinned_context_files {
+        let Ok(bytes) = std::fs::read(project_root.join(&file.path)) else {
+            logger::log(format!(
+                "Pinned context file missing, skipping: {}/{}",
+                key, file.path
+            ));
+            continue;
+        };
+
+        let hash = sha256_hex(&bytes);
+        if resumed && file.attached_sha256.as_deref() == Some(hash.as_str()) {
+            continue;
+        }
+
+        if bytes.len() as u64 > MAX_PINNED_FILE_BYTES
+            || total_bytes + bytes.len() > MAX_PINNED_CONTEXT_BYTES
+        {
+            logger::log(format!(
+                "Pinned context file exceeds size budget, skipping: {}/{}",
+                key, file.path
+            ));
+            continue;
+        }
+
+        total_bytes += bytes.len();
+        sections.push(format!(
+            "<file path=\"{}\">\n{}\n</file>",
+            file.path,
+            String::from_utf8_lossy(&bytes)
+        ));
+        hashes.push((file.path.clone(), hash));
+    }
+
+    if sections.is_empty() {
+        return None;
+    }
+
+    let heading = if resumed {
+        "Pinned project files changed since they were last shared:"
+    } else {
+        "Pinned project files for context:"
+    };
+
+    Some(PendingPinnedContext {
+        project_key: key,
+        block: format!(
+            "<pinned-context>\n{}\n{}\n</pinned-context>",
+            heading,
+            sections.join("\n")
+        ),
+        hashes,
+    })
+}
+
+/// Record that pinned context was delivered so unchanged files are not re-attached.
+pub fn mark_pinned_context_attached(app: &AppHandle, context: &PendingPinnedContext) {
+    let result = update_project_metadata(app, &context.project_key, |metadata| {
+        for file in metadata.pinned_context_files.iter_mut() {
+            if let Some((_, hash)) = context.hashes.iter().find(|(path, _)| *path == file.path) {
+                file.attached_sha256 = Some(hash.clone());
+            }
+        }
+        Ok(())
+    });
+
+    if let Err(error) = result {
+        logger::log(format!("Failed to record pinned context hashes: {}", error));
+    }
+}
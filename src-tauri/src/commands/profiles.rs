@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+
+use super::action_sequence::{run_action_sequence, SequenceAction};
+use super::sidecar_lifecycle::send_command_with_response;
+use super::workspace_trust::restrict_session_tools;
+use crate::instance_lock;
+use crate::logger;
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::crypto_random_uuid;
+
+const PROFILES_STORE_FILE: &str = "profiles.json";
+const PROFILES_KEY: &str = "profiles";
+const SET_SESSION_PROFILE_TIMEOUT_SECS: u64 = 5;
+
+/// A named bundle of session settings, e.g. "cheap explorer" or "careful refactorer".
+///
+/// Unset fields leave the session's current value alone when the profile is applied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Profile {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_level: Option<String>,
+    /// Tools to deactivate; an empty list re-enables every tool.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled_tools: Option<Vec<String>>,
+    /// Text appended to the session system prompt; empty removes a previous one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesResponse {
+    pub profiles: Vec<Profile>,
+}
+
+fn load_profiles(app: &AppHandle) -> Result<Vec<Profile>, String> {
+    let store = app
+        .store(PROFILES_STORE_FILE)
+        .map_err(|error| format!("Failed to open profiles store: {}", error))?;
+
+    Ok(store
+        .get(PROFILES_KEY)
+        .and_then(|value| serde_json::from_value::<Vec<Profile>>(value).ok())
+        .unwrap_or_default())
+}
+
+fn save_profiles(app: &AppHandle, profiles: &[Profile]) -> Result<(), String> {
+    instance_lock::ensure_primary()?;
+
+    let value = serde_json::to_value(profiles)
+        .map_err(|error| format!("Failed to serialize profiles: {}", error))?;
+
+    let store = app
+        .store(PROFILES_STORE_FILE)
+        .map_err(|error| format!("Failed to open profiles store: {}", error))?;
+    store.set(PROFILES_KEY, value);
+    store
+        .save()
+        .map_err(|error| format!("Failed to save profiles store: {}", error))
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub fn list_profiles(app: &AppHandle) -> Result<ProfilesResponse, String> {
+    Ok(ProfilesResponse {
+        profiles: load_profiles(app)?,
+    })
+}
+
+/// Create or replace the profile with the same name.
+pub fn save_profile(app: &AppHandle, profile: Profile) -> Result<ProfilesResponse, String> {
+    let name = profile.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+
+    let provider = non_empty(profile.provider);
+    let model_id = non_empty(profile.model_id);
+    if provider.is_some() != model_id.is_some() {
+        return Err("Profile provider and modelId must be set together".to_string());
+    }
+
+    let profile = Profile {
+        name,
+        provider,
+        model_id,
+        thinking_level: non_empty(profile.thinking_level),
+        disabled_tools: profile.disabled_tools.map(|tools| {
+            tools
+                .into_iter()
+                .map(|tool| tool.trim().to_string())
+                .filter(|tool| !tool.is_empty())
+                .collect()
+        }),
+        system_prompt: profile
+            .system_prompt
+            .map(|prompt| prompt.trim().to_string()),
+    };
+
+    let mut profiles = load_profiles(app)?;
+    match profiles
+        .iter_mut()
+        .find(|existing| existing.name == profile.name)
+    {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    save_profiles(app, &profiles)?;
+
+    Ok(ProfilesResponse { profiles })
+}
+
+pub fn delete_profile(app: &AppHandle, name: String) -> Result<ProfilesResponse, String> {
+    let mut profiles = load_profiles(app)?;
+    let before = profiles.len();
+    profiles.retain(|profile| profile.name != name.trim());
+    if profiles.len() == before {
+        return Err(format!("Unknown profile: {}", name));
+    }
+    save_profiles(app, &profiles)?;
+
+    Ok(ProfilesResponse { profiles })
+}
+
+/// Apply a profile to an open session.
+///
+/// Model and thinking level go through an action sequence, so they are rolled
+/// back together if either fails. Tools and system prompt are sent last in a
+/// single `set_session_profile` command.
+pub async fn apply_profile(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: String,
+    name: String,
+) -> Result<Profile, String> {
+    let session_id = session_id.trim().to_string();
+    if session_id.is_empty() {
+        return Err("session_id is required for apply_profile".to_string());
+    }
+
+    let profile = load_profiles(app)?
+        .into_iter()
+        .find(|profile| profile.name == name.trim())
+        .ok_or_else(|| format!("Unknown profile: {}", name))?;
+
+    let mut actions = Vec::new();
+    if let (Some(provider), Some(model_id)) = (&profile.provider, &profile.model_id) {
+        actions.push(SequenceAction::SetModel {
+            provider: provider.clone(),
+            model_id: model_id.clone(),
+        });
+    }
+    if let Some(level) = &profile.thinking_level {
+        actions.push(SequenceAction::SetThinkingLevel {
+            level: level.clone(),
+        });
+    }
+    if !actions.is_empty() {
+        run_action_sequence(
+            app,
+            state,
+            Some(session_id.clone()),
+            actions,
+            HashMap::new(),
+        )
+        .await?;
+    }
+
+    if profile.disabled_tools.is_some() || profile.system_prompt.is_some() {
+        let tools =
+            restrict_session_tools(app, state, &session_id, profile.disabled_tools.clone()).await;
+        let command = RpcCommand {
+            id: Some(crypto_random_uuid()),
+            r#type: "set_session_profile".to_string(),
+            session_id: Some(session_id.clone()),
+            cwd: None,
+            message: profile.system_prompt.clone(),
+            provider: None,
+            model_id: None,
+            streaming_behavior: None,
+            session_file: None,
+            level: None,
+            images: None,
+            env: None,
+            tools,
+        };
+
+        let response =
+            send_command_with_response(state, command, SET_SESSION_PROFILE_TIMEOUT_SECS).await?;
+        if !response.success {
+            return Err(format!(
+                "Applied model settings of profile '{}' but failed to set tools/system prompt: {}",
+                profile.name,
+                response
+                    .error
+                    .unwrap_or_else(|| "unknown error".to_string())
+            ));
+        }
+    }
+
+    logger::log(format!(
+        "Applied profile '{}' to session {}",
+        profile.name, session_id
+    ));
+
+    Ok(profile)
+}
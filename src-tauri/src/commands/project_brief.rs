@@ -0,0 +1,501 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use super::sidecar_lifecycle::{close_agent, create_session_internal, send_command_with_response};
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::sidecar::{language_for_path, RpcClient};
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::crypto_random_uuid;
+
+const AGENTS_FILE_NAME: &str = "AGENTS.md";
+const MAX_SCAN_DEPTH: usize = 6;
+const MAX_SCAN_ENTRIES: usize = 20_000;
+const MAX_README_CHARS: usize = 4_000;
+const REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+/// `language_for_path` ids that are data or docs rather than source code.
+const NON_SOURCE_LANGUAGES: [&str; 10] = [
+    "json",
+    "toml",
+    "yaml",
+    "xml",
+    "markdown",
+    "diff",
+    "ini",
+    "dockerfile",
+    "makefile",
+    "cmake",
+];
+const REPLY_POLL_INTERVAL_MS: u64 = 750;
+const SET_SESSION_PROFILE_TIMEOUT_SECS: u64 = 5;
+/// `set_session_profile` tool name that deactivates every tool.
+const ALL_TOOLS: &str = "*";
+
+const SKIPPED_DIRS: [&str; 9] = [
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "out",
+    "vendor",
+    "venv",
+    "__pycache__",
+    "coverage",
+];
+
+const BUILD_FILES: [&str; 20] = [
+    "Cargo.toml",
+    "package.json",
+    "tsconfig.json",
+    "deno.json",
+    "pyproject.toml",
+    "setup.py",
+    "requirements.txt",
+    "go.mod",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "CMakeLists.txt",
+    "Makefile",
+    "meson.build",
+    "Gemfile",
+    "composer.json",
+    "mix.exs",
+    "Dockerfile",
+    "flake.nix",
+    "justfile",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageShare {
+    pub language: String,
+    pub files: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectInspection {
+    /// Source languages by file count, most common first, as syntax
+    /// highlighting ids (`rust`, `typescript`, ...).
+    pub languages: Vec<LanguageShare>,
+    /// Build/config files found (project-relative paths).
+    pub build_files: Vec<String>,
+    /// README file name, when present.
+    pub readme: Option<String>,
+    /// Whether an AGENTS.md already exists at the project root.
+    pub has_agents_file: bool,
+    /// Whether the scan stopped early because the tree was too large.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectBriefDraft {
+    pub project_dir: String,
+    /// Where the brief will be written once confirmed.
+    pub target_path: String,
+    pub inspection: ProjectInspection,
+    /// Drafted AGENTS.md markdown.
+    pub content: String,
+}
+
+fn should_skip_dir(name: &str) -> bool {
+    name.starts_with('.') || SKIPPED_DIRS.contains(&name)
+}
+
+pub(crate) fn inspect_project(project_dir: &Path) -> ProjectInspection {
+    let mut language_counts = BTreeMap::<&'static str, usize>::new();
+    let mut build_files = Vec::new();
+    let mut visited = 0usize;
+    let mut truncated = false;
+    let mut pending = vec![(project_dir.to_path_buf(), 0usize)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            visited += 1;
+            if visited > MAX_SCAN_ENTRIES {
+                truncated = true;
+                break;
+            }
+
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            if file_type.is_dir() {
+                if depth < MAX_SCAN_DEPTH && !should_skip_dir(&name) {
+                    pending.push((path, depth + 1));
+                }
+                continue;
+            }
+
+            if BUILD_FILES.contains(&name.as_str()) {
+                if let Ok(relative) = path.strip_prefix(project_dir) {
+                    build_files.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+
+            if let Some(language) =
+                language_for_path(&name).filter(|language| !NON_SOURCE_LANGUAGES.contains(language))
+            {
+                *language_counts.entry(language).or_default() += 1;
+            }
+        }
+
+        if truncated {
+            break;
+        }
+    }
+
+    let mut languages = language_counts
+        .into_iter()
+        .map(|(language, files)| LanguageShare {
+            language: language.to_string(),
+            files,
+        })
+        .collect::<Vec<_>>();
+    languages.sort_by(|a, b| b.files.cmp(&a.files).then(a.language.cmp(&b.language)));
+
+    build_files.sort_by(|a, b| {
+        a.matches('/')
+            .count()
+            .cmp(&b.matches('/').count())
+            .then(a.cmp(b))
+    });
+    build_files.truncate(40);
+
+    ProjectInspection {
+        languages,
+        build_files,
+        readme: find_readme(project_dir).and_then(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        }),
+        has_agents_file: project_dir.join(AGENTS_FILE_NAME).is_file(),
+        truncated,
+    }
+}
+
+fn find_readme(project_dir: &Path) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(project_dir).ok()?;
+    entries.flatten().map(|entry| entry.path()).find(|path| {
+        path.is_file()
+            && path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(|stem| stem.eq_ignore_ascii_case("readme"))
+                .unwrap_or(false)
+    })
+}
+
+fn build_brief_prompt(project_dir: &Path, inspection: &ProjectInspection) -> String {
+    let languages = if inspection.languages.is_empty() {
+        "(none detected)".to_string()
+    } else {
+        inspection
+            .languages
+            .iter()
+            .take(8)
+            .map(|share| format!("{} ({} files)", share.language, share.files))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let build_files = if inspection.build_files.is_empty() {
+        "(none detected)".to_string()
+    } else {
+        inspection.build_files.join(", ")
+    };
+
+    let readme_excerpt = find_readme(project_dir)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|content| content.chars().take(MAX_README_CHARS).collect::<String>())
+        .unwrap_or_else(|| "(no README)".to_string());
+
+    format!(
+        "Draft an {agents} file for this repository so coding agents can work in it effectively.\n\
+         Cover: project purpose, stack, build/test/lint commands, project structure, and conventions.\n\
+         Keep it concise. Reply with the markdown content of {agents} only, without code fences or commentary.\n\n\
+         Languages: {languages}\n\
+         Build files: {build_files}\n\n\
+         README excerpt:\n{readme_excerpt}",
+        agents = AGENTS_FILE_NAME,
+    )
+}
+
+fn extract_last_assistant_text(messages: &serde_json::Value) -> Option<String> {
+    let messages = messages
+        .get("messages")
+        .and_then(|value| value.as_array())
+        .or_else(|| messages.as_array())?;
+
+    let message = messages
+        .iter()
+        .rev()
+        .find(|message| message.get("role").and_then(|v| v.as_str()) == Some("assistant"))?;
+
+    let content = message.get("content")?;
+    if let Some(text) = content.as_str() {
+        return Some(text.trim().to_string());
+    }
+
+    let text = content
+        .as_array()?
+        .iter()
+        .filter(|block| block.get("type").and_then(|v| v.as_str()) == Some("text"))
+        .filter_map(|block| block.get("text").and_then(|v| v.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+fn session_command(command_type: &str, session_id: &str, message: Option<String>) -> RpcCommand {
+    RpcCommand {
+        id: Some(crypto_random_uuid()),
+        r#type: command_type.to_string(),
+        session_id: Some(session_id.to_string()),
+        cwd: None,
+        message,
+        provider: None,
+        model_id: None,
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    }
+}
+
+/// Run one prompt in a throwaway session and return the assistant's reply text.
+async fn run_one_shot_prompt(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    prompt: String,
+    reply_timeout: Duration,
+) -> Result<String, String> {
+    RpcClient::send_command(state, session_command("prompt", session_id, Some(prompt))).await?;
+
+    let started = Instant::now();
+    loop {
+        sleep(Duration::from_millis(REPLY_POLL_INTERVAL_MS)).await;
+
+        if started.elapsed() > reply_timeout {
+            let _ =
+                RpcClient::send_command(state, session_command("abort", session_id, None)).await;
+            return Err("Timed out waiting for the agent's reply".to_string());
+        }
+
+        let response =
+            send_command_with_response(state, session_command("get_state", session_id, None), 5)
+                .await?;
+        let Some(data) = response.data.filter(|_| response.success) else {
+            continue;
+        };
+
+        let is_streaming = data
+            .get("isStreaming")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let message_count = data
+            .get("messageCount")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+
+        if !is_streaming && message_count >= 2 {
+            break;
+        }
+    }
+
+    let response =
+        send_command_with_response(state, session_command("get_messages", session_id, None), 10)
+            .await?;
+    if !response.success {
+        return Err(response
+            .error
+            .unwrap_or_else(|| "Failed to read the agent's reply".to_string()));
+    }
+
+    response
+        .data
+        .as_ref()
+        .and_then(extract_last_assistant_text)
+        .ok_or_else(|| "The agent did not reply".to_string())
+}
+
+/// Deactivate every tool of a session, so its agent can only answer.
+async fn disable_all_tools(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+) -> Result<(), String> {
+    let mut command = session_command("set_session_profile", session_id, None);
+    command.tools = Some(vec![ALL_TOOLS.to_string()]);
+
+    let response =
+        send_command_with_response(state, command, SET_SESSION_PROFILE_TIMEOUT_SECS).await?;
+    if response.success {
+        Ok(())
+    } else {
+        Err(response
+            .error
+            .unwrap_or_else(|| "Failed to deactivate the session's tools".to_string()))
+    }
+}
+
+/// Run `prompt` in a new session in `project_dir` whose agent has no tools,
+/// and return the reply text; fails after `reply_timeout`.
+///
+/// The agent can only answer, so it cannot change the project it is asked
+/// about. The session is closed and its session file removed afterwards, so
+/// nothing shows up in the project's history.
+pub(super) async fn run_tool_free_prompt(
+    app: AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: &Path,
+    prompt: String,
+    reply_timeout: Duration,
+) -> Result<String, String> {
+    let create_response = create_session_internal(
+        app,
+        state,
+        project_dir.to_string_lossy().to_string(),
+        None,
+        None,
+        None,
+    )
+    .await?;
+
+    if !create_response.success {
+        return Err(create_response
+            .error
+            .unwrap_or_else(|| "Failed to create session".to_string()));
+    }
+
+    let data = create_response.data.unwrap_or_default();
+    let session_id = data
+        .get("sessionId")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Session id missing from create_session response".to_string())?
+        .to_string();
+    let session_file = data
+        .get("sessionFile")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from);
+
+    let result = match disable_all_tools(state, &session_id).await {
+        Ok(()) => run_one_shot_prompt(state, &session_id, prompt, reply_timeout).await,
+        Err(error) => Err(error),
+    };
+
+    if let Err(error) = close_agent(state, session_id.clone()).await {
+        logger::log(format!(
+            "Failed to close throwaway session {}: {}",
+            session_id, error
+        ));
+    }
+
+    if let Some(session_file) = session_file.filter(|path| path.is_file()) {
+        if let Err(error) = std::fs::remove_file(&session_file) {
+            logger::log(format!(
+                "Failed to remove throwaway session file {}: {}",
+                session_file.display(),
+                error
+            ));
+        }
+    }
+
+    result
+}
+
+/// Inspect a project and ask the agent to draft an AGENTS.md for it.
+///
+/// Nothing is written: the draft is returned so the user can review it and
+/// confirm via `write_project_brief`. The throwaway session used for the
+/// draft is closed and its session file removed afterwards.
+pub async fn generate_project_brief(
+    app: AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: String,
+) -> Result<ProjectBriefDraft, String> {
+    let project_path = PathBuf::from(project_dir.trim());
+    if !project_path.is_dir() {
+        return Err(messages::error(
+            ErrorCode::ProjectDirNotFound,
+            &[&project_dir],
+        ));
+    }
+
+    let inspection = inspect_project(&project_path);
+    let prompt = build_brief_prompt(&project_path, &inspection);
+
+    let content = run_tool_free_prompt(app, state, &project_path, prompt, REPLY_TIMEOUT).await?;
+
+    Ok(ProjectBriefDraft {
+        project_dir: project_path.to_string_lossy().to_string(),
+        target_path: project_path
+            .join(AGENTS_FILE_NAME)
+            .to_string_lossy()
+            .to_string(),
+        inspection,
+        content,
+    })
+}
+
+/// Write a confirmed project brief to `<project_dir>/AGENTS.md`.
+///
+/// Refuses to replace an existing file unless `overwrite` is set.
+pub fn write_project_brief(
+    project_dir: String,
+    content: String,
+    overwrite: Option<bool>,
+) -> Result<String, String> {
+    let project_path = PathBuf::from(project_dir.trim());
+    if !project_path.is_dir() {
+        return Err(messages::error(
+            ErrorCode::ProjectDirNotFound,
+            &[&project_dir],
+        ));
+    }
+
+    if content.trim().is_empty() {
+        return Err("content cannot be empty".to_string());
+    }
+
+    let target = project_path.join(AGENTS_FILE_NAME);
+    if target.exists() && !overwrite.unwrap_or(false) {
+        return Err(format!("{} already exists", target.display()));
+    }
+
+    let content = if content.ends_with('\n') {
+        content
+    } else {
+        format!("{}\n", content)
+    };
+
+    std::fs::write(&target, content)
+        .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+
+    logger::log(format!("Wrote project brief to {}", target.display()));
+    Ok(target.to_string_lossy().to_string())
+}
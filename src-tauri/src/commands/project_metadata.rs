@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use super::session_scopes::normalize_path_for_comparison;
+use super::watch_rules::WatchRule;
+use crate::instance_lock;
+use crate::messages::{self, ErrorCode};
+
+const PROJECT_METADATA_STORE_FILE: &str = "project-metadata.json";
+
+/// A project file pinned as context for new sessions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PinnedContextFile {
+    /// Path relative to the project directory.
+    pub path: String,
+    /// SHA-256 of the content that was last attached to a session, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attached_sha256: Option<String>,
+}
+
+/// Graphone-owned per-project metadata, keyed by normalized project directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectMetadata {
+    pub pinned_context_files: Vec<PinnedContextFile>,
+    /// When the project was registered from graphone (unix millis), so it is
+    /// listed before any session has been persisted for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registered_at: Option<u64>,
+    /// Environment overrides applied to tool executions in this project's sessions.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// File-change rules that send a prompt to a session.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub watch_rules: Vec<WatchRule>,
+    /// Append an environment footer (git branch, dirty files, OS, model) to
+    /// prompts sent in this project.
+    pub prompt_footer: bool,
+}
+
+impl ProjectMetadata {
+    fn is_empty(&self) -> bool {
+        self.pinned_context_files.is_empty()
+            && self.registered_at.is_none()
+            && self.env.is_empty()
+            && self.watch_rules.is_empty()
+            && !self.prompt_footer
+    }
+}
+
+pub(crate) fn project_key(project_dir: &str) -> Result<String, String> {
+    let normalized = normalize_path_for_comparison(project_dir);
+    if normalized.is_empty() {
+        return Err(messages::error(ErrorCode::ProjectDirRequired, &[]));
+    }
+
+    Ok(normalized)
+}
+
+pub(crate) fn load_project_metadata(
+    app: &AppHandle,
+    project_key: &str,
+) -> Result<ProjectMetadata, String> {
+    let store = app
+        .store(PROJECT_METADATA_STORE_FILE)
+        .map_err(|error| format!("Failed to open project metadata store: {}", error))?;
+
+    Ok(store
+        .get(project_key)
+        .and_then(|value| serde_json::from_value::<ProjectMetadata>(value).ok())
+        .unwrap_or_default())
+}
+
+/// Read-modify-write a project's metadata entry. Entries that end up empty are removed.
+pub(crate) fn update_project_metadata<T>(
+    app: &AppHandle,
+    project_key: &str,
+    update: impl FnOnce(&mut ProjectMetadata) -> Result<T, String>,
+) -> Result<(ProjectMetadata, T), String> {
+    instance_lock::ensure_primary()?;
+
+    let store = app
+        .store(PROJECT_METADATA_STORE_FILE)
+        .map_err(|error| format!("Failed to open project metadata store: {}", error))?;
+
+    let mut metadata = store
+        .get(project_key)
+        .and_then(|value| serde_json::from_value::<ProjectMetadata>(value).ok())
+        .unwrap_or_default();
+
+    let output = update(&mut metadata)?;
+
+    if metadata.is_empty() {
+        store.delete(project_key);
+    } else {
+        let value = serde_json::to_value(&metadata)
+            .map_err(|error| format!("Failed to serialize project metadata: {}", error))?;
+        store.set(project_key, value);
+    }
+
+    store
+        .save()
+        .map_err(|error| format!("Failed to save project metadata: {}", error))?;
+
+    Ok((metadata, output))
+}
+
+/// Enabled watch rules of every project that has any, keyed by project directory.
+pub(crate) fn enabled_watch_rules(app: &AppHandle) -> Vec<(String, Vec<WatchRule>)> {
+    let Ok(store) = app.store(PROJECT_METADATA_STORE_FILE) else {
+        return Vec::new();
+    };
+
+    store
+        .entries()
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let metadata = serde_json::from_value::<ProjectMetadata>(value).ok()?;
+            let rules = metadata
+                .watch_rules
+                .into_iter()
+                .filter(|rule| rule.enabled)
+                .collect::<Vec<_>>();
+            (!rules.is_empty()).then_some((key, rules))
+        })
+        .collect()
+}
+
+/// Project directories registered from graphone (e.g. created via `create_project`).
+pub(crate) fn registered_project_dirs(app: &AppHandle) -> Vec<String> {
+    let Ok(store) = app.store(PROJECT_METADATA_STORE_FILE) else {
+        return Vec::new();
+    };
+
+    store
+        .entries()
+        .into_iter()
+        .filter(|(_, value)| {
+            serde_json::from_value::<ProjectMetadata>(value.clone())
+                .map(|metadata| metadata.registered_at.is_some())
+                .unwrap_or(false)
+        })
+        .map(|(key, _)| key)
+        .collect()
+}
@@ -0,0 +1,242 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::git::init_repository;
+use super::project_metadata::{project_key, update_project_metadata};
+use super::sidecar_lifecycle::create_session_internal;
+use crate::logger;
+use crate::state::SidecarState;
+use crate::utils::now_millis;
+
+const DEFAULT_TEMPLATE: &str = "blank";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateProjectResponse {
+    pub project_dir: String,
+    pub template: String,
+    pub git_initialized: bool,
+    /// Files written by the scaffold (project-relative paths).
+    pub created_files: Vec<String>,
+    /// `create_session` response data for the session opened in the new project.
+    pub session: serde_json::Value,
+}
+
+struct ProjectTemplate {
+    name: &'static str,
+    stack: &'static str,
+    commands: &'static [&'static str],
+    gitignore: &'static [&'static str],
+}
+
+const TEMPLATES: [ProjectTemplate; 4] = [
+    ProjectTemplate {
+        name: "blank",
+        stack: "Not decided yet.",
+        commands: &[],
+        gitignore: &[".DS_Store"],
+    },
+    ProjectTemplate {
+        name: "rust",
+        stack: "Rust (cargo).",
+        commands: &[
+            "cargo build",
+            "cargo test",
+            "cargo clippy --all-targets -- -D warnings",
+            "cargo fmt",
+        ],
+        gitignore: &["/target", ".DS_Store"],
+    },
+    ProjectTemplate {
+        name: "node",
+        stack: "TypeScript / Node.js.",
+        commands: &["npm install", "npm run build", "npm test"],
+        gitignore: &["node_modules/", "dist/", ".env", ".DS_Store"],
+    },
+    ProjectTemplate {
+        name: "python",
+        stack: "Python.",
+        commands: &["python -m venv .venv", "pip install -e .", "pytest"],
+        gitignore: &[".venv/", "__pycache__/", "*.pyc", ".env", ".DS_Store"],
+    },
+];
+
+fn find_template(name: &str) -> Result<&'static ProjectTemplate, String> {
+    TEMPLATES
+        .iter()
+        .find(|template| template.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names = TEMPLATES
+                .iter()
+                .map(|template| template.name)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "Unknown project template '{}' (expected one of: {})",
+                name, names
+            )
+        })
+}
+
+fn render_agents_md(project_name: &str, template: &ProjectTemplate) -> String {
+    let commands = if template.commands.is_empty() {
+        "- _Add build, test and lint commands here._".to_string()
+    } else {
+        template
+            .commands
+            .iter()
+            .map(|command| format!("- `{}`", command))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    format!(
+        "# {project_name}\n\n\
+         ## Purpose\n\n\
+         _Describe what this project is for._\n\n\
+         ## Stack\n\n\
+         {stack}\n\n\
+         ## Commands\n\n\
+         {commands}\n\n\
+         ## Conventions\n\n\
+         - Keep changes small and focused.\n\
+         - Update this file when the workflow changes.\n",
+        stack = template.stack,
+    )
+}
+
+fn write_new_file(
+    project_path: &Path,
+    relative: &str,
+    content: &str,
+    created: &mut Vec<String>,
+) -> Result<(), String> {
+    let path = project_path.join(relative);
+    if path.exists() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    created.push(relative.to_string());
+    Ok(())
+}
+
+/// Scaffold a new agent project and open a session in it.
+///
+/// Creates the directory (which must be new or empty), optionally runs
+/// `git init`, writes a starter `.pi/settings.json`, `AGENTS.md` and
+/// `.gitignore`, and registers the project so it shows up in the project
+/// list before any session has been saved.
+pub async fn create_project(
+    app: AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    path: String,
+    template: Option<String>,
+    git_init: Option<bool>,
+) -> Result<CreateProjectResponse, String> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err("path cannot be empty".to_string());
+    }
+
+    let template = find_template(template.as_deref().unwrap_or(DEFAULT_TEMPLATE))?;
+    let project_path = PathBuf::from(trimmed);
+
+    if project_path.exists() {
+        if !project_path.is_dir() {
+            return Err(format!("{} exists and is not a directory", trimmed));
+        }
+
+        let mut entries = std::fs::read_dir(&project_path)
+            .map_err(|e| format!("Failed to read {}: {}", trimmed, e))?;
+        if entries.next().is_some() {
+            return Err(format!("{} already exists and is not empty", trimmed));
+        }
+    }
+
+    std::fs::create_dir_all(&project_path)
+        .map_err(|e| format!("Failed to create {}: {}", trimmed, e))?;
+
+    let git_initialized = if git_init.unwrap_or(true) {
+        match init_repository(&project_path) {
+            Ok(()) => true,
+            Err(error) => {
+                logger::log(format!("create_project: {}", error));
+                false
+            }
+        }
+    } else {
+        false
+    };
+
+    let project_name = project_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| trimmed.to_string());
+
+    let mut created_files = Vec::new();
+    write_new_file(
+        &project_path,
+        ".pi/settings.json",
+        "{}\n",
+        &mut created_files,
+    )?;
+    write_new_file(
+        &project_path,
+        "AGENTS.md",
+        &render_agents_md(&project_name, template),
+        &mut created_files,
+    )?;
+    if git_initialized {
+        write_new_file(
+            &project_path,
+            ".gitignore",
+            &format!("{}\n", template.gitignore.join("\n")),
+            &mut created_files,
+        )?;
+    }
+
+    let project_dir = project_path.to_string_lossy().to_string();
+    let key = project_key(&project_dir)?;
+    update_project_metadata(&app, &key, |metadata| {
+        metadata.registered_at.get_or_insert_with(now_millis);
+        Ok(())
+    })?;
+
+    let response =
+        create_session_internal(app, state, project_dir.clone(), None, None, None).await?;
+    if !response.success {
+        return Err(format!(
+            "Project created at {} but the session failed to start: {}",
+            project_dir,
+            response
+                .error
+                .unwrap_or_else(|| "unknown error".to_string())
+        ));
+    }
+
+    logger::log(format!(
+        "Created {} project at {} ({} files)",
+        template.name,
+        project_dir,
+        created_files.len()
+    ));
+
+    Ok(CreateProjectResponse {
+        project_dir,
+        template: template.name.to_string(),
+        git_initialized,
+        created_files,
+        session: response.data.unwrap_or_default(),
+    })
+}
@@ -0,0 +1,243 @@
+use std::sync::Arc;
+
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use crate::logger;
+use crate::sidecar::{session_state_snapshot, EventHandler};
+use crate::state::SidecarState;
+use crate::types::RpcImageAttachment;
+
+const DEFAULT_MAX_ATTACHMENT_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Recompression steps tried in order: (longest edge in px, JPEG quality).
+const RECOMPRESSION_STEPS: [(u32, u8); 5] =
+    [(2048, 85), (1600, 80), (1280, 75), (1024, 65), (768, 55)];
+
+/// Drop image attachments the session's active model cannot accept.
+///
+/// Uses the `supportsImageInput` flag cached from `get_available_models`,
+/// falling back to the model's declared input kinds. Images are stripped with
+/// an `attachments_dropped` warning event so the text still goes through; an
+/// image-only prompt is rejected instead. When the model can't be determined
+/// the images are forwarded unchanged.
+pub(crate) async fn filter_images_for_model(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    prompt: &str,
+    images: Option<Vec<RpcImageAttachment>>,
+) -> Result<Option<Vec<RpcImageAttachment>>, String> {
+    let Some(images) = images else {
+        return Ok(None);
+    };
+
+    let Some(model) = session_state_snapshot(state, session_id, false)
+        .await
+        .ok()
+        .and_then(|snapshot| snapshot.model)
+    else {
+        return Ok(Some(images));
+    };
+
+    let cached = state
+        .lock()
+        .await
+        .model_image_support
+        .get(&format!("{}/{}", model.provider, model.id))
+        .copied();
+
+    if cached.or(model.supports_image_input).unwrap_or(true) {
+        return Ok(Some(images));
+    }
+
+    if prompt.trim().is_empty() {
+        return Err(format!(
+            "Model {}/{} does not support image input",
+            model.provider, model.id
+        ));
+    }
+
+    logger::log(format!(
+        "Dropping {} image attachment(s) for {}/{} (no image input)",
+        images.len(),
+        model.provider,
+        model.id
+    ));
+
+    EventHandler::emit_session_event(
+        app,
+        session_id,
+        serde_json::json!({
+            "type": "attachments_dropped",
+            "reason": "image_input_unsupported",
+            "provider": model.provider,
+            "modelId": model.id,
+            "count": images.len(),
+        }),
+    );
+
+    Ok(None)
+}
+
+struct DecodedAttachment {
+    mime_type: String,
+    original_len: usize,
+    bytes: Vec<u8>,
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.0} KB", bytes as f64 / 1024.0)
+    }
+}
+
+fn total_len(attachments: &[DecodedAttachment]) -> u64 {
+    attachments
+        .iter()
+        .map(|attachment| attachment.bytes.len() as u64)
+        .sum()
+}
+
+/// Re-encode as JPEG within `max_edge`, flattening transparency onto white.
+fn recompress_image(bytes: &[u8], max_edge: u32, quality: u8) -> Option<Vec<u8>> {
+    use image::codecs::jpeg::JpegEncoder;
+    use image::imageops::FilterType;
+    use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+
+    let decoded = image::load_from_memory(bytes).ok()?;
+    let (width, height) = decoded.dimensions();
+    let resized = if width.max(height) > max_edge {
+        decoded.resize(max_edge, max_edge, FilterType::Triangle)
+    } else {
+        decoded
+    };
+
+    let rgba = resized.to_rgba8();
+    let flattened = RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend =
+            |channel: u8| ((channel as u32 * a as u32 + 255 * (255 - a as u32)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    });
+
+    let mut output = Vec::new();
+    DynamicImage::ImageRgb8(flattened)
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut output, quality))
+        .ok()?;
+    Some(output)
+}
+
+fn fit_attachments_to_budget(
+    images: Vec<RpcImageAttachment>,
+    budget: u64,
+) -> Result<Vec<RpcImageAttachment>, String> {
+    use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+    use base64::Engine;
+
+    let mut attachments = images
+        .into_iter()
+        .enumerate()
+        .map(|(index, image)| {
+            BASE64_STANDARD
+                .decode(image.data.as_bytes())
+                .map(|bytes| DecodedAttachment {
+                    mime_type: image.mime_type,
+                    original_len: bytes.len(),
+                    bytes,
+                })
+                .map_err(|error| {
+                    format!("Attachment #{} is not valid base64: {}", index + 1, error)
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if total_len(&attachments) > budget {
+        let originals = attachments
+            .iter()
+            .map(|attachment| attachment.bytes.clone())
+            .collect::<Vec<_>>();
+
+        for (max_edge, quality) in RECOMPRESSION_STEPS {
+            for (attachment, original) in attachments.iter_mut().zip(&originals) {
+                if let Some(recompressed) = recompress_image(original, max_edge, quality) {
+                    if recompressed.len() < attachment.bytes.len() {
+                        attachment.bytes = recompressed;
+                        attachment.mime_type = "image/jpeg".to_string();
+                    }
+                }
+            }
+
+            if total_len(&attachments) <= budget {
+                break;
+            }
+        }
+    }
+
+    let total = total_len(&attachments);
+    if total > budget {
+        let details = attachments
+            .iter()
+            .enumerate()
+            .map(|(index, attachment)| {
+                format!(
+                    "#{} {} ({} -> {})",
+                    index + 1,
+                    attachment.mime_type,
+                    format_bytes(attachment.original_len as u64),
+                    format_bytes(attachment.bytes.len() as u64)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        return Err(format!(
+            "Attachments total {} after recompression, over the {} budget: {}",
+            format_bytes(total),
+            format_bytes(budget),
+            details
+        ));
+    }
+
+    Ok(attachments
+        .into_iter()
+        .map(|attachment| RpcImageAttachment {
+            r#type: "image".to_string(),
+            data: BASE64_STANDARD.encode(&attachment.bytes),
+            mime_type: attachment.mime_type,
+        })
+        .collect())
+}
+
+/// Keep a prompt's image attachments within the configured byte budget.
+///
+/// Oversized sets are recompressed stepwise (smaller dimensions, lower JPEG
+/// quality) until they fit; if they still don't, the error lists each
+/// attachment with its original and best-effort size.
+pub(crate) async fn enforce_attachment_budget(
+    images: Option<Vec<RpcImageAttachment>>,
+) -> Result<Option<Vec<RpcImageAttachment>>, String> {
+    let Some(images) = images else {
+        return Ok(None);
+    };
+
+    let budget = crate::preferences::current()
+        .max_attachment_bytes
+        .unwrap_or(DEFAULT_MAX_ATTACHMENT_BYTES);
+
+    // Base64 is ~4/3 of the decoded size; skip decoding when clearly within budget.
+    let estimated = images
+        .iter()
+        .map(|image| image.data.len() as u64 * 3 / 4)
+        .sum::<u64>();
+    if estimated <= budget {
+        return Ok(Some(images));
+    }
+
+    tokio::task::spawn_blocking(move || fit_attachments_to_budget(images, budget))
+        .await
+        .map_err(|error| format!("Attachment recompression failed: {}", error))?
+        .map(Some)
+}
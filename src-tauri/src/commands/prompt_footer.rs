@@ -0,0 +1,128 @@
+//! Optional environment footer appended to prompts: git branch, dirty files,
+//! OS and active model, so the agent has basic context without the user
+//! typing it. Enabled per project.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::git::{current_branch, dirty_files};
+use super::project_metadata::{load_project_metadata, project_key, update_project_metadata};
+use crate::sidecar::session_state_snapshot;
+use crate::state::SidecarState;
+
+/// Dirty files listed by name; the rest are only counted.
+const MAX_FOOTER_DIRTY_FILES: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFooterResponse {
+    pub project_dir: String,
+    pub enabled: bool,
+}
+
+pub fn get_prompt_footer(
+    app: &AppHandle,
+    project_dir: String,
+) -> Result<PromptFooterResponse, String> {
+    let key = project_key(&project_dir)?;
+    let enabled = load_project_metadata(app, &key)?.prompt_footer;
+    Ok(PromptFooterResponse {
+        project_dir,
+        enabled,
+    })
+}
+
+pub fn set_prompt_footer(
+    app: &AppHandle,
+    project_dir: String,
+    enabled: bool,
+) -> Result<PromptFooterResponse, String> {
+    let key = project_key(&project_dir)?;
+    update_project_metadata(app, &key, |metadata| {
+        metadata.prompt_footer = enabled;
+        Ok(())
+    })?;
+    Ok(PromptFooterResponse {
+        project_dir,
+        enabled,
+    })
+}
+
+fn git_lines(project_dir: PathBuf) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(branch) = current_branch(&project_dir) {
+        lines.push(format!("Git branch: {}", branch));
+    }
+    match dirty_files(&project_dir) {
+        Some(files) if files.is_empty() => lines.push("Uncommitted changes: none".to_string()),
+        Some(files) => {
+            let mut listed = files
+                .iter()
+                .take(MAX_FOOTER_DIRTY_FILES)
+                .cloned()
+                .collect::<Vec<_>>();
+            if files.len() > MAX_FOOTER_DIRTY_FILES {
+                listed.push(format!(
+                    "... and {} more",
+                    files.len() - MAX_FOOTER_DIRTY_FILES
+                ));
+            }
+            lines.push(format!(
+                "Uncommitted changes ({}): {}",
+                files.len(),
+                listed.join(", ")
+            ));
+        }
+        None => {}
+    }
+    lines
+}
+
+/// The footer for `prompt` to `session_id`, when its project has it enabled.
+///
+/// Slash commands (`/skill:...`, extension commands, prompt templates) get
+/// none: the sidecar would pass the footer on as part of their arguments.
+pub(crate) async fn prompt_footer(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    prompt: &str,
+) -> Option<String> {
+    if prompt.trim_start().starts_with('/') {
+        return None;
+    }
+
+    let cwd = state.lock().await.session_cwds.get(session_id).cloned()?;
+    let key = project_key(&cwd).ok()?;
+    if !load_project_metadata(app, &key).ok()?.prompt_footer {
+        return None;
+    }
+
+    let model = session_state_snapshot(state, session_id, false)
+        .await
+        .ok()
+        .and_then(|snapshot| snapshot.model)
+        .map(|model| format!("{}/{}", model.provider, model.id));
+
+    let project_dir = PathBuf::from(&cwd);
+    let mut lines = tauri::async_runtime::spawn_blocking(move || git_lines(project_dir))
+        .await
+        .unwrap_or_default();
+    lines.push(format!(
+        "OS: {} ({})",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    ));
+    if let Some(model) = model {
+        lines.push(format!("Model: {}", model));
+    }
+
+    Some(format!(
+        "<environment>\n{}\n</environment>",
+        lines.join("\n")
+    ))
+}
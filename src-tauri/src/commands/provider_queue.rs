@@ -0,0 +1,234 @@
+//! Per-provider cap on concurrent agent turns.
+//!
+//! With many sessions on one provider, starting every prompt at once trips
+//! its rate limits. When `providerTurnLimits` caps a provider, prompts past
+//! the cap wait here and are sent in order as that provider's runs end.
+//! Each change is reported with a `prompt-queue` event.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::logger;
+use crate::preferences;
+use crate::sidecar::{session_state_snapshot, RpcClient};
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::now_millis;
+
+/// A prompt waiting for its provider to drop below the turn limit.
+#[derive(Debug, Clone)]
+pub struct QueuedPrompt {
+    pub session_id: String,
+    pub provider: String,
+    pub command: RpcCommand,
+    pub queued_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptQueueEvent<'a> {
+    session_id: &'a str,
+    provider: &'a str,
+    /// `queued`, `sent` or `dropped`.
+    status: &'a str,
+    /// 1-based place among the provider's queued prompts while `queued`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<usize>,
+    limit: usize,
+    queued_at: u64,
+}
+
+fn emit_queue_event(app: &AppHandle, prompt: &QueuedPrompt, status: &str, position: Option<usize>) {
+    let limit = turn_limit(&prompt.provider).unwrap_or(0);
+    let event = PromptQueueEvent {
+        session_id: &prompt.session_id,
+        provider: &prompt.provider,
+        status,
+        position,
+        limit,
+        queued_at: prompt.queued_at,
+    };
+    if let Err(error) = app.emit("prompt-queue", &event) {
+        logger::log(format!("Failed to emit prompt-queue event: {}", error));
+    }
+}
+
+fn turn_limit(provider: &str) -> Option<usize> {
+    preferences::current()
+        .provider_turn_limits
+        .get(provider)
+        .copied()
+        .filter(|limit| *limit > 0)
+}
+
+fn session_provider(state: &SidecarState, session_id: &str) -> Option<String> {
+    state
+        .session_states
+        .get(session_id)
+        .and_then(|snapshot| snapshot.model.as_ref())
+        .map(|model| model.provider.clone())
+}
+
+/// Sessions other than `session_id` with a run on `provider` that is active
+/// or was just sent.
+fn active_turns(state: &SidecarState, provider: &str, session_id: &str) -> usize {
+    state
+        .running_sessions
+        .iter()
+        .chain(state.prompts_awaiting_start.keys())
+        .filter(|active| active.as_str() != session_id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter(|active| session_provider(state, active).as_deref() == Some(provider))
+        .count()
+}
+
+pub(crate) fn has_queued_prompt(state: &SidecarState, session_id: &str) -> bool {
+    state
+        .queued_prompts
+        .iter()
+        .any(|prompt| prompt.session_id == session_id)
+}
+
+/// Send a prompt now, or queue it when its provider is at the turn limit.
+///
+/// Returns whether the prompt was sent now.
+pub(crate) async fn send_or_queue_prompt(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    command: RpcCommand,
+) -> Result<bool, String> {
+    let limits_configured = !preferences::current().provider_turn_limits.is_empty();
+    let session_id = command.session_id.clone().unwrap_or_default();
+    if !limits_configured || session_id.is_empty() {
+        return RpcClient::send_command(state, command).await.map(|()| true);
+    }
+
+    let provider = match session_state_snapshot(state, &session_id, false).await {
+        Ok(snapshot) => snapshot.model.map(|model| model.provider),
+        Err(error) => {
+            logger::log(format!(
+                "Sending prompt for {} without a provider limit check: {}",
+                session_id, error
+            ));
+            None
+        }
+    };
+    let Some((provider, limit)) =
+        provider.and_then(|provider| turn_limit(&provider).map(|limit| (provider, limit)))
+    else {
+        return RpcClient::send_command(state, command).await.map(|()| true);
+    };
+
+    let queued = {
+        let mut state_guard = state.lock().await;
+        let waiting = state_guard
+            .queued_prompts
+            .iter()
+            .filter(|prompt| prompt.provider == provider)
+            .count();
+        if waiting == 0 && active_turns(&state_guard, &provider, &session_id) < limit {
+            None
+        } else {
+            let prompt = QueuedPrompt {
+                session_id: session_id.clone(),
+                provider,
+                command: command.clone(),
+                queued_at: now_millis(),
+            };
+            state_guard.queued_prompts.push_back(prompt.clone());
+            // Only counts as active once it is sent.
+            state_guard.prompts_awaiting_start.remove(&session_id);
+            Some((prompt, waiting + 1))
+        }
+    };
+
+    match queued {
+        Some((prompt, position)) => {
+            logger::log(format!(
+                "Queued prompt for {}: {} is at its limit of {} turns",
+                prompt.session_id, prompt.provider, limit
+            ));
+            emit_queue_event(app, &prompt, "queued", Some(position));
+            Ok(false)
+        }
+        None => RpcClient::send_command(state, command).await.map(|()| true),
+    }
+}
+
+/// Send queued prompts whose provider has room again. Called when a run ends.
+pub(crate) async fn send_queued_prompts(app: &AppHandle, state: &Arc<Mutex<SidecarState>>) {
+    loop {
+        let next = {
+            let mut state_guard = state.lock().await;
+            let mut blocked = HashSet::new();
+            let ready = state_guard.queued_prompts.iter().position(|prompt| {
+                if blocked.contains(&prompt.provider) {
+                    return false;
+                }
+                let has_room = turn_limit(&prompt.provider).is_none_or(|limit| {
+                    active_turns(&state_guard, &prompt.provider, &prompt.session_id) < limit
+                });
+                if !has_room {
+                    // Keep each provider's prompts in order.
+                    blocked.insert(prompt.provider.clone());
+                }
+                has_room
+            });
+            let Some(prompt) = ready.and_then(|index| state_guard.queued_prompts.remove(index))
+            else {
+                return;
+            };
+            // Counts as the session's run from now on, like a fresh prompt.
+            state_guard
+                .prompts_awaiting_start
+                .insert(prompt.session_id.clone(), Instant::now());
+            prompt
+        };
+
+        match RpcClient::send_command(state, next.command.clone()).await {
+            Ok(()) => emit_queue_event(app, &next, "sent", None),
+            Err(error) => {
+                logger::log(format!(
+                    "Failed to send queued prompt for {}: {}",
+                    next.session_id, error
+                ));
+                state
+                    .lock()
+                    .await
+                    .prompts_awaiting_start
+                    .remove(&next.session_id);
+                emit_queue_event(app, &next, "dropped", None);
+            }
+        }
+    }
+}
+
+/// Drop a session's queued prompt (aborted or closed before it was sent).
+pub(crate) async fn drop_queued_prompt(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+) {
+    let dropped = {
+        let mut state_guard = state.lock().await;
+        let Some(index) = state_guard
+            .queued_prompts
+            .iter()
+            .position(|prompt| prompt.session_id == session_id)
+        else {
+            return;
+        };
+        state_guard.prompts_awaiting_start.remove(session_id);
+        state_guard.queued_prompts.remove(index)
+    };
+
+    if let Some(prompt) = dropped {
+        emit_queue_event(app, &prompt, "dropped", None);
+    }
+}
@@ -0,0 +1,103 @@
+//! Quiet hours: a daily window from the preferences during which run
+//! notifications stay silent and watch-rule prompts wait for the window to
+//! end. `snooze_notifications` silences notifications alone for a while.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{NaiveTime, Utc};
+use serde::Serialize;
+
+use super::session_scopes::display_time_zone;
+use crate::preferences::{self, QuietHours};
+use crate::utils::now_millis;
+
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Unix millis until which notifications are snoozed; 0 when not snoozed.
+static SNOOZED_UNTIL: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHoursStatus {
+    /// Inside the configured quiet-hours window right now.
+    pub quiet: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<u64>,
+    /// Run notifications are currently not shown.
+    pub notifications_suppressed: bool,
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), TIME_FORMAT)
+        .map_err(|_| format!("Invalid quiet-hours time '{}' (expected HH:MM)", value))
+}
+
+pub(super) fn validate_quiet_hours(quiet_hours: QuietHours) -> Result<QuietHours, String> {
+    let start = parse_time(&quiet_hours.start)?;
+    let end = parse_time(&quiet_hours.end)?;
+    if start == end {
+        return Err("Quiet hours must start and end at different times".to_string());
+    }
+
+    Ok(QuietHours {
+        start: start.format(TIME_FORMAT).to_string(),
+        end: end.format(TIME_FORMAT).to_string(),
+    })
+}
+
+/// `end` is exclusive; a window whose end is before its start spans midnight.
+fn window_contains(quiet_hours: &QuietHours, now: NaiveTime) -> bool {
+    let (Ok(start), Ok(end)) = (parse_time(&quiet_hours.start), parse_time(&quiet_hours.end))
+    else {
+        return false;
+    };
+
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Whether the current time, in the preferred time zone, is inside the
+/// configured quiet hours.
+pub(crate) fn in_quiet_hours() -> bool {
+    let preferences = preferences::current();
+    let Some(quiet_hours) = &preferences.quiet_hours else {
+        return false;
+    };
+    let now = Utc::now().with_timezone(&display_time_zone()).time();
+    window_contains(quiet_hours, now)
+}
+
+fn snoozed_until() -> Option<u64> {
+    let until = SNOOZED_UNTIL.load(Ordering::Relaxed);
+    (until > now_millis()).then_some(until)
+}
+
+/// Run notifications (toasts and their sounds) are not shown while this holds.
+#[cfg(target_os = "windows")]
+pub(crate) fn notifications_suppressed() -> bool {
+    snoozed_until().is_some() || in_quiet_hours()
+}
+
+pub fn quiet_hours_status() -> QuietHoursStatus {
+    let quiet = in_quiet_hours();
+    let snoozed_until = snoozed_until();
+    QuietHoursStatus {
+        quiet,
+        snoozed_until,
+        notifications_suppressed: quiet || snoozed_until.is_some(),
+    }
+}
+
+/// Suppress notifications for `duration_minutes` from now, replacing any
+/// earlier snooze; 0 ends the snooze.
+pub fn snooze_notifications(duration_minutes: u64) -> QuietHoursStatus {
+    let until = match duration_minutes {
+        0 => 0,
+        minutes => now_millis().saturating_add(minutes.saturating_mul(60_000)),
+    };
+    SNOOZED_UNTIL.store(until, Ordering::Relaxed);
+    quiet_hours_status()
+}
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Duration, Instant};
+
+use super::oauth_and_models::{cancel_oauth_login, poll_oauth_login, start_oauth_login};
+use super::sidecar_lifecycle::send_command_with_response;
+use super::{open_external_url, turn_guard};
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::sidecar::emit_auth_expired_event;
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::crypto_random_uuid;
+
+const LOGIN_POLL_INTERVAL: Duration = Duration::from_millis(1000);
+/// Upper bound for the user to finish the browser login.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Provider of the session's active model, for failures that did not name one.
+async fn current_provider(state: &Arc<Mutex<SidecarState>>, session_id: &str) -> Option<String> {
+    let command = RpcCommand {
+        id: Some(crypto_random_uuid()),
+        r#type: "get_state".to_string(),
+        session_id: Some(session_id.to_string()),
+        cwd: None,
+        message: None,
+        provider: None,
+        model_id: None,
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    };
+
+    let response = send_command_with_response(state, command, 5)
+        .await
+        .ok()
+        .filter(|response| response.success)?;
+
+    response
+        .data
+        .as_ref()?
+        .get("model")?
+        .get("provider")?
+        .as_str()
+        .map(|provider| provider.to_string())
+}
+
+/// Poll the login flow until it finishes, forwarding updates as
+/// `auth-expired` events and opening the authorization URL in the browser.
+async fn wait_for_login(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    provider: &str,
+) -> Result<(), String> {
+    let deadline = Instant::now() + LOGIN_TIMEOUT;
+
+    loop {
+        if Instant::now() >= deadline {
+            let _ = cancel_oauth_login(state, session_id.to_string()).await;
+            return Err(messages::error(ErrorCode::LoginTimedOut, &[&provider]));
+        }
+
+        let response = poll_oauth_login(state, session_id.to_string()).await?;
+        if !response.success {
+            return Err(response
+                .error
+                .unwrap_or_else(|| "Failed to poll login".to_string()));
+        }
+        let data = response.data.unwrap_or_default();
+
+        for update in data
+            .get("updates")
+            .and_then(|updates| updates.as_array())
+            .into_iter()
+            .flatten()
+        {
+            if update.get("type").and_then(|value| value.as_str()) == Some("auth") {
+                if let Some(url) = update.get("url").and_then(|value| value.as_str()) {
+                    if let Err(error) = open_external_url(app.clone(), url.to_string()) {
+                        logger::log(format!("Failed to open login URL: {}", error));
+                    }
+                }
+            }
+            emit_auth_expired_event(app, session_id, "login", Some(provider), update.clone());
+        }
+
+        match data.get("status").and_then(|value| value.as_str()) {
+            Some("completed") => return Ok(()),
+            Some("failed") => return Err(messages::error(ErrorCode::LoginFailed, &[&provider])),
+            Some("cancelled") => {
+                return Err(messages::error(ErrorCode::LoginCancelled, &[&provider]))
+            }
+            Some("idle") => return Err(messages::error(ErrorCode::LoginInactive, &[&provider])),
+            _ => sleep(LOGIN_POLL_INTERVAL).await,
+        }
+    }
+}
+
+/// Re-run the provider login for a session whose last prompt failed on
+/// expired credentials, then resubmit that prompt.
+///
+/// Login prompts that need user input surface as `auth-expired` events with
+/// status `login`; answer them with `submit_oauth_login_input`.
+pub async fn reauthenticate_and_retry(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: String,
+) -> Result<(), String> {
+    let session_id = session_id.trim().to_string();
+
+    let recorded_provider = state
+        .lock()
+        .await
+        .auth_expired_sessions
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "Session {} has no prompt that failed on expired credentials",
+                session_id
+            )
+        })?;
+
+    let provider = match recorded_provider {
+        Some(provider) => provider,
+        None => current_provider(state, &session_id).await.ok_or_else(|| {
+            format!(
+                "Could not determine the provider for session {}",
+                session_id
+            )
+        })?,
+    };
+
+    let result = async {
+        let response = start_oauth_login(state, provider.clone(), session_id.clone()).await?;
+        if !response.success {
+            return Err(response
+                .error
+                .unwrap_or_else(|| format!("Failed to start login for {}", provider)));
+        }
+
+        wait_for_login(app, state, &session_id, &provider).await?;
+
+        state.lock().await.auth_expired_sessions.remove(&session_id);
+        emit_auth_expired_event(
+            app,
+            &session_id,
+            "retrying",
+            Some(&provider),
+            serde_json::Value::Null,
+        );
+
+        turn_guard::retry_last_prompt(app, state, &session_id).await?;
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(error) = &result {
+        logger::log(format!(
+            "Re-authentication for session {} failed: {}",
+            session_id, error
+        ));
+        emit_auth_expired_event(
+            app,
+            &session_id,
+            "failed",
+            Some(&provider),
+            serde_json::json!({ "errorMessage": error }),
+        );
+    }
+
+    result
+}
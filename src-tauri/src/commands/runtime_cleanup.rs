@@ -0,0 +1,133 @@
+//! Startup sweep of sidecar runtimes left in app local data by older app
+//! versions.
+//!
+//! Only the Linux build extracts its runtime; other platforms run the sidecar
+//! from the app bundle and have nothing to sweep.
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::logger;
+use crate::utils::now_millis;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeCleanupReport {
+    /// Paths removed by the sweep.
+    pub removed: Vec<String>,
+    pub reclaimed_bytes: u64,
+    pub swept_at: u64,
+}
+
+static LAST_CLEANUP: OnceLock<RuntimeCleanupReport> = OnceLock::new();
+
+/// Result of this launch's sweep, when one ran.
+pub(super) fn last_runtime_cleanup() -> Option<RuntimeCleanupReport> {
+    LAST_CLEANUP.get().cloned()
+}
+
+#[cfg(target_os = "linux")]
+fn path_size(path: &std::path::Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| path_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Remove everything under `<app local data>/sidecar` except a runtime
+/// extracted from the bundled sidecar: runtimes with another version's stamp,
+/// half-finished extractions and stray binaries.
+#[cfg(target_os = "linux")]
+fn sweep_linux_runtimes(app: &AppHandle) -> Result<RuntimeCleanupReport, String> {
+    use std::fs;
+
+    let mut report = RuntimeCleanupReport {
+        swept_at: now_millis(),
+        ..RuntimeCleanupReport::default()
+    };
+
+    let runtime_dir = crate::sidecar::linux_sidecar_runtime_dir(app)?;
+    let Some(sidecar_dir) = runtime_dir.parent().filter(|dir| dir.is_dir()) else {
+        return Ok(report);
+    };
+    // Without the bundle there is no telling which runtime is current.
+    let installed_stamp = crate::sidecar::installed_linux_sidecar_stamp(app)?;
+
+    let entries = fs::read_dir(sidecar_dir)
+        .map_err(|error| format!("Failed to read {}: {}", sidecar_dir.display(), error))?;
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path == runtime_dir {
+            let current = fs::read_to_string(path.join(".stamp"))
+                .is_ok_and(|stamp| stamp.trim() == installed_stamp);
+            if current {
+                continue;
+            }
+        }
+
+        let size = path_size(&path);
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        match removed {
+            Ok(()) => {
+                report.reclaimed_bytes += size;
+                report.removed.push(path.display().to_string());
+            }
+            Err(error) => logger::log(format!(
+                "Failed to remove orphaned sidecar runtime {}: {}",
+                path.display(),
+                error
+            )),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Sweep orphaned sidecar runtimes. Runs once at startup, before the sidecar
+/// is first spawned, and only in the instance that owns the app data.
+pub fn sweep_orphaned_runtimes(app: &AppHandle) {
+    #[cfg(target_os = "linux")]
+    let report = match sweep_linux_runtimes(app) {
+        Ok(report) => report,
+        Err(error) => {
+            logger::log(format!("Skipped sidecar runtime cleanup: {}", error));
+            return;
+        }
+    };
+
+    #[cfg(not(target_os = "linux"))]
+    let report = {
+        let _ = app;
+        RuntimeCleanupReport {
+            swept_at: now_millis(),
+            ..RuntimeCleanupReport::default()
+        }
+    };
+
+    if !report.removed.is_empty() {
+        logger::log(format!(
+            "Removed orphaned sidecar runtime files ({} bytes reclaimed): {}",
+            report.reclaimed_bytes,
+            report.removed.join(", ")
+        ));
+    }
+
+    let _ = LAST_CLEANUP.set(report);
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::action_sequence::snapshot_session;
+use super::session_scopes::infer_session_scope;
+use super::sidecar_lifecycle::create_session_internal;
+use crate::state::SidecarState;
+use crate::types::RpcResponse;
+
+/// Everything `create_session` can be told about the session to open.
+///
+/// The running sidecar is always reused; it is started on demand when none
+/// is running yet.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CreateSessionOptions {
+    pub project_dir: String,
+    /// Model for the new session; both must be set together.
+    pub provider: Option<String>,
+    pub model_id: Option<String>,
+    /// Start on the model of this open session when no model is given.
+    pub inherit_model_from: Option<String>,
+    /// Resume this session file instead of starting a new one.
+    pub session_file: Option<String>,
+    /// Scope a new session to the enclosing git root; unset follows the
+    /// `useGitRootAsScope` preference. Ignored when resuming.
+    pub use_git_root: Option<bool>,
+}
+
+/// Open a session: the single entry point for creating or resuming one.
+pub async fn create_session(
+    app: AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    options: CreateSessionOptions,
+) -> Result<RpcResponse, String> {
+    let provider = options
+        .provider
+        .clone()
+        .filter(|value| !value.trim().is_empty());
+    let model_id = options
+        .model_id
+        .clone()
+        .filter(|value| !value.trim().is_empty());
+    if provider.is_some() != model_id.is_some() {
+        return Err("provider and modelId must be set together".to_string());
+    }
+
+    open_session(
+        app,
+        state,
+        CreateSessionOptions {
+            provider,
+            model_id,
+            ..options
+        },
+    )
+    .await
+}
+
+/// `create_session` without its checks on the model, for the `create_agent`
+/// wrapper, which has always passed provider and model through as given.
+pub(super) async fn open_session(
+    app: AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    options: CreateSessionOptions,
+) -> Result<RpcResponse, String> {
+    let project_dir = options.project_dir.trim().to_string();
+    if project_dir.is_empty() {
+        return Err("projectDir is required to create a session".to_string());
+    }
+
+    let (provider, model_id) = match (
+        options.provider,
+        options.model_id,
+        options.inherit_model_from,
+    ) {
+        (None, None, Some(source_session_id)) => {
+            let model = snapshot_session(state, &source_session_id)
+                .await
+                .map_err(|error| {
+                    format!(
+                        "Failed to read the model of session {}: {}",
+                        source_session_id, error
+                    )
+                })?
+                .model
+                .ok_or_else(|| format!("Session {} has no model set", source_session_id))?;
+            (Some(model.provider), Some(model.model_id))
+        }
+        (provider, model_id, _) => (provider, model_id),
+    };
+
+    let session_file = options
+        .session_file
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty());
+
+    // A resumed session keeps the cwd it was recorded with.
+    let project_dir = if session_file.is_some() {
+        project_dir
+    } else {
+        infer_session_scope(&project_dir, options.use_git_root)
+    };
+
+    create_session_internal(app, state, project_dir, provider, model_id, session_file).await
+}
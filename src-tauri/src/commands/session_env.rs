@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::project_metadata::{load_project_metadata, project_key, update_project_metadata};
+use super::session_scopes::normalize_path_for_comparison;
+use super::sidecar_lifecycle::send_command_with_response;
+use crate::logger;
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::crypto_random_uuid;
+
+const SET_SESSION_ENV_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEnvResponse {
+    pub project_dir: String,
+    pub env: BTreeMap<String, String>,
+    /// Open sessions in the project that received the new overrides.
+    pub updated_sessions: usize,
+}
+
+fn is_valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+fn validate_env(env: BTreeMap<String, String>) -> Result<BTreeMap<String, String>, String> {
+    env.into_iter()
+        .map(|(name, value)| {
+            let name = name.trim().to_string();
+            if !is_valid_env_name(&name) {
+                return Err(format!("Invalid environment variable name '{}'", name));
+            }
+            if value.contains('\0') {
+                return Err(format!("Value for {} contains a NUL byte", name));
+            }
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// Env overrides to pass with `create_session`, if the project has any.
+pub(crate) fn project_env(app: &AppHandle, project_dir: &str) -> Option<BTreeMap<String, String>> {
+    let key = project_key(project_dir).ok()?;
+    let env = load_project_metadata(app, &key).ok()?.env;
+    (!env.is_empty()).then_some(env)
+}
+
+pub fn get_project_env(
+    app: &AppHandle,
+    project_dir: String,
+) -> Result<BTreeMap<String, String>, String> {
+    let key = project_key(&project_dir)?;
+    Ok(load_project_metadata(app, &key)?.env)
+}
+
+/// Persist a project's env overrides and push them to its open sessions.
+///
+/// The full map is replaced; an empty map clears the overrides.
+pub async fn set_project_env(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: String,
+    env: BTreeMap<String, String>,
+) -> Result<ProjectEnvResponse, String> {
+    let key = project_key(&project_dir)?;
+    let env = validate_env(env)?;
+
+    update_project_metadata(app, &key, |metadata| {
+        metadata.env = env.clone();
+        Ok(())
+    })?;
+
+    let session_ids = {
+        let state_guard = state.lock().await;
+        if state_guard.child.is_none() {
+            Vec::new()
+        } else {
+            state_guard
+                .session_cwds
+                .iter()
+                .filter(|(_, cwd)| normalize_path_for_comparison(cwd) == key)
+                .map(|(session_id, _)| session_id.clone())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let mut updated_sessions = 0;
+    for session_id in session_ids {
+        let command = RpcCommand {
+            id: Some(crypto_random_uuid()),
+            r#type: "set_session_env".to_string(),
+            session_id: Some(session_id.clone()),
+            cwd: None,
+            message: None,
+            provider: None,
+            model_id: None,
+            streaming_behavior: None,
+            session_file: None,
+            level: None,
+            images: None,
+            env: Some(env.clone()),
+            tools: None,
+        };
+
+        match send_command_with_response(state, command, SET_SESSION_ENV_TIMEOUT_SECS).await {
+            Ok(response) if response.success => updated_sessions += 1,
+            Ok(response) => logger::log(format!(
+                "set_session_env failed for {}: {}",
+                session_id,
+                response.error.unwrap_or_default()
+            )),
+            Err(error) => logger::log(format!(
+                "set_session_env failed for {}: {}",
+                session_id, error
+            )),
+        }
+    }
+
+    Ok(ProjectEnvResponse {
+        project_dir,
+        env,
+        updated_sessions,
+    })
+}
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use super::session_scopes::is_read_only_error;
+use crate::instance_lock;
+use crate::messages::{self, ErrorCode};
+use crate::utils::now_millis;
+
+const SESSION_METADATA_STORE_FILE: &str = "session-metadata.json";
+const MAX_SESSION_NOTES_BYTES: usize = 256 * 1024;
+
+/// Graphone-owned metadata for a persisted session, keyed by session id.
+///
+/// Kept outside the pi session JSONL so nothing here leaks into the conversation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionMetadata {
+    /// Free-form markdown notes the user keeps alongside the session.
+    pub notes: String,
+    /// When the notes were last changed (unix millis).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes_updated_at: Option<u64>,
+    /// User-assigned labels used to filter the session list.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl SessionMetadata {
+    fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.tags.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTagsResponse {
+    pub session_id: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionNotesResponse {
+    pub session_id: String,
+    pub notes: String,
+    pub updated_at: Option<u64>,
+}
+
+fn require_session_key(session_id: &str) -> Result<String, String> {
+    let trimmed = session_id.trim();
+    if trimmed.is_empty() {
+        return Err(messages::error(ErrorCode::SessionIdRequired, &[]));
+    }
+
+    Ok(trimmed.to_string())
+}
+
+pub(crate) fn load_session_metadata(
+    app: &AppHandle,
+    session_id: &str,
+) -> Result<SessionMetadata, String> {
+    let store = app
+        .store(SESSION_METADATA_STORE_FILE)
+        .map_err(|error| format!("Failed to open session metadata store: {}", error))?;
+
+    Ok(store
+        .get(session_id)
+        .and_then(|value| serde_json::from_value::<SessionMetadata>(value).ok())
+        .unwrap_or_default())
+}
+
+/// Read-modify-write a session's metadata entry. Entries that end up empty are removed.
+pub(crate) fn update_session_metadata(
+    app: &AppHandle,
+    session_id: &str,
+    update: impl FnOnce(&mut SessionMetadata),
+) -> Result<SessionMetadata, String> {
+    instance_lock::ensure_primary()?;
+
+    let store = app
+        .store(SESSION_METADATA_STORE_FILE)
+        .map_err(|error| format!("Failed to open session metadata store: {}", error))?;
+
+    let mut metadata = store
+        .get(session_id)
+        .and_then(|value| serde_json::from_value::<SessionMetadata>(value).ok())
+        .unwrap_or_default();
+
+    update(&mut metadata);
+
+    let value = serde_json::to_value(&metadata)
+        .map_err(|error| format!("Failed to serialize session metadata: {}", error))?;
+
+    if metadata.is_empty() {
+        store.delete(session_id);
+    } else {
+        store.set(session_id, value);
+    }
+
+    store.save().map_err(|error| match &error {
+        tauri_plugin_store::Error::Io(io_error) if is_read_only_error(io_error) => {
+            messages::error(ErrorCode::ReadOnlyStorage, &[&SESSION_METADATA_STORE_FILE])
+        }
+        _ => format!("Failed to save session metadata: {}", error),
+    })?;
+
+    Ok(metadata)
+}
+
+pub fn get_session_notes(
+    app: &AppHandle,
+    session_id: String,
+) -> Result<SessionNotesResponse, String> {
+    let session_id = require_session_key(&session_id)?;
+    let metadata = load_session_metadata(app, &session_id)?;
+
+    Ok(SessionNotesResponse {
+        session_id,
+        notes: metadata.notes,
+        updated_at: metadata.notes_updated_at,
+    })
+}
+
+pub fn set_session_notes(
+    app: &AppHandle,
+    session_id: String,
+    markdown: String,
+) -> Result<SessionNotesResponse, String> {
+    let session_id = require_session_key(&session_id)?;
+
+    if markdown.len() > MAX_SESSION_NOTES_BYTES {
+        return Err(format!(
+            "Session notes exceed the {} KB limit",
+            MAX_SESSION_NOTES_BYTES / 1024
+        ));
+    }
+
+    let metadata = update_session_metadata(app, &session_id, |metadata| {
+        if markdown.trim().is_empty() {
+            metadata.notes = String::new();
+            metadata.notes_updated_at = None;
+        } else {
+            metadata.notes = markdown;
+            metadata.notes_updated_at = Some(now_millis());
+        }
+    })?;
+
+    Ok(SessionNotesResponse {
+        session_id,
+        notes: metadata.notes,
+        updated_at: metadata.notes_updated_at,
+    })
+}
+
+/// Replace a session's tags. Tags are trimmed, empty ones dropped and
+/// duplicates removed (case-insensitively).
+pub fn set_session_tags(
+    app: &AppHandle,
+    session_id: String,
+    tags: Vec<String>,
+) -> Result<SessionTagsResponse, String> {
+    let session_id = require_session_key(&session_id)?;
+
+    let mut seen = HashSet::new();
+    let tags = tags
+        .into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()))
+        .collect::<Vec<_>>();
+
+    let metadata = update_session_metadata(app, &session_id, |metadata| {
+        metadata.tags = tags;
+    })?;
+
+    Ok(SessionTagsResponse {
+        session_id,
+        tags: metadata.tags,
+    })
+}
+
+/// Ids of every session carrying `tag` (case-insensitive).
+pub(crate) fn session_ids_with_tag(app: &AppHandle, tag: &str) -> Result<HashSet<String>, String> {
+    let store = app
+        .store(SESSION_METADATA_STORE_FILE)
+        .map_err(|error| format!("Failed to open session metadata store: {}", error))?;
+
+    let tag = tag.trim().to_lowercase();
+    Ok(store
+        .entries()
+        .into_iter()
+        .filter_map(|(session_id, value)| {
+            let metadata = serde_json::from_value::<SessionMetadata>(value).ok()?;
+            metadata
+                .tags
+                .iter()
+                .any(|candidate| candidate.to_lowercase() == tag)
+                .then_some(session_id)
+        })
+        .collect())
+}
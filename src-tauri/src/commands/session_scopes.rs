@@ -1,25 +1,48 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::time::UNIX_EPOCH;
+use std::sync::Mutex;
 
-use serde::Serialize;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
 
+use super::git::{find_git_root, has_uncommitted_changes};
+use super::operations::OperationToken;
 use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::preferences;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PersistedSessionSummary {
     /// Session id as stored in the JSONL session header.
     pub session_id: String,
-    /// Session creation timestamp from the session header (ISO string), when available.
+    /// Session creation timestamp from the session header, as written, when available.
     pub timestamp: Option<String>,
+    /// Instant the session is ordered by, in Unix millis: the parsed header
+    /// timestamp, or the file modification time when it is missing or unparseable.
+    pub timestamp_millis: i64,
+    /// `timestamp_millis` in `time_zone` (`YYYY-MM-DD HH:MM`).
+    pub formatted_timestamp: String,
+    /// Session file modification time (Unix millis).
+    pub modified_millis: i64,
+    /// `modified_millis` in `time_zone` (`YYYY-MM-DD HH:MM`).
+    pub formatted_modified: String,
+    /// IANA zone the formatted timestamps are in: the `timeZone` preference,
+    /// else the system zone.
+    pub time_zone: String,
+    /// Session file size in bytes.
+    pub size_bytes: u64,
     /// First user message found in the session file.
     pub first_user_message: Option<String>,
     /// Where the session file was discovered from: "global" or "local".
     pub source: String,
     /// Absolute path to the backing session JSONL file.
     pub file_path: String,
+    /// The file's directory cannot be written (e.g. a share mounted
+    /// read-only), so the session cannot be deleted or continued.
+    pub read_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -29,6 +52,19 @@ pub struct SessionScopeHistory {
     pub scope: String,
     /// Persisted sessions discovered for this scope.
     pub sessions: Vec<PersistedSessionSummary>,
+    /// Current state of the project folder on disk.
+    pub health: ProjectScopeHealth,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectScopeHealth {
+    /// Whether the project folder still exists (false once moved or deleted).
+    pub exists: bool,
+    /// Whether the folder is inside a git work tree.
+    pub is_git_repo: bool,
+    /// Whether the work tree has uncommitted changes; `None` when unknown.
+    pub has_uncommitted_changes: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -40,6 +76,51 @@ pub struct SessionProjectScopesResponse {
     pub histories: Vec<SessionScopeHistory>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionSortField {
+    /// Header timestamp (file mtime when missing).
+    #[default]
+    Created,
+    LastModified,
+    Size,
+    /// First user message, case-insensitive; untitled sessions sort last.
+    Title,
+}
+
+/// Sorting and filtering applied to each project's session history.
+///
+/// Defaults list every session, newest first.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionListOptions {
+    pub sort: SessionSortField,
+    /// Sort direction; defaults to descending, except A-Z for titles.
+    pub ascending: Option<bool>,
+    /// Only sessions found in this kind of root: "global" or "local".
+    pub source: Option<String>,
+    /// Only sessions carrying this tag (see `set_session_tags`).
+    pub tag: Option<String>,
+    /// Only sessions that do (or do not) contain image content.
+    pub has_images: Option<bool>,
+    /// Inclusive bounds on the created instant (Unix millis).
+    pub created_after: Option<i64>,
+    pub created_before: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteProjectScopeResponse {
+    pub dry_run: bool,
+    /// Session files deleted, or that a dry run would delete.
+    pub files: Vec<String>,
+    /// Scope directories removed with their contents, or that a dry run
+    /// would remove.
+    pub directories: Vec<String>,
+    /// Session files deleted; always 0 for a dry run.
+    pub deleted_count: usize,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteProjectSessionResponse {
@@ -82,7 +163,10 @@ struct SessionHistoryInternal {
     first_user_message: Option<String>,
     source: SessionRootSource,
     file_path: String,
-    sort_key: String,
+    sort_key: DateTime<Utc>,
+    modified: DateTime<Utc>,
+    size_bytes: u64,
+    read_only: bool,
 }
 
 fn expand_tilde(path: &str) -> PathBuf {
@@ -113,6 +197,20 @@ fn local_session_roots_for_scope(scope: &str) -> Vec<SessionRoot> {
     ]
 }
 
+/// pi's agent directory: `$PI_CODING_AGENT_DIR` when set, otherwise `~/.pi/agent`.
+pub(crate) fn pi_agent_dir() -> Option<PathBuf> {
+    if let Ok(agent_dir) = std::env::var("PI_CODING_AGENT_DIR") {
+        return Some(expand_tilde(&agent_dir));
+    }
+
+    dirs::home_dir().map(|home| home.join(".pi").join("agent"))
+}
+
+/// Session root pi writes new sessions to.
+pub(crate) fn global_session_root() -> Option<PathBuf> {
+    pi_agent_dir().map(|agent_dir| agent_dir.join("sessions"))
+}
+
 fn candidate_session_roots(seed_scopes: &[String]) -> Vec<SessionRoot> {
     let mut roots = Vec::new();
 
@@ -160,7 +258,7 @@ fn candidate_session_roots(seed_scopes: &[String]) -> Vec<SessionRoot> {
     dedup.into_values().collect::<Vec<_>>()
 }
 
-fn extract_text_from_message_content(content: &serde_json::Value) -> Option<String> {
+pub(super) fn extract_text_from_message_content(content: &serde_json::Value) -> Option<String> {
     if let Some(text) = content.as_str() {
         let trimmed = text.trim();
         if !trimmed.is_empty() {
@@ -210,8 +308,32 @@ fn extract_first_user_message(entry: &serde_json::Value) -> Option<String> {
     extract_text_from_message_content(content)
 }
 
+/// Directory a new session should be scoped to.
+///
+/// With `use_git_root` (falling back to the `useGitRootAsScope` preference) a
+/// path inside a git work tree resolves to the work tree root; otherwise, or
+/// outside a repository, `project_dir` is kept as given.
+pub(super) fn infer_session_scope(project_dir: &str, use_git_root: Option<bool>) -> String {
+    if !use_git_root.unwrap_or_else(|| preferences::current().use_git_root_as_scope) {
+        return project_dir.to_string();
+    }
+
+    let path = expand_tilde(project_dir.trim());
+    match find_git_root(&path) {
+        Some(root) if root != path => {
+            let root = root.to_string_lossy().to_string();
+            logger::log(format!(
+                "Scoping session for {} to git root {}",
+                project_dir, root
+            ));
+            root
+        }
+        _ => project_dir.to_string(),
+    }
+}
+
 /// Normalize a path for comparison: trim whitespace and remove trailing slashes.
-fn normalize_path_for_comparison(path: &str) -> String {
+pub(super) fn normalize_path_for_comparison(path: &str) -> String {
     let trimmed = path.trim();
     trimmed
         .trim_end_matches(|c| c == '/' || c == '\\')
@@ -238,6 +360,27 @@ fn path_is_within_root(path: &Path, root: &Path) -> bool {
     path_canonical.starts_with(root_canonical)
 }
 
+/// Whether `path` is a JSONL session file under a global session root or a
+/// local root of the scope named in its header.
+pub(super) fn is_known_session_file(path: &Path) -> bool {
+    if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+        return false;
+    }
+
+    if candidate_session_roots(&[])
+        .iter()
+        .any(|root| path_is_within_root(path, &root.path))
+    {
+        return true;
+    }
+
+    extract_session_header_from_file(path).is_some_and(|header| {
+        local_session_roots_for_scope(&normalize_path_for_comparison(&header.scope))
+            .iter()
+            .any(|root| path_is_within_root(path, &root.path))
+    })
+}
+
 fn extract_session_header_from_file(path: &Path) -> Option<SessionFileHeader> {
     let file = std::fs::File::open(path).ok()?;
     let mut reader = BufReader::new(file);
@@ -350,22 +493,147 @@ fn collect_session_files_from_root(root: &Path, files: &mut Vec<PathBuf>) {
     }
 }
 
-fn build_session_sort_key(path: &Path, timestamp: Option<&str>) -> String {
-    if let Some(ts) = timestamp {
-        return ts.to_string();
+/// Scopes with sessions in the user's own session roots (`~/.pi/agent` and
+/// `$PI_CODING_AGENT_DIR`), from the header of one session per scope
+/// directory. Session dirs inside projects are skipped: a repository can
+/// ship those.
+pub(super) fn user_session_history_scopes() -> BTreeSet<String> {
+    let mut roots = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join(".pi").join("agent").join("sessions"));
     }
+    if let Ok(agent_dir) = std::env::var("PI_CODING_AGENT_DIR") {
+        roots.push(expand_tilde(&agent_dir).join("sessions"));
+    }
+    roots.dedup();
 
-    let modified_millis = std::fs::metadata(path)
+    let mut scopes = BTreeSet::new();
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(files) = std::fs::read_dir(entry.path()) else {
+                continue;
+            };
+            let header = files
+                .flatten()
+                .map(|file| file.path())
+                .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+                .find_map(|path| extract_session_header_from_file(&path));
+            if let Some(header) = header {
+                scopes.insert(header.scope);
+            }
+        }
+    }
+    scopes
+}
+
+fn local_to_utc(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+}
+
+/// Parse a session header timestamp. pi writes RFC 3339, but older or
+/// hand-edited headers may hold Unix seconds/millis or a zone-less date-time,
+/// which is taken as local time.
+pub(super) fn parse_session_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(raw) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+
+    if let Ok(number) = raw.parse::<i64>() {
+        // Anything past 1e11 would be a seconds value beyond year 5000.
+        return if number.abs() >= 100_000_000_000 {
+            Utc.timestamp_millis_opt(number).single()
+        } else {
+            Utc.timestamp_opt(number, 0).single()
+        };
+    }
+
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(raw, format) {
+            return local_to_utc(naive);
+        }
+    }
+
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(local_to_utc)
+}
+
+/// Check a `timeZone` preference value: an IANA zone name such as
+/// `Europe/Berlin`.
+pub(super) fn validate_time_zone(time_zone: &str) -> Result<String, String> {
+    let time_zone = time_zone.trim();
+    time_zone
+        .parse::<Tz>()
+        .map(|zone| zone.name().to_string())
+        .map_err(|_| {
+            format!(
+                "Unknown time zone '{}' (expected e.g. Europe/Berlin)",
+                time_zone
+            )
+        })
+}
+
+/// Zone history timestamps are shown in and quiet hours are read in. A
+/// configured zone keeps histories synced between machines in different zones
+/// consistent; otherwise the system zone is used, or UTC when it cannot be
+/// named.
+pub(super) fn display_time_zone() -> Tz {
+    preferences::current()
+        .time_zone
+        .as_deref()
+        .and_then(|time_zone| time_zone.parse::<Tz>().ok())
+        .or_else(|| {
+            iana_time_zone::get_timezone()
+                .ok()
+                .and_then(|time_zone| time_zone.parse::<Tz>().ok())
+        })
+        .unwrap_or(Tz::UTC)
+}
+
+fn format_in_zone(instant: DateTime<Utc>, zone: Tz) -> String {
+    instant
+        .with_timezone(&zone)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn file_modified(path: &Path) -> DateTime<Utc> {
+    std::fs::metadata(path)
         .ok()
         .and_then(|metadata| metadata.modified().ok())
-        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
-        .map(|duration| duration.as_millis())
-        .unwrap_or(0);
+        .map(DateTime::<Utc>::from)
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
 
-    format!("{modified_millis:020}")
+fn build_session_sort_key(path: &Path, timestamp: Option<&str>) -> DateTime<Utc> {
+    timestamp
+        .and_then(parse_session_timestamp)
+        .unwrap_or_else(|| file_modified(path))
 }
 
-fn load_session_scope_histories(seed_scopes: &[String]) -> Vec<SessionScopeHistory> {
+/// Walk the session roots and group every session file by scope. With an
+/// `operation`, each root is reported as progress and cancellation is
+/// checked between files.
+fn load_session_scope_histories(
+    seed_scopes: &[String],
+    operation: Option<&OperationToken>,
+) -> Result<Vec<SessionScopeHistory>, String> {
     let mut pending_roots = candidate_session_roots(seed_scopes);
     let mut seen_roots = HashSet::<String>::new();
     let mut discovered_scopes = HashSet::<String>::new();
@@ -375,9 +643,13 @@ fn load_session_scope_histories(seed_scopes: &[String]) -> Vec<SessionScopeHisto
 
     while let Some(root) = pending_roots.pop() {
         let root_key = root.path.to_string_lossy().to_string();
-        if !seen_roots.insert(root_key) {
+        if !seen_roots.insert(root_key.clone()) {
             continue;
         }
+        if let Some(operation) = operation {
+            operation.check_cancelled()?;
+            operation.report_progress(seen_roots.len() - 1, None, Some(&root_key));
+        }
 
         let mut session_files = Vec::new();
         collect_session_files_from_root(&root.path, &mut session_files);
@@ -403,8 +675,12 @@ fn load_session_scope_histories(seed_scopes: &[String]) -> Vec<SessionScopeHisto
     }
 
     let mut grouped = BTreeMap::<String, Vec<SessionHistoryInternal>>::new();
+    let mut read_only_dirs = HashMap::<PathBuf, bool>::new();
 
     for (file_key, (path, source)) in file_sources {
+        if let Some(operation) = operation {
+            operation.check_cancelled()?;
+        }
         let Some(header) = header_cache
             .get(&file_key)
             .cloned()
@@ -421,12 +697,22 @@ fn load_session_scope_histories(seed_scopes: &[String]) -> Vec<SessionScopeHisto
             source,
             file_path: path.to_string_lossy().to_string(),
             sort_key: build_session_sort_key(&path, header.timestamp.as_deref()),
+            modified: file_modified(&path),
+            size_bytes: std::fs::metadata(&path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0),
+            read_only: path.parent().is_some_and(|dir| {
+                *read_only_dirs
+                    .entry(dir.to_path_buf())
+                    .or_insert_with(|| directory_is_read_only(dir))
+            }),
         };
 
         grouped.entry(scope).or_default().push(history);
     }
 
-    grouped
+    let zone = display_time_zone();
+    Ok(grouped
         .into_iter()
         .map(|(scope, mut sessions)| {
             sessions.sort_by(|a, b| {
@@ -437,37 +723,185 @@ fn load_session_scope_histories(seed_scopes: &[String]) -> Vec<SessionScopeHisto
 
             SessionScopeHistory {
                 scope,
+                health: ProjectScopeHealth::default(),
                 sessions: sessions
                     .into_iter()
                     .map(|session| PersistedSessionSummary {
                         session_id: session.session_id,
                         timestamp: session.timestamp,
+                        timestamp_millis: session.sort_key.timestamp_millis(),
+                        formatted_timestamp: format_in_zone(session.sort_key, zone),
+                        modified_millis: session.modified.timestamp_millis(),
+                        formatted_modified: format_in_zone(session.modified, zone),
+                        time_zone: zone.name().to_string(),
+                        size_bytes: session.size_bytes,
                         first_user_message: session.first_user_message,
                         source: session.source.as_str().to_string(),
                         file_path: session.file_path,
+                        read_only: session.read_only,
                     })
                     .collect::<Vec<_>>(),
             }
         })
-        .collect::<Vec<_>>()
+        .collect::<Vec<_>>())
+}
+
+/// Every persisted session file reachable from the global roots and `seed_scopes`.
+pub(super) fn persisted_session_files(seed_scopes: &[String]) -> Vec<PathBuf> {
+    load_session_scope_histories(seed_scopes, None)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|history| history.sessions)
+        .map(|session| PathBuf::from(session.file_path))
+        .collect()
+}
+
+fn project_scope_health(scope: &str) -> ProjectScopeHealth {
+    let path = expand_tilde(scope);
+    if !path.is_dir() {
+        return ProjectScopeHealth::default();
+    }
+
+    let is_git_repo = find_git_root(&path).is_some();
+    ProjectScopeHealth {
+        exists: true,
+        is_git_repo,
+        has_uncommitted_changes: if is_git_repo {
+            has_uncommitted_changes(&path)
+        } else {
+            None
+        },
+    }
+}
+
+/// Whether a session file holds image content (attached or returned by a tool).
+fn session_file_has_images(path: &Path) -> bool {
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .any(|line| line.contains(r#""type":"image""#))
+}
+
+fn session_matches(
+    session: &PersistedSessionSummary,
+    options: &SessionListOptions,
+    tagged_session_ids: Option<&HashSet<String>>,
+) -> bool {
+    if options
+        .source
+        .as_ref()
+        .is_some_and(|source| session.source != *source)
+    {
+        return false;
+    }
+
+    if tagged_session_ids.is_some_and(|tagged| !tagged.contains(&session.session_id)) {
+        return false;
+    }
+
+    if options
+        .created_after
+        .is_some_and(|after| session.timestamp_millis < after)
+        || options
+            .created_before
+            .is_some_and(|before| session.timestamp_millis > before)
+    {
+        return false;
+    }
+
+    // Checked last: it reads the whole file.
+    options.has_images.is_none_or(|has_images| {
+        session_file_has_images(Path::new(&session.file_path)) == has_images
+    })
+}
+
+fn sort_sessions(sessions: &mut [PersistedSessionSummary], options: &SessionListOptions) {
+    let ascending = options
+        .ascending
+        .unwrap_or(options.sort == SessionSortField::Title);
+
+    sessions.sort_by(|a, b| {
+        let ordering = match options.sort {
+            SessionSortField::Created => a.timestamp_millis.cmp(&b.timestamp_millis),
+            SessionSortField::LastModified => a.modified_millis.cmp(&b.modified_millis),
+            SessionSortField::Size => a.size_bytes.cmp(&b.size_bytes),
+            SessionSortField::Title => match (&a.first_user_message, &b.first_user_message) {
+                (Some(a), Some(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+                // Untitled sessions stay last in either direction.
+                (Some(_), None) => return std::cmp::Ordering::Less,
+                (None, Some(_)) => return std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            },
+        };
+
+        let ordering = if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        };
+        ordering.then_with(|| a.session_id.cmp(&b.session_id))
+    });
 }
 
 /// List unique project folders discovered from persisted pi session files, along
-/// with grouped history entries from global + local session stores.
+/// with grouped history entries from global + local session stores and a
+/// health check per folder (exists / git repo / uncommitted changes), so moved
+/// or dirty projects can be flagged before launching an agent.
 ///
 /// `seed_scopes` lets the UI explicitly seed local project roots (for example,
 /// the last selected scope) without relying on the app process cwd.
+/// `registered_scopes` are projects known to graphone that may not have any
+/// persisted sessions yet; they are listed with an empty history.
+///
+/// `options` sorts and filters each history; `tagged_session_ids` is the
+/// resolved tag filter (`None` when no tag was requested).
 pub fn list_session_project_scopes(
     seed_scopes: Option<Vec<String>>,
-) -> SessionProjectScopesResponse {
-    let seed_scopes = seed_scopes.unwrap_or_default();
-    let histories = load_session_scope_histories(&seed_scopes);
+    registered_scopes: Vec<String>,
+    options: SessionListOptions,
+    tagged_session_ids: Option<HashSet<String>>,
+    operation: Option<&OperationToken>,
+) -> Result<SessionProjectScopesResponse, String> {
+    let mut seed_scopes = seed_scopes.unwrap_or_default();
+    seed_scopes.extend(registered_scopes.iter().cloned());
+    let mut histories = load_session_scope_histories(&seed_scopes, operation)?;
+
+    for history in &mut histories {
+        history
+            .sessions
+            .retain(|session| session_matches(session, &options, tagged_session_ids.as_ref()));
+        sort_sessions(&mut history.sessions, &options);
+    }
+
+    let known_scopes = histories
+        .iter()
+        .map(|history| normalize_path_for_comparison(&history.scope))
+        .collect::<HashSet<_>>();
+    for scope in registered_scopes {
+        if !known_scopes.contains(&normalize_path_for_comparison(&scope)) {
+            histories.push(SessionScopeHistory {
+                scope,
+                sessions: Vec::new(),
+                health: ProjectScopeHealth::default(),
+            });
+        }
+    }
+    histories.sort_by(|a, b| a.scope.cmp(&b.scope));
+
+    for history in &mut histories {
+        history.health = project_scope_health(&history.scope);
+    }
+
     let scopes = histories
         .iter()
         .map(|history| history.scope.clone())
         .collect::<Vec<_>>();
 
-    SessionProjectScopesResponse { scopes, histories }
+    Ok(SessionProjectScopesResponse { scopes, histories })
 }
 
 /// Encode a cwd path into the directory name format used by pi-mono.
@@ -481,24 +915,67 @@ fn encode_scope_dir_name(cwd: &str) -> String {
     format!("--{}--", normalized)
 }
 
+/// Probe results by directory, kept for the process lifetime so listing
+/// history does not write a scratch file each time.
+static READ_ONLY_DIRS: Mutex<Option<HashMap<PathBuf, bool>>> = Mutex::new(None);
+
+/// A read-only mount. Permission errors are reported as they are, since
+/// they are not about the storage.
+pub(super) fn is_read_only_error(error: &std::io::Error) -> bool {
+    error.kind() == std::io::ErrorKind::ReadOnlyFilesystem
+}
+
+/// Whether entries cannot be created or removed in `dir`, e.g. on a share
+/// mounted read-only. Probed once per directory with a scratch file, since
+/// permission bits do not reflect read-only mounts.
+fn directory_is_read_only(dir: &Path) -> bool {
+    let mut cache = READ_ONLY_DIRS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *cache
+        .get_or_insert_with(HashMap::new)
+        .entry(dir.to_path_buf())
+        .or_insert_with(|| {
+            let probe = dir.join(format!(".graphone-write-probe-{}", std::process::id()));
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&probe)
+            {
+                Ok(_) => {
+                    let _ = std::fs::remove_file(&probe);
+                    false
+                }
+                Err(error) => is_read_only_error(&error),
+            }
+        })
+}
+
+fn read_only_storage_error(path: &Path) -> String {
+    messages::error(ErrorCode::ReadOnlyStorage, &[&path.display()])
+}
+
 /// Delete all session files and the scope directory for a given project scope.
 ///
 /// Finds all JSONL session files whose header cwd matches the project_dir,
-/// deletes them from disk, and also removes the scope's session directory.
-/// Returns the count of deleted files.
-pub fn delete_project_scope(project_dir: String) -> Result<usize, String> {
+/// deletes them from disk, and also removes the scope's session directories.
+/// With `dry_run` nothing is touched; the response lists what would go.
+pub fn delete_project_scope(
+    project_dir: String,
+    dry_run: bool,
+) -> Result<DeleteProjectScopeResponse, String> {
     let normalized_scope = normalize_path_for_comparison(&project_dir);
     if normalized_scope.is_empty() {
-        return Err("project_dir cannot be empty".to_string());
+        return Err(messages::error(ErrorCode::ProjectDirRequired, &[]));
     }
 
     let mut roots = candidate_session_roots(&[]);
     roots.extend(local_session_roots_for_scope(&normalized_scope));
 
     let mut seen_roots = HashSet::<String>::new();
-    let mut deleted_count = 0;
+    let mut target_files = Vec::new();
 
-    // First pass: delete individual JSONL files by matching header cwd
+    // Individual JSONL files, matched by header cwd
     for root in roots {
         let root_key = root.path.to_string_lossy().to_string();
         if !seen_roots.insert(root_key) {
@@ -508,91 +985,115 @@ pub fn delete_project_scope(project_dir: String) -> Result<usize, String> {
         let mut session_files = Vec::new();
         collect_session_files_from_root(&root.path, &mut session_files);
 
-        for session_file in session_files {
-            if let Some(header) = extract_session_header_from_file(&session_file) {
-                let header_scope = normalize_path_for_comparison(&header.scope);
-                if header_scope == normalized_scope {
-                    match std::fs::remove_file(&session_file) {
-                        Ok(()) => {
-                            logger::log(format!(
-                                "Deleted session file for scope '{}': {}",
-                                normalized_scope,
-                                session_file.display()
-                            ));
-                            deleted_count += 1;
-                        }
-                        Err(e) => {
-                            logger::log(format!(
-                                "Failed to delete session file {}: {}",
-                                session_file.display(),
-                                e
-                            ));
-                        }
-                    }
-                }
-            }
-        }
+        target_files.extend(session_files.into_iter().filter(|session_file| {
+            extract_session_header_from_file(session_file).is_some_and(|header| {
+                normalize_path_for_comparison(&header.scope) == normalized_scope
+            })
+        }));
     }
 
-    // Second pass: delete scope directories under global session roots
-    // pi-mono encodes the cwd into a directory name like `--home-user-project--`
+    // Scope directories under the global session roots. pi-mono encodes the
+    // cwd into a directory name like `--home-user-project--`.
     let encoded_dir_name = encode_scope_dir_name(&normalized_scope);
+    let mut scope_dir_candidates = Vec::new();
 
     if let Some(home) = dirs::home_dir() {
-        let global_session_roots = vec![
+        for sessions_root in [
             home.join(".pi").join("agent").join("sessions"),
             home.join(".pi").join("sessions"),
-        ];
-
-        for sessions_root in global_session_roots {
-            let scope_dir = sessions_root.join(&encoded_dir_name);
-            if scope_dir.exists() && scope_dir.is_dir() {
-                match std::fs::remove_dir_all(&scope_dir) {
-                    Ok(()) => {
-                        logger::log(format!(
-                            "Deleted scope directory for '{}': {}",
-                            normalized_scope,
-                            scope_dir.display()
-                        ));
-                    }
-                    Err(e) => {
-                        logger::log(format!(
-                            "Failed to delete scope directory {}: {}",
-                            scope_dir.display(),
-                            e
-                        ));
-                    }
-                }
-            }
+        ] {
+            scope_dir_candidates.push(sessions_root.join(&encoded_dir_name));
         }
     }
 
-    // Also delete encoded local scope directories if they exist.
+    // Also encoded local scope directories.
     // Important: do NOT remove the whole local sessions root, because it may
     // contain other scope directories.
     for local_root in local_session_roots_for_scope(&normalized_scope) {
-        let scope_dir = local_root.path.join(&encoded_dir_name);
-        if scope_dir.exists() && scope_dir.is_dir() {
-            match std::fs::remove_dir_all(&scope_dir) {
-                Ok(()) => {
-                    logger::log(format!(
-                        "Deleted local encoded scope directory for '{}': {}",
-                        normalized_scope,
-                        scope_dir.display()
-                    ));
-                }
-                Err(e) => {
-                    logger::log(format!(
-                        "Failed to delete local encoded scope directory {}: {}",
-                        scope_dir.display(),
-                        e
-                    ));
-                }
+        scope_dir_candidates.push(local_root.path.join(&encoded_dir_name));
+    }
+
+    let target_dirs = scope_dir_candidates
+        .into_iter()
+        .filter(|scope_dir| scope_dir.is_dir())
+        .collect::<Vec<_>>();
+
+    // Refuse up front rather than deleting part of the scope.
+    let mut checked_dirs = HashSet::<PathBuf>::new();
+    let parents = target_files
+        .iter()
+        .chain(&target_dirs)
+        .filter_map(|path| path.parent());
+    for dir in parents {
+        if checked_dirs.insert(dir.to_path_buf()) && directory_is_read_only(dir) {
+            return Err(read_only_storage_error(dir));
+        }
+    }
+
+    if dry_run {
+        return Ok(DeleteProjectScopeResponse {
+            dry_run,
+            deleted_count: 0,
+            files: display_paths(&target_files),
+            directories: display_paths(&target_dirs),
+        });
+    }
+
+    let mut deleted_files = Vec::new();
+    for session_file in target_files {
+        match std::fs::remove_file(&session_file) {
+            Ok(()) => {
+                logger::log(format!(
+                    "Deleted session file for scope '{}': {}",
+                    normalized_scope,
+                    session_file.display()
+                ));
+                deleted_files.push(session_file);
+            }
+            Err(e) => {
+                logger::log(format!(
+                    "Failed to delete session file {}: {}",
+                    session_file.display(),
+                    e
+                ));
             }
         }
     }
 
-    Ok(deleted_count)
+    let mut deleted_dirs = Vec::new();
+    for scope_dir in target_dirs {
+        match std::fs::remove_dir_all(&scope_dir) {
+            Ok(()) => {
+                logger::log(format!(
+                    "Deleted scope directory for '{}': {}",
+                    normalized_scope,
+                    scope_dir.display()
+                ));
+                deleted_dirs.push(scope_dir);
+            }
+            Err(e) => {
+                logger::log(format!(
+                    "Failed to delete scope directory {}: {}",
+                    scope_dir.display(),
+                    e
+                ));
+            }
+        }
+    }
+
+    Ok(DeleteProjectScopeResponse {
+        dry_run,
+        deleted_count: deleted_files.len(),
+        files: display_paths(&deleted_files),
+        directories: display_paths(&deleted_dirs),
+    })
+}
+
+fn display_paths(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
 }
 
 /// Delete a single persisted session file for a project scope.
@@ -606,12 +1107,12 @@ pub fn delete_project_session(
 ) -> Result<DeleteProjectSessionResponse, String> {
     let normalized_scope = normalize_path_for_comparison(&project_dir);
     if normalized_scope.is_empty() {
-        return Err("project_dir cannot be empty".to_string());
+        return Err(messages::error(ErrorCode::ProjectDirRequired, &[]));
     }
 
     let normalized_session_id = session_id.trim().to_string();
     if normalized_session_id.is_empty() {
-        return Err("session_id cannot be empty".to_string());
+        return Err(messages::error(ErrorCode::SessionIdRequired, &[]));
     }
 
     let normalized_file_path = file_path.trim();
@@ -660,6 +1161,9 @@ pub fn delete_project_session(
     }
 
     std::fs::remove_file(&target_path).map_err(|e| {
+        if is_read_only_error(&e) {
+            return read_only_storage_error(&target_path);
+        }
         format!(
             "Failed to delete session file {}: {e}",
             target_path.display()
@@ -0,0 +1,105 @@
+//! Incremental reads of a session JSONL file, so a raw-transcript view can
+//! follow the file the sidecar is writing.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use super::session_scopes::is_known_session_file;
+
+/// Bytes read per call; a longer single line is still returned whole.
+const MAX_TAIL_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionFileTail {
+    pub file_path: String,
+    /// Complete entries written since the requested offset, in file order.
+    pub entries: Vec<serde_json::Value>,
+    /// Lines in the read range that were not valid JSON.
+    pub invalid_lines: usize,
+    /// Offset to pass on the next call. Never points into a partial line.
+    pub next_offset: u64,
+    pub file_size: u64,
+    /// More complete entries are available past `next_offset`.
+    pub has_more: bool,
+    /// The file shrank below the requested offset (rewritten), so reading
+    /// restarted from the beginning.
+    pub reset: bool,
+}
+
+/// Entries of a session file from `from_offset` on (the whole file when
+/// unset), up to the last complete line.
+pub fn tail_session_file(
+    file_path: String,
+    from_offset: Option<u64>,
+) -> Result<SessionFileTail, String> {
+    let path = PathBuf::from(file_path.trim());
+    if !path.is_file() {
+        return Err(format!("Session file not found: {}", path.display()));
+    }
+    if !is_known_session_file(&path) {
+        return Err("file_path is not a session file under a known session root".to_string());
+    }
+
+    let mut file = File::open(&path)
+        .map_err(|error| format!("Failed to open {}: {}", path.display(), error))?;
+    let file_size = file
+        .metadata()
+        .map_err(|error| format!("Failed to stat {}: {}", path.display(), error))?
+        .len();
+
+    let requested = from_offset.unwrap_or(0);
+    let reset = requested > file_size;
+    let offset = if reset { 0 } else { requested };
+
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|error| format!("Failed to seek {}: {}", path.display(), error))?;
+    let mut reader = BufReader::new(file);
+
+    let mut bytes = Vec::new();
+    (&mut reader)
+        .take(MAX_TAIL_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+
+    // A line longer than the budget: finish it rather than stall on it.
+    if bytes.len() as u64 == MAX_TAIL_BYTES && !bytes.contains(&b'\n') {
+        reader
+            .read_until(b'\n', &mut bytes)
+            .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+    }
+
+    // Leave a line the sidecar is still writing for the next call.
+    let read_len = bytes.len() as u64;
+    let complete_len = bytes
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1);
+    bytes.truncate(complete_len);
+
+    let mut entries = Vec::new();
+    let mut invalid_lines = 0;
+    for line in bytes.split(|byte| *byte == b'\n') {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        match serde_json::from_slice::<serde_json::Value>(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => invalid_lines += 1,
+        }
+    }
+
+    let next_offset = offset + complete_len as u64;
+    Ok(SessionFileTail {
+        file_path: path.to_string_lossy().to_string(),
+        entries,
+        invalid_lines,
+        next_offset,
+        file_size,
+        has_more: read_len >= MAX_TAIL_BYTES && next_offset < file_size,
+        reset,
+    })
+}
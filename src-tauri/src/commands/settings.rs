@@ -1,8 +1,16 @@
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::Serialize;
 
+use crate::instance_lock;
 use crate::logger;
+use crate::messages::{self, ErrorCode};
+
+mod jsonc;
+
+const SETTINGS_BACKUP_EXTENSION: &str = "bak";
+const MAX_SETTINGS_BACKUPS: usize = 5;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct EnabledModelsResponse {
@@ -16,6 +24,28 @@ pub struct EnabledModelsResponse {
     pub source: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSettingsBackupResponse {
+    /// Settings file that was restored.
+    pub settings_path: String,
+    /// Backup file whose contents were written back.
+    pub backup_path: String,
+    /// Backup of the settings file as it was before the restore; restoring
+    /// again brings it back.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_backup_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectToolsResponse {
+    /// Tool names the sidecar deactivates for new sessions in the project.
+    pub disabled_tools: Vec<String>,
+    /// Project settings file holding the `disabledTools` key.
+    pub settings_path: String,
+}
+
 fn home_settings_path() -> Option<PathBuf> {
     dirs::home_dir().map(|h| h.join(".pi").join("settings.json"))
 }
@@ -45,7 +75,7 @@ fn read_enabled_models_from_settings(path: &Path) -> (bool, Vec<String>) {
         }
     };
 
-    let settings = match serde_json::from_str::<serde_json::Value>(&content) {
+    let settings = match jsonc::parse(&content) {
         Ok(v) => v,
         Err(e) => {
             logger::log(format!(
@@ -87,7 +117,120 @@ fn read_enabled_models_from_settings(path: &Path) -> (bool, Vec<String>) {
     (true, patterns)
 }
 
-fn write_enabled_models_to_settings(path: &Path, patterns: &[String]) -> Result<(), String> {
+fn settings_backup_prefix(path: &Path) -> Option<String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| format!("{}.", name))
+}
+
+/// List timestamped backups (`settings.json.<millis>.bak`) for a settings file, newest first.
+fn list_settings_backups(path: &Path) -> Vec<PathBuf> {
+    let (Some(parent), Some(prefix)) = (path.parent(), settings_backup_prefix(path)) else {
+        return Vec::new();
+    };
+
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return Vec::new();
+    };
+
+    let suffix = format!(".{}", SETTINGS_BACKUP_EXTENSION);
+    let mut backups = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let millis = name
+                .strip_prefix(&prefix)?
+                .strip_suffix(&suffix)?
+                .parse::<u128>()
+                .ok()?;
+            Some((millis, entry.path()))
+        })
+        .collect::<Vec<_>>();
+
+    backups.sort_by_key(|(millis, _)| std::cmp::Reverse(*millis));
+    backups.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Copy the current settings file to a timestamped `.bak` next to it and prune old backups.
+fn backup_settings_file(path: &Path) -> Result<Option<PathBuf>, String> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let prefix = settings_backup_prefix(path)
+        .ok_or_else(|| format!("Invalid settings file path {}", path.display()))?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0);
+    let backup_path = path.with_file_name(format!(
+        "{}{}.{}",
+        prefix, millis, SETTINGS_BACKUP_EXTENSION
+    ));
+
+    std::fs::copy(path, &backup_path).map_err(|e| {
+        format!(
+            "Failed to back up settings file {} to {}: {}",
+            path.display(),
+            backup_path.display(),
+            e
+        )
+    })?;
+
+    for stale in list_settings_backups(path)
+        .into_iter()
+        .skip(MAX_SETTINGS_BACKUPS)
+    {
+        if let Err(error) = std::fs::remove_file(&stale) {
+            logger::log(format!(
+                "Failed to remove old settings backup {}: {}",
+                stale.display(),
+                error
+            ));
+        }
+    }
+
+    Ok(Some(backup_path))
+}
+
+/// Write `contents` to a sibling temp file, flush it, then rename it over `path`.
+///
+/// A crash mid-write leaves either the old or the new file, never a truncated one.
+fn write_file_atomically(path: &Path, contents: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("Invalid settings file path {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let write_result = std::fs::File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    });
+
+    if let Err(error) = write_result.and_then(|_| std::fs::rename(&temp_path, path)) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(format!(
+            "Failed to write settings file {}: {}",
+            path.display(),
+            error
+        ));
+    }
+
+    Ok(())
+}
+
+/// Set a single top-level key in a pi settings file.
+///
+/// Existing files are edited in place so comments, key order, and formatting
+/// the user chose are preserved. A file that exists but cannot be parsed as
+/// JSON/JSONC is left untouched and reported as an error. The previous version
+/// is kept as a timestamped `.bak` and the new contents are written atomically.
+fn write_settings_property(path: &Path, key: &str, value: serde_json::Value) -> Result<(), String> {
+    instance_lock::ensure_primary()?;
+
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| {
             format!(
@@ -98,33 +241,50 @@ fn write_enabled_models_to_settings(path: &Path, patterns: &[String]) -> Result<
         })?;
     }
 
-    let mut root: serde_json::Value = if path.exists() {
+    let existing = if path.exists() {
         std::fs::read_to_string(path)
-            .ok()
-            .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
-            .unwrap_or_else(|| serde_json::json!({}))
+            .map_err(|e| format!("Failed to read settings file {}: {}", path.display(), e))?
     } else {
-        serde_json::json!({})
+        String::new()
     };
 
-    if !root.is_object() {
-        root = serde_json::json!({});
-    }
+    let next = if existing.trim().is_empty() {
+        let mut root = serde_json::Map::new();
+        root.insert(key.to_string(), value);
+
+        let serialized = serde_json::to_string_pretty(&serde_json::Value::Object(root))
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        format!("{}\n", serialized)
+    } else {
+        let edited = jsonc::set_top_level_property(&existing, key, &value).ok_or_else(|| {
+            format!(
+                "Settings file {} is not a JSON object; refusing to overwrite it",
+                path.display()
+            )
+        })?;
 
+        jsonc::parse(&edited).map_err(|e| {
+            format!(
+                "Refusing to write settings file {}: edit produced invalid JSON ({})",
+                path.display(),
+                e
+            )
+        })?;
+
+        edited
+    };
+
+    backup_settings_file(path)?;
+    write_file_atomically(path, &next)
+}
+
+fn write_enabled_models_to_settings(path: &Path, patterns: &[String]) -> Result<(), String> {
     let arr = patterns
         .iter()
         .map(|s| serde_json::Value::String(s.clone()))
         .collect::<Vec<_>>();
 
-    root["enabledModels"] = serde_json::Value::Array(arr);
-
-    let serialized = serde_json::to_string_pretty(&root)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-    std::fs::write(path, format!("{}\n", serialized))
-        .map_err(|e| format!("Failed to write settings file {}: {}", path.display(), e))?;
-
-    Ok(())
+    write_settings_property(path, "enabledModels", serde_json::Value::Array(arr))
 }
 
 fn load_enabled_models(project_dir: Option<&str>) -> EnabledModelsResponse {
@@ -171,29 +331,20 @@ fn load_enabled_models(project_dir: Option<&str>) -> EnabledModelsResponse {
     }
 }
 
-/// Get enabledModels patterns from pi settings.
+/// Resolve which pi settings file a mutation targets.
 ///
-/// Precedence: project settings (`<projectDir>/.pi/settings.json`) override home settings (`~/.pi/settings.json`), which override agent settings (`~/.pi/agent/settings.json`).
-pub fn get_enabled_models(project_dir: Option<String>) -> EnabledModelsResponse {
-    load_enabled_models(project_dir.as_deref())
-}
-
-/// Persist enabledModels patterns to a pi settings file.
-///
-/// - patterns: the enabledModels array to write. Empty means "no scoping" (all models enabled).
 /// - scope: "auto" (default), "project", or "global".
-pub fn set_enabled_models(
-    patterns: Vec<String>,
+fn resolve_settings_target_path(
     scope: Option<String>,
-    project_dir: Option<String>,
-) -> Result<EnabledModelsResponse, String> {
+    project_dir: Option<&str>,
+) -> Result<PathBuf, String> {
     let scope = scope.unwrap_or_else(|| "auto".to_string());
 
-    let project_path = project_settings_path(project_dir.as_deref());
+    let project_path = project_settings_path(project_dir);
     let home_path = home_settings_path();
     let agent_path = agent_settings_path();
 
-    let target_path: PathBuf = match scope.as_str() {
+    let target_path = match scope.as_str() {
         "project" => {
             project_path.ok_or_else(|| "Failed to determine project settings path".to_string())?
         }
@@ -239,7 +390,161 @@ pub fn set_enabled_models(
         }
     };
 
+    Ok(target_path)
+}
+
+/// Get enabledModels patterns from pi settings.
+///
+/// Precedence: project settings (`<projectDir>/.pi/settings.json`) override home settings (`~/.pi/settings.json`), which override agent settings (`~/.pi/agent/settings.json`).
+pub fn get_enabled_models(project_dir: Option<String>) -> EnabledModelsResponse {
+    load_enabled_models(project_dir.as_deref())
+}
+
+/// Persist enabledModels patterns to a pi settings file.
+///
+/// - patterns: the enabledModels array to write. Empty means "no scoping" (all models enabled).
+/// - scope: "auto" (default), "project", or "global".
+pub fn set_enabled_models(
+    patterns: Vec<String>,
+    scope: Option<String>,
+    project_dir: Option<String>,
+) -> Result<EnabledModelsResponse, String> {
+    let target_path = resolve_settings_target_path(scope, project_dir.as_deref())?;
+
     write_enabled_models_to_settings(&target_path, &patterns)?;
 
     Ok(load_enabled_models(project_dir.as_deref()))
 }
+
+fn read_disabled_tools_from_settings(path: &Path) -> Vec<String> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    let settings = match jsonc::parse(&content) {
+        Ok(v) => v,
+        Err(e) => {
+            logger::log(format!(
+                "Failed to parse settings file {} as JSON: {}",
+                path.display(),
+                e
+            ));
+            return Vec::new();
+        }
+    };
+
+    settings
+        .get("disabledTools")
+        .and_then(|value| value.as_array())
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.trim().to_string()))
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn require_project_settings_path(project_dir: &str) -> Result<PathBuf, String> {
+    if project_dir.trim().is_empty() {
+        return Err(messages::error(ErrorCode::ProjectDirRequired, &[]));
+    }
+
+    project_settings_path(Some(project_dir.trim()))
+        .ok_or_else(|| "Failed to determine project settings path".to_string())
+}
+
+/// Get the tools disabled for a project (`disabledTools` in `<projectDir>/.pi/settings.json`).
+pub fn get_project_tools(project_dir: String) -> Result<ProjectToolsResponse, String> {
+    let path = require_project_settings_path(&project_dir)?;
+
+    Ok(ProjectToolsResponse {
+        disabled_tools: read_disabled_tools_from_settings(&path),
+        settings_path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// Persist the tools disabled for a project. Empty enables every tool again.
+///
+/// The sidecar applies the list when it creates a session, so sessions that
+/// are already open keep their current tool set.
+pub fn set_project_tools(
+    project_dir: String,
+    disabled_tools: Vec<String>,
+) -> Result<ProjectToolsResponse, String> {
+    let path = require_project_settings_path(&project_dir)?;
+
+    let mut tools = Vec::<String>::new();
+    for tool in disabled_tools {
+        let tool = tool.trim().to_string();
+        if tool.is_empty()
+            || !tool
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("Invalid tool name '{}'", tool));
+        }
+        if !tools.contains(&tool) {
+            tools.push(tool);
+        }
+    }
+
+    let value = serde_json::Value::Array(
+        tools
+            .iter()
+            .map(|tool| serde_json::Value::String(tool.clone()))
+            .collect(),
+    );
+    write_settings_property(&path, "disabledTools", value)?;
+
+    logger::log(format!(
+        "Set disabledTools for {} to [{}]",
+        project_dir,
+        tools.join(", ")
+    ));
+
+    get_project_tools(project_dir)
+}
+
+/// Restore the most recent `.bak` of a pi settings file.
+///
+/// The current file is backed up first, so that backup becomes the most
+/// recent one and a second restore undoes the first.
+///
+/// - scope: "auto" (default), "project", or "global" (same resolution as writes).
+pub fn restore_settings_backup(
+    scope: Option<String>,
+    project_dir: Option<String>,
+) -> Result<RestoreSettingsBackupResponse, String> {
+    let target_path = resolve_settings_target_path(scope, project_dir.as_deref())?;
+
+    let backup_path = list_settings_backups(&target_path)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No settings backup found for {}", target_path.display()))?;
+
+    let contents = std::fs::read_to_string(&backup_path).map_err(|e| {
+        format!(
+            "Failed to read settings backup {}: {}",
+            backup_path.display(),
+            e
+        )
+    })?;
+
+    instance_lock::ensure_primary()?;
+    let previous_backup_path = backup_settings_file(&target_path)?;
+    write_file_atomically(&target_path, &contents)?;
+
+    logger::log(format!(
+        "Restored settings file {} from backup {}",
+        target_path.display(),
+        backup_path.display()
+    ));
+
+    Ok(RestoreSettingsBackupResponse {
+        settings_path: target_path.to_string_lossy().to_string(),
+        backup_path: backup_path.to_string_lossy().to_string(),
+        previous_backup_path: previous_backup_path.map(|path| path.to_string_lossy().to_string()),
+    })
+}
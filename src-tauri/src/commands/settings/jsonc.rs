@@ -0,0 +1,332 @@
+const DEFAULT_INDENT_UNIT: &str = "  ";
+
+#[derive(Debug, Clone)]
+struct MemberSpan {
+    key: String,
+    value_start: usize,
+    value_end: usize,
+}
+
+#[derive(Debug, Clone)]
+struct ObjectLayout {
+    close: usize,
+    members: Vec<MemberSpan>,
+}
+
+/// Parse JSONC text (comments and trailing commas allowed) into a JSON value.
+pub(crate) fn parse(text: &str) -> Result<serde_json::Value, serde_json::Error> {
+    serde_json::from_str(&strip_comments_and_trailing_commas(text))
+}
+
+/// Set a top-level property in a JSONC object document, preserving all other text.
+///
+/// pi settings may be hand-edited and contain comments or trailing commas, so
+/// only the targeted member is replaced (or a new one inserted). Returns
+/// `None` when `text` is not a single top-level object that can be edited
+/// safely.
+pub(crate) fn set_top_level_property(
+    text: &str,
+    key: &str,
+    value: &serde_json::Value,
+) -> Option<String> {
+    let layout = scan_top_level_object(text)?;
+    let indent_unit = detect_indent_unit(text, &layout);
+
+    if let Some(member) = layout.members.iter().find(|member| member.key == key) {
+        let member_indent = line_indent(text, member.value_start);
+        let formatted = format_value(value, &indent_unit, member_indent)?;
+
+        return Some(format!(
+            "{}{}{}",
+            &text[..member.value_start],
+            formatted,
+            &text[member.value_end..]
+        ));
+    }
+
+    let key_json = serde_json::to_string(key).ok()?;
+
+    let Some(last) = layout.members.last() else {
+        let brace_indent = line_indent(text, layout.close);
+        let member_indent = format!("{}{}", brace_indent, indent_unit);
+        let formatted = format_value(value, &indent_unit, &member_indent)?;
+
+        return Some(format!(
+            "{}\n{}{}: {}\n{}{}",
+            text[..layout.close].trim_end(),
+            member_indent,
+            key_json,
+            formatted,
+            brace_indent,
+            &text[layout.close..]
+        ));
+    };
+
+    let member_indent = line_indent(text, last.value_start).to_string();
+    let formatted = format_value(value, &indent_unit, &member_indent)?;
+    let new_member = format!("{}{}: {}", member_indent, key_json, formatted);
+
+    let after_value = &text[last.value_end..layout.close];
+    let trailing_comma_offset = after_value
+        .find(|c: char| !c.is_whitespace())
+        .filter(|offset| after_value[*offset..].starts_with(','));
+
+    match trailing_comma_offset {
+        Some(offset) => {
+            // Keep the existing trailing comma style: `"a": 1,` -> `"a": 1,\n  "b": 2,`
+            let insert_at = line_end_after_comment(text, last.value_end + offset + 1);
+            Some(format!(
+                "{}\n{},{}",
+                &text[..insert_at],
+                new_member,
+                &text[insert_at..]
+            ))
+        }
+        None => {
+            // Keep trailing line comments attached to the member they annotate.
+            let insert_at = line_end_after_comment(text, last.value_end);
+            Some(format!(
+                "{},{}\n{}{}",
+                &text[..last.value_end],
+                &text[last.value_end..insert_at],
+                new_member,
+                &text[insert_at..]
+            ))
+        }
+    }
+}
+
+/// Returns the end of the current line when only whitespace and a `//` comment
+/// follow `position` on that line; otherwise returns `position` unchanged.
+fn line_end_after_comment(text: &str, position: usize) -> usize {
+    let rest = &text[position..];
+    let line_len = rest.find('\n').unwrap_or(rest.len());
+    let line_rest = rest[..line_len].trim();
+
+    if line_rest.starts_with("//") {
+        let mut end = position + line_len;
+        if text[..end].ends_with('\r') {
+            end -= 1;
+        }
+        return end;
+    }
+
+    position
+}
+
+fn line_indent(text: &str, position: usize) -> &str {
+    let line_start = text[..position].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = &text[line_start..position];
+    let indent_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+    &line[..indent_len]
+}
+
+fn detect_indent_unit(text: &str, layout: &ObjectLayout) -> String {
+    layout
+        .members
+        .first()
+        .map(|member| line_indent(text, member.value_start))
+        .filter(|indent| !indent.is_empty())
+        .map(|indent| indent.to_string())
+        .unwrap_or_else(|| DEFAULT_INDENT_UNIT.to_string())
+}
+
+fn format_value(value: &serde_json::Value, indent_unit: &str, base_indent: &str) -> Option<String> {
+    use serde::Serialize;
+
+    let mut buffer = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent_unit.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+    value.serialize(&mut serializer).ok()?;
+
+    let pretty = String::from_utf8(buffer).ok()?;
+    Some(pretty.replace('\n', &format!("\n{}", base_indent)))
+}
+
+/// Returns the byte index just past the closing quote of the string starting at `start`.
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut index = start + 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'"' => return Some(index + 1),
+            _ => index += 1,
+        }
+    }
+
+    None
+}
+
+/// Returns the byte index just past the comment starting at `start`, if any.
+fn comment_end(bytes: &[u8], start: usize) -> Option<usize> {
+    match bytes.get(start + 1) {
+        Some(b'/') => Some(
+            bytes[start..]
+                .iter()
+                .position(|b| *b == b'\n')
+                .map(|offset| start + offset)
+                .unwrap_or(bytes.len()),
+        ),
+        Some(b'*') => Some(
+            bytes[start + 2..]
+                .windows(2)
+                .position(|window| window == b"*/")
+                .map(|offset| start + 2 + offset + 2)
+                .unwrap_or(bytes.len()),
+        ),
+        _ => None,
+    }
+}
+
+fn finish_member(
+    members: &mut Vec<MemberSpan>,
+    key: &mut Option<String>,
+    value_start: &mut Option<usize>,
+    value_end: usize,
+) {
+    if let (Some(key), Some(value_start)) = (key.take(), value_start.take()) {
+        members.push(MemberSpan {
+            key,
+            value_start,
+            value_end,
+        });
+    }
+}
+
+fn scan_top_level_object(text: &str) -> Option<ObjectLayout> {
+    let bytes = text.as_bytes();
+    let mut index = if text.starts_with('\u{feff}') { 3 } else { 0 };
+
+    let mut depth = 0usize;
+    let mut opened = false;
+    let mut expecting_key = false;
+    let mut current_key: Option<String> = None;
+    let mut value_start: Option<usize> = None;
+    let mut last_end = 0usize;
+    let mut members = Vec::new();
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+
+        if byte == b'/' {
+            if let Some(end) = comment_end(bytes, index) {
+                index = end;
+                continue;
+            }
+        }
+
+        match byte {
+            b'"' => {
+                if depth == 0 {
+                    return None;
+                }
+
+                let end = string_end(bytes, index)?;
+                if depth == 1 && expecting_key {
+                    current_key = Some(serde_json::from_str::<String>(&text[index..end]).ok()?);
+                    expecting_key = false;
+                } else if depth == 1 && current_key.is_some() && value_start.is_none() {
+                    value_start = Some(index);
+                }
+
+                last_end = end;
+                index = end;
+                continue;
+            }
+            b'{' | b'[' => {
+                if depth == 0 {
+                    if byte != b'{' || opened {
+                        return None;
+                    }
+                    opened = true;
+                    expecting_key = true;
+                } else if depth == 1 && current_key.is_some() && value_start.is_none() {
+                    value_start = Some(index);
+                }
+
+                depth += 1;
+                last_end = index + 1;
+            }
+            b'}' | b']' => {
+                if depth == 0 {
+                    return None;
+                }
+
+                if depth == 1 {
+                    finish_member(&mut members, &mut current_key, &mut value_start, last_end);
+                    return Some(ObjectLayout {
+                        close: index,
+                        members,
+                    });
+                }
+
+                depth -= 1;
+                last_end = index + 1;
+            }
+            b',' if depth == 1 => {
+                finish_member(&mut members, &mut current_key, &mut value_start, last_end);
+                expecting_key = true;
+            }
+            b':' if depth == 1 => {}
+            byte if byte.is_ascii_whitespace() => {}
+            _ => {
+                if depth == 0 {
+                    return None;
+                }
+
+                if depth == 1 && current_key.is_some() && value_start.is_none() {
+                    value_start = Some(index);
+                }
+                last_end = index + 1;
+            }
+        }
+
+        index += 1;
+    }
+
+    None
+}
+
+/// Replace comments with whitespace and drop trailing commas so the result is
+/// strict JSON. Byte offsets of all remaining tokens are preserved.
+fn strip_comments_and_trailing_commas(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = bytes.to_vec();
+    let mut index = 0usize;
+    let mut pending_comma: Option<usize> = None;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'"' => {
+                pending_comma = None;
+                index = string_end(bytes, index).unwrap_or(bytes.len());
+                continue;
+            }
+            b'/' => {
+                if let Some(end) = comment_end(bytes, index) {
+                    for byte in &mut out[index..end] {
+                        if *byte != b'\n' {
+                            *byte = b' ';
+                        }
+                    }
+                    index = end;
+                    continue;
+                }
+                pending_comma = None;
+            }
+            b',' => pending_comma = Some(index),
+            b'}' | b']' => {
+                if let Some(comma) = pending_comma.take() {
+                    out[comma] = b' ';
+                }
+            }
+            byte if byte.is_ascii_whitespace() => {}
+            _ => pending_comma = None,
+        }
+
+        index += 1;
+    }
+
+    // Comments are blanked byte-by-byte and commas are ASCII, so UTF-8 stays valid.
+    String::from_utf8(out).unwrap_or_else(|_| text.to_string())
+}
@@ -0,0 +1,177 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+
+use super::project_metadata::registered_project_dirs;
+use super::session_scopes::pi_agent_dir;
+use crate::instance_lock;
+use crate::state::SidecarState;
+use crate::utils::now_millis;
+
+const ONBOARDING_STORE_FILE: &str = "onboarding.json";
+const ONBOARDING_KEY: &str = "onboarding";
+
+/// First-run steps, in the order the wizard presents them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OnboardingStep {
+    /// The sidecar started and answered a capabilities request.
+    SidecarVerified,
+    /// At least one provider has stored credentials.
+    ProviderAuthenticated,
+    /// A project directory has been registered or opened.
+    ProjectChosen,
+}
+
+impl OnboardingStep {
+    const ALL: [OnboardingStep; 3] = [
+        OnboardingStep::SidecarVerified,
+        OnboardingStep::ProviderAuthenticated,
+        OnboardingStep::ProjectChosen,
+    ];
+}
+
+/// Persisted progress: when each step was first seen complete (unix millis).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct OnboardingProgress {
+    completed_at: BTreeMap<OnboardingStep, u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingStepState {
+    pub step: OnboardingStep,
+    pub completed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingState {
+    pub steps: Vec<OnboardingStepState>,
+    /// First incomplete step, or `None` once onboarding is done.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_step: Option<OnboardingStep>,
+    pub complete: bool,
+}
+
+fn load_progress(app: &AppHandle) -> Result<OnboardingProgress, String> {
+    let store = app
+        .store(ONBOARDING_STORE_FILE)
+        .map_err(|error| format!("Failed to open onboarding store: {}", error))?;
+
+    Ok(store
+        .get(ONBOARDING_KEY)
+        .and_then(|value| serde_json::from_value::<OnboardingProgress>(value).ok())
+        .unwrap_or_default())
+}
+
+fn save_progress(app: &AppHandle, progress: &OnboardingProgress) -> Result<(), String> {
+    instance_lock::ensure_primary()?;
+
+    let value = serde_json::to_value(progress)
+        .map_err(|error| format!("Failed to serialize onboarding state: {}", error))?;
+
+    let store = app
+        .store(ONBOARDING_STORE_FILE)
+        .map_err(|error| format!("Failed to open onboarding store: {}", error))?;
+    store.set(ONBOARDING_KEY, value);
+    store
+        .save()
+        .map_err(|error| format!("Failed to save onboarding store: {}", error))
+}
+
+/// Whether pi's `auth.json` holds credentials for any provider.
+fn has_stored_credentials() -> bool {
+    pi_agent_dir()
+        .and_then(|agent_dir| std::fs::read_to_string(agent_dir.join("auth.json")).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|value| value.as_object().map(|entries| !entries.is_empty()))
+        .unwrap_or(false)
+}
+
+/// Check a step against live backend state.
+async fn detect_step(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    step: OnboardingStep,
+) -> bool {
+    match step {
+        OnboardingStep::SidecarVerified => state.lock().await.capabilities.is_some(),
+        OnboardingStep::ProviderAuthenticated => has_stored_credentials(),
+        OnboardingStep::ProjectChosen => {
+            !state.lock().await.session_cwds.is_empty() || !registered_project_dirs(app).is_empty()
+        }
+    }
+}
+
+fn build_state(progress: &OnboardingProgress) -> OnboardingState {
+    let steps = OnboardingStep::ALL
+        .iter()
+        .map(|step| {
+            let completed_at = progress.completed_at.get(step).copied();
+            OnboardingStepState {
+                step: *step,
+                completed: completed_at.is_some(),
+                completed_at,
+            }
+        })
+        .collect::<Vec<_>>();
+    let next_step = steps
+        .iter()
+        .find(|step| !step.completed)
+        .map(|step| step.step);
+
+    OnboardingState {
+        steps,
+        next_step,
+        complete: next_step.is_none(),
+    }
+}
+
+/// Current onboarding progress. Steps the backend can observe as done are
+/// recorded on the way, so the wizard never asks for something already set up.
+pub async fn get_onboarding_state(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+) -> Result<OnboardingState, String> {
+    let mut progress = load_progress(app)?;
+    let mut changed = false;
+
+    for step in OnboardingStep::ALL {
+        if progress.completed_at.contains_key(&step) {
+            continue;
+        }
+        if detect_step(app, state, step).await {
+            progress.completed_at.insert(step, now_millis());
+            changed = true;
+        }
+    }
+
+    if changed {
+        save_progress(app, &progress)?;
+    }
+
+    Ok(build_state(&progress))
+}
+
+/// Mark a step done, e.g. when the user skips it in the wizard.
+pub fn complete_onboarding_step(
+    app: &AppHandle,
+    step: OnboardingStep,
+) -> Result<OnboardingState, String> {
+    let mut progress = load_progress(app)?;
+    if let Entry::Vacant(entry) = progress.completed_at.entry(step) {
+        entry.insert(now_millis());
+        save_progress(app, &progress)?;
+    }
+
+    Ok(build_state(&progress))
+}
@@ -0,0 +1,214 @@
+//! One-shot runs of the bundled pi CLI for maintenance tasks (version,
+//! model listing, HTML export of a session), without an RPC wrapper for each.
+//!
+//! Only allow-listed invocations run; anything that would start an
+//! interactive or agent session is rejected. Exports are written to the
+//! downloads dir (the app data dir when there is none), never elsewhere.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_shell::process::CommandEvent;
+
+use super::operations::OperationToken;
+use super::session_scopes::is_known_session_file;
+use crate::logger;
+use crate::sidecar::SidecarManager;
+
+const SIDECAR_CLI_TIMEOUT: Duration = Duration::from_secs(120);
+/// Bytes of stdout/stderr returned per stream.
+const MAX_CLI_OUTPUT_BYTES: usize = 1024 * 1024;
+/// Under the app data dir, for systems without a downloads dir.
+const EXPORT_DIR_NAME: &str = "exports";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarCliOutput {
+    pub args: Vec<String>,
+    /// `None` when the process was ended by a signal.
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+    /// Either stream was cut to 1 MiB.
+    pub truncated: bool,
+    pub duration_ms: u64,
+    /// Where `--export` wrote the HTML file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_path: Option<String>,
+}
+
+fn is_flag(arg: &str) -> bool {
+    arg.starts_with('-')
+}
+
+fn export_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .download_dir()
+        .or_else(|_| {
+            app.path()
+                .app_data_dir()
+                .map(|dir| dir.join(EXPORT_DIR_NAME))
+        })
+        .map_err(|error| format!("Failed to resolve export directory: {}", error))
+}
+
+/// Absolute path in the export dir for `output`, a plain `.html` file name,
+/// or one named after the session file.
+fn resolve_export_path(
+    app: &AppHandle,
+    session_file: &Path,
+    output: Option<&String>,
+) -> Result<PathBuf, String> {
+    let file_name = match output {
+        Some(output) => {
+            let is_plain_name = Path::new(output).file_name() == Some(output.as_ref());
+            if is_flag(output) || !is_plain_name || !output.ends_with(".html") {
+                return Err(format!(
+                    "Export output must be an .html file name without a directory, got '{}'",
+                    output
+                ));
+            }
+            output.clone()
+        }
+        None => format!(
+            "{}.html",
+            session_file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| "session".to_string())
+        ),
+    };
+
+    let dir = export_dir(app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|error| format!("Failed to create {}: {}", dir.display(), error))?;
+    Ok(dir.join(file_name))
+}
+
+/// Accept `--version`, `--help`, `--list-models [search]` and
+/// `--export <session file> [output.html]`, and return the arguments to run
+/// with the export output resolved into the export dir.
+fn resolve_cli_args(
+    app: &AppHandle,
+    args: &[String],
+) -> Result<(Vec<String>, Option<PathBuf>), String> {
+    let Some((subcommand, rest)) = args.split_first() else {
+        return Err("run_sidecar_cli needs at least one argument".to_string());
+    };
+
+    match (subcommand.as_str(), rest) {
+        ("--version" | "--help", []) => Ok((args.to_vec(), None)),
+        ("--list-models", []) => Ok((args.to_vec(), None)),
+        ("--list-models", [search]) if !is_flag(search) => Ok((args.to_vec(), None)),
+        ("--export", [session_file, output @ ..]) if output.len() <= 1 => {
+            if !is_known_session_file(Path::new(session_file)) {
+                return Err(format!(
+                    "{} is not a session file under a known session root",
+                    session_file
+                ));
+            }
+            let export_path = resolve_export_path(app, Path::new(session_file), output.first())?;
+            let args = vec![
+                subcommand.clone(),
+                session_file.clone(),
+                export_path.to_string_lossy().to_string(),
+            ];
+            Ok((args, Some(export_path)))
+        }
+        ("--version" | "--help" | "--list-models" | "--export", _) => Err(format!(
+            "Unexpected arguments for {}: {}",
+            subcommand,
+            rest.join(" ")
+        )),
+        _ => Err(format!(
+            "Sidecar CLI argument '{}' is not allowed (allowed: --version, --help, --list-models, --export)",
+            subcommand
+        )),
+    }
+}
+
+/// Append a line to `buffer` up to `MAX_CLI_OUTPUT_BYTES`; returns whether
+/// anything was cut.
+fn append_output_line(buffer: &mut Vec<u8>, line: &[u8]) -> bool {
+    let room = MAX_CLI_OUTPUT_BYTES.saturating_sub(buffer.len());
+    let line_len = line.len() + 1;
+    if line_len <= room {
+        buffer.extend_from_slice(line);
+        buffer.push(b'\n');
+        false
+    } else {
+        buffer.extend_from_slice(&line[..room.min(line.len())]);
+        true
+    }
+}
+
+pub async fn run_sidecar_cli(
+    app: &AppHandle,
+    args: Vec<String>,
+    operation: &OperationToken,
+) -> Result<SidecarCliOutput, String> {
+    let args = args
+        .into_iter()
+        .map(|arg| arg.trim().to_string())
+        .collect::<Vec<_>>();
+    let (args, export_path) = resolve_cli_args(app, &args)?;
+
+    let command = SidecarManager::build_sidecar_cli_command(app, &args)?;
+    logger::log(format!("Running sidecar CLI: pi {}", args.join(" ")));
+
+    let started = Instant::now();
+    let (mut events, child) = command
+        .spawn()
+        .map_err(|error| format!("Failed to run sidecar CLI: {}", error))?;
+    let deadline = tokio::time::sleep(SIDECAR_CLI_TIMEOUT);
+    tokio::pin!(deadline);
+
+    let mut exit_code = None;
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut truncated = false;
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Some(CommandEvent::Stdout(line)) => {
+                    truncated |= append_output_line(&mut stdout, &line);
+                }
+                Some(CommandEvent::Stderr(line)) => {
+                    truncated |= append_output_line(&mut stderr, &line);
+                }
+                Some(CommandEvent::Terminated(payload)) => exit_code = payload.code,
+                Some(_) => {}
+                None => break,
+            },
+            _ = &mut deadline => {
+                let _ = child.kill();
+                return Err(format!(
+                    "Sidecar CLI did not finish within {}s",
+                    SIDECAR_CLI_TIMEOUT.as_secs()
+                ));
+            }
+            _ = operation.cancelled() => {
+                let _ = child.kill();
+                logger::log(format!(
+                    "Killed cancelled sidecar CLI run: pi {}",
+                    args.join(" ")
+                ));
+                return Err(operation.cancelled_error());
+            }
+        }
+    }
+
+    Ok(SidecarCliOutput {
+        args,
+        exit_code,
+        success: exit_code == Some(0),
+        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+        export_path: export_path.map(|path| path.to_string_lossy().to_string()),
+    })
+}
@@ -5,8 +5,17 @@ use tauri::AppHandle;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration};
 
+use super::disk_space::ensure_session_disk_space;
+use super::orphan_sidecars::{clear_sidecar_pid, record_sidecar_pid};
+use super::pinned_context::prepare_pinned_context;
+use super::session_env::project_env;
+use super::workspace_trust::disabled_tools_for_new_session;
 use crate::logger;
-use crate::sidecar::{EventHandler, RpcClient, SidecarManager};
+use crate::messages::{self, ErrorCode};
+use crate::platform::process_suspend::resume_processes;
+use crate::sidecar::{
+    forget_all_sequences, forget_session_sequence, EventHandler, RpcClient, SidecarManager,
+};
 use crate::state::SidecarState;
 use crate::types::{RpcCommand, RpcResponse};
 use crate::utils::crypto_random_uuid;
@@ -45,17 +54,24 @@ pub async fn ensure_sidecar_started(
     let (event_rx, child) = SidecarManager::spawn_sidecar(sidecar_command).await?;
 
     logger::log("Sidecar spawned successfully");
+    let sidecar_pid = child.pid();
+    record_sidecar_pid(app, sidecar_pid);
 
     let (response_tx, response_rx) = tokio::sync::mpsc::channel::<(String, RpcResponse)>(100);
     state_guard.response_tx = Some(response_tx);
 
     let child_arc = Arc::new(Mutex::new(child));
     state_guard.child = Some(child_arc);
+    state_guard.capabilities = None;
+    // A suspension was of the previous sidecar, whose pids may be reused.
+    if let Some(pause) = state_guard.pause.as_mut() {
+        pause.suspended_pids.clear();
+    }
 
     drop(state_guard);
 
     EventHandler::spawn_response_handler(state.clone(), response_rx);
-    EventHandler::spawn_event_listener(app.clone(), state.clone(), event_rx);
+    EventHandler::spawn_event_listener(app.clone(), state.clone(), event_rx, sidecar_pid);
 
     wait_for_sidecar_ready(state, SIDECAR_READY_ATTEMPTS, SIDECAR_READY_TIMEOUT_SECS).await
 }
@@ -80,6 +96,8 @@ async fn wait_for_sidecar_ready(
             session_file: None,
             level: None,
             images: None,
+            env: None,
+            tools: None,
         };
 
         match send_command_with_response(state, cmd, timeout_secs).await {
@@ -106,7 +124,13 @@ async fn wait_for_sidecar_ready(
 }
 
 fn is_timeout_error(error: &str) -> bool {
-    error.contains("Timeout waiting for response")
+    ErrorCode::ResponseTimeout.matches(error)
+}
+
+/// pi fails `create_session` this way when npm is missing from the sidecar's
+/// PATH and it cannot load configured packages/extensions.
+fn is_npm_unavailable_error(error: &str) -> bool {
+    error.contains("Failed to run npm root -g") || error.contains("npm install -g")
 }
 
 pub async fn send_command_with_response(
@@ -202,7 +226,7 @@ fn extract_session_ids_from_list_response(response: &RpcResponse) -> Vec<String>
     session_ids
 }
 
-fn force_kill_process_by_pid(pid: u32) -> Result<(), String> {
+pub(super) fn force_kill_process_by_pid(pid: u32) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
         let status = std::process::Command::new("taskkill")
@@ -278,6 +302,24 @@ pub async fn shutdown_sidecar_gracefully(state: &Arc<Mutex<SidecarState>>) -> Re
         return Ok(());
     }
 
+    // A suspended sidecar can't answer the shutdown handshake.
+    let suspended_pids = {
+        let mut state_guard = state.lock().await;
+        state_guard
+            .pause
+            .take()
+            .map(|pause| pause.suspended_pids)
+            .unwrap_or_default()
+    };
+    if !suspended_pids.is_empty() {
+        if let Err(error) = resume_processes(&suspended_pids) {
+            logger::log(format!(
+                "Failed to resume sidecar before shutdown: {}",
+                error
+            ));
+        }
+    }
+
     let list_command = RpcCommand {
         id: Some(crypto_random_uuid()),
         r#type: "list_sessions".to_string(),
@@ -290,6 +332,8 @@ pub async fn shutdown_sidecar_gracefully(state: &Arc<Mutex<SidecarState>>) -> Re
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     let list_response =
@@ -321,6 +365,8 @@ pub async fn shutdown_sidecar_gracefully(state: &Arc<Mutex<SidecarState>>) -> Re
             session_file: None,
             level: None,
             images: None,
+            env: None,
+            tools: None,
         };
 
         let _ = send_command_with_response(state, abort_command, SHUTDOWN_ABORT_TIMEOUT_SECS).await;
@@ -338,6 +384,8 @@ pub async fn shutdown_sidecar_gracefully(state: &Arc<Mutex<SidecarState>>) -> Re
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     let shutdown_succeeded =
@@ -350,6 +398,10 @@ pub async fn shutdown_sidecar_gracefully(state: &Arc<Mutex<SidecarState>>) -> Re
         let mut state_guard = state.lock().await;
         state_guard.child.take()
     };
+    let sidecar_pid = match &child_arc {
+        Some(child) => Some(child.lock().await.pid()),
+        None => None,
+    };
 
     let result = if shutdown_succeeded {
         logger::log("sidecar shutdown complete");
@@ -363,12 +415,32 @@ pub async fn shutdown_sidecar_gracefully(state: &Arc<Mutex<SidecarState>>) -> Re
             Err("Sidecar child handle missing during forced kill".to_string())
         }
     };
+    if let Some(sidecar_pid) = sidecar_pid {
+        clear_sidecar_pid(sidecar_pid);
+    }
 
     let mut state_guard = state.lock().await;
     state_guard.child = None;
     state_guard.pending_requests.clear();
     state_guard.response_tx = None;
     state_guard.session_cwds.clear();
+    forget_all_sequences();
+    state_guard.last_prompts.clear();
+    state_guard.pending_model_fallbacks.clear();
+    state_guard.auth_expired_sessions.clear();
+    state_guard.run_errors.clear();
+    state_guard.turn_file_snapshots.clear();
+    state_guard.code_blocks.clear();
+    state_guard.session_states.clear();
+    state_guard.model_fallback_retried.clear();
+    state_guard.pending_pinned_context.clear();
+    state_guard.capabilities = None;
+    state_guard.model_image_support.clear();
+    state_guard.session_last_activity.clear();
+    state_guard.running_sessions.clear();
+    state_guard.prompts_awaiting_start.clear();
+    state_guard.held_prompts.clear();
+    state_guard.queued_prompts.clear();
 
     result
 }
@@ -381,12 +453,15 @@ pub async fn create_session_internal(
     model: Option<String>,
     session_file: Option<String>,
 ) -> Result<RpcResponse, String> {
+    ensure_session_disk_space(&app, &project_dir)?;
     ensure_sidecar_started(&app, state, provider.clone(), model.clone()).await?;
 
     // Keep one internal session id for the full create/retry flow so retries
     // correlate to the same runtime session in the sidecar.
     let requested_session_id = crypto_random_uuid();
     let mut last_error = "Failed to create session".to_string();
+    let env = project_env(&app, &project_dir);
+    let disabled_tools = disabled_tools_for_new_session(&app, &project_dir);
 
     logger::log(format!(
         "create_session requested: project_dir={} session_file={} provider={} model={} requested_session_id={}",
@@ -410,10 +485,12 @@ pub async fn create_session_internal(
             session_file: session_file.clone(),
             level: None,
             images: None,
+            env: env.clone(),
+            tools: disabled_tools.clone(),
         };
 
         match send_command_with_response(state, command, CREATE_SESSION_TIMEOUT_SECS).await {
-            Ok(response) => {
+            Ok(mut response) => {
                 let response_error = response.error.as_deref().unwrap_or("<none>");
                 let response_session_id = response
                     .data
@@ -445,8 +522,27 @@ pub async fn create_session_internal(
                     response_session_file,
                 ));
 
+                if response
+                    .error
+                    .as_deref()
+                    .is_some_and(is_npm_unavailable_error)
+                {
+                    response.error = Some(messages::error(ErrorCode::NpmUnavailable, &[]));
+                }
+
+                let pinned_context = if response.success && response_session_id != "<none>" {
+                    prepare_pinned_context(&app, response_cwd, session_file.is_some())
+                } else {
+                    None
+                };
+
                 let mut state_guard = state.lock().await;
                 cache_session_from_create_response(&mut state_guard, &response);
+                if let Some(pinned_context) = pinned_context {
+                    state_guard
+                        .pending_pinned_context
+                        .insert(response_session_id.to_string(), pinned_context);
+                }
                 return Ok(response);
             }
             Err(error) => {
@@ -492,6 +588,8 @@ pub async fn close_agent(
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     let response = send_command_with_response(state, command, 5).await?;
@@ -499,6 +597,22 @@ pub async fn close_agent(
     if response.success {
         let mut state_guard = state.lock().await;
         state_guard.session_cwds.remove(&session_id);
+        forget_session_sequence(&session_id);
+        state_guard.last_prompts.remove(&session_id);
+        state_guard.pending_model_fallbacks.remove(&session_id);
+        state_guard.auth_expired_sessions.remove(&session_id);
+        state_guard.run_errors.remove(&session_id);
+        state_guard.turn_file_snapshots.remove(&session_id);
+        state_guard.code_blocks.remove(&session_id);
+        state_guard.session_states.remove(&session_id);
+        state_guard.model_fallback_retried.remove(&session_id);
+        state_guard.pending_pinned_context.remove(&session_id);
+        state_guard.session_last_activity.remove(&session_id);
+        state_guard.running_sessions.remove(&session_id);
+        state_guard.prompts_awaiting_start.remove(&session_id);
+        state_guard
+            .queued_prompts
+            .retain(|prompt| prompt.session_id != session_id);
     }
 
     Ok(response)
@@ -533,6 +647,8 @@ pub async fn list_agents(state: &Arc<Mutex<SidecarState>>) -> Result<RpcResponse
         session_file: None,
         level: None,
         images: None,
+        env: None,
+        tools: None,
     };
 
     let response = send_command_with_response(state, command, 5).await?;
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+
+use super::quiet_hours::notifications_suppressed;
+use super::turn_guard::retry_last_prompt;
+use crate::logger;
+use crate::platform::windows_toast::{show_toast, ToastAction};
+use crate::sidecar::RpcClient;
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::crypto_random_uuid;
+
+const MAIN_WINDOW_LABEL: &str = "main";
+
+fn main_window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window(MAIN_WINDOW_LABEL)
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false)
+}
+
+fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+async fn abort_session(state: &Arc<Mutex<SidecarState>>, session_id: &str) -> Result<(), String> {
+    let command = RpcCommand {
+        id: Some(crypto_random_uuid()),
+        r#type: "abort".to_string(),
+        session_id: Some(session_id.to_string()),
+        cwd: None,
+        message: None,
+        provider: None,
+        model_id: None,
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    };
+
+    RpcClient::send_command(state, command).await
+}
+
+/// Map a toast button argument (`<action>:<sessionId>`) to the matching command.
+///
+/// `show` focuses the window and emits `toast-action` so the frontend can
+/// select the session; `retry` resubmits the last prompt; `abort` stops the run.
+async fn handle_toast_action(app: AppHandle, argument: String) {
+    let Some((action, session_id)) = argument.split_once(':') else {
+        return;
+    };
+
+    let state = app.state::<Arc<Mutex<SidecarState>>>().inner().clone();
+    let result = match action {
+        "show" => {
+            focus_main_window(&app);
+            Ok(())
+        }
+        "retry" => {
+            focus_main_window(&app);
+            retry_last_prompt(&app, &state, session_id).await
+        }
+        "abort" => abort_session(&state, session_id).await,
+        other => Err(format!("Unknown toast action: {}", other)),
+    };
+
+    if let Err(error) = &result {
+        logger::log(format!(
+            "Toast action {} for session {} failed: {}",
+            action, session_id, error
+        ));
+    }
+
+    let _ = app.emit(
+        "toast-action",
+        serde_json::json!({
+            "action": action,
+            "sessionId": session_id,
+            "error": result.err(),
+        }),
+    );
+}
+
+fn show_session_toast(
+    app: &AppHandle,
+    session_id: &str,
+    title: &str,
+    body: &str,
+    actions: &[(&str, &str)],
+) {
+    if main_window_focused(app) {
+        return;
+    }
+    if notifications_suppressed() {
+        logger::log(format!(
+            "Suppressed notification for session {} (quiet hours or snooze)",
+            session_id
+        ));
+        return;
+    }
+
+    let actions = actions
+        .iter()
+        .map(|(label, action)| ToastAction {
+            label: label.to_string(),
+            argument: format!("{}:{}", action, session_id),
+        })
+        .collect::<Vec<_>>();
+
+    let handler_app = app.clone();
+    let result = show_toast(
+        &app.config().identifier,
+        title,
+        body,
+        &format!("show:{}", session_id),
+        &actions,
+        move |argument| {
+            let app = handler_app.clone();
+            tauri::async_runtime::spawn(handle_toast_action(app, argument));
+        },
+    );
+
+    if let Err(error) = result {
+        logger::log(format!(
+            "Failed to show notification for session {}: {}",
+            session_id, error
+        ));
+    }
+}
+
+/// Toast for a run that finished cleanly while the window was in the background.
+pub(crate) fn notify_run_finished(app: &AppHandle, session_id: &str) {
+    show_session_toast(
+        app,
+        session_id,
+        "Agent run finished",
+        "The agent is waiting for your next prompt.",
+        &[("Show", "show")],
+    );
+}
+
+/// Toast for a failed assistant message. The run may still be going (pi
+/// retries transient errors), so Abort is offered alongside Retry.
+pub(crate) fn notify_run_error(app: &AppHandle, session_id: &str, error: &str) {
+    show_session_toast(
+        app,
+        session_id,
+        "Agent run failed",
+        error,
+        &[("Show", "show"), ("Retry", "retry"), ("Abort", "abort")],
+    );
+}
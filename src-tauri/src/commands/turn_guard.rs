@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::pause::send_or_hold_prompt;
+use super::provider_queue::has_queued_prompt;
+use crate::messages::{self, ErrorCode};
+use crate::state::SidecarState;
+use crate::utils::crypto_random_uuid;
+
+/// How long a sent prompt counts as in progress without an `agent_start`.
+///
+/// The host acknowledges `prompt` before the run starts and only logs a run
+/// that fails early, so an unacknowledged prompt must not block forever.
+const AWAITING_START_GRACE: Duration = Duration::from_secs(30);
+
+/// Reserve the session for a new prompt, or fail with a `turn-in-progress` error
+/// when a run is active or a previous prompt hasn't started (or been sent) yet.
+pub(crate) async fn begin_turn(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+) -> Result<(), String> {
+    let mut state_guard = state.lock().await;
+
+    let awaiting_start = state_guard
+        .prompts_awaiting_start
+        .get(session_id)
+        .is_some_and(|sent_at| sent_at.elapsed() < AWAITING_START_GRACE);
+
+    if awaiting_start
+        || state_guard.running_sessions.contains(session_id)
+        || has_queued_prompt(&state_guard, session_id)
+    {
+        return Err(messages::error(ErrorCode::TurnInProgress, &[&session_id]));
+    }
+
+    state_guard
+        .prompts_awaiting_start
+        .insert(session_id.to_string(), Instant::now());
+    Ok(())
+}
+
+/// Release a reservation whose prompt never reached the sidecar.
+pub(crate) async fn cancel_turn(state: &Arc<Mutex<SidecarState>>, session_id: &str) {
+    state.lock().await.prompts_awaiting_start.remove(session_id);
+}
+
+/// Resubmit the session's last prompt as a new turn.
+pub(crate) async fn retry_last_prompt(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+) -> Result<(), String> {
+    let mut prompt = state
+        .lock()
+        .await
+        .last_prompts
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| format!("No prompt recorded to retry for {}", session_id))?;
+    prompt.id = Some(crypto_random_uuid());
+
+    begin_turn(state, session_id).await?;
+    if let Err(error) = send_or_hold_prompt(app, state, prompt, None).await {
+        cancel_turn(state, session_id).await;
+        return Err(error);
+    }
+
+    Ok(())
+}
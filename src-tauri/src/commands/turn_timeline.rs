@@ -0,0 +1,321 @@
+//! Post-hoc timeline of one turn, rebuilt from the session file, for
+//! inspecting a turn that was not watched live.
+//!
+//! A turn is one assistant message plus the tool results that answer it, as
+//! in `turn_summary` and the activity stats. The file holds complete
+//! messages, so streamed deltas are already collapsed.
+
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use super::session_scopes::{extract_text_from_message_content, parse_session_timestamp};
+use crate::sidecar::FILE_WRITING_TOOLS;
+use crate::state::SidecarState;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TimelineItem {
+    Text {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        at: Option<i64>,
+        text: String,
+    },
+    /// Only the size of the reasoning, not its text.
+    Thinking {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        at: Option<i64>,
+        chars: usize,
+    },
+    ToolCall {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        at: Option<i64>,
+        tool_call_id: String,
+        tool_name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        /// From the assistant message being written to its result being
+        /// written; approximate for calls that ran in parallel.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<u64>,
+        /// `None` when the call has no result in the file (interrupted).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        is_error: Option<bool>,
+    },
+    Error {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        at: Option<i64>,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnTimeline {
+    pub session_id: String,
+    pub session_file: String,
+    /// 0-based index of the turn among the session's turns.
+    pub turn: usize,
+    pub turn_count: usize,
+    /// The user prompt the turn answers, when one precedes it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// First to last entry of the turn (unix millis).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<i64>,
+    pub items: Vec<TimelineItem>,
+    /// Files modified by edit/write tools, as given to the tool.
+    pub files_touched: Vec<String>,
+    pub total_tokens: u64,
+}
+
+/// A session file `message` entry with the time it was appended.
+struct MessageEntry {
+    at: Option<i64>,
+    message: serde_json::Value,
+}
+
+fn read_message_entries(path: &Path) -> Result<Vec<MessageEntry>, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|error| format!("Failed to open {}: {}", path.display(), error))?;
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+        let Ok(mut entry) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        if entry.get("type").and_then(|value| value.as_str()) != Some("message") {
+            continue;
+        }
+
+        let at = entry
+            .get("timestamp")
+            .and_then(|value| value.as_str())
+            .and_then(parse_session_timestamp)
+            .map(|timestamp| timestamp.timestamp_millis());
+        if let Some(message) = entry.get_mut("message").map(serde_json::Value::take) {
+            entries.push(MessageEntry { at, message });
+        }
+    }
+
+    Ok(entries)
+}
+
+fn role(message: &serde_json::Value) -> Option<&str> {
+    message.get("role").and_then(|value| value.as_str())
+}
+
+fn str_field<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(|value| value.as_str())
+}
+
+fn path_arg(arguments: Option<&serde_json::Value>) -> Option<String> {
+    let arguments = arguments?;
+    ["path", "file_path", "filePath"]
+        .iter()
+        .find_map(|key| str_field(arguments, key))
+        .map(str::to_string)
+}
+
+fn build_timeline(
+    session_id: String,
+    session_file: String,
+    entries: &[MessageEntry],
+    turn: Option<usize>,
+) -> Result<TurnTimeline, String> {
+    let assistant_indices = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| role(&entry.message) == Some("assistant"))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let turn_count = assistant_indices.len();
+    if turn_count == 0 {
+        return Err("Session has no turns yet".to_string());
+    }
+
+    let turn = turn.unwrap_or(turn_count - 1);
+    let Some(&start) = assistant_indices.get(turn) else {
+        return Err(format!(
+            "Turn {} does not exist (session has {} turns)",
+            turn, turn_count
+        ));
+    };
+
+    let assistant = &entries[start];
+    let results = entries[start + 1..]
+        .iter()
+        .take_while(|entry| role(&entry.message) == Some("toolResult"))
+        .collect::<Vec<_>>();
+    let prompt = entries[..start]
+        .iter()
+        .rev()
+        .find(|entry| role(&entry.message) == Some("user"))
+        .and_then(|entry| entry.message.get("content"))
+        .and_then(extract_text_from_message_content);
+
+    let message = &assistant.message;
+    let message_at = message.get("timestamp").and_then(|value| value.as_i64());
+    let item_at = message_at.or(assistant.at);
+
+    let mut items = Vec::new();
+    let mut files_touched = BTreeSet::new();
+    let blocks = message
+        .get("content")
+        .and_then(|content| content.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for block in blocks {
+        match str_field(block, "type") {
+            Some("text") => {
+                let text = str_field(block, "text").unwrap_or_default().trim();
+                if !text.is_empty() {
+                    items.push(TimelineItem::Text {
+                        at: item_at,
+                        text: text.to_string(),
+                    });
+                }
+            }
+            Some("thinking") => {
+                let chars = str_field(block, "thinking")
+                    .map(|thinking| thinking.chars().count())
+                    .unwrap_or(0);
+                if chars > 0 {
+                    items.push(TimelineItem::Thinking { at: item_at, chars });
+                }
+            }
+            Some("toolCall") => {
+                let tool_call_id = str_field(block, "id").unwrap_or_default().to_string();
+                let tool_name = str_field(block, "name").unwrap_or("unknown").to_string();
+                let path = path_arg(block.get("arguments"));
+                if let Some(path) = path
+                    .as_ref()
+                    .filter(|_| FILE_WRITING_TOOLS.contains(&tool_name.as_str()))
+                {
+                    files_touched.insert(path.clone());
+                }
+
+                let result = results.iter().find(|result| {
+                    str_field(&result.message, "toolCallId") == Some(tool_call_id.as_str())
+                });
+                let duration_ms = result.and_then(|result| {
+                    let ended = result.at?;
+                    let started = assistant.at?;
+                    u64::try_from(ended - started).ok()
+                });
+
+                items.push(TimelineItem::ToolCall {
+                    at: assistant.at,
+                    tool_call_id,
+                    tool_name,
+                    path,
+                    duration_ms,
+                    is_error: result.map(|result| {
+                        result
+                            .message
+                            .get("isError")
+                            .and_then(|value| value.as_bool())
+                            == Some(true)
+                    }),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if str_field(message, "stopReason") == Some("error") {
+        items.push(TimelineItem::Error {
+            at: assistant.at,
+            message: str_field(message, "errorMessage")
+                .unwrap_or("Assistant message failed")
+                .to_string(),
+        });
+    }
+    for result in &results {
+        if result
+            .message
+            .get("isError")
+            .and_then(|value| value.as_bool())
+            != Some(true)
+        {
+            continue;
+        }
+        let tool_name = str_field(&result.message, "toolName").unwrap_or("tool");
+        let detail = result
+            .message
+            .get("content")
+            .and_then(extract_text_from_message_content)
+            .and_then(|text| text.lines().next().map(str::to_string));
+        items.push(TimelineItem::Error {
+            at: result.at,
+            message: match detail {
+                Some(detail) => format!("{} failed: {}", tool_name, detail),
+                None => format!("{} failed", tool_name),
+            },
+        });
+    }
+
+    let ended_at = results
+        .last()
+        .map_or(assistant.at, |result| result.at.or(assistant.at));
+    let model = match (str_field(message, "provider"), str_field(message, "model")) {
+        (Some(provider), Some(model)) => Some(format!("{}/{}", provider, model)),
+        (None, Some(model)) => Some(model.to_string()),
+        _ => None,
+    };
+
+    Ok(TurnTimeline {
+        session_id,
+        session_file,
+        turn,
+        turn_count,
+        prompt,
+        model,
+        started_at: message_at.or(assistant.at),
+        ended_at,
+        items,
+        files_touched: files_touched.into_iter().collect(),
+        total_tokens: message
+            .get("usage")
+            .and_then(|usage| usage.get("totalTokens"))
+            .and_then(|value| value.as_u64())
+            .unwrap_or(0),
+    })
+}
+
+/// Timeline of turn `turn` (the latest when unset) of an open session.
+pub async fn get_turn_timeline(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: String,
+    turn: Option<usize>,
+) -> Result<TurnTimeline, String> {
+    let snapshot = crate::sidecar::session_state_snapshot(state, &session_id, false).await?;
+    let session_file = match snapshot.session_file {
+        Some(session_file) => session_file,
+        // Cached before the host knew the file; ask again.
+        None => crate::sidecar::session_state_snapshot(state, &session_id, true)
+            .await?
+            .session_file
+            .ok_or_else(|| "Session has no session file (in-memory session)".to_string())?,
+    };
+
+    let path = PathBuf::from(&session_file);
+    let entries = tokio::task::spawn_blocking(move || read_message_entries(&path))
+        .await
+        .map_err(|error| format!("Failed to read session file: {}", error))??;
+
+    build_timeline(session_id, session_file, &entries, turn)
+}
@@ -0,0 +1,495 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use glob::{MatchOptions, Pattern};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use super::action_sequence::render_template;
+use super::project_metadata::{
+    enabled_watch_rules, load_project_metadata, project_key, update_project_metadata,
+};
+use super::provider_queue::has_queued_prompt;
+use super::quiet_hours::in_quiet_hours;
+use super::workspace_trust::ensure_project_trusted;
+use super::{dispatch_prompt, turn_guard};
+use crate::logger;
+use crate::state::SidecarState;
+use crate::utils::crypto_random_uuid;
+
+/// How often file events are drained and debounce windows checked.
+const WATCH_TICK_INTERVAL: Duration = Duration::from_millis(500);
+const DEFAULT_DEBOUNCE_MS: u64 = 1_500;
+/// Changes arriving this soon after the target session was last seen busy
+/// are taken to be the agent's own edits, whose events can lag the run.
+const AGENT_EDIT_GRACE: Duration = Duration::from_secs(3);
+const SKIPPED_DIRS: [&str; 6] = [".git", ".pi", "node_modules", "target", "dist", "build"];
+
+/// Send a templated prompt to a session when files matching `glob` change.
+///
+/// The template can use `{{files}}` (changed paths, one per line, relative to
+/// the project) and `{{projectDir}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchRule {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    /// Glob relative to the project root, e.g. `tests/**/*.rs`.
+    pub glob: String,
+    /// Quiet period after the last change before the prompt is sent.
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+    /// Session the prompt goes to; the rule idles while it is not open.
+    pub session_id: String,
+    pub template: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_debounce_ms() -> u64 {
+    DEFAULT_DEBOUNCE_MS
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchRulesResponse {
+    pub project_dir: String,
+    pub rules: Vec<WatchRule>,
+}
+
+fn match_options() -> MatchOptions {
+    MatchOptions {
+        case_sensitive: !cfg!(windows),
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    }
+}
+
+fn compile_glob(glob: &str) -> Result<Pattern, String> {
+    Pattern::new(glob.trim()).map_err(|error| format!("Invalid glob '{}': {}", glob, error))
+}
+
+pub fn list_watch_rules(
+    app: &AppHandle,
+    project_dir: String,
+) -> Result<WatchRulesResponse, String> {
+    let key = project_key(&project_dir)?;
+    let metadata = load_project_metadata(app, &key)?;
+
+    Ok(WatchRulesResponse {
+        project_dir: key,
+        rules: metadata.watch_rules,
+    })
+}
+
+/// Create a rule (empty `id`) or replace the rule with the same id.
+pub fn save_watch_rule(
+    app: &AppHandle,
+    project_dir: String,
+    rule: WatchRule,
+) -> Result<WatchRulesResponse, String> {
+    let key = project_key(&project_dir)?;
+
+    compile_glob(&rule.glob)?;
+    if rule.session_id.trim().is_empty() {
+        return Err("Watch rule needs a sessionId".to_string());
+    }
+    if rule.template.trim().is_empty() {
+        return Err("Watch rule template cannot be empty".to_string());
+    }
+
+    let rule = WatchRule {
+        id: match rule.id.trim() {
+            "" => crypto_random_uuid(),
+            id => id.to_string(),
+        },
+        name: rule.name.trim().to_string(),
+        glob: rule.glob.trim().to_string(),
+        debounce_ms: rule.debounce_ms,
+        session_id: rule.session_id.trim().to_string(),
+        template: rule.template,
+        enabled: rule.enabled,
+    };
+
+    let (metadata, _) = update_project_metadata(app, &key, |metadata| {
+        match metadata
+            .watch_rules
+            .iter_mut()
+            .find(|existing| existing.id == rule.id)
+        {
+            Some(existing) => *existing = rule,
+            None => metadata.watch_rules.push(rule),
+        }
+        Ok(())
+    })?;
+
+    Ok(WatchRulesResponse {
+        project_dir: key,
+        rules: metadata.watch_rules,
+    })
+}
+
+pub fn delete_watch_rule(
+    app: &AppHandle,
+    project_dir: String,
+    rule_id: String,
+) -> Result<WatchRulesResponse, String> {
+    let key = project_key(&project_dir)?;
+
+    let (metadata, _) = update_project_metadata(app, &key, |metadata| {
+        let before = metadata.watch_rules.len();
+        metadata.watch_rules.retain(|rule| rule.id != rule_id);
+        if metadata.watch_rules.len() == before {
+            return Err(format!("Unknown watch rule: {}", rule_id));
+        }
+        Ok(())
+    })?;
+
+    Ok(WatchRulesResponse {
+        project_dir: key,
+        rules: metadata.watch_rules,
+    })
+}
+
+pub fn set_watch_rule_enabled(
+    app: &AppHandle,
+    project_dir: String,
+    rule_id: String,
+    enabled: bool,
+) -> Result<WatchRulesResponse, String> {
+    let key = project_key(&project_dir)?;
+
+    let (metadata, _) = update_project_metadata(app, &key, |metadata| {
+        let rule = metadata
+            .watch_rules
+            .iter_mut()
+            .find(|rule| rule.id == rule_id)
+            .ok_or_else(|| format!("Unknown watch rule: {}", rule_id))?;
+        rule.enabled = enabled;
+        Ok(())
+    })?;
+
+    Ok(WatchRulesResponse {
+        project_dir: key,
+        rules: metadata.watch_rules,
+    })
+}
+
+/// The project's `.gitignore` and `.git/info/exclude` rules.
+fn load_gitignore(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for file in [root.join(".gitignore"), root.join(".git/info/exclude")] {
+        if file.is_file() {
+            if let Some(error) = builder.add(&file) {
+                logger::log(format!("Ignoring part of {}: {}", file.display(), error));
+            }
+        }
+    }
+    builder.build().unwrap_or_else(|error| {
+        logger::log(format!(
+            "Failed to load ignore rules for {}: {}",
+            root.display(),
+            error
+        ));
+        Gitignore::empty()
+    })
+}
+
+/// `path` relative to the project with `/` separators, or `None` when it is
+/// outside the project, in a skipped directory or ignored by git.
+fn watched_relative_path(root: &Path, gitignore: &Gitignore, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let skipped = relative
+        .components()
+        .any(|component| SKIPPED_DIRS.contains(&component.as_os_str().to_string_lossy().as_ref()));
+    if skipped
+        || relative.as_os_str().is_empty()
+        || path.is_dir()
+        || gitignore
+            .matched_path_or_any_parents(relative, false)
+            .is_ignore()
+    {
+        return None;
+    }
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Changes collected for a rule while its debounce window is open.
+struct PendingTrigger {
+    files: BTreeSet<String>,
+    last_change: Instant,
+}
+
+/// A recursive file watcher on one project and the triggers it has collected.
+struct ProjectWatch {
+    root: PathBuf,
+    gitignore: Gitignore,
+    /// Dropping the watcher stops the events.
+    _watcher: RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    pending: HashMap<String, PendingTrigger>,
+    /// When each target session was last seen busy.
+    busy_at: HashMap<String, Instant>,
+}
+
+impl ProjectWatch {
+    fn start(project_dir: &str) -> Result<Self, String> {
+        // Events report canonical paths (e.g. through symlinked temp dirs).
+        let root = std::fs::canonicalize(project_dir)
+            .map_err(|error| format!("Failed to resolve {}: {}", project_dir, error))?;
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|error| format!("Failed to create file watcher: {}", error))?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|error| format!("Failed to watch {}: {}", root.display(), error))?;
+
+        Ok(Self {
+            gitignore: load_gitignore(&root),
+            root,
+            _watcher: watcher,
+            events,
+            pending: HashMap::new(),
+            busy_at: HashMap::new(),
+        })
+    }
+
+    /// Project-relative paths changed since the last call.
+    fn drain_changes(&mut self) -> BTreeSet<String> {
+        let mut changed = BTreeSet::new();
+        let mut gitignore_changed = false;
+        while let Ok(event) = self.events.try_recv() {
+            let event = match event {
+                Ok(event) if !event.kind.is_access() && !event.kind.is_other() => event,
+                Ok(_) => continue,
+                Err(error) => {
+                    logger::log(format!(
+                        "File watcher error for {}: {}",
+                        self.root.display(),
+                        error
+                    ));
+                    continue;
+                }
+            };
+            for path in &event.paths {
+                gitignore_changed |= path == &self.root.join(".gitignore");
+                if let Some(relative) = watched_relative_path(&self.root, &self.gitignore, path) {
+                    changed.insert(relative);
+                }
+            }
+        }
+        if gitignore_changed {
+            self.gitignore = load_gitignore(&self.root);
+        }
+        changed
+    }
+}
+
+fn emit_watch_rule_event(
+    app: &AppHandle,
+    project_dir: &str,
+    rule: &WatchRule,
+    files: &[String],
+    error: Option<&str>,
+) {
+    let payload = serde_json::json!({
+        "projectDir": project_dir,
+        "ruleId": rule.id,
+        "sessionId": rule.session_id,
+        "files": files,
+        "error": error,
+    });
+
+    if let Err(error) = app.emit("watch-rule-triggered", &payload) {
+        logger::log(format!(
+            "Failed to emit watch-rule-triggered event: {}",
+            error
+        ));
+    }
+}
+
+async fn trigger_rule(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: &str,
+    rule: &WatchRule,
+    files: Vec<String>,
+) {
+    let variables = HashMap::from([
+        ("files".to_string(), files.join("\n")),
+        ("projectDir".to_string(), project_dir.to_string()),
+    ]);
+
+    let result: Result<(), String> = async {
+        ensure_project_trusted(app, project_dir)?;
+        let prompt = render_template(&rule.template, &variables)?;
+        turn_guard::begin_turn(state, &rule.session_id).await?;
+        if let Err(error) = dispatch_prompt(app, state, prompt, &rule.session_id, None).await {
+            turn_guard::cancel_turn(state, &rule.session_id).await;
+            return Err(error);
+        }
+        Ok(())
+    }
+    .await;
+
+    match &result {
+        Ok(()) => logger::log(format!(
+            "Watch rule {} sent a prompt to session {} for {} changed file(s)",
+            rule.id,
+            rule.session_id,
+            files.len()
+        )),
+        Err(error) => logger::log(format!("Watch rule {} failed: {}", rule.id, error)),
+    }
+    emit_watch_rule_event(app, project_dir, rule, &files, result.err().as_deref());
+}
+
+/// Whether the rule's session is open and idle. Changes seen while it is busy,
+/// or within `AGENT_EDIT_GRACE` after, are ignored, so a rule never
+/// re-triggers on the agent's own edits.
+async fn session_ready(state: &Arc<Mutex<SidecarState>>, session_id: &str) -> bool {
+    let state_guard = state.lock().await;
+    state_guard.session_cwds.contains_key(session_id)
+        && !state_guard.running_sessions.contains(session_id)
+        && !state_guard.prompts_awaiting_start.contains_key(session_id)
+        && !has_queued_prompt(&state_guard, session_id)
+}
+
+async fn poll_project(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: &str,
+    rules: &[WatchRule],
+    watch: &mut ProjectWatch,
+) {
+    // Check the sessions before draining, so an edit made just before a run
+    // ended still falls in the grace window.
+    let now = Instant::now();
+    for rule in rules {
+        if !session_ready(state, &rule.session_id).await {
+            watch.busy_at.insert(rule.session_id.clone(), now);
+        }
+    }
+    watch
+        .busy_at
+        .retain(|_, busy_at| now.duration_since(*busy_at) < AGENT_EDIT_GRACE);
+
+    let changed = watch.drain_changes();
+
+    for rule in rules {
+        let Ok(pattern) = compile_glob(&rule.glob) else {
+            continue;
+        };
+
+        let matching = changed
+            .iter()
+            .filter(|path| pattern.matches_with(path, match_options()))
+            .cloned()
+            .collect::<Vec<_>>();
+        if matching.is_empty() {
+            continue;
+        }
+
+        if watch.busy_at.contains_key(&rule.session_id) {
+            watch.pending.remove(&rule.id);
+            continue;
+        }
+
+        let pending = watch
+            .pending
+            .entry(rule.id.clone())
+            .or_insert_with(|| PendingTrigger {
+                files: BTreeSet::new(),
+                last_change: now,
+            });
+        pending.files.extend(matching);
+        pending.last_change = now;
+    }
+
+    watch
+        .pending
+        .retain(|rule_id, _| rules.iter().any(|rule| rule.id == *rule_id));
+
+    // Triggers stay pending through quiet hours and fire once they end.
+    if in_quiet_hours() {
+        return;
+    }
+
+    for rule in rules {
+        let due = watch.pending.get(&rule.id).is_some_and(|pending| {
+            now.duration_since(pending.last_change) >= Duration::from_millis(rule.debounce_ms)
+        });
+        if !due {
+            continue;
+        }
+
+        if let Some(pending) = watch.pending.remove(&rule.id) {
+            trigger_rule(
+                app,
+                state,
+                project_dir,
+                rule,
+                pending.files.into_iter().collect(),
+            )
+            .await;
+        }
+    }
+}
+
+/// Watch projects with enabled watch rules for file changes (skipping files
+/// ignored by git) and fire the matching rules once their debounce window has
+/// passed.
+///
+/// Rules are re-read from the project metadata on every pass, so saving,
+/// deleting or toggling a rule takes effect without a restart.
+pub(crate) fn spawn_watch_rule_runner(app: AppHandle, state: Arc<Mutex<SidecarState>>) {
+    tauri::async_runtime::spawn(async move {
+        let mut watches = HashMap::<String, ProjectWatch>::new();
+        // Projects whose watcher could not start; retried after their rules are
+        // disabled and enabled again.
+        let mut failed = BTreeSet::<String>::new();
+
+        loop {
+            tokio::time::sleep(WATCH_TICK_INTERVAL).await;
+
+            let projects = enabled_watch_rules(&app);
+            watches.retain(|project_dir, _| projects.iter().any(|(dir, _)| dir == project_dir));
+            failed.retain(|project_dir| projects.iter().any(|(dir, _)| dir == project_dir));
+
+            for (project_dir, rules) in projects {
+                if !watches.contains_key(&project_dir) {
+                    if failed.contains(&project_dir) {
+                        continue;
+                    }
+                    match ProjectWatch::start(&project_dir) {
+                        Ok(watch) => {
+                            watches.insert(project_dir.clone(), watch);
+                        }
+                        Err(error) => {
+                            logger::log(format!(
+                                "Watch rules for {} are idle: {}",
+                                project_dir, error
+                            ));
+                            failed.insert(project_dir);
+                            continue;
+                        }
+                    }
+                }
+                if let Some(watch) = watches.get_mut(&project_dir) {
+                    poll_project(&app, &state, &project_dir, &rules, watch).await;
+                }
+            }
+        }
+    });
+}
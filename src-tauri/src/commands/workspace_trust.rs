@@ -0,0 +1,297 @@
+//! Workspace trust. A project is untrusted from the first time it is opened
+//! until `trust_project` is called for it: its sessions start without the
+//! tools that change files or run commands, and prompts nobody typed (watch
+//! rules, git-hook reviews) are refused.
+//!
+//! Projects that already had pi sessions when trust was introduced were
+//! worked in before it existed; `migrate_workspace_trust` records them as
+//! trusted once.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Mutex;
+
+use super::project_metadata::project_key;
+use super::session_scopes::user_session_history_scopes;
+use super::settings::get_project_tools;
+use super::sidecar_lifecycle::send_command_with_response;
+use crate::instance_lock;
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::state::SidecarState;
+use crate::types::RpcCommand;
+use crate::utils::{crypto_random_uuid, now_millis};
+
+const WORKSPACE_TRUST_STORE_FILE: &str = "workspace-trust.json";
+/// Set once `migrate_workspace_trust` has run; no project key starts with `@`.
+const MIGRATED_KEY: &str = "@sessionHistoryMigrated";
+const SET_SESSION_PROFILE_TIMEOUT_SECS: u64 = 5;
+/// Deactivated in sessions of untrusted projects; what remains can only read.
+const RESTRICTED_TOOLS: [&str; 3] = ["bash", "edit", "write"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct TrustEntry {
+    first_opened_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trusted_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTrust {
+    pub project_dir: String,
+    pub trusted: bool,
+    /// When graphone first opened the project; unset if it never has.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_opened_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trusted_at: Option<u64>,
+    /// Tools deactivated in the project's sessions while it is untrusted.
+    pub restricted_tools: Vec<String>,
+}
+
+fn restricted_tools() -> Vec<String> {
+    RESTRICTED_TOOLS
+        .iter()
+        .map(|tool| tool.to_string())
+        .collect()
+}
+
+fn load_entry(app: &AppHandle, key: &str) -> Result<Option<TrustEntry>, String> {
+    let store = app
+        .store(WORKSPACE_TRUST_STORE_FILE)
+        .map_err(|error| format!("Failed to open workspace trust store: {}", error))?;
+
+    Ok(store
+        .get(key)
+        .and_then(|value| serde_json::from_value::<TrustEntry>(value).ok()))
+}
+
+fn save_entry(app: &AppHandle, key: &str, entry: &TrustEntry) -> Result<(), String> {
+    instance_lock::ensure_primary()?;
+
+    let value = serde_json::to_value(entry)
+        .map_err(|error| format!("Failed to serialize workspace trust: {}", error))?;
+
+    let store = app
+        .store(WORKSPACE_TRUST_STORE_FILE)
+        .map_err(|error| format!("Failed to open workspace trust store: {}", error))?;
+    store.set(key, value);
+    store
+        .save()
+        .map_err(|error| format!("Failed to save workspace trust store: {}", error))
+}
+
+/// Trust every project with sessions in the user's session roots, once: on
+/// the first launch with workspace trust. Projects opened afterwards start
+/// untrusted even if they gain history, including from restricted sessions.
+pub(crate) fn migrate_workspace_trust(app: &AppHandle) -> Result<(), String> {
+    instance_lock::ensure_primary()?;
+
+    let store = app
+        .store(WORKSPACE_TRUST_STORE_FILE)
+        .map_err(|error| format!("Failed to open workspace trust store: {}", error))?;
+    if store.has(MIGRATED_KEY) {
+        return Ok(());
+    }
+
+    let now = now_millis();
+    let mut trusted = 0;
+    for key in user_session_history_scopes() {
+        if store.has(&key) {
+            continue;
+        }
+        let entry = TrustEntry {
+            first_opened_at: now,
+            trusted_at: Some(now),
+        };
+        let value = serde_json::to_value(&entry)
+            .map_err(|error| format!("Failed to serialize workspace trust: {}", error))?;
+        store.set(key, value);
+        trusted += 1;
+    }
+    store.set(MIGRATED_KEY, serde_json::json!(now));
+    store
+        .save()
+        .map_err(|error| format!("Failed to save workspace trust store: {}", error))?;
+
+    logger::log(format!(
+        "Trusted {} projects with sessions from before workspace trust",
+        trusted
+    ));
+    Ok(())
+}
+
+fn is_trusted(app: &AppHandle, project_dir: &str) -> bool {
+    project_key(project_dir)
+        .and_then(|key| load_entry(app, &key))
+        .ok()
+        .flatten()
+        .is_some_and(|entry| entry.trusted_at.is_some())
+}
+
+/// Fail with `project-untrusted` unless the project has been trusted.
+pub(crate) fn ensure_project_trusted(app: &AppHandle, project_dir: &str) -> Result<(), String> {
+    if is_trusted(app, project_dir) {
+        Ok(())
+    } else {
+        Err(messages::error(
+            ErrorCode::ProjectUntrusted,
+            &[&project_dir],
+        ))
+    }
+}
+
+/// Tools to deactivate in a session opening in `project_dir`, or `None` when
+/// the project is trusted. Records the first open of an unknown project.
+pub(super) fn disabled_tools_for_new_session(
+    app: &AppHandle,
+    project_dir: &str,
+) -> Option<Vec<String>> {
+    let key = project_key(project_dir).ok()?;
+    match load_entry(app, &key) {
+        Ok(Some(entry)) if entry.trusted_at.is_some() => None,
+        Ok(Some(_)) => Some(restricted_tools()),
+        Ok(None) => {
+            let entry = TrustEntry {
+                first_opened_at: now_millis(),
+                trusted_at: None,
+            };
+            match save_entry(app, &key, &entry) {
+                Ok(()) => logger::log(format!(
+                    "Opened {} for the first time; untrusted until trust_project",
+                    project_dir
+                )),
+                Err(error) => logger::log(format!(
+                    "Failed to record first open of {}: {}",
+                    project_dir, error
+                )),
+            }
+            Some(restricted_tools())
+        }
+        Err(error) => {
+            logger::log(format!("Treating {} as untrusted: {}", project_dir, error));
+            Some(restricted_tools())
+        }
+    }
+}
+
+/// `tools` plus the tools the project's own settings disable, and the
+/// restricted tools when the session's project is untrusted, so a profile
+/// cannot re-enable any of them.
+pub(super) async fn restrict_session_tools(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    tools: Option<Vec<String>>,
+) -> Option<Vec<String>> {
+    let mut tools = tools?;
+    let cwd = state.lock().await.session_cwds.get(session_id).cloned();
+
+    let mut also_disabled = cwd
+        .as_ref()
+        .and_then(|cwd| get_project_tools(cwd.clone()).ok())
+        .map(|response| response.disabled_tools)
+        .unwrap_or_default();
+    if !cwd.is_some_and(|cwd| is_trusted(app, &cwd)) {
+        also_disabled.extend(restricted_tools());
+    }
+    for tool in also_disabled {
+        if !tools.contains(&tool) {
+            tools.push(tool);
+        }
+    }
+    Some(tools)
+}
+
+pub fn get_project_trust(app: &AppHandle, project_dir: String) -> Result<ProjectTrust, String> {
+    let key = project_key(&project_dir)?;
+    let entry = load_entry(app, &key)?;
+    let trusted = entry
+        .as_ref()
+        .is_some_and(|entry| entry.trusted_at.is_some());
+
+    Ok(ProjectTrust {
+        project_dir: project_dir.trim().to_string(),
+        trusted,
+        first_opened_at: entry.as_ref().map(|entry| entry.first_opened_at),
+        trusted_at: entry.and_then(|entry| entry.trusted_at),
+        restricted_tools: if trusted {
+            Vec::new()
+        } else {
+            restricted_tools()
+        },
+    })
+}
+
+/// Trust a project and give its open sessions their full tool set back
+/// (minus the project's own `disabledTools`).
+pub async fn trust_project(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    project_dir: String,
+) -> Result<ProjectTrust, String> {
+    let key = project_key(&project_dir)?;
+    let now = now_millis();
+    let mut entry = load_entry(app, &key)?.unwrap_or(TrustEntry {
+        first_opened_at: now,
+        trusted_at: None,
+    });
+    if entry.trusted_at.is_none() {
+        entry.trusted_at = Some(now);
+        save_entry(app, &key, &entry)?;
+        logger::log(format!("Trusted project {}", project_dir.trim()));
+    }
+
+    let session_ids = state
+        .lock()
+        .await
+        .session_cwds
+        .iter()
+        .filter(|(_, cwd)| project_key(cwd).is_ok_and(|cwd_key| cwd_key == key))
+        .map(|(session_id, _)| session_id.clone())
+        .collect::<Vec<_>>();
+    let disabled_tools = get_project_tools(project_dir.clone())
+        .map(|response| response.disabled_tools)
+        .unwrap_or_default();
+
+    for session_id in session_ids {
+        let command = RpcCommand {
+            id: Some(crypto_random_uuid()),
+            r#type: "set_session_profile".to_string(),
+            session_id: Some(session_id.clone()),
+            cwd: None,
+            message: None,
+            provider: None,
+            model_id: None,
+            streaming_behavior: None,
+            session_file: None,
+            level: None,
+            images: None,
+            env: None,
+            tools: Some(disabled_tools.clone()),
+        };
+
+        let result =
+            send_command_with_response(state, command, SET_SESSION_PROFILE_TIMEOUT_SECS).await;
+        let error = match result {
+            Ok(response) if response.success => continue,
+            Ok(response) => response
+                .error
+                .unwrap_or_else(|| "unknown error".to_string()),
+            Err(error) => error,
+        };
+        logger::log(format!(
+            "Failed to restore tools of session {} after trusting {}: {}",
+            session_id,
+            project_dir.trim(),
+            error
+        ));
+    }
+
+    get_project_trust(app, project_dir)
+}
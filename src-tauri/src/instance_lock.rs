@@ -0,0 +1,160 @@
+//! Single-writer guard for the app data shared by every graphone instance.
+//!
+//! The first instance takes an OS file lock in the app data directory and
+//! owns the stores and settings files. Instances started while it runs stay
+//! usable but reject writes to shared state with an `instance-conflict:`
+//! error instead of racing the owner. The OS drops the lock with the process,
+//! so a crash never leaves a stale lock behind.
+
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::utils::now_millis;
+
+const LOCK_FILE_NAME: &str = "instance.lock";
+/// Owner details live beside the lock: a locked file cannot be read on Windows.
+const OWNER_FILE_NAME: &str = "instance.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceOwner {
+    pub pid: u32,
+    pub started_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceStatus {
+    /// Whether this instance owns the shared app data and may write to it.
+    pub primary: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_path: Option<String>,
+    /// The owning instance, when it is another process.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<InstanceOwner>,
+}
+
+static STATUS: OnceLock<InstanceStatus> = OnceLock::new();
+/// Held for the life of the process; dropping it would release the lock.
+static LOCK_FILE: OnceLock<File> = OnceLock::new();
+
+fn read_owner(dir: &Path) -> Option<InstanceOwner> {
+    let content = fs::read_to_string(dir.join(OWNER_FILE_NAME)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_owner(dir: &Path) -> Result<(), String> {
+    let owner = InstanceOwner {
+        pid: std::process::id(),
+        started_at: now_millis(),
+    };
+    let content = serde_json::to_string(&owner)
+        .map_err(|error| format!("Failed to serialize instance owner: {}", error))?;
+    let path = dir.join(OWNER_FILE_NAME);
+    fs::write(&path, content)
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
+}
+
+fn try_acquire(dir: &Path) -> Result<InstanceStatus, String> {
+    fs::create_dir_all(dir)
+        .map_err(|error| format!("Failed to create {}: {}", dir.display(), error))?;
+
+    let lock_path = dir.join(LOCK_FILE_NAME);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|error| format!("Failed to open {}: {}", lock_path.display(), error))?;
+
+    match file.try_lock() {
+        Ok(()) => {
+            write_owner(dir)?;
+            let _ = LOCK_FILE.set(file);
+            Ok(InstanceStatus {
+                primary: true,
+                lock_path: Some(lock_path.display().to_string()),
+                owner: None,
+            })
+        }
+        Err(TryLockError::WouldBlock) => Ok(InstanceStatus {
+            primary: false,
+            lock_path: Some(lock_path.display().to_string()),
+            owner: read_owner(dir),
+        }),
+        Err(TryLockError::Error(error)) => {
+            Err(format!("Failed to lock {}: {}", lock_path.display(), error))
+        }
+    }
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|error| format!("Failed to resolve app data dir: {}", error))
+}
+
+/// Take the instance lock. Called once at startup, before anything writes
+/// to the stores.
+///
+/// If the lock cannot be set up at all this instance acts as the owner, as
+/// it did before the lock existed.
+pub fn acquire(app: &AppHandle) -> InstanceStatus {
+    let status = app_data_dir(app)
+        .and_then(|dir| try_acquire(&dir))
+        .unwrap_or_else(|error| {
+            logger::log(format!("Instance lock unavailable: {}", error));
+            InstanceStatus {
+                primary: true,
+                lock_path: None,
+                owner: None,
+            }
+        });
+
+    if !status.primary {
+        logger::log(format!(
+            "Another graphone instance{} owns the app data; shared settings are read-only here",
+            status
+                .owner
+                .as_ref()
+                .map(|owner| format!(" (pid {})", owner.pid))
+                .unwrap_or_default()
+        ));
+    }
+
+    STATUS.get_or_init(|| status).clone()
+}
+
+/// This instance's lock status; an instance that has not taken the lock yet
+/// counts as the owner.
+pub fn status() -> InstanceStatus {
+    STATUS.get().cloned().unwrap_or(InstanceStatus {
+        primary: true,
+        lock_path: None,
+        owner: None,
+    })
+}
+
+pub fn is_primary() -> bool {
+    STATUS.get().is_none_or(|status| status.primary)
+}
+
+/// Fail with an `instance-conflict:` error unless this instance owns the
+/// shared app data. Call before writing stores or settings files.
+pub fn ensure_primary() -> Result<(), String> {
+    if is_primary() {
+        return Ok(());
+    }
+
+    let owner = status()
+        .owner
+        .map(|owner| format!(" (pid {})", owner.pid))
+        .unwrap_or_default();
+    Err(messages::error(ErrorCode::InstanceConflict, &[&owner]))
+}
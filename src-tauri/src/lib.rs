@@ -1,10 +1,14 @@
 mod commands;
+mod instance_lock;
 mod logger;
+mod messages;
 mod platform;
+mod preferences;
 mod sidecar;
 mod state;
 mod types;
 mod utils;
+mod window_registry;
 
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -54,25 +58,114 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .manage(sidecar_state)
+        .setup(|app| {
+            let instance = instance_lock::acquire(app.handle());
+            preferences::load(app.handle());
+            preferences::spawn_preferences_watcher(app.handle().clone());
+            commands::spawn_idle_session_reaper(
+                app.handle().clone(),
+                app.state::<Arc<Mutex<SidecarState>>>().inner().clone(),
+            );
+            commands::spawn_watch_rule_runner(
+                app.handle().clone(),
+                app.state::<Arc<Mutex<SidecarState>>>().inner().clone(),
+            );
+            // Pid records are per instance, so any instance may sweep
+            // those of crashed ones.
+            commands::sweep_orphaned_sidecars(app.handle());
+            // The sidecar runtime and hook endpoint file are shared; leave
+            // them to the owning instance.
+            if instance.primary {
+                commands::sweep_orphaned_runtimes(app.handle());
+                if let Err(error) = commands::migrate_workspace_trust(app.handle()) {
+                    logger::log(format!("Workspace trust migration failed: {}", error));
+                }
+                // Otherwise it starts with the first `install_git_hook`.
+                commands::spawn_git_hook_server(
+                    app.handle().clone(),
+                    app.state::<Arc<Mutex<SidecarState>>>().inner().clone(),
+                );
+            }
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                window_registry::forget_window(window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
+            commands::get_instance_status,
+            commands::set_locale,
+            commands::subscribe_window_events,
+            commands::unsubscribe_window_events,
+            commands::list_window_subscriptions,
+            commands::get_onboarding_state,
+            commands::complete_onboarding_step,
+            commands::verify_installation,
+            commands::repair_installation,
+            commands::install_git_hook,
+            commands::uninstall_git_hook,
             commands::path_exists,
             commands::open_external_url,
             commands::list_session_project_scopes,
+            commands::get_activity_stats,
             commands::delete_project_scope,
             commands::delete_project_session,
+            commands::tail_session_file,
+            commands::get_session_notes,
+            commands::set_session_notes,
+            commands::set_session_tags,
+            commands::list_pinned_context_files,
+            commands::pin_context_file,
+            commands::unpin_context_file,
+            commands::get_project_env,
+            commands::set_project_env,
+            commands::get_prompt_footer,
+            commands::set_prompt_footer,
+            commands::generate_project_brief,
+            commands::write_project_brief,
+            commands::create_project,
+            commands::get_sidecar_capabilities,
+            commands::get_feature_flags,
+            commands::create_session,
             commands::create_agent,
             commands::close_agent,
             commands::list_agents,
             commands::send_prompt,
+            commands::run_action_sequence,
+            commands::run_batch,
+            commands::generate_change_summary,
+            commands::list_code_blocks,
+            commands::copy_code_block,
+            commands::list_watch_rules,
+            commands::save_watch_rule,
+            commands::delete_watch_rule,
+            commands::set_watch_rule_enabled,
             commands::send_bash_command,
             commands::read_clipboard_image,
+            commands::read_clipboard_files,
+            commands::read_image_files_from_uri_list,
+            commands::get_paste_spill_threshold,
+            commands::spill_paste_to_file,
+            commands::read_tool_output_lines,
+            commands::read_event_overflow,
+            commands::get_event_pipeline_stats,
+            commands::get_parse_failures,
+            commands::run_sidecar_cli,
+            commands::cancel_operation,
             commands::abort_agent,
+            commands::abort_all_agents,
+            commands::pause_agents,
+            commands::resume_agents,
+            commands::get_pause_status,
             commands::abort_branch_summary,
             commands::abort_bash,
             commands::get_messages,
             commands::get_session_tree,
             commands::navigate_session_tree,
             commands::get_state,
+            commands::get_session_state,
+            commands::get_turn_timeline,
             commands::get_available_models,
             commands::get_registered_extensions,
             commands::get_commands,
@@ -82,11 +175,27 @@ pub fn run() {
             commands::submit_oauth_login_input,
             commands::cancel_oauth_login,
             commands::logout_oauth_provider,
+            commands::reauthenticate_and_retry,
             commands::set_model,
             commands::set_thinking_level,
+            commands::get_supported_thinking_levels,
             commands::cycle_model,
             commands::get_enabled_models,
             commands::set_enabled_models,
+            commands::list_profiles,
+            commands::save_profile,
+            commands::delete_profile,
+            commands::apply_profile,
+            commands::get_project_tools,
+            commands::set_project_tools,
+            commands::get_project_trust,
+            commands::trust_project,
+            commands::restore_settings_backup,
+            commands::get_preferences,
+            commands::set_log_destination,
+            commands::set_preferences,
+            commands::get_quiet_hours_status,
+            commands::snooze_notifications,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
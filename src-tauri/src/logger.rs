@@ -5,8 +5,12 @@ use std::path::{Path, PathBuf};
 use std::sync::{Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-static LOG_FILE: OnceLock<Option<Mutex<File>>> = OnceLock::new();
+use serde::Serialize;
+
+static LOG_SINK: OnceLock<Mutex<LogSink>> = OnceLock::new();
 static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Outcome of moving a pre-XDG log file, reported once the log is open.
+static LOG_MIGRATION: OnceLock<String> = OnceLock::new();
 
 fn timestamp() -> String {
     match SystemTime::now().duration_since(UNIX_EPOCH) {
@@ -84,6 +88,28 @@ fn get_portable_log_path() -> Option<PathBuf> {
     Some(exe_dir.join("graphone.log"))
 }
 
+/// `$XDG_STATE_HOME/graphone/logs/graphone.log`, taking over the log that
+/// older builds wrote to the temp dir.
+#[cfg(target_os = "linux")]
+fn get_xdg_state_log_path() -> Option<PathBuf> {
+    let path = crate::platform::xdg_dirs::state_dir()?
+        .join("logs")
+        .join("graphone.log");
+
+    let legacy_path = env::temp_dir().join("graphone.log");
+    match crate::platform::xdg_dirs::migrate_legacy_file(&legacy_path, &path) {
+        Ok(true) => {
+            let _ = LOG_MIGRATION.set(format!("Moved previous log from {}", legacy_path.display()));
+        }
+        Ok(false) => {}
+        Err(error) => {
+            let _ = LOG_MIGRATION.set(format!("Failed to move previous log: {}", error));
+        }
+    }
+
+    Some(path)
+}
+
 fn resolve_log_path() -> PathBuf {
     if let Ok(path) = env::var("GRAPHONE_LOG_PATH") {
         return PathBuf::from(path);
@@ -99,32 +125,148 @@ fn resolve_log_path() -> PathBuf {
         }
     }
 
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(path) = get_xdg_state_log_path() {
+            return path;
+        }
+    }
+
     env::temp_dir().join("graphone.log")
 }
 
-fn init_log_file() -> Option<Mutex<File>> {
-    let path = log_path();
+/// Where log lines currently go; switchable at runtime with `set_destination`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LogDestination {
+    File { path: PathBuf },
+    Stdout,
+    Disabled,
+}
+
+impl LogDestination {
+    /// Parse `stdout`, `disabled`, `default` (re-resolve `GRAPHONE_LOG_PATH`
+    /// and the platform default) or a file path.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "" => Err("Log destination cannot be empty".to_string()),
+            "stdout" => Ok(Self::Stdout),
+            "disabled" => Ok(Self::Disabled),
+            "default" => Ok(Self::File {
+                path: resolve_log_path(),
+            }),
+            path => Ok(Self::File {
+                path: PathBuf::from(path),
+            }),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::File { path } => path.display().to_string(),
+            Self::Stdout => "stdout".to_string(),
+            Self::Disabled => "disabled".to_string(),
+        }
+    }
+}
+
+enum LogSink {
+    File { path: PathBuf, file: File },
+    Stdout,
+    Disabled,
+}
+
+impl LogSink {
+    fn destination(&self) -> LogDestination {
+        match self {
+            LogSink::File { path, .. } => LogDestination::File { path: path.clone() },
+            LogSink::Stdout => LogDestination::Stdout,
+            LogSink::Disabled => LogDestination::Disabled,
+        }
+    }
 
+    fn write_line(&mut self, line: &str) {
+        match self {
+            LogSink::File { file, .. } => {
+                let _ = writeln!(file, "{}", line);
+            }
+            LogSink::Stdout => {
+                let _ = writeln!(std::io::stdout().lock(), "{}", line);
+            }
+            LogSink::Disabled => {}
+        }
+    }
+}
+
+fn open_log_file(path: &Path) -> Result<File, String> {
     if let Some(parent) = path.parent() {
-        create_dir_all(parent).ok()?;
+        create_dir_all(parent)
+            .map_err(|error| format!("Failed to create {}: {}", parent.display(), error))?;
     }
 
-    let file = OpenOptions::new()
+    OpenOptions::new()
         .create(true)
         .append(true)
-        .open(&path)
-        .ok()?;
+        .open(path)
+        .map_err(|error| format!("Failed to open log file {}: {}", path.display(), error))
+}
 
-    Some(Mutex::new(file))
+fn open_sink(destination: LogDestination) -> Result<LogSink, String> {
+    Ok(match destination {
+        LogDestination::File { path } => LogSink::File {
+            file: open_log_file(&path)?,
+            path,
+        },
+        LogDestination::Stdout => LogSink::Stdout,
+        LogDestination::Disabled => LogSink::Disabled,
+    })
 }
 
+fn init_log_sink() -> Mutex<LogSink> {
+    let sink = open_sink(LogDestination::File { path: log_path() }).unwrap_or(LogSink::Disabled);
+    Mutex::new(sink)
+}
+
+fn log_sink() -> &'static Mutex<LogSink> {
+    LOG_SINK.get_or_init(init_log_sink)
+}
+
+/// Path of the log file chosen at startup.
 pub fn log_path() -> PathBuf {
     LOG_PATH.get_or_init(resolve_log_path).clone()
 }
 
 pub fn init() {
-    let _ = LOG_FILE.get_or_init(init_log_file);
+    let _ = log_sink();
     log(format!("Logger initialized at {}", log_path().display()));
+    if let Some(migration) = LOG_MIGRATION.get() {
+        log(migration);
+    }
+}
+
+/// Switch the log destination without restarting. The old destination gets a
+/// closing line pointing at the new one; on error the old one stays active.
+pub fn set_destination(destination: LogDestination) -> Result<LogDestination, String> {
+    let new_sink = open_sink(destination.clone())?;
+
+    let mut sink = log_sink()
+        .lock()
+        .map_err(|_| "Logger lock is poisoned".to_string())?;
+    let previous = sink.destination();
+
+    sink.write_line(&format!(
+        "[{}] Log destination switched to {}",
+        timestamp(),
+        destination.describe()
+    ));
+    *sink = new_sink;
+    sink.write_line(&format!(
+        "[{}] Log destination switched from {}",
+        timestamp(),
+        previous.describe()
+    ));
+
+    Ok(destination)
 }
 
 pub fn log(message: impl AsRef<str>) {
@@ -133,9 +275,7 @@ pub fn log(message: impl AsRef<str>) {
     #[cfg(debug_assertions)]
     eprintln!("{}", message);
 
-    if let Some(file_mutex) = LOG_FILE.get_or_init(init_log_file).as_ref() {
-        if let Ok(mut file) = file_mutex.lock() {
-            let _ = writeln!(file, "[{}] {}", timestamp(), message);
-        }
+    if let Ok(mut sink) = log_sink().lock() {
+        sink.write_line(&format!("[{}] {}", timestamp(), message));
     }
 }
@@ -0,0 +1,223 @@
+//! Catalog of user-facing backend error messages, keyed by error code.
+//!
+//! Errors built here read `<code>: <message>`, with the message in the locale
+//! picked by `set_locale`. The frontend can branch on the stable code and show
+//! the rest as is, instead of matching English substrings.
+
+use std::fmt::Display;
+use std::sync::RwLock;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Locale {
+    En,
+    De,
+}
+
+impl Locale {
+    pub const SUPPORTED: [Locale; 2] = [Locale::En, Locale::De];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+        }
+    }
+
+    /// Match a BCP 47 tag such as `de-AT` or `en_US` on its language part.
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let language = tag
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        Self::SUPPORTED
+            .into_iter()
+            .find(|locale| locale.as_str() == language)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Another instance owns the shared app data.
+    InstanceConflict,
+    ProjectDirRequired,
+    /// `{}`: the directory.
+    ProjectDirNotFound,
+    SessionIdRequired,
+    /// `{}`: the session id.
+    UnknownSession,
+    SidecarNotRunning,
+    /// Agents are paused with the sidecar process suspended.
+    SidecarSuspended,
+    /// The sidecar did not answer a command in time.
+    ResponseTimeout,
+    /// pi could not load packages/extensions because npm was missing.
+    NpmUnavailable,
+    /// `{}`: the provider.
+    LoginFailed,
+    /// `{}`: the provider.
+    LoginCancelled,
+    /// `{}`: the provider.
+    LoginTimedOut,
+    /// `{}`: the provider.
+    LoginInactive,
+    /// The provider rejected its API key. `{}`: the provider.
+    InvalidApiKey,
+    /// `{}`: the read-only file or directory.
+    ReadOnlyStorage,
+    /// `{}`: the project directory.
+    ProjectUntrusted,
+    /// `{}`: the op id of the cancelled operation.
+    OperationCancelled,
+    /// `{}`: the op id already in use.
+    OperationAlreadyRunning,
+    /// A run is active or a prompt is pending. `{}`: the session id.
+    TurnInProgress,
+    /// `{}`: free MiB, `{}`: the path, `{}`: required MiB.
+    InsufficientDiskSpace,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InstanceConflict => "instance-conflict",
+            ErrorCode::ProjectDirRequired => "project-dir-required",
+            ErrorCode::ProjectDirNotFound => "project-dir-not-found",
+            ErrorCode::SessionIdRequired => "session-id-required",
+            ErrorCode::UnknownSession => "unknown-session",
+            ErrorCode::SidecarNotRunning => "sidecar-not-running",
+            ErrorCode::SidecarSuspended => "sidecar-suspended",
+            ErrorCode::ResponseTimeout => "response-timeout",
+            ErrorCode::NpmUnavailable => "npm-unavailable",
+            ErrorCode::LoginFailed => "login-failed",
+            ErrorCode::LoginCancelled => "login-cancelled",
+            ErrorCode::LoginTimedOut => "login-timed-out",
+            ErrorCode::LoginInactive => "login-inactive",
+            ErrorCode::InvalidApiKey => "invalid-api-key",
+            ErrorCode::ReadOnlyStorage => "read-only-storage",
+            ErrorCode::ProjectUntrusted => "project-untrusted",
+            ErrorCode::OperationCancelled => "operation-cancelled",
+            ErrorCode::OperationAlreadyRunning => "operation-already-running",
+            ErrorCode::TurnInProgress => "turn-in-progress",
+            ErrorCode::InsufficientDiskSpace => "insufficient-disk-space",
+        }
+    }
+
+    /// Prefix of every error with this code, e.g. `unknown-session:`.
+    pub fn prefix(self) -> String {
+        format!("{}:", self.as_str())
+    }
+
+    /// Whether `error` was built from this code.
+    pub fn matches(self, error: &str) -> bool {
+        error
+            .strip_prefix(self.as_str())
+            .is_some_and(|rest| rest.starts_with(':'))
+    }
+}
+
+fn template(code: ErrorCode, locale: Locale) -> &'static str {
+    match (code, locale) {
+        (ErrorCode::InstanceConflict, Locale::En) => {
+            "Another Graphone instance{} is using the same data; close it to save changes here"
+        }
+        (ErrorCode::InstanceConflict, Locale::De) => {
+            "Eine andere Graphone-Instanz{} verwendet dieselben Daten; schließe sie, um hier Änderungen zu speichern"
+        }
+        (ErrorCode::ProjectDirRequired, Locale::En) => "A project directory is required",
+        (ErrorCode::ProjectDirRequired, Locale::De) => "Ein Projektverzeichnis ist erforderlich",
+        (ErrorCode::ProjectDirNotFound, Locale::En) => "Project directory not found: {}",
+        (ErrorCode::ProjectDirNotFound, Locale::De) => "Projektverzeichnis nicht gefunden: {}",
+        (ErrorCode::SessionIdRequired, Locale::En) => "A session id is required",
+        (ErrorCode::SessionIdRequired, Locale::De) => "Eine Sitzungs-ID ist erforderlich",
+        (ErrorCode::UnknownSession, Locale::En) => "Unknown session: {}",
+        (ErrorCode::UnknownSession, Locale::De) => "Unbekannte Sitzung: {}",
+        (ErrorCode::SidecarNotRunning, Locale::En) => "The agent host is not running",
+        (ErrorCode::SidecarNotRunning, Locale::De) => "Der Agent-Host läuft nicht",
+        (ErrorCode::SidecarSuspended, Locale::En) => {
+            "Agents are paused with the agent host suspended; resume them first"
+        }
+        (ErrorCode::SidecarSuspended, Locale::De) => {
+            "Agenten sind pausiert und der Agent-Host angehalten; setze sie zuerst fort"
+        }
+        (ErrorCode::ResponseTimeout, Locale::En) => "The agent host did not respond in time",
+        (ErrorCode::ResponseTimeout, Locale::De) => {
+            "Der Agent-Host hat nicht rechtzeitig geantwortet"
+        }
+        (ErrorCode::NpmUnavailable, Locale::En) => {
+            "Graphone could not initialize sessions because npm was not available to load your configured pi packages/extensions. Launch Graphone from the desktop launcher wrapper or a shell where Node/npm is available."
+        }
+        (ErrorCode::NpmUnavailable, Locale::De) => {
+            "Graphone konnte keine Sitzungen starten, weil npm zum Laden deiner pi-Pakete/-Erweiterungen nicht verfügbar war. Starte Graphone über den Desktop-Launcher oder aus einer Shell, in der Node/npm verfügbar ist."
+        }
+        (ErrorCode::LoginFailed, Locale::En) => "Login for {} failed",
+        (ErrorCode::LoginFailed, Locale::De) => "Anmeldung bei {} fehlgeschlagen",
+        (ErrorCode::LoginCancelled, Locale::En) => "Login for {} was cancelled",
+        (ErrorCode::LoginCancelled, Locale::De) => "Anmeldung bei {} wurde abgebrochen",
+        (ErrorCode::LoginTimedOut, Locale::En) => "Login for {} timed out",
+        (ErrorCode::LoginTimedOut, Locale::De) => "Zeitüberschreitung bei der Anmeldung bei {}",
+        (ErrorCode::LoginInactive, Locale::En) => "Login for {} is no longer active",
+        (ErrorCode::LoginInactive, Locale::De) => "Anmeldung bei {} ist nicht mehr aktiv",
+        (ErrorCode::InvalidApiKey, Locale::En) => {
+            "{} rejected the API key; check the key in its provider settings"
+        }
+        (ErrorCode::InvalidApiKey, Locale::De) => {
+            "{} hat den API-Schlüssel abgelehnt; prüfe den Schlüssel in den Anbieter-Einstellungen"
+        }
+        (ErrorCode::ReadOnlyStorage, Locale::En) => "Session storage is read-only: {}",
+        (ErrorCode::ReadOnlyStorage, Locale::De) => "Sitzungsspeicher ist schreibgeschützt: {}",
+        (ErrorCode::ProjectUntrusted, Locale::En) => {
+            "Project {} is not trusted; trust it to allow automated prompts"
+        }
+        (ErrorCode::ProjectUntrusted, Locale::De) => {
+            "Projekt {} ist nicht vertrauenswürdig; vertraue ihm, um automatische Prompts zu erlauben"
+        }
+        (ErrorCode::OperationCancelled, Locale::En) => "Operation {} was cancelled",
+        (ErrorCode::OperationCancelled, Locale::De) => "Vorgang {} wurde abgebrochen",
+        (ErrorCode::OperationAlreadyRunning, Locale::En) => "Operation {} is already running",
+        (ErrorCode::OperationAlreadyRunning, Locale::De) => "Vorgang {} läuft bereits",
+        (ErrorCode::TurnInProgress, Locale::En) => {
+            "Session {} is already running a turn; steer it or wait for it to finish"
+        }
+        (ErrorCode::TurnInProgress, Locale::De) => {
+            "Sitzung {} führt bereits einen Durchlauf aus; steuere ihn oder warte, bis er fertig ist"
+        }
+        (ErrorCode::InsufficientDiskSpace, Locale::En) => {
+            "Only {} MiB free at {} ({} MiB required)"
+        }
+        (ErrorCode::InsufficientDiskSpace, Locale::De) => {
+            "Nur {} MiB frei unter {} ({} MiB erforderlich)"
+        }
+    }
+}
+
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::En);
+
+pub fn locale() -> Locale {
+    LOCALE.read().map(|guard| *guard).unwrap_or(Locale::En)
+}
+
+/// Switch the message locale; unsupported tags fall back to English.
+pub fn set_locale(tag: &str) -> Locale {
+    let locale = Locale::parse(tag).unwrap_or(Locale::En);
+    if let Ok(mut guard) = LOCALE.write() {
+        *guard = locale;
+    }
+    locale
+}
+
+/// `<code>: <message>` in the current locale, filling the template's `{}`
+/// placeholders with `args` in order.
+pub fn error(code: ErrorCode, args: &[&dyn Display]) -> String {
+    let mut message = template(code, locale()).to_string();
+    for arg in args {
+        if let Some(index) = message.find("{}") {
+            message.replace_range(index..index + 2, &arg.to_string());
+        }
+    }
+    format!("{} {}", code.prefix(), message)
+}
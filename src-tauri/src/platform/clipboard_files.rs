@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+
+use crate::types::RpcImageAttachment;
+
+const MAX_CLIPBOARD_FILES: usize = 16;
+
+fn decode_percent_escapes(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let hex = |byte: u8| (byte as char).to_digit(16);
+            if let (Some(high), Some(low)) = (hex(bytes[index + 1]), hex(bytes[index + 2])) {
+                decoded.push((high * 16 + low) as u8);
+                index += 3;
+                continue;
+            }
+        }
+
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Parse a `text/uri-list` payload (or GNOME's `x-special/gnome-copied-files`,
+/// whose first line is the operation) into local file paths.
+pub fn parse_file_uri_list(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.strip_prefix("file://"))
+        .map(|rest| {
+            // Drop an optional host (`file://localhost/...`).
+            let path = match rest.find('/') {
+                Some(0) | None => rest,
+                Some(slash) => &rest[slash..],
+            };
+            let decoded = decode_percent_escapes(path);
+
+            // `file:///C:/Users/...` -> `C:/Users/...` on Windows.
+            if cfg!(windows) && decoded.len() > 2 && decoded.as_bytes()[2] == b':' {
+                PathBuf::from(&decoded[1..])
+            } else {
+                PathBuf::from(decoded)
+            }
+        })
+        .take(MAX_CLIPBOARD_FILES)
+        .collect()
+}
+
+fn image_mime_type_for_path(path: &Path) -> Option<&'static str> {
+    let mime_type = match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => return None,
+    };
+
+    Some(mime_type)
+}
+
+/// Read the image files among `paths` as prompt attachments. Other files are skipped.
+pub fn read_image_attachments(paths: &[PathBuf]) -> Vec<RpcImageAttachment> {
+    paths
+        .iter()
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let mime_type = image_mime_type_for_path(path)?;
+            let bytes = std::fs::read(path).ok()?;
+            if bytes.is_empty() {
+                return None;
+            }
+
+            Some(RpcImageAttachment {
+                r#type: "image".to_string(),
+                data: BASE64_STANDARD.encode(bytes),
+                mime_type: mime_type.to_string(),
+            })
+        })
+        .collect()
+}
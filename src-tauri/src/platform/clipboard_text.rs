@@ -0,0 +1,67 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Pipe `text` into a clipboard tool such as `pbcopy` or `wl-copy`.
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+fn pipe_to_command(command: &str, args: &[&str], text: &str) -> Result<(), String> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|error| format!("Failed to run {}: {}", command, error))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|error| format!("Failed to write to {}: {}", command, error))?;
+    }
+
+    let status = child
+        .wait()
+        .map_err(|error| format!("Failed to wait for {}: {}", command, error))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", command, status));
+    }
+
+    Ok(())
+}
+
+/// Put `text` on the system clipboard.
+///
+/// Linux tries `wl-copy`, then `xclip` and `xsel`, the same tools the image
+/// paste fallback reads with.
+pub fn write_clipboard_text(text: &str) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut errors = Vec::new();
+        for (command, args) in [
+            ("wl-copy", &[][..]),
+            ("xclip", &["-selection", "clipboard"][..]),
+            ("xsel", &["--clipboard", "--input"][..]),
+        ] {
+            match pipe_to_command(command, args, text) {
+                Ok(()) => return Ok(()),
+                Err(error) => errors.push(error),
+            }
+        }
+        Err(format!("No clipboard tool worked: {}", errors.join("; ")))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        pipe_to_command("pbcopy", &[], text)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        super::windows_clipboard::write_clipboard_text_windows(text)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        let _ = text;
+        Err("Copying to the clipboard is not supported on this platform".to_string())
+    }
+}
@@ -115,3 +115,25 @@ pub fn read_clipboard_image_linux() -> Option<RpcImageAttachment> {
 
     None
 }
+
+/// File paths copied in a file manager (`text/uri-list` or GNOME's copied-files target).
+pub fn read_clipboard_file_paths_linux() -> Vec<std::path::PathBuf> {
+    const URI_LIST_TYPES: [&str; 2] = ["text/uri-list", "x-special/gnome-copied-files"];
+
+    for mime_type in URI_LIST_TYPES {
+        let text = run_command_stdout("wl-paste", &["--type", mime_type, "--no-newline"])
+            .or_else(|| {
+                run_command_stdout("xclip", &["-selection", "clipboard", "-t", mime_type, "-o"])
+            })
+            .and_then(|output| String::from_utf8(output).ok());
+
+        if let Some(text) = text {
+            let paths = super::clipboard_files::parse_file_uri_list(&text);
+            if !paths.is_empty() {
+                return paths;
+            }
+        }
+    }
+
+    Vec::new()
+}
@@ -1,5 +1,19 @@
+pub mod clipboard_files;
+pub mod clipboard_text;
+pub mod process_priority;
+pub mod process_suspend;
+
 #[cfg(target_os = "linux")]
 pub mod linux_clipboard;
 
 #[cfg(target_os = "linux")]
 pub mod linux_open_url;
+
+#[cfg(target_os = "linux")]
+pub mod xdg_dirs;
+
+#[cfg(target_os = "windows")]
+pub mod windows_clipboard;
+
+#[cfg(target_os = "windows")]
+pub mod windows_toast;
@@ -0,0 +1,89 @@
+//! Lower the scheduling priority of a spawned process (renice / SetPriorityClass).
+//!
+//! Children spawned afterwards inherit the priority, so applying it right after
+//! the sidecar starts also covers the tools and builds it launches.
+
+const MIN_NICE: i32 = 0;
+const MAX_NICE: i32 = 19;
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, SetPriorityClass, BELOW_NORMAL_PRIORITY_CLASS, IDLE_PRIORITY_CLASS,
+        NORMAL_PRIORITY_CLASS, PROCESS_SET_INFORMATION,
+    };
+
+    pub fn set_priority(pid: u32, nice: i32) -> Result<(), String> {
+        let class = match nice {
+            15.. => IDLE_PRIORITY_CLASS,
+            1.. => BELOW_NORMAL_PRIORITY_CLASS,
+            _ => NORMAL_PRIORITY_CLASS,
+        };
+
+        // SAFETY: the handle is checked for null and closed before returning.
+        unsafe {
+            let process = OpenProcess(PROCESS_SET_INFORMATION, 0, pid);
+            if process.is_null() {
+                return Err(format!("Failed to open process {}", pid));
+            }
+
+            let ok = SetPriorityClass(process, class);
+            CloseHandle(process);
+
+            if ok == 0 {
+                return Err(format!("SetPriorityClass failed for process {}", pid));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Set the niceness of `pid` (0 = normal, 19 = lowest). On Windows the value is
+/// mapped onto the below-normal / idle priority classes.
+pub fn set_process_nice(pid: u32, nice: i32) -> Result<(), String> {
+    let nice = nice.clamp(MIN_NICE, MAX_NICE);
+
+    #[cfg(target_os = "windows")]
+    {
+        windows::set_priority(pid, nice)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        // The positional form sets an absolute value with both util-linux and BSD renice.
+        let status = std::process::Command::new("renice")
+            .args([&nice.to_string(), "-p", &pid.to_string()])
+            .stdout(std::process::Stdio::null())
+            .status()
+            .map_err(|error| format!("Failed to invoke renice for {}: {}", pid, error))?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "renice {} failed for {} with status {}",
+                nice, pid, status
+            ))
+        }
+    }
+}
+
+/// Whether transient systemd user scopes can be created, which is how the
+/// sidecar is placed in a CPU-limited cgroup on Linux. Probed once.
+#[cfg(target_os = "linux")]
+pub fn systemd_user_scope_available() -> bool {
+    use std::sync::OnceLock;
+
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        std::process::Command::new("systemd-run")
+            .args(["--user", "--scope", "--quiet", "--collect", "true"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    })
+}
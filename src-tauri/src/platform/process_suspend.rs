@@ -0,0 +1,201 @@
+//! Suspend/resume a whole process (SIGSTOP/SIGCONT, NtSuspendProcess/NtResumeProcess),
+//! alone or together with every process it started.
+
+/// Passes over the process table when suspending a tree; a process that was
+/// still starting children while the previous pass ran is caught by the next.
+const TREE_SUSPEND_PASSES: usize = 3;
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_SUSPEND_RESUME};
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtSuspendProcess(process: HANDLE) -> i32;
+        fn NtResumeProcess(process: HANDLE) -> i32;
+    }
+
+    pub fn set_suspended(pid: u32, suspend: bool) -> Result<(), String> {
+        // SAFETY: the handle is checked for null and closed before returning.
+        unsafe {
+            let process = OpenProcess(PROCESS_SUSPEND_RESUME, 0, pid);
+            if process.is_null() {
+                return Err(format!("Failed to open process {}", pid));
+            }
+
+            let status = if suspend {
+                NtSuspendProcess(process)
+            } else {
+                NtResumeProcess(process)
+            };
+            CloseHandle(process);
+
+            if status < 0 {
+                return Err(format!(
+                    "{} failed for process {} (NTSTATUS {:#x})",
+                    if suspend {
+                        "NtSuspendProcess"
+                    } else {
+                        "NtResumeProcess"
+                    },
+                    pid,
+                    status
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `(pid, parent pid)` of every running process.
+    pub fn process_parents() -> Vec<(u32, u32)> {
+        let mut parents = Vec::new();
+
+        // SAFETY: the snapshot handle is checked and closed before returning;
+        // `entry.dwSize` is set as Process32FirstW requires.
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return parents;
+            }
+
+            let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+            entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+            let mut found = Process32FirstW(snapshot, &mut entry);
+            while found != 0 {
+                parents.push((entry.th32ProcessID, entry.th32ParentProcessID));
+                found = Process32NextW(snapshot, &mut entry);
+            }
+            CloseHandle(snapshot);
+        }
+
+        parents
+    }
+}
+
+/// `(pid, parent pid)` of every running process.
+#[cfg(not(target_os = "windows"))]
+fn process_parents() -> Vec<(u32, u32)> {
+    let Ok(output) = std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=", "-o", "ppid="])
+        .stderr(std::process::Stdio::null())
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let parent = fields.next()?.parse().ok()?;
+            Some((pid, parent))
+        })
+        .collect()
+}
+
+/// Every process below `root` in the process tree, parents before children.
+fn descendants(root: u32) -> Vec<u32> {
+    #[cfg(target_os = "windows")]
+    let parents = windows::process_parents();
+    #[cfg(not(target_os = "windows"))]
+    let parents = process_parents();
+
+    let mut found = vec![root];
+    let mut index = 0;
+    while index < found.len() {
+        let parent = found[index];
+        let children = parents
+            .iter()
+            .filter(|(pid, ppid)| *ppid == parent && *pid != parent && !found.contains(pid))
+            .map(|(pid, _)| *pid)
+            .collect::<Vec<_>>();
+        found.extend(children);
+        index += 1;
+    }
+    found.remove(0);
+    found
+}
+
+fn set_suspended(pid: u32, suspend: bool) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        windows::set_suspended(pid, suspend)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let signal = if suspend { "-STOP" } else { "-CONT" };
+        let status = std::process::Command::new("kill")
+            .args([signal, &pid.to_string()])
+            .status()
+            .map_err(|error| format!("Failed to invoke kill {} for {}: {}", signal, pid, error))?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "kill {} failed for {} with status {}",
+                signal, pid, status
+            ))
+        }
+    }
+}
+
+pub fn suspend_process(pid: u32) -> Result<(), String> {
+    set_suspended(pid, true)
+}
+
+pub fn resume_process(pid: u32) -> Result<(), String> {
+    set_suspended(pid, false)
+}
+
+/// Suspend `pid` and every process it started (tool commands, builds), so
+/// none of them keeps running while the sidecar is frozen. Returns the pids
+/// that were suspended, `pid` first; pass them to `resume_processes`.
+pub fn suspend_process_tree(pid: u32) -> Result<Vec<u32>, String> {
+    // Frozen first, so it cannot start new children while the tree is walked.
+    suspend_process(pid)?;
+    let mut suspended = vec![pid];
+
+    for _ in 0..TREE_SUSPEND_PASSES {
+        let pending = descendants(pid)
+            .into_iter()
+            .filter(|child| !suspended.contains(child))
+            .collect::<Vec<_>>();
+        if pending.is_empty() {
+            break;
+        }
+
+        for child in pending {
+            // A child may exit between the walk and the signal.
+            if suspend_process(child).is_ok() {
+                suspended.push(child);
+            }
+        }
+    }
+
+    Ok(suspended)
+}
+
+/// Resume processes suspended by `suspend_process_tree`, children first. Every
+/// pid is resumed even if one fails; the first failure is returned.
+pub fn resume_processes(pids: &[u32]) -> Result<(), String> {
+    let mut first_error = None;
+    for pid in pids.iter().rev() {
+        if let Err(error) = resume_process(*pid) {
+            first_error.get_or_insert(error);
+        }
+    }
+
+    match first_error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
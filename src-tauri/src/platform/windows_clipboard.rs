@@ -0,0 +1,95 @@
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::path::PathBuf;
+
+use windows_sys::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+    SetClipboardData,
+};
+use windows_sys::Win32::System::Memory::{
+    GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE,
+};
+use windows_sys::Win32::System::Ole::{CF_HDROP, CF_UNICODETEXT};
+use windows_sys::Win32::UI::Shell::DragQueryFileW;
+
+/// File paths copied in Explorer (`CF_HDROP`).
+pub fn read_clipboard_file_paths_windows() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    // SAFETY: the clipboard is opened and closed within this function and the
+    // HDROP handle is only used while it is open.
+    unsafe {
+        if IsClipboardFormatAvailable(CF_HDROP as u32) == 0 {
+            return paths;
+        }
+
+        if OpenClipboard(std::ptr::null_mut()) == 0 {
+            return paths;
+        }
+
+        let hdrop = GetClipboardData(CF_HDROP as u32);
+        if !hdrop.is_null() {
+            let count = DragQueryFileW(hdrop, u32::MAX, std::ptr::null_mut(), 0);
+            for index in 0..count {
+                let len = DragQueryFileW(hdrop, index, std::ptr::null_mut(), 0);
+                if len == 0 {
+                    continue;
+                }
+
+                let mut buffer = vec![0u16; len as usize + 1];
+                let written =
+                    DragQueryFileW(hdrop, index, buffer.as_mut_ptr(), buffer.len() as u32);
+                buffer.truncate(written as usize);
+                paths.push(PathBuf::from(OsString::from_wide(&buffer)));
+            }
+        }
+
+        CloseClipboard();
+    }
+
+    paths
+}
+
+/// Replace the clipboard content with `text` as `CF_UNICODETEXT`.
+pub fn write_clipboard_text_windows(text: &str) -> Result<(), String> {
+    let wide = text.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let byte_len = wide.len() * std::mem::size_of::<u16>();
+
+    // SAFETY: the global memory block is sized for `wide` and only written
+    // while locked. Once `SetClipboardData` succeeds the clipboard owns it;
+    // otherwise it is freed here.
+    unsafe {
+        if OpenClipboard(std::ptr::null_mut()) == 0 {
+            return Err("Failed to open the clipboard".to_string());
+        }
+
+        let result = (|| {
+            if EmptyClipboard() == 0 {
+                return Err("Failed to empty the clipboard".to_string());
+            }
+
+            let memory = GlobalAlloc(GMEM_MOVEABLE, byte_len);
+            if memory.is_null() {
+                return Err("Failed to allocate clipboard memory".to_string());
+            }
+
+            let target = GlobalLock(memory) as *mut u16;
+            if target.is_null() {
+                GlobalFree(memory);
+                return Err("Failed to lock clipboard memory".to_string());
+            }
+            std::ptr::copy_nonoverlapping(wide.as_ptr(), target, wide.len());
+            GlobalUnlock(memory);
+
+            if SetClipboardData(CF_UNICODETEXT as u32, memory).is_null() {
+                GlobalFree(memory);
+                return Err("Failed to set clipboard data".to_string());
+            }
+
+            Ok(())
+        })();
+
+        CloseClipboard();
+        result
+    }
+}
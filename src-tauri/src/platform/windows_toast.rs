@@ -0,0 +1,101 @@
+//! Windows toast notifications whose buttons are routed back into the app.
+
+use std::sync::{Mutex, OnceLock};
+
+use windows::core::{Interface, HSTRING};
+use windows::Data::Xml::Dom::XmlDocument;
+use windows::Foundation::TypedEventHandler;
+use windows::UI::Notifications::{
+    ToastActivatedEventArgs, ToastNotification, ToastNotificationManager,
+};
+
+/// Shown toasts are kept alive so their activation handlers still fire after
+/// `show_toast` returns; older ones are dropped from the front.
+const MAX_LIVE_TOASTS: usize = 8;
+
+fn live_toasts() -> &'static Mutex<Vec<ToastNotification>> {
+    static TOASTS: OnceLock<Mutex<Vec<ToastNotification>>> = OnceLock::new();
+    TOASTS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// A toast button: its label and the argument string passed back on click.
+pub struct ToastAction {
+    pub label: String,
+    pub argument: String,
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn toast_xml(title: &str, body: &str, launch: &str, actions: &[ToastAction]) -> String {
+    let actions = actions
+        .iter()
+        .map(|action| {
+            format!(
+                r#"<action content="{}" arguments="{}" activationType="foreground"/>"#,
+                escape_xml(&action.label),
+                escape_xml(&action.argument)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"<toast launch="{}"><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual><actions>{}</actions></toast>"#,
+        escape_xml(launch),
+        escape_xml(title),
+        escape_xml(body),
+        actions
+    )
+}
+
+/// Show a toast for `app_id` (the AppUserModelID the installer registers).
+///
+/// `on_activate` receives the clicked button's argument, or `launch` when the
+/// toast body itself is clicked. It runs on a system thread.
+pub fn show_toast(
+    app_id: &str,
+    title: &str,
+    body: &str,
+    launch: &str,
+    actions: &[ToastAction],
+    on_activate: impl Fn(String) + Send + 'static,
+) -> Result<(), String> {
+    let show = || -> windows::core::Result<ToastNotification> {
+        let document = XmlDocument::new()?;
+        document.LoadXml(&HSTRING::from(toast_xml(title, body, launch, actions)))?;
+
+        let toast = ToastNotification::CreateToastNotification(&document)?;
+        toast.Activated(&TypedEventHandler::new(
+            move |_, args: windows::core::Ref<'_, windows::core::IInspectable>| {
+                let argument = args
+                    .ok()
+                    .and_then(|args| args.cast::<ToastActivatedEventArgs>())
+                    .and_then(|args| args.Arguments())
+                    .map(|argument| argument.to_string())
+                    .unwrap_or_default();
+                on_activate(argument);
+                Ok(())
+            },
+        ))?;
+
+        ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))?
+            .Show(&toast)?;
+        Ok(toast)
+    };
+
+    let toast = show().map_err(|error| format!("Failed to show toast: {}", error))?;
+
+    if let Ok(mut toasts) = live_toasts().lock() {
+        toasts.push(toast);
+        let excess = toasts.len().saturating_sub(MAX_LIVE_TOASTS);
+        toasts.drain(..excess);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! XDG base directories for graphone-owned files on Linux.
+//!
+//! Stores, the extracted sidecar runtime and spilled tool output already go
+//! through Tauri's path resolver, which honors `$XDG_DATA_HOME` and
+//! `$XDG_CACHE_HOME`; this covers what Tauri has no directory for.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const APP_DIR_NAME: &str = "graphone";
+
+/// `$var` when set to an absolute path (the spec says to ignore relative
+/// values), otherwise `~/<fallback>`.
+fn base_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    if let Some(value) = env::var_os(var) {
+        let path = PathBuf::from(value);
+        if path.is_absolute() {
+            return Some(path);
+        }
+    }
+
+    dirs::home_dir().map(|home| home.join(fallback))
+}
+
+/// `$XDG_STATE_HOME/graphone` (default `~/.local/state/graphone`), for logs
+/// and other state that should survive restarts but is not user data.
+pub fn state_dir() -> Option<PathBuf> {
+    base_dir("XDG_STATE_HOME", ".local/state").map(|dir| dir.join(APP_DIR_NAME))
+}
+
+/// Move `legacy` to `target` unless `target` already exists.
+///
+/// Falls back to copy + remove when the two are on different filesystems
+/// (e.g. a tmpfs `/tmp`). Returns whether anything was moved.
+pub fn migrate_legacy_file(legacy: &Path, target: &Path) -> Result<bool, String> {
+    if !legacy.is_file() || target.exists() {
+        return Ok(false);
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to create {}: {}", parent.display(), error))?;
+    }
+
+    if fs::rename(legacy, target).is_ok() {
+        return Ok(true);
+    }
+
+    fs::copy(legacy, target).map_err(|error| {
+        format!(
+            "Failed to copy {} to {}: {}",
+            legacy.display(),
+            target.display(),
+            error
+        )
+    })?;
+    fs::remove_file(legacy)
+        .map_err(|error| format!("Failed to remove {}: {}", legacy.display(), error))?;
+
+    Ok(true)
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::watch;
+
+use crate::instance_lock;
+use crate::logger;
+
+const PREFERENCES_STORE_FILE: &str = "preferences.json";
+const PREFERENCES_KEY: &str = "preferences";
+const PREFERENCES_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const PREFERENCES_CHANGED_EVENT: &str = "preferences-changed";
+
+/// Provider + model id pair, as accepted by the host `set_model` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelRef {
+    pub provider: String,
+    pub model_id: String,
+}
+
+/// Tool-result truncation overrides; unset fields keep the built-in limits.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolResultLimitOverrides {
+    /// Max chars for plain-string results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_result_chars: Option<usize>,
+    /// Max chars per text content block.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_content_text_chars: Option<usize>,
+    /// Max content blocks kept per result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_content_blocks: Option<usize>,
+    /// Max chars per string inside `details`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_details_string_chars: Option<usize>,
+    /// Max nesting depth kept inside `details`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_details_depth: Option<usize>,
+}
+
+impl ToolResultLimitOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Debug-only delays and drops for the sidecar event pipeline and RPC
+/// responses. Only builds with the `latency-injection` feature honour them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LatencyInjection {
+    /// Delay before each sidecar event is handled.
+    pub event_delay_ms: u64,
+    /// Drop every Nth sidecar event; 0 drops none.
+    pub event_drop_every: u64,
+    /// Delay before each RPC response reaches its caller.
+    pub response_delay_ms: u64,
+    /// Drop every Nth RPC response, so its caller times out; 0 drops none.
+    pub response_drop_every: u64,
+}
+
+impl LatencyInjection {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Daily window, as local `HH:MM` times in `time_zone`, in which run
+/// notifications are suppressed and watch-rule prompts are deferred. A window
+/// whose end is before its start spans midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// Backend preferences owned by graphone (not pi settings).
+///
+/// Persisted in the app store and cached in memory so hot paths (event
+/// handling, prompt dispatch) can read them without touching disk. Edits to
+/// the store file made outside this instance are picked up while running.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Preferences {
+    /// Ordered models to switch to when a turn fails because its provider is unavailable.
+    pub fallback_models: Vec<ModelRef>,
+    /// Total decoded image bytes allowed per prompt; `None` uses the built-in budget.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_attachment_bytes: Option<u64>,
+    /// Paste length (chars) above which the UI offers to spill it to a file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paste_spill_threshold_chars: Option<usize>,
+    /// Close sessions idle for this many hours; `None` keeps them open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_session_close_hours: Option<u64>,
+    /// Truncation limits applied to every tool result sent to the frontend.
+    #[serde(skip_serializing_if = "ToolResultLimitOverrides::is_empty")]
+    pub tool_result_limits: ToolResultLimitOverrides,
+    /// Per-tool limits keyed by tool name, layered over `tool_result_limits`.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub tool_result_limits_by_tool: HashMap<String, ToolResultLimitOverrides>,
+    /// Niceness (0-19) applied to the sidecar at spawn; inherited by its tool children.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sidecar_nice: Option<i32>,
+    /// Linux only: cap the sidecar cgroup at this share of one CPU (100 = one core).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sidecar_cpu_quota_percent: Option<u32>,
+    /// Extra sidecar flags from the host's allow-list (e.g. `--no-skills`),
+    /// applied when the sidecar next starts.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sidecar_args: Vec<String>,
+    /// Max concurrent agent turns per provider id; further prompts for that
+    /// provider queue until a run ends. Unlisted providers are unlimited.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub provider_turn_limits: HashMap<String, usize>,
+    /// Open new sessions at the enclosing git work tree root instead of the
+    /// chosen subdirectory, so one repository keeps one session history.
+    pub use_git_root_as_scope: bool,
+    /// IANA zone (e.g. `Europe/Berlin`) session history timestamps are
+    /// shown in and quiet hours are read in; `None` uses the system zone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    /// Show tool path arguments under the session cwd relative to it; the
+    /// absolute path stays in the event's `absolutePaths`.
+    pub workspace_relative_paths: bool,
+    /// How long streaming deltas are coalesced before being sent to the
+    /// frontend; `None` uses 16 ms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_flush_interval_ms: Option<u64>,
+    /// Largest payload sent inline on `agent-event` (20,000 to 1,000,000);
+    /// bigger ones are written to an overflow file and referenced. `None`
+    /// uses 60,000.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_event_chars: Option<usize>,
+    /// Silence notifications and defer watch-rule prompts in this window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+    /// Simulated slow network, for testing loading and timeout handling.
+    #[serde(skip_serializing_if = "LatencyInjection::is_empty")]
+    pub latency_injection: LatencyInjection,
+}
+
+fn cache() -> &'static RwLock<Arc<Preferences>> {
+    static PREFERENCES: OnceLock<RwLock<Arc<Preferences>>> = OnceLock::new();
+    PREFERENCES.get_or_init(|| RwLock::new(Arc::new(Preferences::default())))
+}
+
+fn changes() -> &'static watch::Sender<Preferences> {
+    static CHANGES: OnceLock<watch::Sender<Preferences>> = OnceLock::new();
+    CHANGES.get_or_init(|| watch::Sender::new(Preferences::default()))
+}
+
+/// Receiver that is marked changed whenever the preferences are replaced,
+/// for subsystems that keep derived state instead of reading `current()`.
+pub fn subscribe() -> watch::Receiver<Preferences> {
+    changes().subscribe()
+}
+
+/// Make `preferences` the live set: update the cache, wake subscribers and
+/// tell the frontend.
+fn publish(app: &AppHandle, preferences: Preferences) {
+    if let Ok(mut guard) = cache().write() {
+        *guard = Arc::new(preferences.clone());
+    }
+    changes().send_replace(preferences.clone());
+    if let Err(error) = app.emit(PREFERENCES_CHANGED_EVENT, &preferences) {
+        logger::log(format!("Failed to emit preferences change: {}", error));
+    }
+}
+
+fn read_stored(app: &AppHandle) -> Result<Preferences, String> {
+    let store = app
+        .store(PREFERENCES_STORE_FILE)
+        .map_err(|error| format!("Failed to open preferences store: {}", error))?;
+    match store.get(PREFERENCES_KEY) {
+        Some(value) => serde_json::from_value::<Preferences>(value)
+            .map_err(|error| format!("Failed to parse stored preferences: {}", error)),
+        None => Ok(Preferences::default()),
+    }
+}
+
+/// Load persisted preferences into the in-memory cache. Called once at startup.
+pub fn load(app: &AppHandle) {
+    let loaded = read_stored(app)
+        .and_then(crate::commands::validate_preferences)
+        .unwrap_or_else(|error| {
+            logger::log(format!("{}, using defaults", error));
+            Preferences::default()
+        });
+    if let Ok(mut guard) = cache().write() {
+        *guard = Arc::new(loaded.clone());
+    }
+    changes().send_replace(loaded);
+}
+
+fn store_modified(app: &AppHandle) -> Option<SystemTime> {
+    let path = tauri_plugin_store::resolve_store_path(app, PREFERENCES_STORE_FILE).ok()?;
+    std::fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Poll the preferences store file and apply edits made outside this
+/// instance (by hand, or by the instance that owns the app data) without a
+/// restart.
+pub fn spawn_preferences_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_modified = store_modified(&app);
+        loop {
+            tokio::time::sleep(PREFERENCES_POLL_INTERVAL).await;
+
+            let modified = store_modified(&app);
+            if modified == last_modified {
+                continue;
+            }
+
+            // A half-written or invalid file keeps the current preferences;
+            // it is read again on its next change.
+            let stored = app
+                .store(PREFERENCES_STORE_FILE)
+                .and_then(|store| store.reload())
+                .map_err(|error| format!("Failed to reload preferences store: {}", error))
+                .and_then(|()| read_stored(&app))
+                .and_then(|stored| {
+                    crate::commands::validate_preferences(stored)
+                        .map_err(|error| format!("Ignored invalid preferences on disk: {}", error))
+                });
+            last_modified = modified;
+            let stored = match stored {
+                Ok(stored) => stored,
+                Err(error) => {
+                    logger::log(error);
+                    continue;
+                }
+            };
+
+            // Our own saves land here too; they are already live.
+            if stored != *current() {
+                logger::log("Applied preferences changed on disk");
+                publish(&app, stored);
+            }
+        }
+    });
+}
+
+/// Snapshot of the current preferences. Cheap enough for per-event reads: the
+/// cached set is shared, not copied.
+pub fn current() -> Arc<Preferences> {
+    cache()
+        .read()
+        .map(|guard| Arc::clone(&guard))
+        .unwrap_or_default()
+}
+
+/// Persist preferences and apply them immediately.
+pub fn save(app: &AppHandle, preferences: Preferences) -> Result<Preferences, String> {
+    instance_lock::ensure_primary()?;
+
+    let value = serde_json::to_value(&preferences)
+        .map_err(|error| format!("Failed to serialize preferences: {}", error))?;
+
+    let store = app
+        .store(PREFERENCES_STORE_FILE)
+        .map_err(|error| format!("Failed to open preferences store: {}", error))?;
+    store.set(PREFERENCES_KEY, value);
+    store
+        .save()
+        .map_err(|error| format!("Failed to save preferences: {}", error))?;
+
+    publish(app, preferences.clone());
+
+    Ok(preferences)
+}
@@ -7,27 +7,77 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 #[cfg(target_os = "macos")]
 use tauri::Manager;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, EventTarget};
 use tauri_plugin_shell::process::CommandEvent;
 use tauri_plugin_shell::ShellExt;
 use tokio::sync::Mutex;
 
+mod auth_expired;
+mod code_blocks;
+mod event_overflow;
 mod event_payload;
+mod event_sequence;
+mod file_diff;
+mod language_hint;
+mod latency_injection;
 #[cfg(target_os = "linux")]
 mod linux_runtime;
+mod model_fallback;
 mod ndjson;
-
-use event_payload::{compact_session_event_for_frontend, shorten_for_log};
+mod parse_quarantine;
+mod pipeline_stats;
+#[cfg(target_os = "windows")]
+mod run_notifications;
+mod session_state;
+mod spawn_args;
+mod tool_calls;
+mod tool_heartbeat;
+mod tool_output_spill;
+mod turn_errors;
+mod turn_summary;
+mod workspace_paths;
+
+pub(crate) use auth_expired::emit_auth_expired_event;
+pub(crate) use code_blocks::{CodeBlock, CodeBlockHistory};
+pub(crate) use event_overflow::{read_event_overflow, validate_max_event_chars};
+use event_payload::{
+    compact_session_event_for_frontend, oversized_tool_output_text, shorten_for_log,
+};
+pub(crate) use event_sequence::{forget_all_sequences, forget_session_sequence};
+use file_diff::{emit_file_diff, take_file_snapshot, FileSnapshot};
+pub(crate) use file_diff::{file_patch, FilePatch};
+pub(crate) use language_hint::language_for_path;
 #[cfg(target_os = "linux")]
 use linux_runtime::prepare_linux_sidecar_runtime;
-use ndjson::{debug_prefix_codepoints, decode_utf8_lossy, extract_lines, sanitize_json_line};
+#[cfg(target_os = "linux")]
+pub(crate) use linux_runtime::{
+    installed_linux_sidecar_stamp, linux_sidecar_runtime_dir, repair_linux_sidecar_runtime,
+    validate_linux_sidecar_binary,
+};
+use ndjson::{
+    debug_prefix_codepoints, decode_utf8_lossy, extract_lines, parse_stdout_line, ParsedLine,
+};
+pub(crate) use parse_quarantine::{parse_failures, ParseFailuresResponse};
+pub(crate) use pipeline_stats::{event_pipeline_stats, EventPipelineStats};
+pub(crate) use session_state::{session_state_snapshot, SessionStateSnapshot};
+pub(crate) use spawn_args::{last_spawn_argv, validate_sidecar_args};
+use spawn_args::{record_spawn_argv, sidecar_spawn_args};
+use tool_calls::ToolCallTracker;
+use tool_heartbeat::TOOL_HEARTBEAT_CHECK_INTERVAL;
+use tool_output_spill::spill_tool_output;
+pub(crate) use tool_output_spill::{read_tool_output_lines, ToolOutputLines};
+pub(crate) use turn_summary::FILE_WRITING_TOOLS;
 
 use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::platform::process_priority::set_process_nice;
+#[cfg(target_os = "linux")]
+use crate::platform::process_priority::systemd_user_scope_available;
+use crate::preferences;
 use crate::state::SidecarState;
 use crate::types::{RpcCommand, RpcResponse, SessionEventEnvelope};
+use crate::window_registry;
 
-const GRAPHONE_HOST_FLAG: &str = "--graphone-host";
-const MAX_AGENT_EVENT_CHARS: usize = 60_000;
 const MAX_AGENT_EVENT_CHUNK_SOURCE_BYTES: usize = 16_000;
 
 static AGENT_EVENT_CHUNK_COUNTER: AtomicU64 = AtomicU64::new(1);
@@ -182,6 +232,39 @@ fn resolve_non_linux_sidecar_runtime_dir() -> Option<PathBuf> {
     Some(exe_dir.to_path_buf())
 }
 
+/// Runtime directory the sidecar runs from and the path of its binary,
+/// without extracting or validating anything.
+pub(crate) fn resolve_sidecar_install_paths(app: &AppHandle) -> Result<(PathBuf, PathBuf), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let runtime_dir = linux_runtime::linux_sidecar_runtime_dir(app)?;
+        let binary = runtime_dir.join("pi");
+        Ok((runtime_dir, binary))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        #[cfg(target_os = "macos")]
+        let runtime_dir = resolve_non_linux_sidecar_runtime_dir(app);
+
+        #[cfg(all(not(target_os = "linux"), not(target_os = "macos")))]
+        let runtime_dir = {
+            let _ = app;
+            resolve_non_linux_sidecar_runtime_dir()
+        };
+
+        let runtime_dir =
+            runtime_dir.ok_or_else(|| "Failed to resolve sidecar runtime directory".to_string())?;
+        let exe_dir = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .ok_or_else(|| "Failed to resolve executable directory".to_string())?;
+        let binary = exe_dir.join(format!("pi{}", env::consts::EXE_SUFFIX));
+
+        Ok((runtime_dir, binary))
+    }
+}
+
 pub struct SidecarManager;
 
 impl SidecarManager {
@@ -190,10 +273,7 @@ impl SidecarManager {
         _provider: Option<String>,
         _model: Option<String>,
     ) -> Result<tauri_plugin_shell::process::Command, String> {
-        logger::log(format!(
-            "Sidecar backend=host args: [{}]",
-            GRAPHONE_HOST_FLAG
-        ));
+        let sidecar_args = sidecar_spawn_args(&preferences::current());
 
         #[cfg(target_os = "linux")]
         {
@@ -206,11 +286,27 @@ impl SidecarManager {
             ));
             log_sidecar_launch_environment(&sidecar_runtime_dir);
 
-            let command = app
-                .shell()
-                .command(&sidecar_binary)
-                .current_dir(&sidecar_runtime_dir)
-                .arg(GRAPHONE_HOST_FLAG);
+            let scope_args = linux_cpu_quota_scope_args();
+            let mut argv = match &scope_args {
+                Some(scope_args) => std::iter::once("systemd-run".to_string())
+                    .chain(scope_args.iter().cloned())
+                    .collect(),
+                None => Vec::new(),
+            };
+            argv.push(sidecar_binary.display().to_string());
+            argv.extend(sidecar_args.iter().cloned());
+            record_spawn_argv(argv);
+
+            let command = match scope_args {
+                Some(scope_args) => app
+                    .shell()
+                    .command("systemd-run")
+                    .args(scope_args)
+                    .arg(&sidecar_binary),
+                None => app.shell().command(&sidecar_binary),
+            }
+            .current_dir(&sidecar_runtime_dir)
+            .args(sidecar_args);
 
             return Ok(with_prepended_runtime_path(command, &sidecar_runtime_dir));
         }
@@ -240,7 +336,53 @@ impl SidecarManager {
                 .current_dir(&sidecar_runtime_dir)
                 .env("PI_PACKAGE_DIR", &sidecar_runtime_dir)
                 .env("NODE_PATH", &node_path)
-                .arg(GRAPHONE_HOST_FLAG);
+                .args(&sidecar_args);
+
+            let program = resolve_sidecar_install_paths(app)
+                .map(|(_, binary)| binary.display().to_string())
+                .unwrap_or_else(|_| "pi".to_string());
+            record_spawn_argv(std::iter::once(program).chain(sidecar_args).collect());
+
+            Ok(with_prepended_runtime_path(command, &sidecar_runtime_dir))
+        }
+    }
+
+    /// The bundled binary in plain CLI mode (no host flag), for one-shot
+    /// maintenance runs next to the running sidecar.
+    pub fn build_sidecar_cli_command(
+        app: &AppHandle,
+        args: &[String],
+    ) -> Result<tauri_plugin_shell::process::Command, String> {
+        #[cfg(target_os = "linux")]
+        {
+            let sidecar_runtime_dir = prepare_linux_sidecar_runtime(app)?;
+            let command = app
+                .shell()
+                .command(sidecar_runtime_dir.join("pi"))
+                .current_dir(std::env::temp_dir())
+                .args(args);
+
+            Ok(with_prepended_runtime_path(command, &sidecar_runtime_dir))
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            #[cfg(target_os = "macos")]
+            let sidecar_runtime_dir = resolve_non_linux_sidecar_runtime_dir(app)
+                .ok_or_else(|| "Failed to resolve sidecar runtime directory".to_string())?;
+
+            #[cfg(all(not(target_os = "linux"), not(target_os = "macos")))]
+            let sidecar_runtime_dir = resolve_non_linux_sidecar_runtime_dir()
+                .ok_or_else(|| "Failed to resolve sidecar runtime directory".to_string())?;
+
+            let command = app
+                .shell()
+                .sidecar("pi")
+                .map_err(|e| format!("Failed to create sidecar: {}", e))?
+                .current_dir(std::env::temp_dir())
+                .env("PI_PACKAGE_DIR", &sidecar_runtime_dir)
+                .env("NODE_PATH", sidecar_runtime_dir.join("node_modules"))
+                .args(args);
 
             Ok(with_prepended_runtime_path(command, &sidecar_runtime_dir))
         }
@@ -255,12 +397,60 @@ impl SidecarManager {
         ),
         String,
     > {
-        command
+        let (event_rx, child) = command
             .spawn()
-            .map_err(|e| format!("Failed to spawn sidecar: {}", e))
+            .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+
+        if let Some(nice) = preferences::current().sidecar_nice {
+            match set_process_nice(child.pid(), nice) {
+                Ok(()) => logger::log(format!("Sidecar priority lowered (nice {})", nice)),
+                Err(error) => logger::log(format!("Failed to lower sidecar priority: {}", error)),
+            }
+        }
+
+        Ok((event_rx, child))
     }
 }
 
+/// `systemd-run` arguments that start the sidecar in its own CPU-limited scope,
+/// or `None` when no quota is configured or user scopes are unavailable.
+#[cfg(target_os = "linux")]
+fn linux_cpu_quota_scope_args() -> Option<Vec<String>> {
+    let quota = preferences::current()
+        .sidecar_cpu_quota_percent
+        .filter(|quota| *quota > 0)?;
+
+    if !systemd_user_scope_available() {
+        logger::log(format!(
+            "Sidecar CPU quota {}% ignored: systemd user scopes are unavailable",
+            quota
+        ));
+        return None;
+    }
+
+    logger::log(format!("Sidecar CPU quota: {}%", quota));
+    Some(vec![
+        "--user".to_string(),
+        "--scope".to_string(),
+        "--quiet".to_string(),
+        "--collect".to_string(),
+        "-p".to_string(),
+        format!("CPUQuota={}%", quota),
+        "--".to_string(),
+    ])
+}
+
+/// Default for how long streaming deltas are coalesced before a flush.
+const DEFAULT_EVENT_FLUSH_INTERVAL_MS: u64 = 16;
+
+fn event_flush_interval(preferences: &preferences::Preferences) -> Duration {
+    Duration::from_millis(
+        preferences
+            .event_flush_interval_ms
+            .unwrap_or(DEFAULT_EVENT_FLUSH_INTERVAL_MS),
+    )
+}
+
 #[derive(Debug, Clone)]
 struct DeltaEventKey {
     delta_type: String,
@@ -302,6 +492,7 @@ impl SessionDeltaCoalescer {
                     && last_key.content_index == key.content_index
                 {
                     if Self::append_delta(last_event, &event) {
+                        pipeline_stats::record_delta_coalesced();
                         return true;
                     }
                 }
@@ -309,9 +500,14 @@ impl SessionDeltaCoalescer {
         }
 
         queue.push(event);
+        pipeline_stats::set_pending_deltas(self.pending_count());
         true
     }
 
+    fn pending_count(&self) -> usize {
+        self.pending_by_session.values().map(Vec::len).sum()
+    }
+
     fn flush_due(&mut self, app: &AppHandle) {
         if self.pending_by_session.is_empty() {
             return;
@@ -329,6 +525,7 @@ impl SessionDeltaCoalescer {
             return;
         };
 
+        pipeline_stats::set_pending_deltas(self.pending_count());
         for event in events {
             EventHandler::emit_session_event(app, session_id, event);
         }
@@ -405,6 +602,9 @@ impl EventHandler {
     ) {
         tauri::async_runtime::spawn(async move {
             while let Some((id, response)) = response_rx.recv().await {
+                if !latency_injection::pass_response().await {
+                    continue;
+                }
                 let mut state_guard = state.lock().await;
                 if let Some(pending) = state_guard.pending_requests.remove(&id) {
                     let _ = pending.sender.send(response);
@@ -417,15 +617,40 @@ impl EventHandler {
         app: AppHandle,
         state: Arc<Mutex<SidecarState>>,
         mut event_rx: tokio::sync::mpsc::Receiver<CommandEvent>,
+        sidecar_pid: u32,
     ) {
         let app_clone = app.clone();
 
         tokio::spawn(async move {
             let mut stdout_buffer: Vec<u8> = Vec::new();
             let mut stderr_buffer: Vec<u8> = Vec::new();
-            let mut delta_coalescer = SessionDeltaCoalescer::new(Duration::from_millis(16));
+            let mut preferences_rx = preferences::subscribe();
+            let mut delta_coalescer = SessionDeltaCoalescer::new(event_flush_interval(
+                &preferences_rx.borrow_and_update(),
+            ));
+            event_overflow::apply_preferences(&preferences_rx.borrow());
+            let mut tool_calls = ToolCallTracker::default();
+            let mut heartbeat_check = tokio::time::interval(TOOL_HEARTBEAT_CHECK_INTERVAL);
+            heartbeat_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                let event = tokio::select! {
+                    event = event_rx.recv() => event,
+                    _ = heartbeat_check.tick() => {
+                        tool_calls.heartbeats.emit_due(&app_clone);
+                        continue;
+                    }
+                };
+                let Some(event) = event else {
+                    break;
+                };
+
+                if preferences_rx.has_changed().unwrap_or(false) {
+                    let preferences = preferences_rx.borrow_and_update();
+                    delta_coalescer.flush_interval = event_flush_interval(&preferences);
+                    event_overflow::apply_preferences(&preferences);
+                }
 
-            while let Some(event) = event_rx.recv().await {
                 let should_continue = Self::handle_event(
                     &app_clone,
                     &state,
@@ -433,6 +658,7 @@ impl EventHandler {
                     &mut stdout_buffer,
                     &mut stderr_buffer,
                     &mut delta_coalescer,
+                    &mut tool_calls,
                 )
                 .await;
 
@@ -441,10 +667,18 @@ impl EventHandler {
                 }
             }
 
-            Self::flush_stdout_buffer(&app_clone, &state, &mut stdout_buffer, &mut delta_coalescer)
-                .await;
+            Self::flush_stdout_buffer(
+                &app_clone,
+                &state,
+                &mut stdout_buffer,
+                &mut delta_coalescer,
+                &mut tool_calls,
+            )
+            .await;
             delta_coalescer.flush_all(&app_clone);
             Self::flush_stderr_buffer(&mut stderr_buffer);
+            crate::commands::clear_sidecar_pid(sidecar_pid);
+            crate::commands::forget_suspension(&state, sidecar_pid).await;
         });
     }
 
@@ -455,10 +689,19 @@ impl EventHandler {
         stdout_buffer: &mut Vec<u8>,
         stderr_buffer: &mut Vec<u8>,
         delta_coalescer: &mut SessionDeltaCoalescer,
+        tool_calls: &mut ToolCallTracker,
     ) -> bool {
         match event {
             CommandEvent::Stdout(chunk) => {
-                Self::handle_stdout(app, state, chunk, stdout_buffer, delta_coalescer).await;
+                Self::handle_stdout(
+                    app,
+                    state,
+                    chunk,
+                    stdout_buffer,
+                    delta_coalescer,
+                    tool_calls,
+                )
+                .await;
                 true
             }
             CommandEvent::Stderr(chunk) => {
@@ -466,7 +709,8 @@ impl EventHandler {
                 true
             }
             CommandEvent::Terminated(payload) => {
-                Self::flush_stdout_buffer(app, state, stdout_buffer, delta_coalescer).await;
+                Self::flush_stdout_buffer(app, state, stdout_buffer, delta_coalescer, tool_calls)
+                    .await;
                 delta_coalescer.flush_all(app);
                 Self::flush_stderr_buffer(stderr_buffer);
                 logger::log(format!("Sidecar terminated with code: {:?}", payload.code));
@@ -474,7 +718,8 @@ impl EventHandler {
                 false
             }
             CommandEvent::Error(e) => {
-                Self::flush_stdout_buffer(app, state, stdout_buffer, delta_coalescer).await;
+                Self::flush_stdout_buffer(app, state, stdout_buffer, delta_coalescer, tool_calls)
+                    .await;
                 delta_coalescer.flush_all(app);
                 Self::flush_stderr_buffer(stderr_buffer);
                 logger::log(format!("Sidecar error: {}", e));
@@ -491,9 +736,10 @@ impl EventHandler {
         chunk: Vec<u8>,
         buffer: &mut Vec<u8>,
         delta_coalescer: &mut SessionDeltaCoalescer,
+        tool_calls: &mut ToolCallTracker,
     ) {
         for line in extract_lines(chunk, buffer) {
-            Self::handle_stdout_line(app, state, line, delta_coalescer).await;
+            Self::handle_stdout_line(app, state, line, delta_coalescer, tool_calls).await;
             delta_coalescer.flush_due(app);
         }
     }
@@ -503,22 +749,30 @@ impl EventHandler {
         state: &Arc<Mutex<SidecarState>>,
         line: String,
         delta_coalescer: &mut SessionDeltaCoalescer,
+        tool_calls: &mut ToolCallTracker,
     ) {
-        let line = sanitize_json_line(line);
-        if line.trim().is_empty() {
-            return;
-        }
-
-        match serde_json::from_str::<serde_json::Value>(&line) {
-            Ok(json) => Self::handle_parsed_json(app, state, line, json, delta_coalescer).await,
-            Err(error) => {
+        match parse_stdout_line(line) {
+            ParsedLine::Empty => {}
+            ParsedLine::Json { line, value } => {
+                pipeline_stats::record_line(line.len());
+                parse_quarantine::record_parsed_line(&value);
+                Self::handle_parsed_json(app, state, line, value, delta_coalescer, tool_calls).await
+            }
+            ParsedLine::Invalid {
+                raw,
+                sanitized,
+                error,
+            } => {
+                pipeline_stats::record_line(sanitized.len());
+                pipeline_stats::record_invalid_line();
                 logger::log(format!(
                     "Sidecar stdout invalid NDJSON line (len={}): {} ({}; prefix={})",
-                    line.len(),
-                    shorten_for_log(&line, 400),
+                    sanitized.len(),
+                    shorten_for_log(&sanitized, 400),
                     error,
-                    debug_prefix_codepoints(&line, 8)
+                    debug_prefix_codepoints(&sanitized, 8)
                 ));
+                parse_quarantine::quarantine_line(&raw, &sanitized, &error);
             }
         }
     }
@@ -529,6 +783,7 @@ impl EventHandler {
         raw: String,
         json: serde_json::Value,
         delta_coalescer: &mut SessionDeltaCoalescer,
+        tool_calls: &mut ToolCallTracker,
     ) {
         let top_level_type = json.get("type").and_then(|t| t.as_str());
 
@@ -543,16 +798,12 @@ impl EventHandler {
                 Ok(response) => {
                     if let Some(id) = response.id.clone() {
                         let cmd = response.command.clone();
-                        let state_guard = state.lock().await;
-                        if let Some(ref tx) = state_guard.response_tx {
-                            if tx.try_send((id.clone(), response)).is_err() {
-                                logger::log(format!(
-                                    "Failed to queue response id={} command={} (channel full/closed)",
-                                    id, cmd
-                                ));
-                            }
-                        } else {
-                            logger::log("Response channel not initialized");
+                        // Clone the sender so the state lock is not held while
+                        // waiting: the response handler needs it to drain the queue.
+                        let response_tx = state.lock().await.response_tx.clone();
+                        match response_tx {
+                            Some(tx) => Self::queue_response(&tx, id, cmd, response).await,
+                            None => logger::log("Response channel not initialized"),
                         }
                     }
                     return;
@@ -570,13 +821,65 @@ impl EventHandler {
             }
         }
 
+        if !latency_injection::pass_event().await {
+            return;
+        }
+
         // Guard against WebView IPC payload truncation (~64KB on some platforms)
         // by chunking oversized payloads before they cross the WebView boundary.
         if top_level_type == Some("session_event") {
+            pipeline_stats::record_session_event(raw.len());
             match serde_json::from_value::<SessionEventEnvelope>(json.clone()) {
-                Ok(envelope) => {
+                Ok(mut envelope) => {
                     let session_id = envelope.session_id;
-                    let compact_event = compact_session_event_for_frontend(envelope.event);
+                    if let Some(snapshot) = take_file_snapshot(&mut envelope.event) {
+                        Self::record_file_snapshot(state, &session_id, &snapshot).await;
+                        if let Some(tool_call_id) = envelope
+                            .event
+                            .get("toolCallId")
+                            .and_then(|value| value.as_str())
+                        {
+                            emit_file_diff(app, &session_id, tool_call_id, snapshot);
+                        }
+                    }
+                    let spilled_output = envelope
+                        .event
+                        .get("toolCallId")
+                        .and_then(|value| value.as_str())
+                        .zip(oversized_tool_output_text(&envelope.event))
+                        .and_then(|(tool_call_id, text)| {
+                            spill_tool_output(app, &session_id, tool_call_id, text)
+                        });
+                    let turn_summary = tool_calls.observe_turn_event(&session_id, &envelope.event);
+                    tool_calls.heartbeats.observe(&session_id, &envelope.event);
+                    if let Some(fenced) = code_blocks::observe_message_end(&envelope.event) {
+                        state
+                            .lock()
+                            .await
+                            .code_blocks
+                            .entry(session_id.clone())
+                            .or_default()
+                            .record_message(&session_id, fenced);
+                    }
+                    let mut compact_event =
+                        compact_session_event_for_frontend(envelope.event, tool_calls);
+                    if let Some(spilled) = spilled_output {
+                        compact_event["spilledOutput"] = serde_json::json!(spilled);
+                    }
+                    if preferences::current().workspace_relative_paths {
+                        let cwd = state.lock().await.session_cwds.get(&session_id).cloned();
+                        if let Some(cwd) = cwd {
+                            workspace_paths::relativize_event_paths(&mut compact_event, &cwd);
+                        }
+                    }
+
+                    match compact_event.get("type").and_then(|value| value.as_str()) {
+                        Some("agent_start") => tool_calls.reset_session_results(&session_id),
+                        Some("tool_execution_end") => {
+                            tool_calls.dedupe_result(&session_id, &mut compact_event)
+                        }
+                        _ => {}
+                    }
 
                     if SessionDeltaCoalescer::is_delta_event(&compact_event) {
                         let _ = delta_coalescer.maybe_queue_delta(&session_id, compact_event);
@@ -585,7 +888,34 @@ impl EventHandler {
                     }
 
                     delta_coalescer.flush_session(app, &session_id);
+                    Self::record_session_activity(state, &session_id, &compact_event).await;
+                    if compact_event.get("type").and_then(|value| value.as_str())
+                        == Some("agent_end")
+                    {
+                        crate::commands::send_queued_prompts(app, state).await;
+                    }
+                    model_fallback::observe_session_event(app, state, &session_id, &compact_event)
+                        .await;
+                    auth_expired::observe_session_event(app, state, &session_id, &compact_event)
+                        .await;
+                    #[cfg(target_os = "windows")]
+                    run_notifications::observe_session_event(
+                        app,
+                        state,
+                        &session_id,
+                        &compact_event,
+                    )
+                    .await;
                     Self::emit_session_event(app, &session_id, compact_event);
+
+                    if let Some(summary) = turn_summary {
+                        match serde_json::to_value(&summary) {
+                            Ok(summary) => Self::emit_session_event(app, &session_id, summary),
+                            Err(error) => {
+                                logger::log(format!("Failed to serialize turn summary: {}", error))
+                            }
+                        }
+                    }
                 }
                 Err(error) => {
                     logger::log(format!(
@@ -604,12 +934,126 @@ impl EventHandler {
             logger::log(format!("Sidecar stdout: {}", shorten_for_log(&raw, 2000)));
         }
 
-        Self::emit_agent_event_payload(app, raw, "agent-event");
+        Self::emit_agent_event_payload(app, raw, "agent-event", None);
+    }
+
+    /// Queue a response for the response handler. A full channel is waited on
+    /// rather than dropping the response, which would surface as a phantom
+    /// timeout; only a closed channel loses it.
+    async fn queue_response(
+        tx: &tokio::sync::mpsc::Sender<(String, RpcResponse)>,
+        id: String,
+        command: String,
+        response: RpcResponse,
+    ) {
+        let item = match tx.try_send((id.clone(), response)) {
+            Ok(()) => {
+                pipeline_stats::record_response(false);
+                return;
+            }
+            Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => {
+                pipeline_stats::record_response(true);
+                logger::log(format!(
+                    "Failed to queue response id={} command={} (channel closed)",
+                    id, command
+                ));
+                return;
+            }
+            Err(tokio::sync::mpsc::error::TrySendError::Full(item)) => item,
+        };
+
+        logger::log(format!(
+            "Response channel full; waiting to queue response id={} command={}",
+            id, command
+        ));
+        let started = Instant::now();
+        let queued = tx.send(item).await.is_ok();
+        pipeline_stats::record_response_backpressure(started.elapsed());
+        pipeline_stats::record_response(!queued);
+        if !queued {
+            logger::log(format!(
+                "Failed to queue response id={} command={} (channel closed)",
+                id, command
+            ));
+        }
+    }
+
+    /// Keep the pre-edit content of each file the current run touches; only
+    /// the first snapshot of a path counts, so the run can be diffed as a whole.
+    async fn record_file_snapshot(
+        state: &Arc<Mutex<SidecarState>>,
+        session_id: &str,
+        snapshot: &FileSnapshot,
+    ) {
+        state
+            .lock()
+            .await
+            .turn_file_snapshots
+            .entry(session_id.to_string())
+            .or_default()
+            .entry(snapshot.path.clone())
+            .or_insert_with(|| snapshot.before.clone());
+    }
+
+    async fn record_session_activity(
+        state: &Arc<Mutex<SidecarState>>,
+        session_id: &str,
+        event: &serde_json::Value,
+    ) {
+        let mut state_guard = state.lock().await;
+        state_guard
+            .session_last_activity
+            .insert(session_id.to_string(), Instant::now());
+        if let Some(snapshot) = state_guard.session_states.get_mut(session_id) {
+            snapshot.apply_event(event);
+        }
+
+        match event.get("type").and_then(|value| value.as_str()) {
+            Some("agent_start") => {
+                state_guard.prompts_awaiting_start.remove(session_id);
+                state_guard.running_sessions.insert(session_id.to_string());
+                state_guard.run_errors.remove(session_id);
+                state_guard.turn_file_snapshots.remove(session_id);
+            }
+            Some("message_end") => {
+                let message = event.get("message");
+                if message
+                    .and_then(|message| message.get("stopReason"))
+                    .and_then(|value| value.as_str())
+                    == Some("error")
+                {
+                    let error_message = message
+                        .and_then(|message| message.get("errorMessage"))
+                        .and_then(|value| value.as_str())
+                        .unwrap_or("The provider returned an error");
+                    state_guard
+                        .run_errors
+                        .insert(session_id.to_string(), error_message.to_string());
+                }
+            }
+            Some("agent_end") => {
+                state_guard.prompts_awaiting_start.remove(session_id);
+                state_guard.running_sessions.remove(session_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Emit a session event tagged with the session's next `seq` number.
+    pub(crate) fn emit_session_event(app: &AppHandle, session_id: &str, event: serde_json::Value) {
+        let seq = event_sequence::next_sequence(session_id);
+        Self::emit_sequenced_session_event(app, session_id, seq, event);
     }
 
-    fn emit_session_event(app: &AppHandle, session_id: &str, event: serde_json::Value) {
+    fn emit_sequenced_session_event(
+        app: &AppHandle,
+        session_id: &str,
+        seq: u64,
+        event: serde_json::Value,
+    ) {
         let payload = serde_json::json!({
             "sessionId": session_id,
+            "seq": seq,
             "event": event,
         });
 
@@ -637,18 +1081,70 @@ impl EventHandler {
             ));
         }
 
-        Self::emit_agent_event_payload(app, payload_string, "session_event");
+        pipeline_stats::record_session_event_emitted(payload_string.len());
+
+        Self::emit_agent_event_payload(app, payload_string, "session_event", Some(session_id));
     }
 
-    fn emit_agent_event_payload(app: &AppHandle, payload_string: String, payload_kind: &str) {
-        if payload_string.len() <= MAX_AGENT_EVENT_CHARS {
-            if let Err(error) = app.emit("agent-event", payload_string) {
+    /// Emit on `agent-event`, skipping windows subscribed to other sessions
+    /// (see `window_registry`).
+    fn emit_agent_event_payload(
+        app: &AppHandle,
+        payload_string: String,
+        payload_kind: &str,
+        session_id: Option<&str>,
+    ) {
+        let accepts = |target: &EventTarget| window_registry::accepts(target, session_id);
+        let max_chars = event_overflow::max_event_chars();
+
+        let payload_string = if payload_string.len() <= max_chars {
+            payload_string
+        } else {
+            match event_overflow::spill_event_payload(
+                app,
+                &payload_string,
+                payload_kind,
+                session_id,
+            ) {
+                Ok(envelope) => {
+                    pipeline_stats::record_overflowed_payload();
+                    envelope
+                }
+                Err(error) => {
+                    logger::log(format!(
+                        "Failed to spill oversized {} payload, chunking instead: {}",
+                        payload_kind, error
+                    ));
+                    Self::emit_agent_event_chunks(app, &payload_string, payload_kind, session_id);
+                    return;
+                }
+            }
+        };
+
+        let payload_len = payload_string.len();
+        match app.emit_filter("agent-event", payload_string, accepts) {
+            Ok(()) => pipeline_stats::record_emit(payload_len),
+            Err(error) => {
+                pipeline_stats::record_dropped_payload();
                 logger::log(format!("Failed to emit agent event: {}", error));
             }
-            return;
         }
+    }
+
+    /// Fallback for oversized payloads when the overflow file cannot be
+    /// written: split them over several `agent_event_chunk` events.
+    fn emit_agent_event_chunks(
+        app: &AppHandle,
+        payload_string: &str,
+        payload_kind: &str,
+        session_id: Option<&str>,
+    ) {
+        let accepts = |target: &EventTarget| window_registry::accepts(target, session_id);
+        let max_chars = event_overflow::max_event_chars();
 
-        let chunks = split_utf8_by_max_bytes(&payload_string, MAX_AGENT_EVENT_CHUNK_SOURCE_BYTES);
+        pipeline_stats::record_chunked_payload();
+
+        let chunks = split_utf8_by_max_bytes(payload_string, MAX_AGENT_EVENT_CHUNK_SOURCE_BYTES);
         let chunk_count = chunks.len();
         let chunk_id = next_agent_event_chunk_id();
 
@@ -682,7 +1178,8 @@ impl EventHandler {
                 }
             };
 
-            if chunk_payload_string.len() > MAX_AGENT_EVENT_CHARS {
+            if chunk_payload_string.len() > max_chars {
+                pipeline_stats::record_dropped_payload();
                 logger::log(format!(
                     "Skipping {} chunk {}/{} because serialized chunk payload is still oversized (len={})",
                     payload_kind,
@@ -693,7 +1190,9 @@ impl EventHandler {
                 return;
             }
 
-            if let Err(error) = app.emit("agent-event", chunk_payload_string) {
+            let chunk_len = chunk_payload_string.len();
+            if let Err(error) = app.emit_filter("agent-event", chunk_payload_string, accepts) {
+                pipeline_stats::record_dropped_payload();
                 logger::log(format!(
                     "Failed to emit {} chunk {}/{}: {}",
                     payload_kind,
@@ -703,6 +1202,7 @@ impl EventHandler {
                 ));
                 return;
             }
+            pipeline_stats::record_emit(chunk_len);
         }
     }
 
@@ -719,6 +1219,7 @@ impl EventHandler {
         state: &Arc<Mutex<SidecarState>>,
         buffer: &mut Vec<u8>,
         delta_coalescer: &mut SessionDeltaCoalescer,
+        tool_calls: &mut ToolCallTracker,
     ) {
         if buffer.is_empty() {
             return;
@@ -733,7 +1234,7 @@ impl EventHandler {
             return;
         }
 
-        Self::handle_stdout_line(app, state, line, delta_coalescer).await;
+        Self::handle_stdout_line(app, state, line, delta_coalescer, tool_calls).await;
     }
 
     fn flush_stderr_buffer(buffer: &mut Vec<u8>) {
@@ -757,12 +1258,19 @@ impl RpcClient {
         command: RpcCommand,
     ) -> Result<(), String> {
         let child_arc = {
-            let state_guard = state.lock().await;
-            state_guard
+            let mut state_guard = state.lock().await;
+            let child_arc = state_guard
                 .child
                 .as_ref()
                 .ok_or("Agent session not started")?
-                .clone()
+                .clone();
+            crate::commands::admit_command(&mut state_guard, &command.r#type)?;
+            if let Some(session_id) = command.session_id.clone() {
+                state_guard
+                    .session_last_activity
+                    .insert(session_id, Instant::now());
+            }
+            child_arc
         };
 
         let json = Self::serialize_command(&command)?;
@@ -777,15 +1285,18 @@ impl RpcClient {
         child_arc: &Arc<Mutex<tauri_plugin_shell::process::CommandChild>>,
         json: &str,
     ) -> Result<(), String> {
-        let mut child_guard = child_arc.lock().await;
-        child_guard
-            .write(json.as_bytes())
-            .map_err(|e| format!("Failed to write to sidecar: {}", e))?;
-        child_guard
-            .write(b"\n")
-            .map_err(|e| format!("Failed to write newline to sidecar: {}", e))?;
-
-        Ok(())
+        let mut line = Vec::with_capacity(json.len() + 1);
+        line.extend_from_slice(json.as_bytes());
+        line.push(b'\n');
+
+        // `write` is `write_all`, which already retries interrupted writes.
+        // Any other failure may leave part of the line in the pipe, so it is
+        // not retried: resending would corrupt the NDJSON stream.
+        child_arc
+            .lock()
+            .await
+            .write(&line)
+            .map_err(|error| format!("Failed to write to sidecar: {}", error))
     }
 
     async fn remove_pending_request(state: &Arc<Mutex<SidecarState>>, id: &str) {
@@ -809,10 +1320,16 @@ impl RpcClient {
                 .as_ref()
                 .ok_or("Agent session not started")?
                 .clone();
+            crate::commands::admit_command(&mut state_guard, &command.r#type)?;
 
             state_guard
                 .pending_requests
                 .insert(id.clone(), crate::state::PendingRequest { sender: tx });
+            if let Some(session_id) = command.session_id.clone() {
+                state_guard
+                    .session_last_activity
+                    .insert(session_id, Instant::now());
+            }
 
             child_arc
         };
@@ -833,10 +1350,12 @@ impl RpcClient {
                 }
                 Err(_) => {
                     Self::remove_pending_request(state, &id).await;
-                    return Err("Timeout waiting for response".to_string());
+                    return Err(messages::error(ErrorCode::ResponseTimeout, &[]));
                 }
             };
 
+        session_state::observe_response(&mut *state.lock().await, &command, &response);
+
         Ok(response)
     }
 }
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use super::turn_errors::{classify_turn_error, TurnErrorKind};
+use crate::logger;
+use crate::messages::{self, ErrorCode};
+use crate::state::SidecarState;
+
+/// Emit an `auth-expired` event for a session.
+///
+/// `status` is `detected` when a run fails on expired credentials, then
+/// `login`/`retrying`/`failed` while `reauthenticate_and_retry` runs. A
+/// rejected API key is reported as `invalid-api-key` instead: logging in
+/// again cannot fix it.
+pub(crate) fn emit_auth_expired_event(
+    app: &AppHandle,
+    session_id: &str,
+    status: &str,
+    provider: Option<&str>,
+    detail: serde_json::Value,
+) {
+    let payload = serde_json::json!({
+        "sessionId": session_id,
+        "status": status,
+        "provider": provider,
+        "detail": detail,
+    });
+
+    if let Err(error) = app.emit("auth-expired", &payload) {
+        logger::log(format!("Failed to emit auth-expired event: {}", error));
+    }
+}
+
+/// Watch for assistant messages that failed because the provider rejected
+/// the stored credentials, remember the provider, and tell the frontend.
+pub(crate) async fn observe_session_event(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    event: &serde_json::Value,
+) {
+    if event.get("type").and_then(|value| value.as_str()) != Some("message_end") {
+        return;
+    }
+
+    let message = event.get("message");
+    let stop_reason = message
+        .and_then(|message| message.get("stopReason"))
+        .and_then(|value| value.as_str());
+    if stop_reason != Some("error") {
+        return;
+    }
+
+    let error_message = message
+        .and_then(|message| message.get("errorMessage"))
+        .and_then(|value| value.as_str())
+        .unwrap_or_default();
+    let kind = classify_turn_error(error_message);
+    if !matches!(
+        kind,
+        TurnErrorKind::AuthExpired | TurnErrorKind::InvalidApiKey
+    ) {
+        return;
+    }
+
+    let provider = message
+        .and_then(|message| message.get("provider"))
+        .and_then(|value| value.as_str())
+        .map(|provider| provider.to_string());
+
+    if kind == TurnErrorKind::InvalidApiKey {
+        logger::log(format!(
+            "API key rejected for session {} (provider {}): {}",
+            session_id,
+            provider.as_deref().unwrap_or("<unknown>"),
+            error_message
+        ));
+        emit_auth_expired_event(
+            app,
+            session_id,
+            "invalid-api-key",
+            provider.as_deref(),
+            serde_json::json!({
+                "errorMessage": error_message,
+                "message": messages::error(
+                    ErrorCode::InvalidApiKey,
+                    &[&provider.as_deref().unwrap_or("The provider")],
+                ),
+            }),
+        );
+        return;
+    }
+
+    logger::log(format!(
+        "Credentials expired for session {} (provider {}): {}",
+        session_id,
+        provider.as_deref().unwrap_or("<unknown>"),
+        error_message
+    ));
+
+    state
+        .lock()
+        .await
+        .auth_expired_sessions
+        .insert(session_id.to_string(), provider.clone());
+
+    emit_auth_expired_event(
+        app,
+        session_id,
+        "detected",
+        provider.as_deref(),
+        serde_json::json!({ "errorMessage": error_message }),
+    );
+}
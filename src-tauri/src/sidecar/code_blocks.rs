@@ -0,0 +1,146 @@
+//! History of fenced code blocks from assistant messages, so an earlier
+//! snippet can be copied again without scrolling back to it.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use crate::utils::{crypto_random_uuid, now_millis};
+
+/// Oldest blocks are dropped beyond this many per session.
+const MAX_CODE_BLOCKS_PER_SESSION: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeBlock {
+    pub id: String,
+    pub session_id: String,
+    /// 1-based count of assistant messages in the session when the block was seen.
+    pub message_index: u64,
+    /// 0-based position of the block within its message.
+    pub block_index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub code: String,
+    pub line_count: usize,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CodeBlockHistory {
+    assistant_messages: u64,
+    blocks: VecDeque<CodeBlock>,
+}
+
+impl CodeBlockHistory {
+    /// Blocks oldest first.
+    pub(crate) fn blocks(&self) -> impl DoubleEndedIterator<Item = &CodeBlock> {
+        self.blocks.iter()
+    }
+
+    pub(crate) fn find(&self, id: &str) -> Option<&CodeBlock> {
+        self.blocks.iter().find(|block| block.id == id)
+    }
+
+    /// Record the fenced blocks of the session's next assistant message.
+    pub(crate) fn record_message(
+        &mut self,
+        session_id: &str,
+        fenced: Vec<(Option<String>, String)>,
+    ) {
+        self.assistant_messages += 1;
+
+        let created_at = now_millis();
+        for (block_index, (language, code)) in fenced.into_iter().enumerate() {
+            self.blocks.push_back(CodeBlock {
+                id: crypto_random_uuid(),
+                session_id: session_id.to_string(),
+                message_index: self.assistant_messages,
+                block_index,
+                language,
+                line_count: code.lines().count(),
+                code,
+                created_at,
+            });
+        }
+
+        while self.blocks.len() > MAX_CODE_BLOCKS_PER_SESSION {
+            self.blocks.pop_front();
+        }
+    }
+}
+
+/// Fenced blocks (```` ``` ```` or `~~~`) in markdown text, as
+/// `(language, code)`. An unclosed fence runs to the end of the text.
+fn extract_fenced_blocks(text: &str) -> Vec<(Option<String>, String)> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(char, usize, Option<String>, Vec<&str>)> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let fence_char = trimmed.chars().next().filter(|ch| *ch == '`' || *ch == '~');
+        let fence_len = fence_char.map_or(0, |ch| trimmed.chars().take_while(|c| *c == ch).count());
+
+        match open.as_mut() {
+            None => {
+                if let Some(ch) = fence_char.filter(|_| fence_len >= 3) {
+                    let info = trimmed[fence_len..].trim();
+                    let language = info
+                        .split_whitespace()
+                        .next()
+                        .filter(|language| !language.contains('`'))
+                        .map(str::to_string);
+                    open = Some((ch, fence_len, language, Vec::new()));
+                }
+            }
+            Some((ch, len, _, lines)) => {
+                let closes = fence_char == Some(*ch)
+                    && fence_len >= *len
+                    && trimmed[fence_len..].trim().is_empty();
+                if !closes {
+                    lines.push(line);
+                } else if let Some((_, _, language, lines)) = open.take() {
+                    blocks.push((language, lines.join("\n")));
+                }
+            }
+        }
+    }
+
+    if let Some((_, _, language, lines)) = open {
+        blocks.push((language, lines.join("\n")));
+    }
+
+    blocks
+        .into_iter()
+        .filter(|(_, code)| !code.trim().is_empty())
+        .collect()
+}
+
+/// Fenced blocks of an assistant `message_end` event, read from the raw
+/// event before it is compacted for the frontend. `None` for other events.
+pub(crate) fn observe_message_end(
+    event: &serde_json::Value,
+) -> Option<Vec<(Option<String>, String)>> {
+    if event.get("type").and_then(|value| value.as_str()) != Some("message_end") {
+        return None;
+    }
+
+    let message = event.get("message")?;
+    if message.get("role").and_then(|value| value.as_str()) != Some("assistant") {
+        return None;
+    }
+
+    let content = message.get("content")?;
+    let text = match content.as_str() {
+        Some(text) => text.to_string(),
+        None => content
+            .as_array()?
+            .iter()
+            .filter(|block| block.get("type").and_then(|value| value.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(|value| value.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    Some(extract_fenced_blocks(&text))
+}
@@ -0,0 +1,178 @@
+//! Payloads too large for one `agent-event` (agent events and session events
+//! alike) are written to the cache dir and replaced by a small
+//! `agent_event_overflow` envelope; the frontend loads the payload with
+//! `read_event_overflow`.
+//!
+//! The envelope is emitted right away and the file is written on a blocking
+//! thread, so the emit path never waits on disk. Until the write has finished
+//! the payload is served from memory.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use tauri::{AppHandle, Manager};
+
+use crate::logger;
+use crate::preferences::Preferences;
+use crate::utils::crypto_random_uuid;
+
+const OVERFLOW_DIR: &str = "event-overflow";
+const DEFAULT_MAX_EVENT_CHARS: usize = 60_000;
+/// Below this even ordinary streaming events would overflow.
+const MIN_MAX_EVENT_CHARS: usize = 20_000;
+/// Above this a single inline event stalls the WebView IPC channel.
+const MAX_MAX_EVENT_CHARS: usize = 1_000_000;
+/// Overflow files older than this are removed on the next spill. Every
+/// subscribed window reads the file, so it is not deleted on first read.
+const OVERFLOW_FILE_TTL: Duration = Duration::from_secs(60 * 60);
+
+static MAX_EVENT_CHARS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_EVENT_CHARS);
+/// Payloads whose file is not written yet (or could not be written), by
+/// overflow id, with the time they were spilled.
+type PendingPayloads = HashMap<String, (Instant, Arc<String>)>;
+
+static PENDING: Mutex<Option<PendingPayloads>> = Mutex::new(None);
+
+pub(crate) fn validate_max_event_chars(value: Option<usize>) -> Result<Option<usize>, String> {
+    match value {
+        Some(max_chars) if !(MIN_MAX_EVENT_CHARS..=MAX_MAX_EVENT_CHARS).contains(&max_chars) => {
+            Err(format!(
+                "maxEventChars must be between {} and {}, got {}",
+                MIN_MAX_EVENT_CHARS, MAX_MAX_EVENT_CHARS, max_chars
+            ))
+        }
+        _ => Ok(value),
+    }
+}
+
+pub(super) fn apply_preferences(preferences: &Preferences) {
+    let max_chars = preferences
+        .max_event_chars
+        .unwrap_or(DEFAULT_MAX_EVENT_CHARS)
+        .clamp(MIN_MAX_EVENT_CHARS, MAX_MAX_EVENT_CHARS);
+    MAX_EVENT_CHARS.store(max_chars, Ordering::Relaxed);
+}
+
+/// Largest payload emitted inline on `agent-event`.
+pub(super) fn max_event_chars() -> usize {
+    MAX_EVENT_CHARS.load(Ordering::Relaxed)
+}
+
+fn overflow_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join(OVERFLOW_DIR))
+        .map_err(|error| format!("Failed to resolve app cache dir: {}", error))
+}
+
+fn prune_expired(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > OVERFLOW_FILE_TTL);
+        if expired {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn with_pending<T>(update: impl FnOnce(&mut PendingPayloads) -> T) -> T {
+    let mut pending = PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    update(pending.get_or_insert_with(HashMap::new))
+}
+
+fn write_overflow_file(dir: &Path, overflow_id: &str, payload: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    prune_expired(dir);
+
+    let path = dir.join(format!("{}.json", overflow_id));
+    std::fs::write(&path, payload)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Hand `payload` to a background write of its overflow file and return the
+/// serialized reference envelope to emit in its place.
+pub(super) fn spill_event_payload(
+    app: &AppHandle,
+    payload: &str,
+    payload_kind: &str,
+    session_id: Option<&str>,
+) -> Result<String, String> {
+    let dir = overflow_dir(app)?;
+    let overflow_id = crypto_random_uuid();
+
+    let mut envelope = serde_json::json!({
+        "type": "agent_event_overflow",
+        "overflowId": overflow_id,
+        "payloadKind": payload_kind,
+        "bytes": payload.len(),
+    });
+    if let Some(session_id) = session_id {
+        envelope["sessionId"] = serde_json::json!(session_id);
+    }
+    let envelope = serde_json::to_string(&envelope)
+        .map_err(|error| format!("Failed to serialize overflow envelope: {}", error))?;
+
+    let payload = Arc::new(payload.to_string());
+    with_pending(|pending| {
+        // Payloads whose write failed are kept no longer than their file would be.
+        pending.retain(|_, (spilled_at, _)| spilled_at.elapsed() <= OVERFLOW_FILE_TTL);
+        pending.insert(overflow_id.clone(), (Instant::now(), payload.clone()));
+    });
+
+    let payload_kind = payload_kind.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        match write_overflow_file(&dir, &overflow_id, &payload) {
+            Ok(path) => {
+                with_pending(|pending| pending.remove(&overflow_id));
+                logger::log(format!(
+                    "Spilled oversized {} payload (len={}) to {}",
+                    payload_kind,
+                    payload.len(),
+                    path.display()
+                ));
+            }
+            Err(error) => logger::log(format!(
+                "Keeping oversized {} payload {} in memory: {}",
+                payload_kind, overflow_id, error
+            )),
+        }
+    });
+
+    Ok(envelope)
+}
+
+/// The payload referenced by an `agent_event_overflow` envelope.
+pub(crate) fn read_event_overflow(app: &AppHandle, overflow_id: &str) -> Result<String, String> {
+    let is_valid_id = !overflow_id.is_empty()
+        && overflow_id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-');
+    if !is_valid_id {
+        return Err(format!("Invalid overflow id '{}'", overflow_id));
+    }
+
+    if let Some(payload) =
+        with_pending(|pending| pending.get(overflow_id).map(|(_, payload)| payload.clone()))
+    {
+        return Ok(payload.to_string());
+    }
+
+    let path = overflow_dir(app)?.join(format!("{}.json", overflow_id));
+    std::fs::read_to_string(&path)
+        .map_err(|error| format!("No overflow payload {}: {}", overflow_id, error))
+}
@@ -1,3 +1,8 @@
+use super::language_hint::{language_for_content, language_for_path};
+use super::tool_calls::{ToolCallInfo, ToolCallTracker};
+
+use crate::preferences::{self, ToolResultLimitOverrides};
+
 pub(crate) fn shorten_for_log(value: &str, max_chars: usize) -> String {
     if value.chars().count() <= max_chars {
         return value.to_string();
@@ -7,7 +12,10 @@ pub(crate) fn shorten_for_log(value: &str, max_chars: usize) -> String {
     format!("{}…", shortened)
 }
 
-pub(crate) fn compact_session_event_for_frontend(event: serde_json::Value) -> serde_json::Value {
+pub(crate) fn compact_session_event_for_frontend(
+    event: serde_json::Value,
+    tool_calls: &mut ToolCallTracker,
+) -> serde_json::Value {
     let event_type = event.get("type").and_then(|value| value.as_str());
 
     match event_type {
@@ -68,9 +76,18 @@ pub(crate) fn compact_session_event_for_frontend(event: serde_json::Value) -> se
                 "assistantMessageEvent": assistant_message_event,
             })
         }
-        Some("tool_execution_start") => compact_tool_execution_start_event(&event),
+        Some("tool_execution_start") => {
+            tool_calls.start(&event);
+            compact_tool_execution_start_event(&event)
+        }
         Some("tool_execution_update") => compact_tool_execution_update_event(&event),
-        Some("tool_execution_end") => compact_tool_execution_end_event(&event),
+        Some("tool_execution_end") => {
+            let call = event
+                .get("toolCallId")
+                .and_then(|value| value.as_str())
+                .and_then(|tool_call_id| tool_calls.finish(tool_call_id));
+            compact_tool_execution_end_event(&event, call.as_ref())
+        }
         Some("auto_compaction_start") => compact_auto_compaction_start_event(&event),
         Some("auto_compaction_end") => compact_auto_compaction_end_event(&event),
         Some("message_end") => {
@@ -345,7 +362,9 @@ fn compact_tool_execution_update_event(event: &serde_json::Value) -> serde_json:
         .unwrap_or_else(|| serde_json::json!({}));
 
     let partial_result = match event.get("partialResult") {
-        Some(value) => compact_tool_execution_end_result(value),
+        Some(value) => {
+            compact_tool_execution_end_result(value, &ToolResultLimits::for_tool(tool_name))
+        }
         None => serde_json::Value::Null,
     };
 
@@ -358,14 +377,62 @@ fn compact_tool_execution_update_event(event: &serde_json::Value) -> serde_json:
     })
 }
 
-fn compact_tool_result_content_item(item: &serde_json::Value) -> serde_json::Value {
-    const MAX_CONTENT_TEXT_CHARS: usize = 16_000;
+/// Truncation limits for one tool's results, resolved from preferences.
+///
+/// Read on every event so preference changes apply without a restart.
+#[derive(Debug, Clone, Copy)]
+struct ToolResultLimits {
+    max_result_chars: usize,
+    max_content_text_chars: usize,
+    max_content_blocks: usize,
+    max_details_string_chars: usize,
+    max_details_depth: usize,
+}
+
+impl ToolResultLimits {
+    const DEFAULT: Self = Self {
+        max_result_chars: 24_000,
+        max_content_text_chars: 16_000,
+        max_content_blocks: 32,
+        max_details_string_chars: 24_000,
+        max_details_depth: 4,
+    };
+
+    fn for_tool(tool_name: &str) -> Self {
+        let preferences = preferences::current();
+        let global = &preferences.tool_result_limits;
+        let tool = preferences.tool_result_limits_by_tool.get(tool_name);
+        let pick = |field: fn(&ToolResultLimitOverrides) -> Option<usize>, default: usize| {
+            tool.and_then(field).or(field(global)).unwrap_or(default)
+        };
+
+        Self {
+            max_result_chars: pick(|o| o.max_result_chars, Self::DEFAULT.max_result_chars),
+            max_content_text_chars: pick(
+                |o| o.max_content_text_chars,
+                Self::DEFAULT.max_content_text_chars,
+            ),
+            max_content_blocks: pick(|o| o.max_content_blocks, Self::DEFAULT.max_content_blocks),
+            max_details_string_chars: pick(
+                |o| o.max_details_string_chars,
+                Self::DEFAULT.max_details_string_chars,
+            ),
+            max_details_depth: pick(|o| o.max_details_depth, Self::DEFAULT.max_details_depth),
+        }
+    }
+}
+
+fn compact_tool_result_content_item(
+    item: &serde_json::Value,
+    limits: &ToolResultLimits,
+) -> serde_json::Value {
     const MAX_IMAGE_DATA_CHARS: usize = 32_000;
+    let max_text_chars = limits.max_content_text_chars;
 
     match item {
         serde_json::Value::String(text) => serde_json::json!({
             "type": "text",
-            "text": truncate_string_with_notice(text, MAX_CONTENT_TEXT_CHARS),
+            "text": truncate_string_with_notice(text, max_text_chars),
         }),
         serde_json::Value::Object(map) => {
             let block_type = map
@@ -385,13 +452,13 @@ fn compact_tool_result_content_item(item: &serde_json::Value) -> serde_json::Val
                     compacted.insert("type".to_string(), serde_json::json!("text"));
                     compacted.insert(
                         "text".to_string(),
-                        serde_json::json!(truncate_string(text, MAX_CONTENT_TEXT_CHARS)),
+                        serde_json::json!(truncate_string(text, max_text_chars)),
                     );
 
-                    if text_len > MAX_CONTENT_TEXT_CHARS {
+                    if text_len > max_text_chars {
                         compacted.insert(
                             "_truncatedTextChars".to_string(),
-                            serde_json::json!(text_len - MAX_CONTENT_TEXT_CHARS),
+                            serde_json::json!(text_len - max_text_chars),
                         );
                     }
 
@@ -433,8 +500,11 @@ fn compact_tool_result_content_item(item: &serde_json::Value) -> serde_json::Val
     }
 }
 
-fn compact_tool_result_content(content: &serde_json::Value) -> serde_json::Value {
-    const MAX_CONTENT_BLOCKS: usize = 32;
+fn compact_tool_result_content(
+    content: &serde_json::Value,
+    limits: &ToolResultLimits,
+) -> serde_json::Value {
+    let max_blocks = limits.max_content_blocks;
 
     let serde_json::Value::Array(items) = content else {
         return compact_json_value(content, 0);
@@ -442,16 +512,16 @@ fn compact_tool_result_content(content: &serde_json::Value) -> serde_json::Value
 
     let mut compacted = items
         .iter()
-        .take(MAX_CONTENT_BLOCKS)
-        .map(compact_tool_result_content_item)
+        .take(max_blocks)
+        .map(|item| compact_tool_result_content_item(item, limits))
         .collect::<Vec<_>>();
 
-    if items.len() > MAX_CONTENT_BLOCKS {
+    if items.len() > max_blocks {
         compacted.push(serde_json::json!({
             "type": "text",
             "text": format!(
                 "[... truncated {} content block(s)]",
-                items.len() - MAX_CONTENT_BLOCKS
+                items.len() - max_blocks
             ),
         }));
     }
@@ -459,18 +529,21 @@ fn compact_tool_result_content(content: &serde_json::Value) -> serde_json::Value
     serde_json::Value::Array(compacted)
 }
 
-fn compact_tool_result_details(value: &serde_json::Value, depth: usize) -> serde_json::Value {
-    const MAX_DEPTH: usize = 4;
-    const MAX_STRING_CHARS: usize = 24_000;
+fn compact_tool_result_details(
+    value: &serde_json::Value,
+    depth: usize,
+    limits: &ToolResultLimits,
+) -> serde_json::Value {
     const MAX_OBJECT_KEYS: usize = 48;
     const MAX_ARRAY_ITEMS: usize = 64;
 
     match value {
-        serde_json::Value::String(text) => {
-            serde_json::Value::String(truncate_string_with_notice(text, MAX_STRING_CHARS))
-        }
+        serde_json::Value::String(text) => serde_json::Value::String(truncate_string_with_notice(
+            text,
+            limits.max_details_string_chars,
+        )),
         serde_json::Value::Array(items) => {
-            if depth >= MAX_DEPTH {
+            if depth >= limits.max_details_depth {
                 return serde_json::json!({
                     "_truncated": true,
                     "_reason": "max_depth",
@@ -482,7 +555,7 @@ fn compact_tool_result_details(value: &serde_json::Value, depth: usize) -> serde
             let mut compacted = items
                 .iter()
                 .take(MAX_ARRAY_ITEMS)
-                .map(|item| compact_tool_result_details(item, depth + 1))
+                .map(|item| compact_tool_result_details(item, depth + 1, limits))
                 .collect::<Vec<_>>();
 
             if items.len() > MAX_ARRAY_ITEMS {
@@ -494,7 +567,7 @@ fn compact_tool_result_details(value: &serde_json::Value, depth: usize) -> serde
             serde_json::Value::Array(compacted)
         }
         serde_json::Value::Object(map) => {
-            if depth >= MAX_DEPTH {
+            if depth >= limits.max_details_depth {
                 return serde_json::json!({
                     "_truncated": true,
                     "_reason": "max_depth",
@@ -514,7 +587,10 @@ fn compact_tool_result_details(value: &serde_json::Value, depth: usize) -> serde
                     break;
                 }
 
-                compacted.insert(key.clone(), compact_tool_result_details(entry, depth + 1));
+                compacted.insert(
+                    key.clone(),
+                    compact_tool_result_details(entry, depth + 1, limits),
+                );
             }
 
             serde_json::Value::Object(compacted)
@@ -525,19 +601,23 @@ fn compact_tool_result_details(value: &serde_json::Value, depth: usize) -> serde
 
 fn compact_canonical_tool_result(
     result: &serde_json::Map<String, serde_json::Value>,
+    limits: &ToolResultLimits,
 ) -> serde_json::Value {
     const MAX_EXTRA_KEYS: usize = 8;
 
     let mut compacted = serde_json::Map::new();
 
     if let Some(content) = result.get("content") {
-        compacted.insert("content".to_string(), compact_tool_result_content(content));
+        compacted.insert(
+            "content".to_string(),
+            compact_tool_result_content(content, limits),
+        );
     }
 
     if let Some(details) = result.get("details") {
         compacted.insert(
             "details".to_string(),
-            compact_tool_result_details(details, 0),
+            compact_tool_result_details(details, 0, limits),
         );
     }
 
@@ -567,24 +647,108 @@ fn compact_canonical_tool_result(
     serde_json::Value::Object(compacted)
 }
 
-fn compact_tool_execution_end_result(result: &serde_json::Value) -> serde_json::Value {
-    const MAX_RESULT_CHARS: usize = 24_000;
-
+fn compact_tool_execution_end_result(
+    result: &serde_json::Value,
+    limits: &ToolResultLimits,
+) -> serde_json::Value {
     match result {
         serde_json::Value::String(text) => {
-            serde_json::Value::String(truncate_string_with_notice(text, MAX_RESULT_CHARS))
+            serde_json::Value::String(truncate_string_with_notice(text, limits.max_result_chars))
         }
-        serde_json::Value::Array(_) => compact_tool_result_content(result),
+        serde_json::Value::Array(_) => compact_tool_result_content(result, limits),
         serde_json::Value::Object(map)
             if map.contains_key("content") || map.contains_key("details") =>
         {
-            compact_canonical_tool_result(map)
+            compact_canonical_tool_result(map, limits)
         }
         _ => compact_json_value(result, 0),
     }
 }
 
-fn compact_tool_execution_end_event(event: &serde_json::Value) -> serde_json::Value {
+/// Language hint for a tool result so the frontend can highlight it without guessing.
+///
+/// Edit results carrying a diff are tagged `diff`; file tools use the path
+/// extension; anything else falls back to content heuristics.
+fn tool_result_language(
+    call: Option<&ToolCallInfo>,
+    result: &serde_json::Value,
+) -> Option<&'static str> {
+    if result
+        .get("details")
+        .and_then(|details| details.get("diff"))
+        .and_then(|value| value.as_str())
+        .is_some()
+    {
+        return Some("diff");
+    }
+
+    if let Some(language) = call
+        .filter(|call| call.tool_name != "bash")
+        .and_then(|call| call.path.as_deref())
+        .and_then(language_for_path)
+    {
+        return Some(language);
+    }
+
+    let text = match result {
+        serde_json::Value::String(text) => Some(text.as_str()),
+        _ => result
+            .get("content")
+            .and_then(|content| content.as_array())
+            .and_then(|items| {
+                items.iter().find_map(|item| {
+                    (item.get("type").and_then(|value| value.as_str()) == Some("text"))
+                        .then(|| item.get("text").and_then(|value| value.as_str()))
+                        .flatten()
+                })
+            }),
+    }?;
+
+    language_for_content(text)
+}
+
+/// Full text output of a `tool_execution_end` event when compaction would truncate it.
+///
+/// String results and text content blocks are joined in order; `None` when the
+/// output fits within the tool's limits or carries no text.
+pub(crate) fn oversized_tool_output_text(event: &serde_json::Value) -> Option<String> {
+    if event.get("type").and_then(|value| value.as_str()) != Some("tool_execution_end") {
+        return None;
+    }
+
+    let tool_name = event
+        .get("toolName")
+        .and_then(|value| value.as_str())
+        .unwrap_or_default();
+    let limits = ToolResultLimits::for_tool(tool_name);
+
+    let (texts, max_chars) = match event.get("result")? {
+        serde_json::Value::String(text) => (vec![text.as_str()], limits.max_result_chars),
+        result => {
+            let content = result.get("content").unwrap_or(result).as_array()?;
+            let texts = content
+                .iter()
+                .filter_map(|item| match item {
+                    serde_json::Value::String(text) => Some(text.as_str()),
+                    _ => (item.get("type").and_then(|value| value.as_str()) == Some("text"))
+                        .then(|| item.get("text").and_then(|value| value.as_str()))
+                        .flatten(),
+                })
+                .collect::<Vec<_>>();
+            (texts, limits.max_content_text_chars)
+        }
+    };
+
+    texts
+        .iter()
+        .any(|text| text.len() > max_chars && text.chars().count() > max_chars)
+        .then(|| texts.join("\n"))
+}
+
+fn compact_tool_execution_end_event(
+    event: &serde_json::Value,
+    call: Option<&ToolCallInfo>,
+) -> serde_json::Value {
     let tool_call_id = event
         .get("toolCallId")
         .and_then(|value| value.as_str())
@@ -600,16 +764,28 @@ fn compact_tool_execution_end_event(event: &serde_json::Value) -> serde_json::Va
         .and_then(|value| value.as_bool())
         .unwrap_or(false);
 
+    let language = event
+        .get("result")
+        .and_then(|value| tool_result_language(call, value));
+
     let result = match event.get("result") {
-        Some(value) => compact_tool_execution_end_result(value),
+        Some(value) => {
+            compact_tool_execution_end_result(value, &ToolResultLimits::for_tool(tool_name))
+        }
         None => serde_json::Value::Null,
     };
 
-    serde_json::json!({
+    let mut compacted = serde_json::json!({
         "type": "tool_execution_end",
         "toolCallId": tool_call_id,
         "toolName": tool_name,
         "isError": is_error,
         "result": result,
-    })
+    });
+
+    if let Some(language) = language {
+        compacted["language"] = serde_json::json!(language);
+    }
+
+    compacted
 }
@@ -0,0 +1,38 @@
+//! Per-session sequence numbers on emitted session events, so the frontend
+//! can tell a dropped or reordered event from a quiet session.
+//!
+//! Numbers start at 1 and count up while the session is open. Closing the
+//! session forgets its counter, so a resumed session starts again at 1.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+static NEXT_SEQUENCE: Mutex<Option<HashMap<String, u64>>> = Mutex::new(None);
+
+fn with_sequences<T>(update: impl FnOnce(&mut HashMap<String, u64>) -> T) -> T {
+    let mut guard = NEXT_SEQUENCE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    update(guard.get_or_insert_with(HashMap::new))
+}
+
+/// Take the session's next sequence number. The lock is released before the
+/// caller emits, so events emitted from several tasks can arrive out of
+/// order; the frontend reports that rather than waiting on a slow emit.
+pub(super) fn next_sequence(session_id: &str) -> u64 {
+    with_sequences(|sequences| {
+        let next = sequences.entry(session_id.to_string()).or_insert(0);
+        *next += 1;
+        *next
+    })
+}
+
+/// Forget the counter of a closed session.
+pub(crate) fn forget_session_sequence(session_id: &str) {
+    with_sequences(|sequences| sequences.remove(session_id));
+}
+
+/// Forget every counter, when the sidecar and all its sessions are gone.
+pub(crate) fn forget_all_sequences() {
+    with_sequences(HashMap::clear);
+}
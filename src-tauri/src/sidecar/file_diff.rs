@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use tauri::{AppHandle, Emitter};
+
+use crate::logger;
+
+/// Keep the diff payload comfortably under the WebView IPC limit.
+const MAX_DIFF_CHARS: usize = 48_000;
+const CONTEXT_LINES: usize = 3;
+
+/// Pre-edit content captured by the host when an edit/write tool started.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct FileSnapshot {
+    pub path: String,
+    /// `None` when the file did not exist before the tool ran.
+    pub before: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileDiffEvent {
+    pub session_id: String,
+    pub tool_call_id: String,
+    pub path: String,
+    pub created: bool,
+    /// Unified diff with `CONTEXT_LINES` lines of context.
+    pub diff: String,
+    pub additions: usize,
+    pub deletions: usize,
+    pub truncated: bool,
+}
+
+/// Take the host's `fileSnapshot` off a successful `tool_execution_end` event.
+pub(crate) fn take_file_snapshot(event: &mut serde_json::Value) -> Option<FileSnapshot> {
+    let object = event.as_object_mut()?;
+    let snapshot = object.remove("fileSnapshot")?;
+
+    if object.get("type").and_then(|value| value.as_str()) != Some("tool_execution_end")
+        || object.get("isError").and_then(|value| value.as_bool()) == Some(true)
+    {
+        return None;
+    }
+
+    serde_json::from_value(snapshot).ok()
+}
+
+/// Unified diff of one file between two states, in `git diff` form.
+pub(crate) struct FilePatch {
+    pub diff: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Diff `before` against `after`, where `None` means the file did not exist.
+/// Returns `None` when nothing changed.
+pub(crate) fn file_patch(
+    path: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Option<FilePatch> {
+    let (old_text, new_text) = (before.unwrap_or_default(), after.unwrap_or_default());
+    if old_text == new_text {
+        return None;
+    }
+
+    let text_diff = TextDiff::from_lines(old_text, new_text);
+    let (mut additions, mut deletions) = (0, 0);
+    for change in text_diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => additions += 1,
+            ChangeTag::Delete => deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+
+    let old_header = match before {
+        Some(_) => format!("a/{}", path),
+        None => "/dev/null".to_string(),
+    };
+    let new_header = match after {
+        Some(_) => format!("b/{}", path),
+        None => "/dev/null".to_string(),
+    };
+    let diff = text_diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&old_header, &new_header)
+        .to_string();
+
+    Some(FilePatch {
+        diff,
+        additions,
+        deletions,
+    })
+}
+
+fn build_file_diff(
+    session_id: String,
+    tool_call_id: String,
+    snapshot: FileSnapshot,
+) -> Option<FileDiffEvent> {
+    let after = match std::fs::read_to_string(&snapshot.path) {
+        Ok(content) => content,
+        Err(error) => {
+            logger::log(format!(
+                "file-diff: failed to read {}: {}",
+                snapshot.path, error
+            ));
+            return None;
+        }
+    };
+
+    let created = snapshot.before.is_none();
+    let FilePatch {
+        mut diff,
+        additions,
+        deletions,
+    } = file_patch(&snapshot.path, snapshot.before.as_deref(), Some(&after))?;
+
+    let truncated = diff.len() > MAX_DIFF_CHARS;
+    if truncated {
+        let mut cut = MAX_DIFF_CHARS;
+        while !diff.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        diff.truncate(diff[..cut].rfind('\n').map_or(cut, |index| index + 1));
+    }
+
+    Some(FileDiffEvent {
+        session_id,
+        tool_call_id,
+        path: snapshot.path,
+        created,
+        diff,
+        additions,
+        deletions,
+        truncated,
+    })
+}
+
+/// Diff the edited file against its snapshot and emit a `file-diff` event.
+///
+/// Runs on a blocking thread so large files don't stall the event listener.
+pub(crate) fn emit_file_diff(
+    app: &AppHandle,
+    session_id: &str,
+    tool_call_id: &str,
+    snapshot: FileSnapshot,
+) {
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    let tool_call_id = tool_call_id.to_string();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(event) = build_file_diff(session_id, tool_call_id, snapshot) else {
+            return;
+        };
+
+        if let Err(error) = app.emit("file-diff", &event) {
+            logger::log(format!("Failed to emit file-diff event: {}", error));
+        }
+    });
+}
@@ -0,0 +1,103 @@
+use std::path::Path;
+
+/// Syntax-highlighting language id for a file path, by extension or well-known name.
+pub(crate) fn language_for_path(path: &str) -> Option<&'static str> {
+    let path = Path::new(path);
+    let file_name = path.file_name()?.to_str()?;
+
+    let by_name = match file_name {
+        "Dockerfile" => Some("dockerfile"),
+        "Makefile" | "makefile" | "GNUmakefile" => Some("makefile"),
+        "CMakeLists.txt" => Some("cmake"),
+        _ => None,
+    };
+    if by_name.is_some() {
+        return by_name;
+    }
+
+    let language = match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "svelte" => "svelte",
+        "vue" => "vue",
+        "py" | "pyi" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "scala" => "scala",
+        "dart" => "dart",
+        "zig" => "zig",
+        "ex" | "exs" => "elixir",
+        "hs" => "haskell",
+        "lua" => "lua",
+        "sh" | "bash" | "zsh" => "bash",
+        "ps1" => "powershell",
+        "sql" => "sql",
+        "json" | "jsonc" | "json5" => "json",
+        "toml" => "toml",
+        "yaml" | "yml" => "yaml",
+        "xml" | "xsd" | "svg" | "plist" => "xml",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "md" | "markdown" => "markdown",
+        "diff" | "patch" => "diff",
+        "ini" | "cfg" | "conf" => "ini",
+        "graphql" | "gql" => "graphql",
+        "proto" => "protobuf",
+        "nix" => "nix",
+        _ => return None,
+    };
+
+    Some(language)
+}
+
+/// Best-effort language guess from the text itself (shebangs, diffs, JSON, XML).
+pub(crate) fn language_for_content(text: &str) -> Option<&'static str> {
+    let trimmed = text.trim_start();
+    let first_line = trimmed.lines().next().unwrap_or_default();
+
+    if let Some(shebang) = first_line.strip_prefix("#!") {
+        return if shebang.contains("python") {
+            Some("python")
+        } else if shebang.contains("node") || shebang.contains("bun") || shebang.contains("deno") {
+            Some("javascript")
+        } else if shebang.contains("sh") {
+            Some("bash")
+        } else {
+            None
+        };
+    }
+
+    if trimmed.starts_with("diff --git ")
+        || (trimmed.starts_with("--- ") && trimmed.contains("\n+++ "))
+        || trimmed.starts_with("@@ ")
+    {
+        return Some("diff");
+    }
+
+    if trimmed.starts_with("<?xml") {
+        return Some("xml");
+    }
+
+    if trimmed.starts_with("<!DOCTYPE html") || trimmed.starts_with("<html") {
+        return Some("html");
+    }
+
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(text).is_ok()
+    {
+        return Some("json");
+    }
+
+    None
+}
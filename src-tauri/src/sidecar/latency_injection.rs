@@ -0,0 +1,89 @@
+//! Artificial delays and drops in the sidecar event pipeline and RPC
+//! responses, for exercising the frontend's loading/timeout states and the
+//! create-session retries.
+//!
+//! Configured by `Preferences::latency_injection`, but only honoured by builds
+//! with the `latency-injection` feature; other builds pass everything through.
+//! Drops hit every Nth message rather than a random share, so a scenario plays
+//! out the same way on every run.
+
+#[cfg(feature = "latency-injection")]
+mod enabled {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+
+    use crate::logger;
+    use crate::preferences;
+
+    static EVENTS_SEEN: AtomicU64 = AtomicU64::new(0);
+    static RESPONSES_SEEN: AtomicU64 = AtomicU64::new(0);
+
+    /// Sleep `delay_ms`, then report whether the message should go through.
+    async fn pass(kind: &str, seen: &AtomicU64, delay_ms: u64, drop_every: u64) -> bool {
+        if delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        }
+
+        if drop_every == 0 {
+            return true;
+        }
+
+        let index = seen.fetch_add(1, Ordering::Relaxed) + 1;
+        if !index.is_multiple_of(drop_every) {
+            return true;
+        }
+
+        logger::log(format!("Latency injection: dropped {} #{}", kind, index));
+        false
+    }
+
+    pub(super) async fn event() -> bool {
+        let preferences = preferences::current();
+        let settings = &preferences.latency_injection;
+        pass(
+            "event",
+            &EVENTS_SEEN,
+            settings.event_delay_ms,
+            settings.event_drop_every,
+        )
+        .await
+    }
+
+    pub(super) async fn response() -> bool {
+        let preferences = preferences::current();
+        let settings = &preferences.latency_injection;
+        pass(
+            "response",
+            &RESPONSES_SEEN,
+            settings.response_delay_ms,
+            settings.response_drop_every,
+        )
+        .await
+    }
+}
+
+/// Delay a sidecar event; `false` means drop it.
+pub(crate) async fn pass_event() -> bool {
+    #[cfg(feature = "latency-injection")]
+    {
+        enabled::event().await
+    }
+
+    #[cfg(not(feature = "latency-injection"))]
+    {
+        true
+    }
+}
+
+/// Delay an RPC response; `false` means drop it, leaving the caller to time out.
+pub(crate) async fn pass_response() -> bool {
+    #[cfg(feature = "latency-injection")]
+    {
+        enabled::response().await
+    }
+
+    #[cfg(not(feature = "latency-injection"))]
+    {
+        true
+    }
+}
@@ -118,7 +118,7 @@ fn source_stamp(path: &Path) -> Result<String, String> {
 }
 
 #[cfg(target_os = "linux")]
-fn validate_linux_sidecar_binary(path: &Path) -> Result<(), String> {
+pub(crate) fn validate_linux_sidecar_binary(path: &Path) -> Result<(), String> {
     let mut file = fs::File::open(path).map_err(|error| {
         format!(
             "Failed to open {} for validation: {}",
@@ -146,13 +146,9 @@ fn validate_linux_sidecar_binary(path: &Path) -> Result<(), String> {
     ))
 }
 
+/// Directory the bundled sidecar is extracted to; it may not exist yet.
 #[cfg(target_os = "linux")]
-pub(crate) fn prepare_linux_sidecar_runtime(app: &AppHandle) -> Result<PathBuf, String> {
-    let source_dir = resolve_linux_sidecar_source_dir(app)?;
-    let compressed_binary = resolve_compressed_sidecar_path(&source_dir)
-        .ok_or_else(|| "Linux sidecar compressed binary not found".to_string())?;
-    let source_stamp = source_stamp(&compressed_binary)?;
-
+pub(crate) fn linux_sidecar_runtime_dir(app: &AppHandle) -> Result<PathBuf, String> {
     let app_local_data_dir = app.path().app_local_data_dir().map_err(|error| {
         format!(
             "Failed to resolve app local data directory for linux sidecar: {}",
@@ -160,7 +156,43 @@ pub(crate) fn prepare_linux_sidecar_runtime(app: &AppHandle) -> Result<PathBuf,
         )
     })?;
 
-    let runtime_dir = app_local_data_dir.join("sidecar").join("linux-runtime");
+    Ok(app_local_data_dir.join("sidecar").join("linux-runtime"))
+}
+
+/// Drop the extraction stamp so the next prepare re-extracts the whole runtime.
+#[cfg(target_os = "linux")]
+pub(crate) fn repair_linux_sidecar_runtime(app: &AppHandle) -> Result<PathBuf, String> {
+    let stamp_path = linux_sidecar_runtime_dir(app)?.join(".stamp");
+    if stamp_path.exists() {
+        fs::remove_file(&stamp_path).map_err(|error| {
+            format!(
+                "Failed to remove linux sidecar stamp {}: {}",
+                stamp_path.display(),
+                error
+            )
+        })?;
+    }
+
+    prepare_linux_sidecar_runtime(app)
+}
+
+/// Stamp an extracted runtime carries when it matches the bundled sidecar.
+#[cfg(target_os = "linux")]
+pub(crate) fn installed_linux_sidecar_stamp(app: &AppHandle) -> Result<String, String> {
+    let source_dir = resolve_linux_sidecar_source_dir(app)?;
+    let compressed_binary = resolve_compressed_sidecar_path(&source_dir)
+        .ok_or_else(|| "Linux sidecar compressed binary not found".to_string())?;
+    source_stamp(&compressed_binary)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn prepare_linux_sidecar_runtime(app: &AppHandle) -> Result<PathBuf, String> {
+    let source_dir = resolve_linux_sidecar_source_dir(app)?;
+    let compressed_binary = resolve_compressed_sidecar_path(&source_dir)
+        .ok_or_else(|| "Linux sidecar compressed binary not found".to_string())?;
+    let source_stamp = source_stamp(&compressed_binary)?;
+
+    let runtime_dir = linux_sidecar_runtime_dir(app)?;
     let stamp_path = runtime_dir.join(".stamp");
     let extracted_binary = runtime_dir.join(SIDECAR_BINARY_NAME);
 
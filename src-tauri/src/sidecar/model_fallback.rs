@@ -0,0 +1,262 @@
+use std::sync::Arc;
+
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use super::session_state::session_state_snapshot;
+use super::turn_errors::{classify_turn_error, TurnErrorKind};
+use super::{EventHandler, RpcClient};
+use crate::logger;
+use crate::preferences::{self, ModelRef};
+use crate::state::SidecarState;
+use crate::types::{RpcCommand, RpcResponse};
+use crate::utils::crypto_random_uuid;
+
+const FALLBACK_RPC_TIMEOUT_SECS: u64 = 10;
+
+/// Watch session events for provider outages and trigger a model fallback.
+///
+/// A failed assistant message marks the session; the switch + retry only runs
+/// once the agent run has ended so the retried prompt doesn't race the
+/// failing turn.
+pub(crate) async fn observe_session_event(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    event: &serde_json::Value,
+) {
+    match event.get("type").and_then(|value| value.as_str()) {
+        Some("message_end") => {
+            let message = event.get("message");
+            let stop_reason = message
+                .and_then(|message| message.get("stopReason"))
+                .and_then(|value| value.as_str());
+            if stop_reason != Some("error") {
+                return;
+            }
+
+            let error_message = message
+                .and_then(|message| message.get("errorMessage"))
+                .and_then(|value| value.as_str())
+                .unwrap_or_default();
+
+            if classify_turn_error(error_message) != TurnErrorKind::ProviderUnavailable {
+                return;
+            }
+
+            if preferences::current().fallback_models.is_empty() {
+                return;
+            }
+
+            let mut state_guard = state.lock().await;
+            if state_guard.model_fallback_retried.contains(session_id)
+                || !state_guard.last_prompts.contains_key(session_id)
+            {
+                return;
+            }
+
+            state_guard
+                .pending_model_fallbacks
+                .insert(session_id.to_string(), error_message.to_string());
+        }
+        Some("agent_end") => {
+            let pending = {
+                let mut state_guard = state.lock().await;
+                state_guard.pending_model_fallbacks.remove(session_id)
+            };
+
+            let Some(error_message) = pending else {
+                return;
+            };
+
+            let app = app.clone();
+            let state = state.clone();
+            let session_id = session_id.to_string();
+
+            // Run detached: the fallback awaits RPC responses that are read by
+            // the same event listener that is calling us.
+            tauri::async_runtime::spawn(async move {
+                run_model_fallback(&app, &state, &session_id, error_message).await;
+            });
+        }
+        _ => {}
+    }
+}
+
+fn next_fallback_model(fallbacks: &[ModelRef], current: Option<&ModelRef>) -> Option<ModelRef> {
+    let start = current
+        .and_then(|current| fallbacks.iter().position(|model| model == current))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+
+    fallbacks
+        .iter()
+        .skip(start)
+        .find(|model| Some(*model) != current)
+        .cloned()
+}
+
+async fn send_session_command(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    command_type: &str,
+    model: Option<&ModelRef>,
+) -> Result<RpcResponse, String> {
+    let id = crypto_random_uuid();
+    let cmd = RpcCommand {
+        id: Some(id.clone()),
+        r#type: command_type.to_string(),
+        session_id: Some(session_id.to_string()),
+        cwd: None,
+        message: None,
+        provider: model.map(|model| model.provider.clone()),
+        model_id: model.map(|model| model.model_id.clone()),
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    };
+
+    RpcClient::send_command_with_response(state, cmd, id, FALLBACK_RPC_TIMEOUT_SECS).await
+}
+
+async fn current_model(state: &Arc<Mutex<SidecarState>>, session_id: &str) -> Option<ModelRef> {
+    let model = session_state_snapshot(state, session_id, false)
+        .await
+        .ok()?
+        .model?;
+    Some(ModelRef {
+        provider: model.provider,
+        model_id: model.id,
+    })
+}
+
+fn emit_fallback_event(
+    app: &AppHandle,
+    session_id: &str,
+    status: &str,
+    from: Option<&ModelRef>,
+    to: Option<&ModelRef>,
+    error_message: &str,
+) {
+    EventHandler::emit_session_event(
+        app,
+        session_id,
+        serde_json::json!({
+            "type": "model_fallback",
+            "status": status,
+            "from": from,
+            "to": to,
+            "errorMessage": error_message,
+        }),
+    );
+}
+
+async fn run_model_fallback(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    error_message: String,
+) {
+    let fallbacks = preferences::current().fallback_models.clone();
+    let current = current_model(state, session_id).await;
+
+    let Some(next) = next_fallback_model(&fallbacks, current.as_ref()) else {
+        logger::log(format!(
+            "Model fallback exhausted for session {}: {}",
+            session_id, error_message
+        ));
+        emit_fallback_event(
+            app,
+            session_id,
+            "exhausted",
+            current.as_ref(),
+            None,
+            &error_message,
+        );
+        return;
+    };
+
+    logger::log(format!(
+        "Provider unavailable for session {}; switching to fallback {}/{}",
+        session_id, next.provider, next.model_id
+    ));
+    emit_fallback_event(
+        app,
+        session_id,
+        "switching",
+        current.as_ref(),
+        Some(&next),
+        &error_message,
+    );
+
+    let switch_error = match send_session_command(state, session_id, "set_model", Some(&next)).await
+    {
+        Ok(response) if response.success => None,
+        Ok(response) => Some(
+            response
+                .error
+                .unwrap_or_else(|| "set_model failed".to_string()),
+        ),
+        Err(error) => Some(error),
+    };
+
+    if let Some(switch_error) = switch_error {
+        logger::log(format!(
+            "Model fallback switch failed for session {}: {}",
+            session_id, switch_error
+        ));
+        emit_fallback_event(
+            app,
+            session_id,
+            "failed",
+            current.as_ref(),
+            Some(&next),
+            &switch_error,
+        );
+        return;
+    }
+
+    let prompt = {
+        let mut state_guard = state.lock().await;
+        state_guard
+            .model_fallback_retried
+            .insert(session_id.to_string());
+        state_guard.last_prompts.get(session_id).cloned()
+    };
+
+    let Some(mut prompt) = prompt else {
+        emit_fallback_event(
+            app,
+            session_id,
+            "failed",
+            current.as_ref(),
+            Some(&next),
+            "No prompt recorded to retry",
+        );
+        return;
+    };
+
+    prompt.id = Some(crypto_random_uuid());
+
+    match crate::commands::send_or_hold_prompt(app, state, prompt, None).await {
+        Ok(_) => emit_fallback_event(
+            app,
+            session_id,
+            "retrying",
+            current.as_ref(),
+            Some(&next),
+            &error_message,
+        ),
+        Err(error) => emit_fallback_event(
+            app,
+            session_id,
+            "failed",
+            current.as_ref(),
+            Some(&next),
+            &error,
+        ),
+    }
+}
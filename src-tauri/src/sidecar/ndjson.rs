@@ -1,3 +1,54 @@
+/// Outcome of reading one sidecar stdout line.
+#[derive(Debug)]
+pub(crate) enum ParsedLine {
+    /// Blank once control bytes and escapes are removed.
+    Empty,
+    /// `line` is the JSON text that parsed (sanitized when the raw line did not).
+    Json {
+        line: String,
+        value: serde_json::Value,
+    },
+    /// Neither the raw line nor its sanitized form is valid JSON.
+    Invalid {
+        raw: String,
+        sanitized: String,
+        error: String,
+    },
+}
+
+/// Parse one stdout line: as is first, then after `sanitize_json_line`.
+///
+/// Pure and total: any input yields a `ParsedLine` without panicking, so it
+/// can be driven directly by a fuzzer.
+pub(crate) fn parse_stdout_line(raw: String) -> ParsedLine {
+    let trimmed = raw.trim();
+    if is_probably_clean_json_line(trimmed) {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed) {
+            return ParsedLine::Json {
+                line: trimmed.to_string(),
+                value,
+            };
+        }
+    }
+
+    let sanitized = sanitize_json_line(raw.clone());
+    if sanitized.trim().is_empty() {
+        return ParsedLine::Empty;
+    }
+
+    match serde_json::from_str::<serde_json::Value>(&sanitized) {
+        Ok(value) => ParsedLine::Json {
+            line: sanitized,
+            value,
+        },
+        Err(error) => ParsedLine::Invalid {
+            raw,
+            sanitized,
+            error: error.to_string(),
+        },
+    }
+}
+
 pub(crate) fn sanitize_json_line(line: String) -> String {
     let trimmed = line
         .trim()
@@ -135,3 +186,52 @@ fn extract_json_object_candidate(input: &str) -> Option<String> {
 
     Some(input[start..=end].to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed_type(raw: &str) -> Option<String> {
+        match parse_stdout_line(raw.to_string()) {
+            ParsedLine::Json { value, .. } => value
+                .get("type")
+                .and_then(|value| value.as_str())
+                .map(str::to_string),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parse_stdout_line_recovers_wrapped_json() {
+        assert_eq!(parsed_type(r#"{"type":"ready"}"#).as_deref(), Some("ready"));
+        assert_eq!(
+            parsed_type("\u{feff}\u{1b}[2K{\"type\":\"ready\"}\u{1b}]0;title\u{07}\0").as_deref(),
+            Some("ready")
+        );
+        assert!(matches!(
+            parse_stdout_line("\u{1b}[0m  \0".to_string()),
+            ParsedLine::Empty
+        ));
+        assert!(matches!(
+            parse_stdout_line("{\"type\":".to_string()),
+            ParsedLine::Invalid { .. }
+        ));
+    }
+
+    /// Fuzz-style sweep: arbitrary bytes (decoded as stdout is) never panic.
+    #[test]
+    fn parse_stdout_line_accepts_arbitrary_input() {
+        const ALPHABET: &[u8] = b"{}[]\"\\:,\x1b\x07\0\r\n PX^_]a0\xef\xbb\xbf\xc3\xff";
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        for _ in 0..5_000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let len = (seed % 48) as usize;
+            let bytes = (0..len)
+                .map(|index| ALPHABET[((seed >> (index % 58)) as usize + index) % ALPHABET.len()])
+                .collect::<Vec<_>>();
+            let _ = parse_stdout_line(decode_utf8_lossy(bytes));
+        }
+    }
+}
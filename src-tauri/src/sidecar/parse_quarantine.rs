@@ -0,0 +1,231 @@
+//! Quarantine for sidecar stdout lines that fail to parse even after
+//! sanitizing, so malformed output can be inspected after the fact instead
+//! of only scrolling by in the log.
+//!
+//! Each failure is appended as one JSON line to `sidecar-parse-failures.jsonl`
+//! next to the log file, and the most recent ones are kept in memory for
+//! `get_parse_failures`. The file is written on its own thread, at most
+//! `MAX_WRITES_PER_WINDOW` failures per `WRITE_WINDOW`, so a sidecar spewing
+//! garbage neither stalls the stdout handler nor floods the disk.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::ndjson::debug_prefix_codepoints;
+use crate::logger;
+use crate::utils::now_millis;
+
+const QUARANTINE_FILE_NAME: &str = "sidecar-parse-failures.jsonl";
+/// The file is moved aside to `<name>.1` once it grows past this.
+const MAX_QUARANTINE_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Raw line bytes kept per failure.
+const MAX_QUARANTINED_LINE_CHARS: usize = 64 * 1024;
+const MAX_RECENT_FAILURES: usize = 100;
+const WRITE_WINDOW: Duration = Duration::from_secs(1);
+const MAX_WRITES_PER_WINDOW: u32 = 20;
+/// Failures waiting for the writer thread; more are dropped from the file.
+const MAX_QUEUED_WRITES: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseFailure {
+    pub at: u64,
+    /// 1-based index of the line in this sidecar's stdout since app start.
+    pub line_number: u64,
+    pub error: String,
+    pub raw_len: usize,
+    /// The raw line, cut to 64K chars.
+    pub raw: String,
+    pub raw_truncated: bool,
+    /// Code points the sanitized line starts with, to spot stray bytes.
+    pub sanitized_prefix: String,
+    /// `type` of the last line that parsed before this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseFailuresResponse {
+    pub quarantine_path: String,
+    /// Failures since the app started.
+    pub total: u64,
+    /// Most recent failures, newest first.
+    pub failures: Vec<ParseFailure>,
+}
+
+struct Context {
+    previous_type: Option<String>,
+    recent: VecDeque<ParseFailure>,
+    window_start: Option<Instant>,
+    writes_in_window: u32,
+    /// Failures kept out of the file since the last one written.
+    skipped_writes: u64,
+}
+
+static LINES_SEEN: AtomicU64 = AtomicU64::new(0);
+static FAILURES: AtomicU64 = AtomicU64::new(0);
+static CONTEXT: Mutex<Context> = Mutex::new(Context {
+    previous_type: None,
+    recent: VecDeque::new(),
+    window_start: None,
+    writes_in_window: 0,
+    skipped_writes: 0,
+});
+static WRITER: OnceLock<Option<SyncSender<ParseFailure>>> = OnceLock::new();
+
+fn quarantine_path() -> PathBuf {
+    logger::log_path().with_file_name(QUARANTINE_FILE_NAME)
+}
+
+/// Count a line that parsed and remember its type as context for the next
+/// failure.
+pub(crate) fn record_parsed_line(value: &serde_json::Value) {
+    LINES_SEEN.fetch_add(1, Ordering::Relaxed);
+    let line_type = value.get("type").and_then(|value| value.as_str());
+    if let Ok(mut context) = CONTEXT.lock() {
+        if context.previous_type.as_deref() != line_type {
+            context.previous_type = line_type.map(str::to_string);
+        }
+    }
+}
+
+fn append_to_file(failure: &ParseFailure) -> Result<(), String> {
+    let path = quarantine_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to create {}: {}", parent.display(), error))?;
+    }
+
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > MAX_QUARANTINE_FILE_BYTES) {
+        let rotated = path.with_file_name(format!("{}.1", QUARANTINE_FILE_NAME));
+        fs::rename(&path, &rotated)
+            .map_err(|error| format!("Failed to rotate {}: {}", path.display(), error))?;
+    }
+
+    let line = serde_json::to_string(failure)
+        .map_err(|error| format!("Failed to serialize parse failure: {}", error))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|error| format!("Failed to open {}: {}", path.display(), error))?;
+    writeln!(file, "{}", line)
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
+}
+
+fn writer() -> Option<&'static SyncSender<ParseFailure>> {
+    WRITER
+        .get_or_init(|| {
+            let (sender, receiver) = mpsc::sync_channel::<ParseFailure>(MAX_QUEUED_WRITES);
+            let spawned = std::thread::Builder::new()
+                .name("parse-quarantine".to_string())
+                .spawn(move || {
+                    for failure in receiver {
+                        if let Err(error) = append_to_file(&failure) {
+                            logger::log(format!("Failed to quarantine sidecar line: {}", error));
+                        }
+                    }
+                });
+            match spawned {
+                Ok(_) => Some(sender),
+                Err(error) => {
+                    logger::log(format!(
+                        "Failed to start parse quarantine writer: {}",
+                        error
+                    ));
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Whether this failure may go to the file, counting it against the window.
+fn take_write_slot(context: &mut Context, now: Instant) -> bool {
+    let window_open = context
+        .window_start
+        .is_some_and(|start| now.duration_since(start) < WRITE_WINDOW);
+    if !window_open {
+        context.window_start = Some(now);
+        context.writes_in_window = 0;
+    }
+    if context.writes_in_window >= MAX_WRITES_PER_WINDOW {
+        context.skipped_writes += 1;
+        return false;
+    }
+    context.writes_in_window += 1;
+    true
+}
+
+/// Quarantine a line that failed to parse raw and sanitized.
+pub(crate) fn quarantine_line(raw: &str, sanitized: &str, error: &str) {
+    let line_number = LINES_SEEN.fetch_add(1, Ordering::Relaxed) + 1;
+    FAILURES.fetch_add(1, Ordering::Relaxed);
+
+    let raw_truncated = raw.chars().count() > MAX_QUARANTINED_LINE_CHARS;
+    let mut failure = ParseFailure {
+        at: now_millis(),
+        line_number,
+        error: error.to_string(),
+        raw_len: raw.len(),
+        raw: raw.chars().take(MAX_QUARANTINED_LINE_CHARS).collect(),
+        raw_truncated,
+        sanitized_prefix: debug_prefix_codepoints(sanitized, 8),
+        previous_type: None,
+    };
+
+    let Ok(mut context) = CONTEXT.lock() else {
+        return;
+    };
+    failure.previous_type = context.previous_type.clone();
+    context.recent.push_front(failure.clone());
+    context.recent.truncate(MAX_RECENT_FAILURES);
+
+    if !take_write_slot(&mut context, Instant::now()) {
+        return;
+    }
+    let skipped = std::mem::take(&mut context.skipped_writes);
+    drop(context);
+
+    if skipped > 0 {
+        logger::log(format!(
+            "Kept {} sidecar parse failure(s) out of {} (rate limit or full queue)",
+            skipped, QUARANTINE_FILE_NAME
+        ));
+    }
+    let queued = writer().is_some_and(|writer| writer.try_send(failure).is_ok());
+    if !queued {
+        if let Ok(mut context) = CONTEXT.lock() {
+            context.skipped_writes += 1;
+        }
+    }
+}
+
+pub(crate) fn parse_failures(limit: Option<usize>) -> ParseFailuresResponse {
+    let failures = CONTEXT
+        .lock()
+        .map(|context| {
+            context
+                .recent
+                .iter()
+                .take(limit.unwrap_or(MAX_RECENT_FAILURES))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    ParseFailuresResponse {
+        quarantine_path: quarantine_path().display().to_string(),
+        total: FAILURES.load(Ordering::Relaxed),
+        failures,
+    }
+}
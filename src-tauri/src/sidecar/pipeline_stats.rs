@@ -0,0 +1,224 @@
+//! Counters for the sidecar stdout -> frontend event pipeline, for diagnosing
+//! streaming performance from inside the app.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::state::SidecarState;
+use crate::utils::now_millis;
+
+struct Counter(AtomicU64);
+
+impl Counter {
+    const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    fn add(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn reset(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
+}
+
+static LINES_RECEIVED: Counter = Counter::new();
+static BYTES_RECEIVED: Counter = Counter::new();
+static INVALID_LINES: Counter = Counter::new();
+static RESPONSES_RECEIVED: Counter = Counter::new();
+static RESPONSES_DROPPED: Counter = Counter::new();
+static RESPONSE_QUEUE_OVERFLOWS: Counter = Counter::new();
+static RESPONSE_QUEUE_WAIT_MILLIS: Counter = Counter::new();
+static SESSION_EVENTS_RECEIVED: Counter = Counter::new();
+static SESSION_EVENT_BYTES_RECEIVED: Counter = Counter::new();
+static DELTAS_COALESCED: Counter = Counter::new();
+static EVENTS_EMITTED: Counter = Counter::new();
+static BYTES_EMITTED: Counter = Counter::new();
+static SESSION_EVENT_BYTES_EMITTED: Counter = Counter::new();
+static CHUNKED_PAYLOADS: Counter = Counter::new();
+static OVERFLOWED_PAYLOADS: Counter = Counter::new();
+static DROPPED_PAYLOADS: Counter = Counter::new();
+static PENDING_DELTAS: Counter = Counter::new();
+static MAX_PENDING_DELTAS: Counter = Counter::new();
+static SINCE_MILLIS: Counter = Counter::new();
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventPipelineStats {
+    /// Start of the counting window (unix millis): first event or last reset.
+    pub since: u64,
+    pub lines_received: u64,
+    pub bytes_received: u64,
+    pub invalid_lines: u64,
+    pub responses_received: u64,
+    /// Responses lost because the response channel was closed.
+    pub responses_dropped: u64,
+    /// Times the response channel was full and the reader waited for a slot,
+    /// and the total time spent waiting.
+    pub response_queue_overflows: u64,
+    pub response_queue_wait_millis: u64,
+    pub session_events_received: u64,
+    /// Delta events merged into a queued one instead of being emitted.
+    pub deltas_coalesced: u64,
+    /// `agent-event` emits, counting each chunk of a split payload.
+    pub events_emitted: u64,
+    pub bytes_emitted: u64,
+    /// Session event bytes received minus bytes emitted for them, i.e. what
+    /// coalescing and compaction saved.
+    pub bytes_saved: u64,
+    /// Oversized payloads written to an overflow file and sent as a reference.
+    pub overflowed_payloads: u64,
+    /// Oversized payloads split into chunks because the overflow file failed.
+    pub chunked_payloads: u64,
+    /// Payloads (or chunks) that could not be emitted at all.
+    pub dropped_payloads: u64,
+    /// Delta events currently waiting in the coalescer, and the peak.
+    pub pending_deltas: u64,
+    pub max_pending_deltas: u64,
+    /// Requests awaiting a sidecar response.
+    pub pending_requests: usize,
+    /// Responses queued for the response handler.
+    pub response_queue_depth: usize,
+}
+
+fn mark_started() {
+    let _ = SINCE_MILLIS
+        .0
+        .compare_exchange(0, now_millis(), Ordering::Relaxed, Ordering::Relaxed);
+}
+
+pub(super) fn record_line(bytes: usize) {
+    mark_started();
+    LINES_RECEIVED.add(1);
+    BYTES_RECEIVED.add(bytes as u64);
+}
+
+pub(super) fn record_invalid_line() {
+    INVALID_LINES.add(1);
+}
+
+pub(super) fn record_response(dropped: bool) {
+    RESPONSES_RECEIVED.add(1);
+    if dropped {
+        RESPONSES_DROPPED.add(1);
+    }
+}
+
+pub(super) fn record_response_backpressure(waited: Duration) {
+    RESPONSE_QUEUE_OVERFLOWS.add(1);
+    RESPONSE_QUEUE_WAIT_MILLIS.add(waited.as_millis() as u64);
+}
+
+pub(super) fn record_session_event(bytes: usize) {
+    SESSION_EVENTS_RECEIVED.add(1);
+    SESSION_EVENT_BYTES_RECEIVED.add(bytes as u64);
+}
+
+pub(super) fn record_delta_coalesced() {
+    DELTAS_COALESCED.add(1);
+}
+
+pub(super) fn set_pending_deltas(count: usize) {
+    let count = count as u64;
+    PENDING_DELTAS.0.store(count, Ordering::Relaxed);
+    MAX_PENDING_DELTAS.0.fetch_max(count, Ordering::Relaxed);
+}
+
+pub(super) fn record_session_event_emitted(bytes: usize) {
+    SESSION_EVENT_BYTES_EMITTED.add(bytes as u64);
+}
+
+pub(super) fn record_emit(bytes: usize) {
+    EVENTS_EMITTED.add(1);
+    BYTES_EMITTED.add(bytes as u64);
+}
+
+pub(super) fn record_overflowed_payload() {
+    OVERFLOWED_PAYLOADS.add(1);
+}
+
+pub(super) fn record_chunked_payload() {
+    CHUNKED_PAYLOADS.add(1);
+}
+
+pub(super) fn record_dropped_payload() {
+    DROPPED_PAYLOADS.add(1);
+}
+
+/// Current counters plus live queue depths; `reset` starts a new window.
+pub(crate) async fn event_pipeline_stats(
+    state: &Arc<Mutex<SidecarState>>,
+    reset: bool,
+) -> EventPipelineStats {
+    let (pending_requests, response_queue_depth) = {
+        let state_guard = state.lock().await;
+        let queue_depth = state_guard
+            .response_tx
+            .as_ref()
+            .map(|tx| tx.max_capacity() - tx.capacity())
+            .unwrap_or(0);
+        (state_guard.pending_requests.len(), queue_depth)
+    };
+
+    let bytes_received = SESSION_EVENT_BYTES_RECEIVED.get();
+    let stats = EventPipelineStats {
+        since: SINCE_MILLIS.get(),
+        lines_received: LINES_RECEIVED.get(),
+        bytes_received: BYTES_RECEIVED.get(),
+        invalid_lines: INVALID_LINES.get(),
+        responses_received: RESPONSES_RECEIVED.get(),
+        responses_dropped: RESPONSES_DROPPED.get(),
+        response_queue_overflows: RESPONSE_QUEUE_OVERFLOWS.get(),
+        response_queue_wait_millis: RESPONSE_QUEUE_WAIT_MILLIS.get(),
+        session_events_received: SESSION_EVENTS_RECEIVED.get(),
+        deltas_coalesced: DELTAS_COALESCED.get(),
+        events_emitted: EVENTS_EMITTED.get(),
+        bytes_emitted: BYTES_EMITTED.get(),
+        bytes_saved: bytes_received.saturating_sub(SESSION_EVENT_BYTES_EMITTED.get()),
+        overflowed_payloads: OVERFLOWED_PAYLOADS.get(),
+        chunked_payloads: CHUNKED_PAYLOADS.get(),
+        dropped_payloads: DROPPED_PAYLOADS.get(),
+        pending_deltas: PENDING_DELTAS.get(),
+        max_pending_deltas: MAX_PENDING_DELTAS.get(),
+        pending_requests,
+        response_queue_depth,
+    };
+
+    if reset {
+        for counter in [
+            &LINES_RECEIVED,
+            &BYTES_RECEIVED,
+            &INVALID_LINES,
+            &RESPONSES_RECEIVED,
+            &RESPONSES_DROPPED,
+            &RESPONSE_QUEUE_OVERFLOWS,
+            &RESPONSE_QUEUE_WAIT_MILLIS,
+            &SESSION_EVENTS_RECEIVED,
+            &SESSION_EVENT_BYTES_RECEIVED,
+            &DELTAS_COALESCED,
+            &EVENTS_EMITTED,
+            &BYTES_EMITTED,
+            &SESSION_EVENT_BYTES_EMITTED,
+            &OVERFLOWED_PAYLOADS,
+            &CHUNKED_PAYLOADS,
+            &DROPPED_PAYLOADS,
+        ] {
+            counter.reset();
+        }
+        MAX_PENDING_DELTAS
+            .0
+            .store(PENDING_DELTAS.get(), Ordering::Relaxed);
+        SINCE_MILLIS.0.store(now_millis(), Ordering::Relaxed);
+    }
+
+    stats
+}
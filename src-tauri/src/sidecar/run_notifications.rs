@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use crate::commands::{notify_run_error, notify_run_finished};
+use crate::state::SidecarState;
+
+/// Raise toasts for failed messages and for runs that end cleanly.
+pub(crate) async fn observe_session_event(
+    app: &AppHandle,
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    event: &serde_json::Value,
+) {
+    match event.get("type").and_then(|value| value.as_str()) {
+        Some("message_end") => {
+            let message = event.get("message");
+            let stop_reason = message
+                .and_then(|message| message.get("stopReason"))
+                .and_then(|value| value.as_str());
+            if stop_reason != Some("error") {
+                return;
+            }
+
+            let error_message = message
+                .and_then(|message| message.get("errorMessage"))
+                .and_then(|value| value.as_str())
+                .unwrap_or("The provider returned an error");
+
+            notify_run_error(app, session_id, error_message);
+        }
+        Some("agent_end") => {
+            // `run_errors` is kept up to date by the activity tracker, which
+            // sees each event before this observer.
+            let failed = state.lock().await.run_errors.contains_key(session_id);
+            if !failed {
+                notify_run_finished(app, session_id);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,243 @@
+//! Typed view of the host `get_state` payload, cached per session so
+//! commands can check the active model or turn status without a round trip.
+//!
+//! Every successful `get_state` response refreshes the cache; `set_model` and
+//! `set_thinking_level` responses and run lifecycle events keep it current in
+//! between.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use super::RpcClient;
+use crate::state::SidecarState;
+use crate::types::{RpcCommand, RpcResponse};
+use crate::utils::{crypto_random_uuid, now_millis};
+
+const GET_STATE_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionModel {
+    pub provider: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// From the model's declared input kinds; `None` when it declares none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_image_input: Option<bool>,
+}
+
+impl SessionModel {
+    fn parse(value: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            provider: value.get("provider")?.as_str()?.to_string(),
+            id: value.get("id")?.as_str()?.to_string(),
+            name: value
+                .get("name")
+                .and_then(|name| name.as_str())
+                .map(str::to_string),
+            supports_image_input: value
+                .get("input")
+                .and_then(|input| input.as_array())
+                .map(|input| input.iter().any(|kind| kind.as_str() == Some("image"))),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TurnStatus {
+    Idle,
+    Streaming,
+    Compacting,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStateSnapshot {
+    pub session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<SessionModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_level: Option<String>,
+    /// How a prompt sent mid-run is queued: `steeringMode` and `followUpMode`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steering_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_up_mode: Option<String>,
+    pub is_streaming: bool,
+    pub is_compacting: bool,
+    pub turn_status: TurnStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_file: Option<String>,
+    /// When the snapshot was last fetched or updated (unix millis).
+    pub updated_at: u64,
+}
+
+impl SessionStateSnapshot {
+    /// Parse a `get_state` payload, rejecting one for another session or
+    /// without the run flags.
+    pub(crate) fn from_get_state(
+        session_id: &str,
+        data: &serde_json::Value,
+    ) -> Result<Self, String> {
+        if let Some(reported) = data.get("sessionId").and_then(|value| value.as_str()) {
+            if reported != session_id {
+                return Err(format!(
+                    "get_state for session {} returned state of session {}",
+                    session_id, reported
+                ));
+            }
+        }
+
+        let flag = |key: &str| {
+            data.get(key)
+                .and_then(|value| value.as_bool())
+                .ok_or_else(|| format!("get_state payload is missing {}", key))
+        };
+        let text = |key: &str| {
+            data.get(key)
+                .and_then(|value| value.as_str())
+                .map(str::to_string)
+        };
+
+        let mut snapshot = Self {
+            session_id: session_id.to_string(),
+            model: data.get("model").and_then(SessionModel::parse),
+            thinking_level: text("thinkingLevel"),
+            steering_mode: text("steeringMode"),
+            follow_up_mode: text("followUpMode"),
+            is_streaming: flag("isStreaming")?,
+            is_compacting: flag("isCompacting")?,
+            turn_status: TurnStatus::Idle,
+            session_file: text("sessionFile"),
+            updated_at: now_millis(),
+        };
+        snapshot.refresh_turn_status();
+        Ok(snapshot)
+    }
+
+    fn refresh_turn_status(&mut self) {
+        self.turn_status = if self.is_compacting {
+            TurnStatus::Compacting
+        } else if self.is_streaming {
+            TurnStatus::Streaming
+        } else {
+            TurnStatus::Idle
+        };
+        self.updated_at = now_millis();
+    }
+
+    /// Follow a run lifecycle event; other events leave the snapshot alone.
+    pub(crate) fn apply_event(&mut self, event: &serde_json::Value) {
+        match event.get("type").and_then(|value| value.as_str()) {
+            Some("agent_start") => self.is_streaming = true,
+            Some("agent_end") => self.is_streaming = false,
+            Some("auto_compaction_start") => self.is_compacting = true,
+            Some("auto_compaction_end") => self.is_compacting = false,
+            _ => return,
+        }
+        self.refresh_turn_status();
+    }
+}
+
+/// Refresh the cache from a successful session command response.
+pub(crate) fn observe_response(
+    state: &mut SidecarState,
+    command: &RpcCommand,
+    response: &RpcResponse,
+) {
+    if !response.success {
+        return;
+    }
+    let Some(session_id) = command.session_id.as_deref() else {
+        return;
+    };
+    let data = response.data.as_ref();
+
+    match command.r#type.as_str() {
+        "get_state" => {
+            match data.map(|data| SessionStateSnapshot::from_get_state(session_id, data)) {
+                Some(Ok(snapshot)) => {
+                    state
+                        .session_states
+                        .insert(session_id.to_string(), snapshot);
+                }
+                Some(Err(error)) => {
+                    crate::logger::log(format!("Ignoring get_state payload: {}", error));
+                }
+                None => {}
+            }
+        }
+        "set_model" => {
+            if let (Some(snapshot), Some(model)) = (
+                state.session_states.get_mut(session_id),
+                data.and_then(SessionModel::parse),
+            ) {
+                snapshot.model = Some(model);
+                snapshot.updated_at = now_millis();
+            }
+        }
+        "set_thinking_level" => {
+            if let (Some(snapshot), Some(level)) = (
+                state.session_states.get_mut(session_id),
+                data.and_then(|data| data.get("level"))
+                    .and_then(|level| level.as_str()),
+            ) {
+                snapshot.thinking_level = Some(level.to_string());
+                snapshot.updated_at = now_millis();
+            }
+        }
+        // The new model is not described reliably; fetch it on next use.
+        "cycle_model" => {
+            state.session_states.remove(session_id);
+        }
+        _ => {}
+    }
+}
+
+/// The session's cached state, fetched with `get_state` when there is none
+/// or `refresh` is set.
+pub(crate) async fn session_state_snapshot(
+    state: &Arc<Mutex<SidecarState>>,
+    session_id: &str,
+    refresh: bool,
+) -> Result<SessionStateSnapshot, String> {
+    if !refresh {
+        if let Some(snapshot) = state.lock().await.session_states.get(session_id) {
+            return Ok(snapshot.clone());
+        }
+    }
+
+    let id = crypto_random_uuid();
+    let command = RpcCommand {
+        id: Some(id.clone()),
+        r#type: "get_state".to_string(),
+        session_id: Some(session_id.to_string()),
+        cwd: None,
+        message: None,
+        provider: None,
+        model_id: None,
+        streaming_behavior: None,
+        session_file: None,
+        level: None,
+        images: None,
+        env: None,
+        tools: None,
+    };
+
+    let response =
+        RpcClient::send_command_with_response(state, command, id, GET_STATE_TIMEOUT_SECS).await?;
+    if !response.success {
+        return Err(response
+            .error
+            .unwrap_or_else(|| "get_state failed".to_string()));
+    }
+
+    let data = response
+        .data
+        .ok_or_else(|| "get_state returned no state".to_string())?;
+    SessionStateSnapshot::from_get_state(session_id, &data)
+}
@@ -0,0 +1,74 @@
+//! Extra command-line flags for the sidecar, configured in preferences and
+//! checked against the flags the host understands.
+//!
+//! The sidecar is one process shared by every session, so the flags apply
+//! from its next start.
+
+use std::sync::Mutex;
+
+use crate::logger;
+use crate::preferences::Preferences;
+
+const GRAPHONE_HOST_FLAG: &str = "--graphone-host";
+
+/// Flags the host honours in host mode.
+const ALLOWED_SIDECAR_ARGS: [&str; 4] = [
+    "--no-session",
+    "--no-skills",
+    "--no-extensions",
+    "--no-prompt-templates",
+];
+
+static LAST_SPAWN_ARGV: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// Trim and dedupe `args`, rejecting any flag outside `ALLOWED_SIDECAR_ARGS`.
+pub(crate) fn validate_sidecar_args(args: &[String]) -> Result<Vec<String>, String> {
+    let mut validated = Vec::new();
+    for arg in args {
+        let arg = arg.trim();
+        if !ALLOWED_SIDECAR_ARGS.contains(&arg) {
+            return Err(format!(
+                "Sidecar argument '{}' is not allowed (allowed: {})",
+                arg,
+                ALLOWED_SIDECAR_ARGS.join(", ")
+            ));
+        }
+        if !validated.iter().any(|existing| existing == arg) {
+            validated.push(arg.to_string());
+        }
+    }
+    Ok(validated)
+}
+
+/// Arguments for the sidecar binary: the host flag plus the configured
+/// extras. A store edited by hand may hold flags `set_preferences` would
+/// reject; those are dropped with a log line.
+pub(super) fn sidecar_spawn_args(preferences: &Preferences) -> Vec<String> {
+    let mut args = vec![GRAPHONE_HOST_FLAG.to_string()];
+    for arg in &preferences.sidecar_args {
+        match validate_sidecar_args(std::slice::from_ref(arg)) {
+            Ok(valid) => {
+                for arg in valid {
+                    if !args.contains(&arg) {
+                        args.push(arg);
+                    }
+                }
+            }
+            Err(error) => logger::log(format!("Ignoring sidecar argument: {}", error)),
+        }
+    }
+    args
+}
+
+pub(super) fn record_spawn_argv(argv: Vec<String>) {
+    logger::log(format!("Sidecar argv: {}", argv.join(" ")));
+    if let Ok(mut last) = LAST_SPAWN_ARGV.lock() {
+        *last = Some(argv);
+    }
+}
+
+/// Program and arguments of the most recent sidecar launch, wrapper
+/// (`systemd-run`) included.
+pub(crate) fn last_spawn_argv() -> Option<Vec<String>> {
+    LAST_SPAWN_ARGV.lock().ok().and_then(|last| last.clone())
+}
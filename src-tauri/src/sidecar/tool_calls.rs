@@ -0,0 +1,160 @@
+use std::collections::{HashMap, VecDeque};
+
+use sha2::{Digest, Sha256};
+
+use super::tool_heartbeat::ToolHeartbeats;
+use super::turn_summary::{TurnSummary, TurnSummaryBuilder};
+
+/// Results smaller than this (serialized bytes) are always sent in full.
+const MIN_DEDUPE_RESULT_BYTES: usize = 512;
+/// Recent result digests remembered per session within one agent run.
+const MAX_RECENT_RESULTS: usize = 64;
+
+/// What graphone remembers about a tool call between its start and end events.
+#[derive(Debug, Clone)]
+pub(crate) struct ToolCallInfo {
+    pub tool_name: String,
+    /// File path argument (`path` / `file_path`), when the tool takes one.
+    pub path: Option<String>,
+}
+
+/// Tracks in-flight tool calls for one sidecar event stream.
+///
+/// `tool_execution_end` carries only the result, so anything derived from the
+/// call's arguments has to be captured at `tool_execution_start`. Recent
+/// result digests are kept per session so repeated identical results (an
+/// agent re-reading the same file) can be sent as references, and each
+/// session's current turn is accumulated into a `turn_summary`. Running
+/// tools are timed for `tool-still-running` heartbeats.
+#[derive(Debug, Default)]
+pub(crate) struct ToolCallTracker {
+    calls: HashMap<String, ToolCallInfo>,
+    recent_results: HashMap<String, VecDeque<([u8; 32], String)>>,
+    turns: HashMap<String, TurnSummaryBuilder>,
+    pub(crate) heartbeats: ToolHeartbeats,
+}
+
+fn path_arg(event: &serde_json::Value) -> Option<&str> {
+    event.get("args").and_then(|args| {
+        ["path", "file_path", "filePath"]
+            .iter()
+            .find_map(|key| args.get(*key).and_then(|value| value.as_str()))
+    })
+}
+
+impl ToolCallTracker {
+    pub(crate) fn start(&mut self, event: &serde_json::Value) {
+        let Some(tool_call_id) = event.get("toolCallId").and_then(|value| value.as_str()) else {
+            return;
+        };
+
+        let tool_name = event
+            .get("toolName")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let path = path_arg(event).map(str::to_string);
+
+        self.calls
+            .insert(tool_call_id.to_string(), ToolCallInfo { tool_name, path });
+    }
+
+    pub(crate) fn finish(&mut self, tool_call_id: &str) -> Option<ToolCallInfo> {
+        self.calls.remove(tool_call_id)
+    }
+
+    /// Feed a raw (uncompacted) session event into the session's turn summary.
+    ///
+    /// Returns the finished summary on `turn_end`.
+    pub(crate) fn observe_turn_event(
+        &mut self,
+        session_id: &str,
+        event: &serde_json::Value,
+    ) -> Option<TurnSummary> {
+        let event_type = event.get("type").and_then(|value| value.as_str())?;
+        let tool_name = event
+            .get("toolName")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+
+        match event_type {
+            "turn_start" => {
+                self.turns
+                    .insert(session_id.to_string(), TurnSummaryBuilder::default());
+            }
+            "tool_execution_start" => self
+                .turns
+                .entry(session_id.to_string())
+                .or_default()
+                .record_tool_start(tool_name, path_arg(event)),
+            "tool_execution_end" => self
+                .turns
+                .entry(session_id.to_string())
+                .or_default()
+                .record_tool_end(tool_name, event),
+            "turn_end" => {
+                return Some(
+                    self.turns
+                        .remove(session_id)
+                        .unwrap_or_default()
+                        .finish(event),
+                );
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Forget result digests for a session (called when a new agent run starts,
+    /// so references never point outside the current run).
+    pub(crate) fn reset_session_results(&mut self, session_id: &str) {
+        self.recent_results.remove(session_id);
+    }
+
+    /// Replace a compact `tool_execution_end` result that is identical to an
+    /// earlier one in the same run with a `sameAsToolCallId` reference.
+    pub(crate) fn dedupe_result(&mut self, session_id: &str, event: &mut serde_json::Value) {
+        let Some(tool_call_id) = event
+            .get("toolCallId")
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+        else {
+            return;
+        };
+
+        let Some(result) = event.get("result").filter(|result| !result.is_null()) else {
+            return;
+        };
+
+        let Ok(serialized) = serde_json::to_vec(&serde_json::json!([
+            event.get("toolName"),
+            event.get("isError"),
+            result,
+        ])) else {
+            return;
+        };
+
+        if serialized.len() < MIN_DEDUPE_RESULT_BYTES {
+            return;
+        }
+
+        let digest: [u8; 32] = Sha256::digest(&serialized).into();
+        let recent = self
+            .recent_results
+            .entry(session_id.to_string())
+            .or_default();
+
+        if let Some((_, original_id)) = recent.iter().find(|(seen, _)| *seen == digest) {
+            event["result"] = serde_json::Value::Null;
+            event["sameAsToolCallId"] = serde_json::json!(original_id);
+            return;
+        }
+
+        recent.push_back((digest, tool_call_id));
+        if recent.len() > MAX_RECENT_RESULTS {
+            recent.pop_front();
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! `tool-still-running` heartbeats for tools that run for a long time without
+//! output (a long build), so the UI can show a live timer instead of looking
+//! frozen.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, EventTarget};
+
+use crate::logger;
+use crate::window_registry;
+
+/// How often the event listener checks for silent tools.
+pub(crate) const TOOL_HEARTBEAT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Silence after which a running tool gets a heartbeat, and between beats.
+const TOOL_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+struct RunningTool {
+    session_id: String,
+    tool_name: String,
+    started_at: Instant,
+    /// Start, last output update or last heartbeat.
+    last_activity: Instant,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolStillRunning<'a> {
+    session_id: &'a str,
+    tool_call_id: &'a str,
+    tool_name: &'a str,
+    elapsed_ms: u64,
+}
+
+/// Tool calls between `tool_execution_start` and `tool_execution_end`.
+#[derive(Debug, Default)]
+pub(crate) struct ToolHeartbeats {
+    running: HashMap<String, RunningTool>,
+}
+
+impl ToolHeartbeats {
+    pub(crate) fn observe(&mut self, session_id: &str, event: &serde_json::Value) {
+        let event_type = event.get("type").and_then(|value| value.as_str());
+        let tool_call_id = event.get("toolCallId").and_then(|value| value.as_str());
+
+        match (event_type, tool_call_id) {
+            (Some("tool_execution_start"), Some(tool_call_id)) => {
+                let now = Instant::now();
+                self.running.insert(
+                    tool_call_id.to_string(),
+                    RunningTool {
+                        session_id: session_id.to_string(),
+                        tool_name: event
+                            .get("toolName")
+                            .and_then(|value| value.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        started_at: now,
+                        last_activity: now,
+                    },
+                );
+            }
+            (Some("tool_execution_update"), Some(tool_call_id)) => {
+                if let Some(tool) = self.running.get_mut(tool_call_id) {
+                    tool.last_activity = Instant::now();
+                }
+            }
+            (Some("tool_execution_end"), Some(tool_call_id)) => {
+                self.running.remove(tool_call_id);
+            }
+            // An aborted run may never end its tools.
+            (Some("agent_end"), _) => {
+                self.running.retain(|_, tool| tool.session_id != session_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Emit a heartbeat for every tool that has been silent for the interval.
+    pub(crate) fn emit_due(&mut self, app: &AppHandle) {
+        for (tool_call_id, tool) in &mut self.running {
+            if tool.last_activity.elapsed() < TOOL_HEARTBEAT_INTERVAL {
+                continue;
+            }
+            tool.last_activity = Instant::now();
+
+            let payload = ToolStillRunning {
+                session_id: &tool.session_id,
+                tool_call_id,
+                tool_name: &tool.tool_name,
+                elapsed_ms: tool.started_at.elapsed().as_millis() as u64,
+            };
+            let accepts =
+                |target: &EventTarget| window_registry::accepts(target, Some(&tool.session_id));
+            if let Err(error) = app.emit_filter("tool-still-running", &payload, accepts) {
+                logger::log(format!(
+                    "Failed to emit tool-still-running event: {}",
+                    error
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Tool outputs too large to send in full are written to the cache dir,
+//! one directory per session, and read back a window of lines at a time.
+//!
+//! The size is reported right away and the files are written on a blocking
+//! thread, so the stdout handler never waits on disk. Files older than
+//! `SPILL_FILE_TTL` are removed on the next spill.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::logger;
+
+const SPILL_DIR: &str = "tool-outputs";
+const MAX_LINES_PER_READ: usize = 5_000;
+const SPILL_FILE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Where a truncated tool output was written in full.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SpilledToolOutput {
+    pub bytes: u64,
+    pub lines: u64,
+}
+
+/// A window of lines read from a spilled tool output.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolOutputLines {
+    pub tool_call_id: String,
+    /// Zero-based index of the first returned line.
+    pub start: u64,
+    pub lines: Vec<String>,
+    pub total_lines: u64,
+}
+
+fn spill_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join(SPILL_DIR))
+        .map_err(|error| format!("Failed to resolve app cache dir: {}", error))
+}
+
+/// Tool call ids come from the model; keep only filename-safe characters.
+fn file_stem(id: &str) -> String {
+    id.chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Remove spilled files older than `SPILL_FILE_TTL`, and session
+/// directories left empty.
+fn prune_expired(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            prune_expired(&path);
+            let _ = std::fs::remove_dir(&path);
+            continue;
+        }
+        let expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > SPILL_FILE_TTL);
+        if expired {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Write the output, then its line index. The index is renamed into place
+/// last, so a readable index always has its complete output next to it.
+///
+/// The index (`<id>.idx`) stores the byte offset of every line start as
+/// little-endian `u64`s, so any line can be reached with a single seek.
+fn write_spill_files(
+    root: &Path,
+    session_dir: &Path,
+    stem: &str,
+    text: &str,
+) -> Result<(), String> {
+    prune_expired(root);
+    std::fs::create_dir_all(session_dir)
+        .map_err(|e| format!("Failed to create {}: {}", session_dir.display(), e))?;
+
+    let output_path = session_dir.join(format!("{}.log", stem));
+    std::fs::write(&output_path, text)
+        .map_err(|e| format!("Failed to write {}: {}", output_path.display(), e))?;
+
+    let mut index = Vec::with_capacity(text.len() / 40 * 8 + 8);
+    index.extend_from_slice(&0u64.to_le_bytes());
+    for (offset, byte) in text.bytes().enumerate() {
+        if byte == b'\n' && offset + 1 < text.len() {
+            index.extend_from_slice(&(offset as u64 + 1).to_le_bytes());
+        }
+    }
+
+    let index_path = session_dir.join(format!("{}.idx", stem));
+    let partial_path = session_dir.join(format!("{}.idx.partial", stem));
+    let mut index_file = File::create(&partial_path)
+        .map_err(|e| format!("Failed to create {}: {}", partial_path.display(), e))?;
+    index_file
+        .write_all(&index)
+        .map_err(|e| format!("Failed to write {}: {}", partial_path.display(), e))?;
+    std::fs::rename(&partial_path, &index_path)
+        .map_err(|e| format!("Failed to write {}: {}", index_path.display(), e))
+}
+
+/// Hand a tool's full output to a background write for
+/// `read_tool_output_lines` and return its size.
+pub(crate) fn spill_tool_output(
+    app: &AppHandle,
+    session_id: &str,
+    tool_call_id: &str,
+    text: String,
+) -> Option<SpilledToolOutput> {
+    let root = match spill_dir(app) {
+        Ok(root) => root,
+        Err(error) => {
+            logger::log(format!("Failed to spill tool output: {}", error));
+            return None;
+        }
+    };
+    let spilled = SpilledToolOutput {
+        bytes: text.len() as u64,
+        lines: 1 + text
+            .strip_suffix('\n')
+            .unwrap_or(&text)
+            .matches('\n')
+            .count() as u64,
+    };
+
+    let session_dir = root.join(file_stem(session_id));
+    let stem = file_stem(tool_call_id);
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(error) = write_spill_files(&root, &session_dir, &stem, &text) {
+            logger::log(format!("Failed to spill tool output: {}", error));
+        }
+    });
+
+    Some(spilled)
+}
+
+/// Read `count` lines starting at `start` from a spilled tool output.
+pub(crate) fn read_tool_output_lines(
+    app: &AppHandle,
+    session_id: &str,
+    tool_call_id: String,
+    start: u64,
+    count: usize,
+) -> Result<ToolOutputLines, String> {
+    let dir = spill_dir(app)?.join(file_stem(session_id));
+    let stem = file_stem(&tool_call_id);
+    let index_path = dir.join(format!("{}.idx", stem));
+    let output_path = dir.join(format!("{}.log", stem));
+
+    let mut index_file = File::open(&index_path)
+        .map_err(|e| format!("No spilled output for tool call {}: {}", tool_call_id, e))?;
+    let total_lines = index_file
+        .metadata()
+        .map_err(|e| format!("Failed to read {}: {}", index_path.display(), e))?
+        .len()
+        / 8;
+
+    if start >= total_lines {
+        return Ok(ToolOutputLines {
+            tool_call_id,
+            start,
+            lines: Vec::new(),
+            total_lines,
+        });
+    }
+
+    let mut offset = [0u8; 8];
+    index_file
+        .seek(SeekFrom::Start(start * 8))
+        .and_then(|_| index_file.read_exact(&mut offset))
+        .map_err(|e| format!("Failed to read {}: {}", index_path.display(), e))?;
+
+    let mut output = File::open(&output_path)
+        .map_err(|e| format!("Failed to open {}: {}", output_path.display(), e))?;
+    output
+        .seek(SeekFrom::Start(u64::from_le_bytes(offset)))
+        .map_err(|e| format!("Failed to read {}: {}", output_path.display(), e))?;
+
+    let lines = BufReader::new(output)
+        .split(b'\n')
+        .take(count.min(MAX_LINES_PER_READ))
+        .map(|line| {
+            line.map(|bytes| {
+                String::from_utf8_lossy(&bytes)
+                    .trim_end_matches('\r')
+                    .to_string()
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read {}: {}", output_path.display(), e))?;
+
+    Ok(ToolOutputLines {
+        tool_call_id,
+        start,
+        lines,
+        total_lines,
+    })
+}
@@ -0,0 +1,99 @@
+/// Coarse classification of a failed turn's error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TurnErrorKind {
+    /// The provider could not be reached or reported an outage/overload.
+    ProviderUnavailable,
+    /// The provider rejected the stored credentials (expired or revoked token).
+    AuthExpired,
+    /// The provider rejected a configured API key; logging in again cannot help.
+    InvalidApiKey,
+    Other,
+}
+
+const INVALID_API_KEY_MARKERS: [&str; 5] = [
+    "invalid api key",
+    "invalid x-api-key",
+    "incorrect api key",
+    "invalid_api_key",
+    "api key not valid",
+];
+
+const AUTH_EXPIRED_STATUS_CODES: [&str; 1] = ["401"];
+
+const AUTH_EXPIRED_MARKERS: [&str; 8] = [
+    "unauthorized",
+    "token expired",
+    "token has expired",
+    "expired token",
+    "invalid_grant",
+    "invalid token",
+    "authentication failed",
+    "please re-authenticate",
+];
+
+const PROVIDER_UNAVAILABLE_STATUS_CODES: [&str; 4] = ["502", "503", "504", "529"];
+
+const PROVIDER_UNAVAILABLE_MARKERS: [&str; 14] = [
+    "bad gateway",
+    "service unavailable",
+    "gateway timeout",
+    "temporarily unavailable",
+    "overloaded",
+    "upstream connect error",
+    "connection refused",
+    "econnrefused",
+    "econnreset",
+    "enotfound",
+    "etimedout",
+    "socket hang up",
+    "fetch failed",
+    "network error",
+];
+
+/// Whether `code` appears in `normalized` as a number of its own, not inside
+/// a longer number or identifier (token counts, ids, ports).
+fn contains_status_code(normalized: &str, code: &str) -> bool {
+    let is_boundary = |neighbour: Option<char>| {
+        !neighbour.is_some_and(|c| c.is_alphanumeric() || "._-".contains(c))
+    };
+    normalized.match_indices(code).any(|(index, _)| {
+        is_boundary(normalized[..index].chars().next_back())
+            && is_boundary(normalized[index + code.len()..].chars().next())
+    })
+}
+
+pub(crate) fn classify_turn_error(error_message: &str) -> TurnErrorKind {
+    let normalized = error_message.to_lowercase();
+
+    // A rejected API key usually comes as a 401 too, but needs a new key
+    // rather than a login.
+    if INVALID_API_KEY_MARKERS
+        .iter()
+        .any(|marker| normalized.contains(marker))
+    {
+        return TurnErrorKind::InvalidApiKey;
+    }
+
+    // Checked before outages: an expired token must not trigger a fallback.
+    if AUTH_EXPIRED_STATUS_CODES
+        .iter()
+        .any(|code| contains_status_code(&normalized, code))
+        || AUTH_EXPIRED_MARKERS
+            .iter()
+            .any(|marker| normalized.contains(marker))
+    {
+        return TurnErrorKind::AuthExpired;
+    }
+
+    if PROVIDER_UNAVAILABLE_STATUS_CODES
+        .iter()
+        .any(|code| contains_status_code(&normalized, code))
+        || PROVIDER_UNAVAILABLE_MARKERS
+            .iter()
+            .any(|marker| normalized.contains(marker))
+    {
+        return TurnErrorKind::ProviderUnavailable;
+    }
+
+    TurnErrorKind::Other
+}
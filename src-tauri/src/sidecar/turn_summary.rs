@@ -0,0 +1,110 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Tools whose `path` argument is a file they modify.
+pub(crate) const FILE_WRITING_TOOLS: [&str; 2] = ["edit", "write"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnTokenUsage {
+    pub input: u64,
+    pub output: u64,
+    pub cache_read: u64,
+    pub cache_write: u64,
+    pub total: u64,
+}
+
+/// Compact `turn_summary` session event emitted right after `turn_end`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnSummary {
+    pub r#type: &'static str,
+    pub duration_ms: u64,
+    /// Tool name → number of calls in this turn.
+    pub tools: BTreeMap<String, usize>,
+    /// Files modified by edit/write tools, as given to the tool.
+    pub files_touched: Vec<String>,
+    pub tokens: TurnTokenUsage,
+    pub errors: Vec<String>,
+}
+
+/// Accumulates what one session's current turn did, from the raw host events.
+#[derive(Debug)]
+pub(crate) struct TurnSummaryBuilder {
+    started_at: Instant,
+    tools: BTreeMap<String, usize>,
+    files_touched: BTreeSet<String>,
+    errors: Vec<String>,
+}
+
+impl Default for TurnSummaryBuilder {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            tools: BTreeMap::new(),
+            files_touched: BTreeSet::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl TurnSummaryBuilder {
+    pub(crate) fn record_tool_start(&mut self, tool_name: &str, path: Option<&str>) {
+        *self.tools.entry(tool_name.to_string()).or_default() += 1;
+
+        if let Some(path) = path.filter(|_| FILE_WRITING_TOOLS.contains(&tool_name)) {
+            self.files_touched.insert(path.to_string());
+        }
+    }
+
+    pub(crate) fn record_tool_end(&mut self, tool_name: &str, event: &serde_json::Value) {
+        if event.get("isError").and_then(|value| value.as_bool()) == Some(true) {
+            self.errors.push(format!("{} failed", tool_name));
+        }
+    }
+
+    /// Finish the turn using the assistant message carried by `turn_end`.
+    pub(crate) fn finish(mut self, turn_end: &serde_json::Value) -> TurnSummary {
+        let message = turn_end.get("message");
+        let usage = message.and_then(|message| message.get("usage"));
+        let usage_field = |key: &str| {
+            usage
+                .and_then(|usage| usage.get(key))
+                .and_then(|value| value.as_u64())
+                .unwrap_or(0)
+        };
+
+        if message
+            .and_then(|message| message.get("stopReason"))
+            .and_then(|value| value.as_str())
+            == Some("error")
+        {
+            self.errors.push(
+                message
+                    .and_then(|message| message.get("errorMessage"))
+                    .and_then(|value| value.as_str())
+                    .unwrap_or("Assistant message failed")
+                    .to_string(),
+            );
+        }
+
+        let tokens = TurnTokenUsage {
+            input: usage_field("input"),
+            output: usage_field("output"),
+            cache_read: usage_field("cacheRead"),
+            cache_write: usage_field("cacheWrite"),
+            total: usage_field("totalTokens"),
+        };
+
+        TurnSummary {
+            r#type: "turn_summary",
+            duration_ms: self.started_at.elapsed().as_millis() as u64,
+            tools: self.tools,
+            files_touched: self.files_touched.into_iter().collect(),
+            tokens,
+            errors: self.errors,
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Optional rewrite of tool path arguments under the session cwd to
+//! workspace-relative form, so events shown in the UI neither leak the home
+//! directory name nor repeat the project prefix on every call.
+//!
+//! The absolute path is kept next to the arguments in `absolutePaths`, keyed
+//! like the argument it replaced.
+
+use std::path::Path;
+
+const PATH_ARGUMENT_KEYS: [&str; 3] = ["path", "file_path", "filePath"];
+
+fn relative_to(path: &str, cwd: &Path) -> Option<String> {
+    let relative = Path::new(path).strip_prefix(cwd).ok()?;
+    if relative.as_os_str().is_empty() {
+        return Some(".".to_string());
+    }
+    Some(relative.to_string_lossy().to_string())
+}
+
+/// Rewrite the path arguments in `arguments`; returns the absolute paths
+/// that were replaced.
+fn relativize_arguments(
+    arguments: &mut serde_json::Value,
+    cwd: &Path,
+) -> Option<serde_json::Map<String, serde_json::Value>> {
+    let arguments = arguments.as_object_mut()?;
+    let mut absolute_paths = serde_json::Map::new();
+
+    for key in PATH_ARGUMENT_KEYS {
+        let Some(value) = arguments.get_mut(key) else {
+            continue;
+        };
+        let Some(relative) = value.as_str().and_then(|path| relative_to(path, cwd)) else {
+            continue;
+        };
+        let absolute = std::mem::replace(value, serde_json::Value::String(relative));
+        absolute_paths.insert(key.to_string(), absolute);
+    }
+
+    (!absolute_paths.is_empty()).then_some(absolute_paths)
+}
+
+/// Rewrite paths in a compacted tool event: `args` of tool execution events
+/// and `arguments` of a streamed tool call.
+pub(crate) fn relativize_event_paths(event: &mut serde_json::Value, cwd: &str) {
+    let cwd = Path::new(cwd);
+    if !cwd.is_absolute() {
+        return;
+    }
+
+    match event.get("type").and_then(|value| value.as_str()) {
+        Some("tool_execution_start" | "tool_execution_update") => {
+            let absolute_paths = event
+                .get_mut("args")
+                .and_then(|args| relativize_arguments(args, cwd));
+            if let Some(absolute_paths) = absolute_paths {
+                event["absolutePaths"] = serde_json::Value::Object(absolute_paths);
+            }
+        }
+        Some("message_update") => {
+            let Some(tool_call) = event
+                .get_mut("assistantMessageEvent")
+                .and_then(|assistant_event| assistant_event.get_mut("toolCall"))
+            else {
+                return;
+            };
+            let absolute_paths = tool_call
+                .get_mut("arguments")
+                .and_then(|arguments| relativize_arguments(arguments, cwd));
+            if let Some(absolute_paths) = absolute_paths {
+                tool_call["absolutePaths"] = serde_json::Value::Object(absolute_paths);
+            }
+        }
+        _ => {}
+    }
+}
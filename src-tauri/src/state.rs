@@ -1,8 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::{mpsc, oneshot, Mutex};
 
-use crate::types::RpcResponse;
+use crate::commands::{
+    HeldPrompt, PauseState, PendingPinnedContext, QueuedPrompt, SidecarCapabilities,
+};
+use crate::sidecar::{CodeBlockHistory, SessionStateSnapshot};
+use crate::types::{RpcCommand, RpcResponse};
 
 pub struct PendingRequest {
     pub sender: oneshot::Sender<RpcResponse>,
@@ -13,6 +18,42 @@ pub struct SidecarState {
     pub pending_requests: HashMap<String, PendingRequest>,
     pub response_tx: Option<mpsc::Sender<(String, RpcResponse)>>,
     pub session_cwds: HashMap<String, String>,
+    /// Last user prompt per session, kept so it can be retried after a model fallback.
+    pub last_prompts: HashMap<String, RpcCommand>,
+    /// Sessions whose current run failed with a provider outage, keyed to the error message.
+    pub pending_model_fallbacks: HashMap<String, String>,
+    /// Sessions whose last run failed on expired credentials, keyed to the provider id (if known).
+    pub auth_expired_sessions: HashMap<String, Option<String>>,
+    /// Error of the last failed message in each session's current or last run;
+    /// cleared on `agent_start`.
+    pub run_errors: HashMap<String, String>,
+    /// Pre-edit content (`None` for new files) of every file changed in each
+    /// session's current or last run, by path; cleared on `agent_start`.
+    pub turn_file_snapshots: HashMap<String, BTreeMap<String, Option<String>>>,
+    /// Fenced code blocks from each session's assistant messages.
+    pub code_blocks: HashMap<String, CodeBlockHistory>,
+    /// Typed `get_state` per session, kept current from responses and events.
+    pub session_states: HashMap<String, SessionStateSnapshot>,
+    /// Sessions that already retried their last prompt on a fallback model.
+    pub model_fallback_retried: HashSet<String>,
+    /// Pinned project files waiting to be prepended to a session's first prompt.
+    pub pending_pinned_context: HashMap<String, PendingPinnedContext>,
+    /// Capabilities reported by the running sidecar, cached until it restarts.
+    pub capabilities: Option<SidecarCapabilities>,
+    /// `supportsImageInput` per `provider/modelId`, filled from `get_available_models`.
+    pub model_image_support: HashMap<String, bool>,
+    /// Last command sent to or event received from each session.
+    pub session_last_activity: HashMap<String, Instant>,
+    /// Sessions with an agent run in progress (between `agent_start` and `agent_end`).
+    pub running_sessions: HashSet<String>,
+    /// Prompts sent but not yet acknowledged by `agent_start`, with when they were sent.
+    pub prompts_awaiting_start: HashMap<String, Instant>,
+    /// Set while agent activity is paused via `pause_agents`.
+    pub pause: Option<PauseState>,
+    /// Prompts held while paused, sent in order on resume.
+    pub held_prompts: Vec<HeldPrompt>,
+    /// Prompts waiting for their provider to drop below its turn limit.
+    pub queued_prompts: VecDeque<QueuedPrompt>,
 }
 
 impl SidecarState {
@@ -22,6 +63,23 @@ impl SidecarState {
             pending_requests: HashMap::new(),
             response_tx: None,
             session_cwds: HashMap::new(),
+            last_prompts: HashMap::new(),
+            pending_model_fallbacks: HashMap::new(),
+            auth_expired_sessions: HashMap::new(),
+            run_errors: HashMap::new(),
+            turn_file_snapshots: HashMap::new(),
+            code_blocks: HashMap::new(),
+            session_states: HashMap::new(),
+            model_fallback_retried: HashSet::new(),
+            pending_pinned_context: HashMap::new(),
+            capabilities: None,
+            model_image_support: HashMap::new(),
+            session_last_activity: HashMap::new(),
+            running_sessions: HashSet::new(),
+            prompts_awaiting_start: HashMap::new(),
+            pause: None,
+            held_prompts: Vec::new(),
+            queued_prompts: VecDeque::new(),
         }
     }
 }
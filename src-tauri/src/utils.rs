@@ -1,5 +1,15 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use uuid::Uuid;
 
 pub fn crypto_random_uuid() -> String {
     Uuid::new_v4().to_string()
 }
+
+/// Current wall-clock time as unix milliseconds (0 if the clock is before the epoch).
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,101 @@
+//! Per-window subscriptions for the `agent-event` stream.
+//!
+//! Windows that never subscribe get every session's events, as before.
+//! A window that subscribes (e.g. a floating session window) only receives
+//! events for its sessions, plus events that belong to no session. Entries
+//! are dropped when the window is destroyed, so closed windows leave nothing
+//! behind.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{OnceLock, RwLock};
+
+use serde::Serialize;
+use tauri::EventTarget;
+
+use crate::utils::now_millis;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowSubscription {
+    pub label: String,
+    pub session_ids: BTreeSet<String>,
+    pub subscribed_at: u64,
+}
+
+fn registry() -> &'static RwLock<HashMap<String, WindowSubscription>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, WindowSubscription>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Limit `label`'s `agent-event` stream to `session_ids`, replacing any
+/// earlier subscription of the window.
+pub fn subscribe(label: &str, session_ids: Vec<String>) -> WindowSubscription {
+    let subscription = WindowSubscription {
+        label: label.to_string(),
+        session_ids: session_ids
+            .into_iter()
+            .map(|session_id| session_id.trim().to_string())
+            .filter(|session_id| !session_id.is_empty())
+            .collect(),
+        subscribed_at: now_millis(),
+    };
+
+    if let Ok(mut guard) = registry().write() {
+        guard.insert(label.to_string(), subscription.clone());
+    }
+
+    subscription
+}
+
+/// Go back to receiving every session's events. Returns whether the window
+/// had a subscription.
+pub fn unsubscribe(label: &str) -> bool {
+    registry()
+        .write()
+        .map(|mut guard| guard.remove(label).is_some())
+        .unwrap_or(false)
+}
+
+/// Drop everything tracked for a window; called when it is destroyed.
+pub fn forget_window(label: &str) {
+    if unsubscribe(label) {
+        crate::logger::log(format!(
+            "Dropped event subscription of closed window {}",
+            label
+        ));
+    }
+}
+
+pub fn subscriptions() -> Vec<WindowSubscription> {
+    let mut subscriptions = registry()
+        .read()
+        .map(|guard| guard.values().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    subscriptions.sort_by(|a, b| a.label.cmp(&b.label));
+    subscriptions
+}
+
+/// Emit filter: whether a listener on `target` should get an event of
+/// `session_id`. Listeners not bound to a window always do.
+pub fn accepts(target: &EventTarget, session_id: Option<&str>) -> bool {
+    let Some(session_id) = session_id else {
+        return true;
+    };
+
+    let label = match target {
+        EventTarget::Window { label }
+        | EventTarget::Webview { label }
+        | EventTarget::WebviewWindow { label }
+        | EventTarget::AnyLabel { label } => label,
+        _ => return true,
+    };
+
+    registry()
+        .read()
+        .map(|guard| {
+            guard
+                .get(label)
+                .is_none_or(|subscription| subscription.session_ids.contains(session_id))
+        })
+        .unwrap_or(true)
+}